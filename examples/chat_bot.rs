@@ -0,0 +1,88 @@
+//! An example chat bot, showing how a Table maps onto a chat channel such as Discord or Slack.
+//!
+//! A real bot would receive messages from a webhook and post replies through the chat service's
+//! API, this one reads "<user>: <text>" lines from stdin and prints its replies to stdout:
+//! "#bigtwo: <text>" for the whole channel and "@<user>: <text>" for a direct message. The turns
+//! are also narrated (see bigtwo::commentary) in "#bigtwo-commentary", for anybody watching.
+//!
+//! ```text
+//! $ cargo run --example chat_bot
+//! alice: join
+//! bob: join
+//! alice: start
+//! alice: 3c
+//! ```
+//!
+//! Up to 4 users may join, the empty seats are played by the AI.
+
+use std::collections::BTreeMap;
+use std::io::BufRead;
+
+use bigtwo::commentary::Commentator;
+use bigtwo::config::GameConfig;
+use bigtwo::constants::NUM_PLAYERS;
+use bigtwo::table::{Message, Recipient, Table};
+
+/// Posts the Table's Messages, to the channel or to the user sitting in the seat.
+fn post(messages: Vec<Message>, users: &BTreeMap<String, usize>) {
+    for message in messages {
+        match message.to {
+            Recipient::Everybody => println!("#bigtwo: {}", message.text),
+            Recipient::Seat(seat) => {
+                for (user, _) in users.iter().filter(|(_, s)| **s == seat) {
+                    println!("@{user}: {}", message.text);
+                }
+            }
+        }
+    }
+}
+
+/// Narrates the turns the Table has played since last time in the commentary channel.
+fn commentate(table: &Table, commentator: &mut Commentator) {
+    for line in commentator.catch_up(table.plays(), table.result()) {
+        println!("#bigtwo-commentary: {line}");
+    }
+}
+
+fn main() {
+    let mut users: BTreeMap<String, usize> = BTreeMap::new();
+    let mut table: Option<Table> = None;
+    let mut commentator = Commentator::default();
+
+    for line in std::io::stdin().lock().lines() {
+        let line = line.expect("could not read stdin");
+        let Some((user, text)) = line.split_once(':') else {
+            continue;
+        };
+        let (user, text) = (user.trim().to_string(), text.trim());
+
+        match (&mut table, text) {
+            (None, "join") => {
+                if users.contains_key(&user) {
+                    println!("@{user}: you have already joined");
+                } else if users.len() == NUM_PLAYERS {
+                    println!("@{user}: the table is full");
+                } else {
+                    let seat = users.len();
+                    users.insert(user.clone(), seat);
+                    println!("#bigtwo: {user} sits in seat {seat}");
+                }
+            }
+            (None, "start") => {
+                let people: Vec<usize> = users.values().copied().collect();
+                let mut new_table = Table::new(&GameConfig::default(), &people);
+                post(new_table.start(), &users);
+                commentate(&new_table, &mut commentator);
+                table = Some(new_table);
+            }
+            (None, _) => println!("@{user}: type \"join\" to sit down, then \"start\""),
+            (Some(table), text) => match users.get(&user) {
+                Some(seat) => {
+                    post(table.handle(*seat, text), &users);
+                    commentate(table, &mut commentator);
+                }
+                None => println!("@{user}: you are not playing in this game"),
+            },
+        }
+    }
+}
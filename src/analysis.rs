@@ -0,0 +1,157 @@
+//! Analysis of a Game from one player's point of view, i.e. using only what that player can see:
+//! their own cards, the cards already played, and how many cards each opponent holds.
+
+pub mod deal_quality;
+pub mod determinize;
+pub mod partition;
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::card::Card;
+use crate::deck::Deck;
+use crate::hand::{generate::playable_hands, Hand};
+use crate::rules::Rules;
+
+/// Everything one player knows about the cards in a Game.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Knowledge {
+    /// The cards this player holds.
+    pub hand: Vec<Card>,
+
+    /// Every card played so far, by anybody.
+    pub played: Vec<Card>,
+
+    /// How many cards each opponent holds, in turn order after this player.
+    pub opponent_card_counts: Vec<usize>,
+}
+
+impl Knowledge {
+    /// Returns every card this player cannot see, i.e. those that might be in an opponent's hand.
+    pub fn unseen_cards(&self) -> Vec<Card> {
+        Deck::new()
+            .cards
+            .into_iter()
+            .filter(|card| !self.hand.contains(card) && !self.played.contains(card))
+            .collect()
+    }
+
+    /// Deals the unseen cards out at random, one possible set of opponent hands consistent with
+    /// what this player knows.
+    ///
+    /// # Panics
+    /// - If the opponents hold more cards than are unseen.
+    pub fn sample_opponent_hands<R: Rng>(&self, rng: &mut R) -> Vec<Vec<Card>> {
+        let mut unseen = self.unseen_cards();
+        let total: usize = self.opponent_card_counts.iter().sum();
+        assert!(
+            total <= unseen.len(),
+            "opponents hold {total} cards, only {} are unseen",
+            unseen.len()
+        );
+        unseen.shuffle(rng);
+        self.opponent_card_counts
+            .iter()
+            .map(|count| unseen.drain(..*count).collect())
+            .collect()
+    }
+
+    /// Estimates the probability that at least one opponent could beat this attempt, by sampling
+    /// many possible deals of the unseen cards. Returns exactly 0.0 if no combination of the
+    /// unseen cards can beat it.
+    ///
+    /// # Panics
+    /// - If samples is 0.
+    pub fn beat_odds<R: Rng>(
+        &self,
+        attempt: &Hand,
+        rules: &Rules,
+        samples: usize,
+        rng: &mut R,
+    ) -> f64 {
+        assert!(samples > 0, "need at least one sample");
+        let can_beat = |cards: &[Card]| {
+            playable_hands(attempt, cards, rules)
+                .iter()
+                .any(|hand| !matches!(hand, Hand::Pass))
+        };
+        if !can_beat(&self.unseen_cards()) {
+            return 0.0;
+        }
+
+        let beaten = (0..samples)
+            .filter(|_| {
+                self.sample_opponent_hands(rng)
+                    .iter()
+                    .any(|cards| can_beat(cards))
+            })
+            .count();
+        beaten as f64 / samples as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::tests::test_util::vec_card_from_str;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    const SAMPLES: usize = 1000;
+
+    #[test]
+    fn test_unseen_cards() {
+        let knowledge = Knowledge {
+            hand: vec_card_from_str("3C 4C"),
+            played: vec_card_from_str("2S 2H"),
+            opponent_card_counts: vec![13, 13, 13],
+        };
+        let unseen = knowledge.unseen_cards();
+        assert_eq!(unseen.len(), 48);
+        assert!(!unseen.contains(&"2S".parse().unwrap()));
+
+        let mut rng = StdRng::seed_from_u64(0);
+        let hands = knowledge.sample_opponent_hands(&mut rng);
+        assert_eq!(hands.iter().map(|cards| cards.len()).sum::<usize>(), 39);
+    }
+
+    #[test]
+    fn test_beat_odds() {
+        let rules = Rules::default();
+        let mut rng = StdRng::seed_from_u64(0);
+
+        // nothing unseen can beat the 2S
+        let knowledge = Knowledge {
+            hand: vec_card_from_str("2S 3C"),
+            played: vec![],
+            opponent_card_counts: vec![13, 13, 12],
+        };
+        let odds = knowledge.beat_odds(&"2S".parse().unwrap(), &rules, SAMPLES, &mut rng);
+        assert_eq!(odds, 0.0);
+
+        // somebody always holds a higher Lone than the 3C
+        let odds = knowledge.beat_odds(&"3C".parse().unwrap(), &rules, SAMPLES, &mut rng);
+        assert_eq!(odds, 1.0);
+
+        // only the 2S beats the 2H, and it is one of the 3 unseen cards
+        let not_played = vec_card_from_str("2H 2S 3C 3D");
+        let knowledge = Knowledge {
+            hand: vec_card_from_str("2H"),
+            played: Deck::new()
+                .cards
+                .into_iter()
+                .filter(|card| !not_played.contains(card))
+                .collect(),
+            opponent_card_counts: vec![1, 1, 1],
+        };
+        let odds = knowledge.beat_odds(&"2H".parse().unwrap(), &rules, SAMPLES, &mut rng);
+        assert_eq!(odds, 1.0);
+        let knowledge = Knowledge {
+            opponent_card_counts: vec![1],
+            ..knowledge
+        };
+        let odds = knowledge.beat_odds(&"2H".parse().unwrap(), &rules, SAMPLES, &mut rng);
+        assert!((odds - 1.0 / 3.0).abs() < 0.05, "{odds}");
+    }
+}
@@ -0,0 +1,144 @@
+//! Judges how good a dealt hand is before anything is played: how many Tricks it can expect to
+//! win, how many control cards it holds, and how many of its cards fit into combos.
+
+use std::fmt::{Display, Formatter};
+use std::hash::{Hash, Hasher};
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use crate::analysis::partition::{partition, Objective};
+use crate::analysis::Knowledge;
+use crate::card::Card;
+use crate::hand::Hand;
+use crate::player::count_controls;
+use crate::rules::Rules;
+
+/// How many deals of the other cards are sampled to judge each Hand the cards break into.
+const DEAL_SAMPLES: usize = 32;
+
+/// How a dealt hand compares to a typical one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DealStrength {
+    Weak,
+    Average,
+    Strong,
+}
+
+impl Display for DealStrength {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            DealStrength::Weak => write!(f, "weak"),
+            DealStrength::Average => write!(f, "average"),
+            DealStrength::Strong => write!(f, "strong"),
+        }
+    }
+}
+
+/// The quality of a dealt hand.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DealQuality {
+    /// How many of the Hands the cards break into (see partition, StrongestCombos) are expected
+    /// to win their Trick when led, against three opponents holding every other card.
+    pub expected_tricks: f64,
+
+    /// How many Tricks it takes to play every card.
+    pub num_plays: usize,
+
+    /// The 2s and Bombs held, see count_controls.
+    pub controls: usize,
+
+    /// The share of the cards that fit in a Pair, Trips or Fiver, from 0 to 1.
+    pub combo_coverage: f64,
+}
+
+impl DealQuality {
+    /// Judges these cards, each Hand they break into against DEAL_SAMPLES deals of the other
+    /// cards. The same cards are always judged the same way.
+    pub fn of(cards: &[Card], rules: &Rules) -> Self {
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        cards.hash(&mut hasher);
+        let mut rng = StdRng::seed_from_u64(hasher.finish());
+
+        let knowledge = Knowledge {
+            hand: cards.to_vec(),
+            played: vec![],
+            opponent_card_counts: vec![cards.len(); 3],
+        };
+        let plays = partition(cards, Objective::StrongestCombos, rules);
+        let expected_tricks = plays
+            .hands
+            .iter()
+            .map(|hand| 1.0 - knowledge.beat_odds(hand, rules, DEAL_SAMPLES, &mut rng))
+            .sum();
+        let singles: usize = plays
+            .hands
+            .iter()
+            .filter(|hand| matches!(hand, Hand::Lone(..)))
+            .count();
+
+        Self {
+            expected_tricks,
+            num_plays: plays.num_plays(),
+            controls: count_controls(cards),
+            combo_coverage: match cards.len() {
+                0 => 0.0,
+                n => (n - singles) as f64 / n as f64,
+            },
+        }
+    }
+
+    /// How many of the Hands the cards break into are expected to lose their Trick, less one
+    /// for every control that can win the lead back. The fewer, the better.
+    pub fn losers(&self) -> f64 {
+        self.num_plays as f64 - self.expected_tricks - self.controls as f64
+    }
+
+    /// Sums the quality up: about a third of all deals are strong (at most 4 losers), and about a
+    /// quarter are weak (more than 6).
+    pub fn strength(&self) -> DealStrength {
+        match self.losers() {
+            losers if losers <= 4.0 => DealStrength::Strong,
+            losers if losers > 6.0 => DealStrength::Weak,
+            _ => DealStrength::Average,
+        }
+    }
+}
+
+impl Display for DealQuality {
+    /// e.g. "a strong hand (expects to win 2.4 of 6 tricks, 2 controls, 77% in combos)".
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "a {} hand (expects to win {:.1} of {} tricks, {} controls, {:.0}% in combos)",
+            self.strength(),
+            self.expected_tricks,
+            self.num_plays,
+            self.controls,
+            self.combo_coverage * 100.0
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::tests::test_util::vec_card_from_str;
+
+    #[test]
+    fn test_deal_quality() {
+        let rules = Rules::default();
+        let strong = vec_card_from_str("3C 3D 5S 5H 5C 8D 8S 2H 2S AC AD KH KS");
+        let quality = DealQuality::of(&strong, &rules);
+        assert_eq!(quality.controls, 2);
+        assert_eq!(quality.combo_coverage, 1.0);
+        assert_eq!(quality.strength(), DealStrength::Strong, "{quality}");
+
+        let weak = vec_card_from_str("3C 3H 4D 4S 6S 6H 8H 8C 9D TD QS KH AC");
+        let quality = DealQuality::of(&weak, &rules);
+        assert_eq!(quality.controls, 0);
+        assert_eq!(quality.strength(), DealStrength::Weak, "{quality}");
+        assert_eq!(DealQuality::of(&weak, &rules), quality);
+    }
+}
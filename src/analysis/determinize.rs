@@ -0,0 +1,176 @@
+//! Determinization, for sampling-based search: deals the cards one player cannot see out to the
+//! others, so that every opponent holds as many cards as they do, and (wherever possible) nothing
+//! that beats a Hand they passed on. Each deal is a solver::Position that can be searched as if
+//! every card were known.
+
+use std::collections::BTreeSet;
+
+use rand::Rng;
+
+use crate::analysis::Knowledge;
+use crate::card::Card;
+use crate::hand::Hand;
+use crate::player::{OpponentModel, TurnView};
+use crate::rules::Rules;
+use crate::solver::Position;
+
+/// How many deals are tried for one that agrees with every pass, before settling for one that
+/// only agrees with the card counts.
+const MAX_TRIES: usize = 200;
+
+/// One possible deal of the unseen cards.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Determinization {
+    /// The Game as it would stand with these cards dealt, the player's own cards in their seat.
+    pub position: Position,
+
+    /// True if no opponent would have passed on a Hand they could beat, false if no such deal
+    /// was found in MAX_TRIES (the opponents may have held back on purpose).
+    pub consistent: bool,
+}
+
+/// Deals the unseen cards of one player's turn, as many times as needed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Determinizer {
+    player_id: usize,
+    cards: Vec<Card>,
+    hand_to_beat: Hand,
+    passed: BTreeSet<usize>,
+    opponent_ids: Vec<usize>,
+    knowledge: Knowledge,
+    model: OpponentModel,
+    rules: Rules,
+}
+
+impl Determinizer {
+    /// Everything the player can see on their turn, ready to deal from.
+    pub fn from_view(view: &TurnView) -> Self {
+        Self {
+            player_id: view.player_id,
+            cards: view.cards.to_vec(),
+            hand_to_beat: view.hand_to_beat,
+            passed: view
+                .trick
+                .iter()
+                .filter(|play| matches!(play.hand, Hand::Pass))
+                .map(|play| play.player_id)
+                .collect(),
+            opponent_ids: view.opponent_ids().collect(),
+            knowledge: view.knowledge(),
+            model: OpponentModel::from_view(view),
+            rules: view.rules,
+        }
+    }
+
+    /// Deals the unseen cards once, preferring a deal that agrees with every pass.
+    ///
+    /// # Panics
+    /// - If the opponents hold more cards than are unseen.
+    pub fn sample<R: Rng>(&self, rng: &mut R) -> Determinization {
+        let mut deal = vec![];
+        for _ in 0..MAX_TRIES {
+            deal = self.knowledge.sample_opponent_hands(rng);
+            if self.is_consistent(&deal) {
+                return self.determinization(deal, true);
+            }
+        }
+        self.determinization(deal, false)
+    }
+
+    /// Deals the unseen cards this many times.
+    pub fn samples<R: Rng>(&self, num_samples: usize, rng: &mut R) -> Vec<Determinization> {
+        (0..num_samples).map(|_| self.sample(rng)).collect()
+    }
+
+    /// Returns true if every opponent could have made all of their passes holding these cards.
+    fn is_consistent(&self, deal: &[Vec<Card>]) -> bool {
+        self.opponent_ids
+            .iter()
+            .zip(deal)
+            .all(|(id, cards)| self.model.is_consistent(*id, cards, &self.rules))
+    }
+
+    /// Seats the opponents' cards, in turn order after the player, around the player's own.
+    fn determinization(&self, deal: Vec<Vec<Card>>, consistent: bool) -> Determinization {
+        let mut hands = vec![vec![]; self.opponent_ids.len() + 1];
+        hands[self.player_id] = self.cards.clone();
+        for (id, mut cards) in self.opponent_ids.iter().zip(deal) {
+            cards.sort();
+            hands[*id] = cards;
+        }
+        Determinization {
+            position: Position {
+                hands,
+                hand_to_beat: self.hand_to_beat,
+                current_player: self.player_id,
+                passed: self.passed.clone(),
+            },
+            consistent,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::deck::Deck;
+    use crate::play::Play;
+    use crate::tests::test_util::vec_card_from_str;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_determinize() {
+        // everything but these cards has been played, the last of it an AS which player 1 passed on
+        let cards = vec_card_from_str("3C KD");
+        let unseen = vec_card_from_str("3D 3H 3S 4C 5D 2S");
+        let ace: Card = "AS".parse().unwrap();
+        let mut history: Vec<Play> = Deck::new()
+            .cards
+            .into_iter()
+            .filter(|card| !cards.contains(card) && !unseen.contains(card) && *card != ace)
+            .enumerate()
+            .map(|(trick, card)| Play::now(3, Hand::Lone(card), trick))
+            .collect();
+        let trick = history.len();
+        history.push(Play::now(3, Hand::Lone(ace), trick));
+        history.push(Play::now(0, Hand::Pass, trick));
+        history.push(Play::now(1, Hand::Pass, trick));
+        let mut view = TurnView::new(&cards, Hand::Pass, 4);
+        view.player_id = 0;
+        view.history = &history;
+        view.card_counts = vec![2, 1, 2, 3];
+
+        let mut rng = StdRng::seed_from_u64(0);
+        let two: Card = "2S".parse().unwrap();
+        let samples = Determinizer::from_view(&view).samples(50, &mut rng);
+        for sample in &samples {
+            assert!(sample.consistent);
+            let hands = &sample.position.hands;
+            assert_eq!(hands[0], cards);
+            assert_eq!(hands.iter().map(Vec::len).collect::<Vec<_>>(), [2, 1, 2, 3]);
+            assert!(!hands[1].contains(&two));
+        }
+        // the 2S still turns up with the others
+        assert!(samples
+            .iter()
+            .any(|sample| sample.position.hands[3].contains(&two)));
+
+        // player 2 passed on the AS too, and player 3 on a KH, yet somebody holds the 2S
+        history.push(Play::now(2, Hand::Pass, trick));
+        history.push(Play::now(0, "KH".parse().unwrap(), trick + 1));
+        history.push(Play::now(3, Hand::Pass, trick + 1));
+        let mut view = TurnView::new(&cards, Hand::Pass, 4);
+        view.player_id = 0;
+        view.history = &history;
+        view.card_counts = vec![2, 1, 2, 3];
+        let sample = Determinizer::from_view(&view).sample(&mut rng);
+        assert!(!sample.consistent);
+        assert!(sample
+            .position
+            .hands
+            .iter()
+            .any(|cards| cards.contains(&two)));
+    }
+}
@@ -0,0 +1,219 @@
+//! Breaks a player's cards into Hands, e.g. "straight, 2 pairs, 3 singles", the way they would
+//! play them out if nobody else played at all.
+
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+use crate::card::Card;
+use crate::deck::Deck;
+use crate::hand::{generate::all_hands, Hand};
+use crate::rules::Rules;
+
+/// What makes one partition better than another.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Objective {
+    /// The fewest Hands, i.e. the fewest Tricks that must be won to go out. Ties go to the
+    /// partition with the fewest singles.
+    #[default]
+    FewestPlays,
+    /// The fewest singles, i.e. as many cards as possible in Pairs, Trips and Fivers. Ties go to
+    /// the partition with the fewest Hands.
+    FewestSingles,
+    /// The fewest Hands, ties go to the partition whose combos (everything but singles) have the
+    /// highest top cards, i.e. are the hardest to beat.
+    StrongestCombos,
+}
+
+/// The Hands a player's cards break into, weakest first.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Partition {
+    pub hands: Vec<Hand>,
+}
+
+impl Partition {
+    /// How many Hands it takes to play every card.
+    pub fn num_plays(&self) -> usize {
+        self.hands.len()
+    }
+
+    /// How many of the Hands are singles.
+    pub fn num_singles(&self) -> usize {
+        self.hands
+            .iter()
+            .filter(|hand| matches!(hand, Hand::Lone(..)))
+            .count()
+    }
+}
+
+/// The name of the kind of Hand, singular then plural.
+fn kind_name(hand: &Hand) -> (&'static str, &'static str) {
+    match hand {
+        Hand::Pass => ("pass", "passes"),
+        Hand::Lone(..) => ("single", "singles"),
+        Hand::Pair(..) => ("pair", "pairs"),
+        Hand::Trips(..) => ("trips", "trips"),
+        Hand::Straight(..) => ("straight", "straights"),
+        Hand::Flush(..) => ("flush", "flushes"),
+        Hand::FullHouse(..) => ("full house", "full houses"),
+        Hand::FourPlusKick(..) => ("four plus kick", "four plus kicks"),
+        Hand::StraightFlush(..) => ("straight flush", "straight flushes"),
+    }
+}
+
+impl Display for Partition {
+    /// Biggest Hands first, e.g. "straight, 2 pairs, 3 singles".
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        let mut kinds: Vec<(usize, (&str, &str), usize)> = vec![];
+        for hand in &self.hands {
+            let name = kind_name(hand);
+            match kinds.iter_mut().find(|(_, kind, _)| *kind == name) {
+                Some((_, _, count)) => *count += 1,
+                None => kinds.push((hand.cards().count(), name, 1)),
+            }
+        }
+        kinds.sort_by_key(|(size, _, _)| std::cmp::Reverse(*size));
+        let out: Vec<String> = kinds
+            .iter()
+            .map(|(_, (singular, plural), count)| match count {
+                1 => singular.to_string(),
+                _ => format!("{count} {plural}"),
+            })
+            .collect();
+        write!(f, "{}", out.join(", "))
+    }
+}
+
+/// Lower is better, compared in order.
+type Cost = (usize, usize);
+
+fn cost(hand: &Hand, objective: Objective) -> Cost {
+    let single = matches!(hand, Hand::Lone(..)) as usize;
+    // how many cards outrank the combo's top card, so stronger combos cost less
+    let weakness = match single {
+        1 => 0,
+        _ => {
+            let top = hand.cards().max().unwrap();
+            Deck::new().cards.iter().filter(|card| *card > top).count()
+        }
+    };
+    match objective {
+        Objective::FewestPlays => (1, single),
+        Objective::FewestSingles => (single, 1),
+        Objective::StrongestCombos => (1, weakness),
+    }
+}
+
+/// Returns the best way to play out these cards under the objective.
+///
+/// # Panics
+/// - If given more than 26 cards.
+pub fn partition(cards: &[Card], objective: Objective, rules: &Rules) -> Partition {
+    assert!(cards.len() <= 26, "too many cards to partition");
+    let mut cards = cards.to_vec();
+    cards.sort();
+
+    let mask_of = |hand: &Hand| -> u32 {
+        hand.cards()
+            .map(|card| 1 << cards.iter().position(|c| c == card).unwrap())
+            .sum()
+    };
+    let hands: Vec<(u32, Hand, Cost)> = all_hands(&cards)
+        .into_iter()
+        .filter(|hand| rules.allow_flush || !matches!(hand, Hand::Flush(..)))
+        .map(|hand| (mask_of(&hand), hand, cost(&hand, objective)))
+        .collect();
+
+    let mut memo: HashMap<u32, (Cost, Vec<Hand>)> = HashMap::new();
+    let (_, mut hands) = best(&hands, (1 << cards.len()) - 1, &mut memo);
+    hands.reverse();
+    Partition { hands }
+}
+
+/// The cheapest way to play out the cards left in the mask, found by always deciding what the
+/// lowest card left is played in.
+fn best(
+    hands: &[(u32, Hand, Cost)],
+    left: u32,
+    memo: &mut HashMap<u32, (Cost, Vec<Hand>)>,
+) -> (Cost, Vec<Hand>) {
+    if left == 0 {
+        return ((0, 0), vec![]);
+    }
+    if let Some(found) = memo.get(&left) {
+        return found.clone();
+    }
+    let lowest = left & left.wrapping_neg();
+    let mut found: Option<(Cost, Vec<Hand>)> = None;
+    for (mask, hand, cost) in hands {
+        if mask & lowest == 0 || mask & !left != 0 {
+            continue;
+        }
+        let (rest_cost, mut rest) = best(hands, left & !mask, memo);
+        let total = (cost.0 + rest_cost.0, cost.1 + rest_cost.1);
+        if found.as_ref().is_none_or(|(best, _)| total < *best) {
+            rest.push(*hand);
+            found = Some((total, rest));
+        }
+    }
+    let found = found.expect("every card can be played as a single");
+    memo.insert(left, found.clone());
+    found
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::tests::test_util::vec_card_from_str;
+
+    #[test]
+    fn test_partition() {
+        let rules = Rules::default();
+        let cards = vec_card_from_str("3C 4D 5S 6H 7C 7D 9S 9H JC KD 2S 2H");
+
+        let fewest = partition(&cards, Objective::FewestPlays, &rules);
+        // the Straight and two Pairs leave 3 singles
+        assert_eq!(fewest.num_plays(), 6);
+        assert_eq!(fewest.num_singles(), 3);
+        assert_eq!(fewest.to_string(), "straight, 2 pairs, 3 singles");
+        let mut played: Vec<Card> = fewest
+            .hands
+            .iter()
+            .flat_map(|h| h.cards().copied())
+            .collect();
+        played.sort();
+        let mut all = cards.clone();
+        all.sort();
+        assert_eq!(played, all);
+
+        // every card is played exactly once, whatever the objective
+        for objective in [Objective::FewestSingles, Objective::StrongestCombos] {
+            let found = partition(&cards, objective, &rules);
+            let count: usize = found.hands.iter().map(|h| h.cards().count()).sum();
+            assert_eq!(count, cards.len());
+            assert_eq!(found.num_plays(), 6);
+        }
+    }
+
+    #[test]
+    fn test_partition_objectives() {
+        let rules = Rules::default();
+        let cards = vec_card_from_str("5C 5D 5H 6S 7D 8C 9H 9S");
+        let fewest = partition(&cards, Objective::FewestPlays, &rules);
+        // the Straight leaves a Pair of Fives and a Nine, the Full-House leaves 3 singles
+        assert_eq!(fewest.num_plays(), 3);
+        assert_eq!(fewest.num_singles(), 1);
+
+        // both Straights leave one single, the stronger Straight leaves the Four
+        let cards = vec_card_from_str("4C 5D 6S 7H 8C 9D");
+        let strongest = partition(&cards, Objective::StrongestCombos, &rules);
+        assert_eq!(strongest.num_plays(), 2);
+        assert!(strongest.hands.contains(&"4C".parse().unwrap()));
+
+        // Trips plus a Pair beat singles
+        let cards = vec_card_from_str("3C 3D 3H 4S 4D");
+        let found = partition(&cards, Objective::FewestSingles, &rules);
+        assert_eq!(found.num_singles(), 0);
+        assert_eq!(found.num_plays(), 1);
+    }
+}
@@ -0,0 +1,202 @@
+//! Identifies every Game, and records what it was played with (the rules, the seed, who sat where
+//! and with which client), so a result found in ratings, stats or an exported file can be traced
+//! back to the exact Game.
+
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use rand::Rng;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::config::GameConfig;
+use crate::rules::{preset::RulePreset, Rules};
+use crate::seats::Controller;
+use crate::trick::NUM_PLAYERS;
+
+/// The version of this crate, recorded with every Game.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Uniquely identifies a Game, written as a (version 4) UUID, e.g.
+/// "5b1c3f0e-8d2a-4c6b-9e1f-2a3b4c5d6e7f". The default is the nil UUID, for Games recorded
+/// before they were identified.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct GameId(pub u128);
+
+impl GameId {
+    /// A new random GameId.
+    pub fn random<R: Rng>(rng: &mut R) -> Self {
+        let bits: u128 = rng.gen();
+        // the version (4, random) and variant (RFC 4122) bits
+        let bits = (bits & !(0xf << 76)) | (0x4 << 76);
+        let bits = (bits & !(0x3 << 62)) | (0x2 << 62);
+        Self(bits)
+    }
+}
+
+impl Display for GameId {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        let hex = format!("{:032x}", self.0);
+        write!(
+            f,
+            "{}-{}-{}-{}-{}",
+            &hex[..8],
+            &hex[8..12],
+            &hex[12..16],
+            &hex[16..20],
+            &hex[20..]
+        )
+    }
+}
+
+/// Represents the possible errors from attempting to parse a GameId from a string.
+#[derive(Debug)]
+pub enum ParseGameIdError {
+    /// Not 32 hex digits, in groups of 8, 4, 4, 4 and 12 separated by dashes.
+    Malformed(String),
+}
+
+impl FromStr for GameId {
+    type Err = ParseGameIdError;
+    fn from_str(id_str: &str) -> Result<Self, Self::Err> {
+        let groups: Vec<&str> = id_str.split('-').collect();
+        let lengths: Vec<usize> = groups.iter().map(|group| group.len()).collect();
+        if lengths != [8, 4, 4, 4, 12] {
+            return Err(Self::Err::Malformed(id_str.to_string()));
+        }
+        u128::from_str_radix(&groups.concat(), 16)
+            .map(GameId)
+            .map_err(|_| Self::Err::Malformed(id_str.to_string()))
+    }
+}
+
+impl Serialize for GameId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for GameId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let id = String::deserialize(deserializer)?;
+        id.parse()
+            .map_err(|_| serde::de::Error::custom(format!("not a game id: {id}")))
+    }
+}
+
+/// Who sat in a seat.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Participant {
+    /// The name shown for the seat.
+    pub name: String,
+
+    /// Who made the decisions for the seat.
+    pub controller: Controller,
+
+    /// The client the seat played with, if it said (e.g. "client mybot/1.2" at a Table).
+    pub client: Option<String>,
+}
+
+/// Everything needed to trace a result back to the Game it came from.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameMeta {
+    /// Unique to the Game.
+    pub id: GameId,
+
+    /// The regional variation the rules were taken from.
+    pub preset: RulePreset,
+
+    /// The rules in effect.
+    pub rules: Rules,
+
+    /// The names of the house rules in effect.
+    pub house_rules: Vec<String>,
+
+    /// The seed that dealt the cards, None if they were dealt some other way (see
+    /// Table::from_deal).
+    pub seed: Option<u64>,
+
+    /// Who sat in each seat, indexed by seat.
+    pub participants: Vec<Participant>,
+
+    /// The version of this crate that ran the Game.
+    pub version: String,
+
+    /// True if the Game was played for practice (see rules::house::Practice), it counts towards
+    /// no ratings or stats.
+    #[serde(default)]
+    pub practice: bool,
+}
+
+impl GameMeta {
+    /// Identifies a new Game, about to be played with this config and dealt by this seed.
+    pub fn new(config: &GameConfig, seed: Option<u64>) -> Self {
+        Self {
+            id: GameId::random(&mut rand::thread_rng()),
+            preset: config.preset,
+            rules: config.rules,
+            house_rules: config
+                .house_rules
+                .names()
+                .into_iter()
+                .map(String::from)
+                .collect(),
+            seed,
+            participants: (0..NUM_PLAYERS)
+                .map(|seat| {
+                    let seat_config = config.seat(seat);
+                    Participant {
+                        name: seat_config.display_name(seat),
+                        controller: seat_config.controller,
+                        client: None,
+                    }
+                })
+                .collect(),
+            version: String::from(VERSION),
+            practice: config.house_rules.is_practice(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_game_id() {
+        let mut rng = rand::thread_rng();
+        let id = GameId::random(&mut rng);
+        assert_ne!(id, GameId::random(&mut rng));
+        let text = id.to_string();
+        assert_eq!(text.len(), 36);
+        assert_eq!(&text[14..15], "4", "version 4");
+        assert!("89ab".contains(&text[19..20]), "RFC 4122 variant");
+        assert_eq!(text.parse::<GameId>().unwrap(), id);
+        assert_eq!(
+            GameId::default().to_string(),
+            "00000000-0000-0000-0000-000000000000"
+        );
+        assert!(matches!(
+            "not-a-game-id".parse::<GameId>(),
+            Err(ParseGameIdError::Malformed(_))
+        ));
+
+        let json = serde_json::to_string(&id).unwrap();
+        assert_eq!(json, format!("\"{id}\""));
+        assert_eq!(serde_json::from_str::<GameId>(&json).unwrap(), id);
+    }
+
+    #[test]
+    fn test_game_meta() {
+        let config = GameConfig {
+            seed: Some(3),
+            ..GameConfig::default()
+        };
+        let meta = GameMeta::new(&config, config.seed);
+        assert_eq!(meta.seed, Some(3));
+        assert_eq!(meta.participants.len(), NUM_PLAYERS);
+        assert_eq!(meta.participants[0].controller, Controller::Human);
+        assert_eq!(meta.version, VERSION);
+        assert_ne!(meta.id, GameMeta::new(&config, config.seed).id);
+    }
+}
@@ -7,12 +7,514 @@
 // mod player;
 // mod trick;
 
-use bigtwo::game::perform_game;
+use std::fs::File;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use bigtwo::card::rank::Rank;
+use bigtwo::config::GameConfig;
+use bigtwo::daily::{daily_config, format_day, today};
+use bigtwo::game::GameResult;
+use bigtwo::heatmap::Heatmap;
+use bigtwo::match_play::{perform_match, MatchSummary, SeatDraw};
+use bigtwo::matrix::{run_matrix, DEFAULT_TURN_CAP};
+use bigtwo::output::{self, Both, Output};
+use bigtwo::player::{Aggressiveness, AiLevel, HumanInput};
+use bigtwo::profile::{MatchRecord, Profile};
+use bigtwo::puzzle::{perform_puzzle, Puzzle, BUILT_IN_PUZZLES};
+use bigtwo::recap::find_key_moments;
+use bigtwo::replay::{load_match, play_back, save_match, Pacing};
+use bigtwo::rules::house::Practice;
+use bigtwo::rules::{cheat_sheet::CheatSheet, preset::RulePreset, PointCardRule, TeamPlay};
+use bigtwo::scoring::Stalemate;
+use bigtwo::seats::TableFile;
+use bigtwo::self_play::{self_play, write_jsonl};
+
+/// The human is configured in seat 0, and sits there unless the seats are drawn.
+const HUMAN_PLAYER_ID: usize = 0;
+
+/// Returns the value following a flag, e.g. "taiwan" for "--preset taiwan".
+fn flag_value<'a>(args: &'a [String], flag: &str) -> Option<&'a String> {
+    let index = args.iter().position(|arg| arg == flag)?;
+    let value = args.get(index + 1);
+    assert!(value.is_some(), "{flag} requires a value");
+    value
+}
+
+/// Reads the optional "--preset <name>" flag, defaults to the README rules.
+fn parse_preset(args: &[String]) -> RulePreset {
+    match flag_value(args, "--preset") {
+        Some(name) => name
+            .parse()
+            .unwrap_or_else(|e| panic!("could not understand preset {name}, {e:?}")),
+        None => RulePreset::default(),
+    }
+}
+
+/// Reads the optional "--ai <level>" flag, "easy" or "hard", defaults to easy.
+fn parse_ai_level(args: &[String]) -> AiLevel {
+    match flag_value(args, "--ai") {
+        Some(level) => level
+            .parse()
+            .unwrap_or_else(|e| panic!("could not understand AI level {level}, {e:?}")),
+        None => AiLevel::default(),
+    }
+}
+
+/// Reads the optional "--aggressiveness <level>" flag, "cautious", "balanced" or "reckless",
+/// defaults to balanced.
+fn parse_aggressiveness(args: &[String]) -> Aggressiveness {
+    match flag_value(args, "--aggressiveness") {
+        Some(level) => level
+            .parse()
+            .unwrap_or_else(|e| panic!("could not understand aggressiveness {level}, {e:?}")),
+        None => Aggressiveness::default(),
+    }
+}
+
+/// Reads the optional "--redeal-threshold <rank>" flag, e.g. "9" lets a player holding nothing
+/// higher than a Nine call for a redeal.
+fn parse_redeal_threshold(args: &[String]) -> Option<Rank> {
+    flag_value(args, "--redeal-threshold").map(|rank| {
+        rank.to_uppercase()
+            .parse()
+            .unwrap_or_else(|e| panic!("could not understand rank {rank}, {e:?}"))
+    })
+}
+
+/// Reads the optional "--point-cards <rule>" flag, "fives-tens-kings" or "hearts", defaults to
+/// the preset's (no point cards).
+fn parse_point_cards(args: &[String]) -> Option<PointCardRule> {
+    flag_value(args, "--point-cards").map(|rule| {
+        rule.parse()
+            .unwrap_or_else(|e| panic!("could not understand point-card rule {rule}, {e:?}"))
+    })
+}
+
+/// Reads the optional "--teams <rule>" flag, "first-out" or "both-out", defaults to the preset's
+/// (everybody for themselves).
+fn parse_teams(args: &[String]) -> Option<TeamPlay> {
+    flag_value(args, "--teams").map(|rule| {
+        rule.parse()
+            .unwrap_or_else(|e| panic!("could not understand team rule {rule}, {e:?}"))
+    })
+}
+
+/// Reads the optional "--stalemate <scoring>" flag, "draw" or "fewest-cards", how a Game stopped
+/// by --max-turns is scored.
+fn parse_stalemate(args: &[String]) -> Option<Stalemate> {
+    flag_value(args, "--stalemate").map(|stalemate| {
+        stalemate
+            .parse()
+            .unwrap_or_else(|e| panic!("unknown stalemate scoring {stalemate}, {e:?}"))
+    })
+}
+
+/// Reads the optional "--seat-draw <draw>" flag, "fixed", "shuffle" or "draw", defaults to
+/// fixed.
+fn parse_seat_draw(args: &[String]) -> SeatDraw {
+    match flag_value(args, "--seat-draw") {
+        Some(draw) => draw
+            .parse()
+            .unwrap_or_else(|e| panic!("unknown seat draw {draw}, {e:?}")),
+        None => SeatDraw::Fixed,
+    }
+}
+
+/// Reads an optional numeric flag, e.g. "--games 3".
+fn parse_number(args: &[String], flag: &str) -> Option<u64> {
+    flag_value(args, flag).map(|number| {
+        number
+            .parse()
+            .unwrap_or_else(|e| panic!("could not understand {flag} {number}, {e:?}"))
+    })
+}
+
+/// Reads the optional "--profile <path>" flag, defaults to ~/.bigtwo_profile.json.
+fn parse_profile_path(args: &[String]) -> PathBuf {
+    match flag_value(args, "--profile") {
+        Some(path) => PathBuf::from(path),
+        None => std::env::var_os("HOME")
+            .map(PathBuf::from)
+            .unwrap_or_default()
+            .join(".bigtwo_profile.json"),
+    }
+}
+
+/// Reads the optional "--puzzle <name|path>" flag, either the name of a built in Puzzle (case
+/// insensitive) or the path of a puzzle file.
+fn parse_puzzle(args: &[String]) -> Option<Puzzle> {
+    let name = flag_value(args, "--puzzle")?;
+    let built_in = BUILT_IN_PUZZLES
+        .iter()
+        .map(|puzzle_str| {
+            puzzle_str
+                .parse::<Puzzle>()
+                .expect("built in puzzles are valid")
+        })
+        .find(|puzzle| puzzle.name.eq_ignore_ascii_case(name));
+    let puzzle = built_in.unwrap_or_else(|| {
+        std::fs::read_to_string(name)
+            .unwrap_or_else(|e| panic!("no built in puzzle or file named {name}, {e:?}"))
+            .parse()
+            .unwrap_or_else(|e| panic!("could not understand puzzle {name}, {e:?}"))
+    });
+    Some(puzzle)
+}
+
+/// Reads the optional "--log-file <path>" flag, everything printed is written to the file as well
+/// as stdout.
+fn parse_output(args: &[String]) -> Output {
+    match flag_value(args, "--log-file") {
+        Some(path) => {
+            let file =
+                File::create(path).unwrap_or_else(|e| panic!("could not create {path}, {e:?}"));
+            Output::to(Both(io::stdout(), file))
+        }
+        None => Output::default(),
+    }
+}
+
+/// Reads the optional "--pacing <pacing>" flag, "real-time", "step" or a speed such as "4x",
+/// defaults to real-time.
+fn parse_pacing(args: &[String]) -> Pacing {
+    match flag_value(args, "--pacing") {
+        Some(pacing) => pacing
+            .parse()
+            .unwrap_or_else(|e| panic!("unknown pacing {pacing}, {e:?}")),
+        None => Pacing::RealTime,
+    }
+}
+
+/// Plays back every Game saved in the file, see "--save-replay".
+fn perform_replay(path: &str, pacing: Pacing) {
+    let summary =
+        load_match(Path::new(path)).unwrap_or_else(|e| panic!("could not load {path}, {e:?}"));
+    for (seat, player) in summary.seating.iter().enumerate() {
+        if seat != *player {
+            println!("=== Seat {seat} was drawn by the player configured in seat {player}");
+        }
+    }
+    let games = &summary.results;
+    for (index, game) in games.iter().enumerate() {
+        println!(
+            "=== Game {} of {}, replayed {pacing}",
+            index + 1,
+            games.len()
+        );
+        // replays saved before key moments were recorded work them out again
+        let moments = match game.key_moments.is_empty() {
+            true => find_key_moments(&game.plays),
+            false => game.key_moments.clone(),
+        };
+        play_back(
+            &game.plays,
+            &moments,
+            pacing,
+            &mut std::io::stdin().lock(),
+            &mut std::io::stdout(),
+            std::thread::sleep,
+        )
+        .expect("could not write the replay");
+        println!("=== Player {} wins", game.winner);
+    }
+}
+
+/// Where a Match is saved to be replayed: the "--save-replay <path>" flag, or else next to the
+/// profile, e.g. ~/.bigtwo_profile_replays/<id of the first Game>.json.
+fn replay_path(args: &[String], profile_path: &Path, summary: &MatchSummary) -> PathBuf {
+    if let Some(path) = flag_value(args, "--save-replay") {
+        return PathBuf::from(path);
+    }
+    let stem = profile_path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let first_game = summary
+        .results
+        .first()
+        .map(|result| result.meta.id)
+        .unwrap_or_default();
+    profile_path
+        .with_file_name(format!("{stem}_replays"))
+        .join(format!("{first_game}.json"))
+}
+
+/// The "history" subcommand, lists every Match in the profile, or with a number (e.g. "history 3")
+/// replays that Match.
+fn perform_history(args: &[String]) {
+    let profile_path = parse_profile_path(args);
+    let profile = Profile::load(&profile_path).expect("could not read profile");
+    match args.get(1).and_then(|entry| entry.parse::<usize>().ok()) {
+        Some(entry) => {
+            let record = entry
+                .checked_sub(1)
+                .and_then(|index| profile.matches.get(index))
+                .unwrap_or_else(|| panic!("there is no Match {entry} in the history"));
+            let path = record
+                .replay
+                .as_ref()
+                .unwrap_or_else(|| panic!("Match {entry} was not saved to be replayed"));
+            println!("=== {record}");
+            perform_replay(&path.to_string_lossy(), parse_pacing(args));
+        }
+        None if profile.matches.is_empty() => println!("No Matches played yet"),
+        None => {
+            for (index, record) in profile.matches.iter().enumerate() {
+                println!("{}. {record}", index + 1);
+            }
+            println!("Type \"history <number>\" to replay a Match");
+        }
+    }
+}
+
+/// The "heatmap" subcommand, e.g. "heatmap a.json b.json", writes the heatmap of every Game in
+/// the replay files as JSON, or with "--csv" as CSV.
+fn perform_heatmap(args: &[String]) {
+    let mut heatmap = Heatmap::default();
+    for path in args[1..].iter().filter(|arg| !arg.starts_with("--")) {
+        let summary =
+            load_match(Path::new(path)).unwrap_or_else(|e| panic!("could not load {path}, {e:?}"));
+        for result in &summary.results {
+            heatmap.add(result);
+        }
+    }
+    let out = &mut std::io::stdout();
+    if args.iter().any(|arg| arg == "--csv") {
+        heatmap.write_csv(out)
+    } else {
+        heatmap.write_json(out)
+    }
+    .expect("could not write the heatmap");
+}
 
 fn main() {
-    println!("-------------------");
-    println!("Welcome to Big Two!");
-    println!("Submit hands by typing the cards in e.g. \"3C 3D 3S\"");
-    println!("-------------------");
-    perform_game();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().is_some_and(|command| command == "history") {
+        perform_history(&args);
+        return;
+    }
+    if args.first().is_some_and(|command| command == "heatmap") {
+        perform_heatmap(&args);
+        return;
+    }
+    if let Some(path) = flag_value(&args, "--replay") {
+        perform_replay(path, parse_pacing(&args));
+        return;
+    }
+    if let Some(num_games) = parse_number(&args, "--matrix") {
+        let reports = run_matrix(0..num_games, DEFAULT_TURN_CAP);
+        for report in &reports {
+            println!("{report}");
+        }
+        if reports.iter().any(|report| !report.findings.is_empty()) {
+            std::process::exit(1);
+        }
+        return;
+    }
+    let output = parse_output(&args);
+    let _output = output::install(output.clone());
+    if let Some(puzzle) = parse_puzzle(&args) {
+        perform_puzzle(&puzzle);
+        return;
+    }
+    let mut config = match flag_value(&args, "--seats") {
+        Some(path) => TableFile::load(Path::new(path))
+            .unwrap_or_else(|e| panic!("could not load seats from {path}, {e:?}"))
+            .config(),
+        None => GameConfig::from_preset(parse_preset(&args)),
+    };
+    if let Some(threshold) = parse_redeal_threshold(&args) {
+        config.rules.redeal_threshold = Some(threshold);
+    }
+    if let Some(rule) = parse_point_cards(&args) {
+        config.rules.point_cards = Some(rule);
+    }
+    if args.iter().any(|arg| arg == "--heads-up") {
+        config.rules.heads_up = true;
+    }
+    if let Some(teams) = parse_teams(&args) {
+        assert!(!config.rules.heads_up, "heads-up play has no teams");
+        config.rules.teams = Some(teams);
+    }
+    if let Some(num_cards) = parse_number(&args, "--exchange") {
+        config.rules.exchange_cards = num_cards as usize;
+    }
+    if let Some(stake) = parse_number(&args, "--stake") {
+        config.stake = stake;
+    }
+    if let Some(time_bank) = parse_number(&args, "--time-bank") {
+        config.time_bank = Some(Duration::from_secs(time_bank));
+    }
+    if let Some(budget) = parse_number(&args, "--ai-budget") {
+        config.ai_time_budget = Some(Duration::from_millis(budget));
+    }
+    if let Some(turns) = parse_number(&args, "--max-turns") {
+        config.max_turns = Some(turns as usize);
+    }
+    if let Some(stalemate) = parse_stalemate(&args) {
+        config.stalemate = stalemate;
+    }
+    if let Some(turns) = parse_number(&args, "--snapshot-every") {
+        config.snapshot_every = Some(turns as usize);
+    }
+    let ai_level = parse_ai_level(&args);
+    let aggressiveness = parse_aggressiveness(&args);
+    config.ai_level = ai_level;
+    config.aggressiveness = aggressiveness;
+    if let Some(num_games) = parse_number(&args, "--self-play") {
+        let samples = self_play(&config, num_games as usize);
+        write_jsonl(&samples, &mut std::io::stdout().lock()).expect("could not write samples");
+        return;
+    }
+    let mut num_games = parse_number(&args, "--games").unwrap_or(1) as usize;
+
+    let daily = args.iter().any(|arg| arg == "--daily").then(today);
+    if let Some(day) = daily {
+        // everybody plays the same Daily Challenge against the standard AI, whatever --ai or
+        // --aggressiveness say
+        config = daily_config(day);
+        num_games = 1;
+        writeln!(output, "Daily Challenge for {}", format_day(day));
+    }
+    if args.iter().any(|arg| arg == "--scripted") {
+        config.human_input = HumanInput::Scripted;
+    }
+    if daily.is_none() {
+        config.seat_draw = parse_seat_draw(&args);
+    }
+    let profile_path = parse_profile_path(&args);
+    // the Daily Challenge is the same for everybody, so it is not adapted either
+    if daily.is_none() && args.iter().any(|arg| arg == "--adaptive") {
+        let profile = Profile::load(&profile_path).expect("could not read profile");
+        config.adaptive = Some(profile.adaptive);
+    }
+    config.show_inferences = args.iter().any(|arg| arg == "--show-inferences");
+    config.show_odds = args.iter().any(|arg| arg == "--show-odds");
+    config.check_invariants = args.iter().any(|arg| arg == "--check-invariants");
+    if args.iter().any(|arg| arg == "--practice") {
+        config.house_rules.add(Arc::new(Practice));
+    }
+    config.max_attempts = parse_number(&args, "--max-attempts").map(|max| max as usize);
+    config.output = output.clone();
+
+    writeln!(output, "-------------------");
+    writeln!(output, "Welcome to Big Two!");
+    writeln!(output, "Playing {} rules", config.preset);
+    if let Some(teams) = config.rules.teams {
+        writeln!(
+            output,
+            "Seats 0 and 2 play seats 1 and 3 as teams ({teams})"
+        );
+    }
+    if args.iter().any(|arg| arg == "--cheat-sheet") {
+        write!(
+            output,
+            "{}",
+            CheatSheet::of(&config.rules, &config.house_rules)
+        );
+    }
+    for (seat, seat_config) in config.seats.iter().enumerate() {
+        writeln!(
+            output,
+            "Seat {seat}: {} ({:?})",
+            seat_config.display_name(seat),
+            seat_config.controller
+        );
+    }
+    writeln!(
+        output,
+        "Submit hands by typing the cards in e.g. \"3C 3D 3S\""
+    );
+    writeln!(output, "-------------------");
+    let summary = perform_match(&config, num_games);
+    writeln!(output, "-------------------");
+    let practice = summary.results.iter().any(GameResult::is_practice);
+    match practice {
+        true => writeln!(output, "Scores (practice): {:?}", summary.totals),
+        false => writeln!(output, "Scores: {:?}", summary.totals),
+    };
+    for (index, result) in summary.results.iter().enumerate() {
+        if !result.key_moments.is_empty() {
+            writeln!(output, "Key moments of Game {}:", index + 1);
+        }
+        for moment in &result.key_moments {
+            writeln!(output, "  {moment}");
+        }
+    }
+    for (player_id, stats) in summary.stats.iter().enumerate() {
+        for (achievement, count) in &stats.achievements {
+            writeln!(output, "Player {player_id} {achievement} (x{count})");
+        }
+    }
+    for player_id in 0..summary.totals.len() {
+        let thinking_time: Duration = summary
+            .results
+            .iter()
+            .map(|result| result.thinking_times[player_id])
+            .sum();
+        writeln!(
+            output,
+            "Player {player_id} spent {thinking_time:.1?} thinking"
+        );
+    }
+    for settlement in &summary.settlements {
+        writeln!(
+            output,
+            "Player {} pays Player {} {}",
+            settlement.payer, settlement.payee, settlement.amount
+        );
+    }
+
+    let replay = replay_path(&args, &profile_path, &summary);
+    let saved = replay
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|()| save_match(&replay, &summary));
+    if let Err(e) = &saved {
+        writeln!(
+            output,
+            "Could not save the replay to {}, {e}",
+            replay.display()
+        );
+    }
+
+    let mut profile = Profile::load(&profile_path).expect("could not read profile");
+    let human_seat = summary.seat_of(HUMAN_PLAYER_ID);
+    if practice {
+        writeln!(
+            output,
+            "This Match was played for practice, it does not count towards your stats"
+        );
+    }
+    profile.matches.push(MatchRecord::new(
+        &summary,
+        human_seat,
+        today(),
+        saved.is_ok().then_some(replay.as_path()),
+    ));
+    for result in &summary.results {
+        if result.is_practice() {
+            continue;
+        }
+        profile.stats.record(human_seat, result);
+        profile.record_rating(human_seat, result);
+        if let Some(day) = daily {
+            if !profile.record_daily(day, human_seat, result) {
+                writeln!(
+                    output,
+                    "Already played today's Daily Challenge, this score was not recorded"
+                );
+            }
+        }
+    }
+    if !practice {
+        writeln!(output, "Your rating is now {:.0}", profile.rating);
+    }
+    if let Some(adaptive) = summary.adaptive {
+        profile.adaptive = adaptive;
+    }
+    profile.save(&profile_path).expect("could not save profile");
 }
@@ -0,0 +1,106 @@
+//! A plain-text server, players join with netcat or telnet and type the same card strings as the
+//! local CLI, e.g. "nc localhost 7878". See bigtwo::server for what it says when.
+//!
+//! Flags:
+//! - "--port <n>", defaults to 7878.
+//! - "--people <n>", how many players to wait for (1 to 4), defaults to 4, the empty seats are
+//!   played by the AI.
+//! - "--preset <name>", defaults to the README rules.
+//! - "--time-bank <seconds>", each player's total thinking time for the Game, untimed by default.
+//! - "--ai <level>", how strongly the empty seats are played, "easy" (default) or "hard".
+//! - "--aggressiveness <level>", how freely the hard AI spends its 2s and Bombs, "cautious",
+//!   "balanced" (default) or "reckless".
+//! - "--seats <path>", a TOML file describing the rules and every seat (see bigtwo::seats), the
+//!   seats not played by the AI are filled by the players in the order they connect. Replaces the
+//!   flags above.
+//! - "--ai-budget <milliseconds>", how long the AI may think about each move, as long as it likes
+//!   by default. Whatever it is, the AI never thinks for longer than is left on its clock.
+//! - "--spectator-delay <seconds>", how far behind the players the spectators watch, defaults to 30.
+//!   Once every seat is taken, everybody else who connects is a spectator, and sees only what is
+//!   said to the whole Table.
+//! - "--commentary", the spectators are told what happens in commentary (see bigtwo::commentary),
+//!   e.g. "Player 2 takes control with a full house, eights over fours", rather than what the
+//!   Table says.
+//! - "--heartbeat <seconds>", how often to send each player "ping <n>", off by default. A client
+//!   that answers "pong <n>" straight away has the time its moves spend in transit (up to a second
+//!   a turn) kept off its clock, see bigtwo::latency.
+
+use std::net::TcpListener;
+use std::path::Path;
+use std::time::Duration;
+
+use bigtwo::config::GameConfig;
+use bigtwo::constants::NUM_PLAYERS;
+use bigtwo::rules::preset::RulePreset;
+use bigtwo::seats::{Controller, SeatConfig, TableFile};
+use bigtwo::server::{serve, ServerOptions, DEFAULT_SPECTATOR_DELAY};
+
+const DEFAULT_PORT: u16 = 7878;
+
+/// Returns the value following a flag, e.g. "7878" for "--port 7878".
+fn flag_value<'a>(args: &'a [String], flag: &str) -> Option<&'a String> {
+    let index = args.iter().position(|arg| arg == flag)?;
+    let value = args.get(index + 1);
+    assert!(value.is_some(), "{flag} requires a value");
+    value
+}
+
+/// Reads an optional flag, e.g. "--people 2".
+fn parse_flag<T: std::str::FromStr>(args: &[String], flag: &str) -> Option<T>
+where
+    T::Err: std::fmt::Debug,
+{
+    flag_value(args, flag).map(|value| {
+        value
+            .parse()
+            .unwrap_or_else(|e| panic!("could not understand {flag} {value}, {e:?}"))
+    })
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let port: u16 = parse_flag(&args, "--port").unwrap_or(DEFAULT_PORT);
+    let mut config = match flag_value(&args, "--seats") {
+        Some(path) => TableFile::load(Path::new(path))
+            .unwrap_or_else(|e| panic!("could not load seats from {path}, {e:?}"))
+            .config(),
+        None => {
+            let people: usize = parse_flag(&args, "--people").unwrap_or(NUM_PLAYERS);
+            assert!(
+                (1..=NUM_PLAYERS).contains(&people),
+                "--people must be between 1 and {NUM_PLAYERS}"
+            );
+            let preset: RulePreset = parse_flag(&args, "--preset").unwrap_or_default();
+            let mut config = GameConfig::from_preset(preset);
+            config.time_bank = parse_flag(&args, "--time-bank").map(Duration::from_secs);
+            config.ai_level = parse_flag(&args, "--ai").unwrap_or_default();
+            config.aggressiveness = parse_flag(&args, "--aggressiveness").unwrap_or_default();
+            config.seats = (0..NUM_PLAYERS)
+                .map(|seat| SeatConfig {
+                    controller: match seat < people {
+                        true => Controller::Network,
+                        false => Controller::Ai,
+                    },
+                    ..config.seat(seat)
+                })
+                .collect();
+            config
+        }
+    };
+    if let Some(budget) = parse_flag(&args, "--ai-budget") {
+        config.ai_time_budget = Some(Duration::from_millis(budget));
+    }
+    let listener = TcpListener::bind(("0.0.0.0", port)).expect("could not listen");
+    println!(
+        "Listening on port {port}, waiting for {} players",
+        config.people().len()
+    );
+
+    let options = ServerOptions {
+        spectator_delay: parse_flag(&args, "--spectator-delay")
+            .map_or(DEFAULT_SPECTATOR_DELAY, Duration::from_secs),
+        commentary: args.iter().any(|arg| arg == "--commentary"),
+        heartbeat: parse_flag(&args, "--heartbeat").map(Duration::from_secs),
+    };
+    serve(listener, config, options);
+}
@@ -100,6 +100,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::redundant_pattern_matching)]
     fn test_good_card_to_from_string() {
         let good_cells = ["2S", "3C", "KD", "AH", "TS", "QC", "JD"];
         for expected_cell in good_cells {
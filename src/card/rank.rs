@@ -127,6 +127,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::redundant_pattern_matching)]
     fn test_good_rank_to_from_string() {
         let good_ranks = ["3", "7", "J", "A", "2"];
         for expected_rank in good_ranks {
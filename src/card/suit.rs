@@ -85,6 +85,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::redundant_pattern_matching)]
     fn test_good_suit_to_from_string() {
         let good_suits = ["C", "D", "H", "S"];
         for expected_suit in good_suits {
@@ -0,0 +1,240 @@
+//! Turns the Plays of a Game into narration for people watching rather than playing, e.g.
+//! "Player 2 takes control with a full house, eights over fours — only 3 cards left!". Used for
+//! the spectators' feed, chat channels, and recaps of finished Games.
+
+use crate::card::rank::Rank;
+use crate::card::suit::Suit;
+use crate::constants::CARDS_PER_PLAYER;
+use crate::game::GameResult;
+use crate::hand::Hand;
+use crate::play::Play;
+use crate::scoring::Stalemate;
+use crate::trick::NUM_PLAYERS;
+
+/// What a Rank is called in commentary, one and many of them.
+fn rank_name(rank: Rank) -> (&'static str, &'static str) {
+    match rank {
+        Rank::Three => ("three", "threes"),
+        Rank::Four => ("four", "fours"),
+        Rank::Five => ("five", "fives"),
+        Rank::Six => ("six", "sixes"),
+        Rank::Seven => ("seven", "sevens"),
+        Rank::Eight => ("eight", "eights"),
+        Rank::Nine => ("nine", "nines"),
+        Rank::Ten => ("ten", "tens"),
+        Rank::Jack => ("jack", "jacks"),
+        Rank::Queen => ("queen", "queens"),
+        Rank::King => ("king", "kings"),
+        Rank::Ace => ("ace", "aces"),
+        Rank::Two => ("two", "twos"),
+    }
+}
+
+fn suit_name(suit: Suit) -> &'static str {
+    match suit {
+        Suit::Clubs => "clubs",
+        Suit::Diamonds => "diamonds",
+        Suit::Hearts => "hearts",
+        Suit::Spades => "spades",
+    }
+}
+
+/// Describes a Hand by its Ranks, e.g. "a pair of tens" or "a full house, eights over fours".
+pub fn describe_hand(hand: &Hand) -> String {
+    let groups = hand.rank_groups();
+    let single = |index: usize| rank_name(groups[index].0).0;
+    let plural = |index: usize| rank_name(groups[index].0).1;
+    match hand {
+        Hand::Pass => String::from("a pass"),
+        Hand::Lone(card) => format!("the {} of {}", single(0), suit_name(card.suit)),
+        Hand::Pair(..) => format!("a pair of {}", plural(0)),
+        Hand::Trips(..) => format!("three {}", plural(0)),
+        Hand::Straight(..) => format!("a straight, {} high", single(0)),
+        Hand::Flush(..) => format!("a flush, {} high", single(0)),
+        Hand::FullHouse(..) => format!("a full house, {} over {}", plural(0), plural(1)),
+        Hand::FourPlusKick(..) => format!("four {}", plural(0)),
+        Hand::StraightFlush(..) => format!("a straight flush, {} high", single(0)),
+    }
+}
+
+/// Returns true if the Hand is a Bomb or holds a 2, the Hands that usually win a Trick outright.
+fn is_big(hand: &Hand) -> bool {
+    matches!(hand, Hand::FourPlusKick(..) | Hand::StraightFlush(..))
+        || hand.cards().any(|card| card.rank == Rank::Two)
+}
+
+/// Narrates a Game one Play at a time, remembering what it needs to from the Plays before: the
+/// Hand to beat, and how many cards each player has left.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Commentator {
+    /// How many Plays have been narrated, see catch_up.
+    narrated: usize,
+
+    /// The Trick of the last Play narrated, and the last Hand played in it.
+    trick: Option<usize>,
+    hand_to_beat: Hand,
+
+    cards_left: [usize; NUM_PLAYERS],
+
+    /// Set once the end of the Game has been narrated.
+    concluded: bool,
+}
+
+impl Default for Commentator {
+    /// Ready for a Game dealt as usual, before its first Play.
+    fn default() -> Self {
+        Self {
+            narrated: 0,
+            trick: None,
+            hand_to_beat: Hand::Pass,
+            cards_left: [CARDS_PER_PLAYER; NUM_PLAYERS],
+            concluded: false,
+        }
+    }
+}
+
+impl Commentator {
+    /// Narrates the next Play of the Game.
+    pub fn narrate(&mut self, play: &Play) -> String {
+        self.narrated += 1;
+        let player_id = play.player_id;
+        if self.trick != Some(play.trick) {
+            self.trick = Some(play.trick);
+            self.hand_to_beat = Hand::Pass;
+        }
+        if let Hand::Pass = play.hand {
+            return match play.is_forced_pass() {
+                true => format!("Player {player_id} can't beat it and passes"),
+                false => format!("Player {player_id} passes"),
+            };
+        }
+
+        let hand = play.hand;
+        let cards_left = &mut self.cards_left[player_id];
+        *cards_left = cards_left.saturating_sub(hand.cards().len());
+        let beaten = std::mem::replace(&mut self.hand_to_beat, hand);
+        let described = describe_hand(&hand);
+        if *cards_left == 0 {
+            return format!("Player {player_id} plays {described} and goes out!");
+        }
+        let text = if let Hand::Pass = beaten {
+            format!("Player {player_id} leads with {described}")
+        } else if is_big(&hand) || std::mem::discriminant(&beaten) != std::mem::discriminant(&hand)
+        {
+            format!("Player {player_id} takes control with {described}")
+        } else {
+            format!("Player {player_id} beats it with {described}")
+        };
+        match *cards_left {
+            1 => format!("{text} — one card left!"),
+            2..=3 => format!("{text} — only {cards_left} cards left!"),
+            _ => text,
+        }
+    }
+
+    /// Narrates the end of the Game.
+    pub fn conclude(&mut self, result: &GameResult) -> String {
+        self.concluded = true;
+        let winner = result.winner;
+        match result.stalemate {
+            Some(Stalemate::Draw) => {
+                String::from("The Game is stopped at the turn cap, and nobody wins")
+            }
+            Some(Stalemate::FewestCards) => format!(
+                "The Game is stopped at the turn cap, Player {winner} wins with the fewest cards"
+            ),
+            None if result.won_by_dragon => {
+                format!("Player {winner} holds a Dragon and wins without a card played!")
+            }
+            None => format!("Player {winner} wins the Game!"),
+        }
+    }
+
+    /// Narrates every one of the Game's Plays not yet narrated, and the end of the Game once there
+    /// is a result. A Commentator narrates one Game, start a new one for the next.
+    pub fn catch_up(&mut self, plays: &[Play], result: Option<&GameResult>) -> Vec<String> {
+        let mut lines: Vec<String> = plays
+            .get(self.narrated..)
+            .unwrap_or_default()
+            .iter()
+            .map(|play| self.narrate(play))
+            .collect();
+        if let (Some(result), false) = (result, self.concluded) {
+            lines.push(self.conclude(result));
+        }
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::config::GameConfig;
+    use crate::play::PassReason;
+    use crate::tests::test_util::vec_card_from_str;
+
+    fn play(player_id: usize, hand: &str, trick: usize) -> Play {
+        Play::now(player_id, hand.parse().unwrap(), trick)
+    }
+
+    #[test]
+    fn test_describe_hand() {
+        let described = [
+            ("AS", "the ace of spades"),
+            ("6H 6D", "a pair of sixes"),
+            ("9S 9H 9C", "three nines"),
+            ("7D 6C 5S 4C 3H", "a straight, seven high"),
+            ("KS KD 8S 8H 8C", "a full house, eights over kings"),
+            ("QS 5S 5H 5D 5C", "four fives"),
+        ];
+        for (hand, description) in described {
+            assert_eq!(describe_hand(&hand.parse().unwrap()), description);
+        }
+    }
+
+    #[test]
+    fn test_commentary() {
+        let mut commentator = Commentator::default();
+        commentator.cards_left[2] = 8;
+        let plays = [
+            play(0, "3C", 0),
+            play(1, "", 0).with_pass_reason(Some(PassReason::Forced)),
+            play(2, "", 0).with_pass_reason(Some(PassReason::Voluntary)),
+            play(3, "4C", 0),
+            play(0, "", 0),
+            play(2, "8S 8H 8C 4D 4C", 1),
+            play(3, "2H", 2),
+            play(0, "", 2),
+            play(2, "2S", 2),
+            play(2, "KD KC", 3),
+        ];
+        let lines = commentator.catch_up(&plays, None);
+        assert_eq!(
+            lines,
+            [
+                "Player 0 leads with the three of clubs",
+                "Player 1 can't beat it and passes",
+                "Player 2 passes",
+                "Player 3 beats it with the four of clubs",
+                "Player 0 passes",
+                "Player 2 leads with a full house, eights over fours — only 3 cards left!",
+                "Player 3 leads with the two of hearts",
+                "Player 0 passes",
+                "Player 2 takes control with the two of spades — only 2 cards left!",
+                "Player 2 plays a pair of kings and goes out!",
+            ]
+            .map(String::from)
+        );
+        assert!(commentator.catch_up(&plays, None).is_empty());
+
+        let config = GameConfig::default();
+        let remaining = ["9D", "TD", "", "JD"].map(vec_card_from_str);
+        let result = GameResult::new(2, false, &remaining, &config);
+        assert_eq!(
+            commentator.catch_up(&plays, Some(&result)),
+            ["Player 2 wins the Game!"]
+        );
+        assert!(commentator.catch_up(&plays, Some(&result)).is_empty());
+    }
+}
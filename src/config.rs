@@ -0,0 +1,169 @@
+//! Everything the caller decides before a Game begins.
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::match_play::SeatDraw;
+use crate::output::Output;
+use crate::player::{AdaptiveAi, Aggressiveness, AiLevel, HumanInput};
+use crate::rules::{house::HouseRules, preset::RulePreset, Rules};
+use crate::scoring::Stalemate;
+use crate::seats::{Controller, SeatConfig};
+use crate::trick::NUM_PLAYERS;
+
+/// Everything the caller decides before a Game begins.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameConfig {
+    /// The regional variation the rules were taken from.
+    pub preset: RulePreset,
+
+    /// The rules in effect, starts out as the preset's rules.
+    pub rules: Rules,
+
+    /// How much is paid per point of penalty when settling up.
+    pub stake: u64,
+
+    /// Seeds the shuffle so the same cards are dealt every time, otherwise the shuffle is random.
+    pub seed: Option<u64>,
+
+    /// A chess-style clock, the total thinking time each player has for the whole Game. A player
+    /// who runs out is flagged and passes (or leads their smallest card) from then on.
+    pub time_bank: Option<Duration>,
+
+    /// How long each AI player may think about each decision, None for as long as it likes. The
+    /// hard AI samples fewer deals when it is short of time, Duration::ZERO suits fast headless
+    /// simulation.
+    #[serde(default)]
+    pub ai_time_budget: Option<Duration>,
+
+    /// How strongly the AI players play.
+    pub ai_level: AiLevel,
+
+    /// How freely the hard AI spends its control cards (2s and Bombs).
+    pub aggressiveness: Aggressiveness,
+
+    /// Adapts the AI seats to how the people at the table are doing: before every Game of a Match
+    /// they are set to its Difficulty (replacing ai_level, aggressiveness and ai_time_budget),
+    /// which is nudged after every Game. None leaves the AI as configured.
+    #[serde(default)]
+    pub adaptive: Option<AdaptiveAi>,
+
+    /// Where the human seat's moves come from.
+    pub human_input: HumanInput,
+
+    /// Strict mode for the human seat, how many invalid attempts they get each turn (and how many
+    /// lines that are not Hands they may type) before they forfeit. None lets them retry forever.
+    pub max_attempts: Option<usize>,
+
+    /// Tells the human seat, on their turn, what each opponent has shown they cannot beat by
+    /// passing, e.g. "Player 3 likely has no singles above JH".
+    #[serde(default)]
+    pub show_inferences: bool,
+
+    /// Tells the human seat, on their turn, how likely each of their legal plays is to be beaten,
+    /// see analysis::Knowledge::beat_odds.
+    #[serde(default)]
+    pub show_odds: bool,
+
+    /// Stops the Game once a Trick ends with at least this many turns taken, and scores it as the
+    /// stalemate rule says, so a buggy controller or a pathological combination of rules can't
+    /// keep a Game going forever. None lets it run until somebody goes out.
+    #[serde(default)]
+    pub max_turns: Option<usize>,
+
+    /// How a Game stopped at max_turns is scored.
+    #[serde(default)]
+    pub stalemate: Stalemate,
+
+    /// Checks the invariants every Game must keep (see sanity) after every turn. A Game that breaks
+    /// one is stopped, and the broken invariant returned (see game::try_perform_game, and
+    /// table::Table::invariant_error) rather than panicking wherever it comes to light.
+    #[serde(default)]
+    pub check_invariants: bool,
+
+    /// Writes a crash-safety snapshot of the Game to a temp file every this many turns, and a
+    /// final one if the engine panics, see snapshot. None writes no snapshots.
+    #[serde(default)]
+    pub snapshot_every: Option<usize>,
+
+    /// How the players in seats find their seats at the start of a Match.
+    #[serde(default)]
+    pub seat_draw: SeatDraw,
+
+    /// Who sits in each seat, empty for the human in seat 0 and the AI (at ai_level and
+    /// aggressiveness) everywhere else.
+    pub seats: Vec<SeatConfig>,
+
+    /// House rules beyond what Rules can describe, added by the caller.
+    #[serde(skip)]
+    pub house_rules: HouseRules,
+
+    /// Where the Game is narrated and the human seat is prompted, stdout by default.
+    #[serde(skip)]
+    pub output: Output,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self::from_preset(RulePreset::default())
+    }
+}
+
+impl GameConfig {
+    /// Returns a GameConfig using the rules of the given preset, playing for 1 per point.
+    pub fn from_preset(preset: RulePreset) -> Self {
+        Self {
+            preset,
+            rules: preset.rules(),
+            stake: 1,
+            seed: None,
+            time_bank: None,
+            ai_time_budget: None,
+            ai_level: AiLevel::default(),
+            aggressiveness: Aggressiveness::default(),
+            adaptive: None,
+            human_input: HumanInput::default(),
+            max_attempts: None,
+            show_inferences: false,
+            show_odds: false,
+            max_turns: None,
+            stalemate: Stalemate::default(),
+            check_invariants: false,
+            snapshot_every: None,
+            seat_draw: SeatDraw::default(),
+            seats: vec![],
+            house_rules: HouseRules::default(),
+            output: Output::default(),
+        }
+    }
+
+    /// The seats not played by the AI, in order.
+    pub fn people(&self) -> Vec<usize> {
+        (0..NUM_PLAYERS)
+            .filter(|seat| !self.seat(*seat).is_ai())
+            .collect()
+    }
+
+    /// Who sits in this seat.
+    pub fn seat(&self, seat: usize) -> SeatConfig {
+        match self.seats.get(seat) {
+            Some(seat_config) => seat_config.clone(),
+            None => SeatConfig {
+                name: None,
+                controller: match seat {
+                    0 => Controller::Human,
+                    _ => Controller::Ai,
+                },
+                ai_level: self.ai_level,
+                aggressiveness: self.aggressiveness,
+            },
+        }
+    }
+
+    /// Returns true if a Game that has taken this many turns must stop at the end of the Trick,
+    /// see max_turns.
+    pub fn reached_turn_cap(&self, turns: usize) -> bool {
+        self.max_turns.is_some_and(|max_turns| turns >= max_turns)
+    }
+}
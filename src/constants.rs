@@ -0,0 +1,33 @@
+//! The standard quantities of the Game, in one place so that the deck, the deal, the scoring and
+//! the server (and any variant built on them) cannot drift out of sync.
+
+pub use crate::trick::NUM_PLAYERS;
+
+/// How many cards there are in one Deck.
+pub const NUM_CARDS_IN_DECK: usize = 52;
+
+/// How many cards each player is dealt in the standard Game, one Deck between NUM_PLAYERS.
+pub const CARDS_PER_PLAYER: usize = cards_per_player(NUM_PLAYERS, 1);
+
+/// How many cards each player is dealt when this many Decks are dealt out evenly between this
+/// many players, any left over are not dealt.
+///
+/// # Panics
+/// - If there are no players.
+pub const fn cards_per_player(num_players: usize, decks: usize) -> usize {
+    NUM_CARDS_IN_DECK * decks / num_players
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_cards_per_player() {
+        assert_eq!(CARDS_PER_PLAYER, 13);
+        assert_eq!(cards_per_player(2, 1), 26);
+        assert_eq!(cards_per_player(3, 1), 17);
+        assert_eq!(cards_per_player(8, 2), 13);
+    }
+}
@@ -0,0 +1,82 @@
+//! The Daily Challenge, everybody in the world is dealt the same cards on the same (UTC) day.
+//! The human always sits in seat 0 against the standard AI, so scores can be compared.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::config::GameConfig;
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Identifies a Daily Challenge, the number of whole days since 1970-01-01 (UTC).
+pub fn day_number(now: SystemTime) -> u64 {
+    now.duration_since(UNIX_EPOCH)
+        .expect("the clock is set before 1970")
+        .as_secs()
+        / SECONDS_PER_DAY
+}
+
+/// Returns today's day number.
+pub fn today() -> u64 {
+    day_number(SystemTime::now())
+}
+
+/// Spreads consecutive day numbers out into unrelated looking seeds (SplitMix64 finalizer).
+pub fn daily_seed(day: u64) -> u64 {
+    let mut z = day.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Returns the GameConfig for the Daily Challenge of the given day, always the default rules so
+/// that every player's score is comparable.
+pub fn daily_config(day: u64) -> GameConfig {
+    GameConfig {
+        seed: Some(daily_seed(day)),
+        ..GameConfig::default()
+    }
+}
+
+/// Formats a day number as a calendar date, e.g. "2023-05-17".
+pub fn format_day(day: u64) -> String {
+    // Howard Hinnant's civil_from_days algorithm
+    let z = day as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = yoe + era * 400 + if m <= 2 { 1 } else { 0 };
+    format!("{y:04}-{m:02}-{d:02}")
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_day_number() {
+        assert_eq!(day_number(UNIX_EPOCH), 0);
+        let almost_a_day = UNIX_EPOCH + Duration::from_secs(SECONDS_PER_DAY - 1);
+        assert_eq!(day_number(almost_a_day), 0);
+        let a_day = UNIX_EPOCH + Duration::from_secs(SECONDS_PER_DAY);
+        assert_eq!(day_number(a_day), 1);
+    }
+
+    #[test]
+    fn test_daily_config() {
+        assert_eq!(daily_config(19000), daily_config(19000));
+        assert_ne!(daily_config(19000).seed, daily_config(19001).seed);
+    }
+
+    #[test]
+    fn test_format_day() {
+        assert_eq!(format_day(0), "1970-01-01");
+        assert_eq!(format_day(19494), "2023-05-17");
+        assert_eq!(format_day(11016), "2000-02-29");
+    }
+}
@@ -3,6 +3,7 @@
 use crate::card::rank::Rank;
 use crate::card::suit::Suit;
 use crate::card::Card;
+use crate::constants::NUM_CARDS_IN_DECK;
 
 /// Represents a full 52 Card Deck of Standard Playing Cards
 #[derive(Debug)]
@@ -20,7 +21,7 @@ impl Default for Deck {
 }
 impl Deck {
     pub fn new() -> Deck {
-        let mut cards: Vec<Card> = Vec::new();
+        let mut cards: Vec<Card> = Vec::with_capacity(NUM_CARDS_IN_DECK);
         let ranks = &Rank::all();
         let suits = &Suit::all();
         for rank in ranks {
@@ -44,8 +45,6 @@ mod tests {
 
     use super::*;
 
-    const NUM_CARDS_IN_DECK: usize = 52;
-
     #[test]
     fn test_new_deck() {
         let deck = Deck::new();
@@ -0,0 +1,263 @@
+//! A reset/step environment around the Table, shaped the way reinforcement learning libraries
+//! expect: the agents sit in some of the seats (one seat against the AI, or every seat for
+//! multi-agent training), each step plays one agent's chosen action, and the AI seats play on
+//! until it is an agent's turn again. Observations are self_play::features, NUM_FEATURES long.
+
+use std::fmt::{Display, Formatter};
+
+use crate::config::GameConfig;
+use crate::hand::Hand;
+use crate::scoring::penalty;
+use crate::self_play::features;
+use crate::table::Table;
+use crate::trick::NUM_PLAYERS;
+
+/// How the agents are rewarded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reward {
+    /// Each agent's net points (see scoring), once the Game is over.
+    Points,
+
+    /// 1 for the winner and -1 for everybody else, once the Game is over.
+    WinLoss,
+
+    /// As Points, plus on every step the fall in the penalty each agent would owe if the Game
+    /// ended then, so shedding cards (especially at the doubling thresholds) pays at once.
+    Shaped,
+}
+
+/// What the agent whose turn it is can see, and what they may do.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Observation {
+    /// The seat whose turn it is.
+    pub player_id: usize,
+
+    /// See self_play::features.
+    pub features: Vec<u8>,
+
+    /// Every Hand the agent may play, an action is an index into these.
+    pub legal_actions: Vec<Hand>,
+}
+
+/// What one step did.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Step {
+    /// What the next agent to play can see, None once the Game is over.
+    pub observation: Option<Observation>,
+
+    /// The reward for each seat, indexed by seat, always 0 for the AI seats.
+    pub rewards: Vec<f64>,
+
+    /// True once the Game is over.
+    pub done: bool,
+}
+
+/// Represents the ways a step cannot be taken.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnvError {
+    /// The Game is over (or was never dealt), call reset.
+    GameOver,
+
+    /// The action is not an index into the legal actions.
+    NoSuchAction(usize),
+}
+
+impl Display for EnvError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            EnvError::GameOver => write!(f, "The Game is over, reset to deal another."),
+            EnvError::NoSuchAction(action) => write!(f, "There is no legal action {action}."),
+        }
+    }
+}
+
+/// Deals Games to the agents, one after another.
+pub struct Env {
+    config: GameConfig,
+    agents: Vec<usize>,
+    reward: Reward,
+    table: Option<Table>,
+
+    /// How many Games have been dealt, each is seeded one higher than the last.
+    games: u64,
+}
+
+impl Env {
+    /// The agents sit in these seats, the AI plays the rest as the config's seats say. If the
+    /// config has a seed, the first Game is dealt by it and each one after by the next seed.
+    ///
+    /// # Panics
+    /// - If a seat is not at the Table.
+    pub fn new(config: &GameConfig, agents: &[usize], reward: Reward) -> Self {
+        assert!(
+            agents.iter().all(|seat| *seat < NUM_PLAYERS),
+            "there are only {NUM_PLAYERS} seats"
+        );
+        Self {
+            config: config.clone(),
+            agents: agents.to_vec(),
+            reward,
+            table: None,
+            games: 0,
+        }
+    }
+
+    /// Deals a new Game and plays the AI seats until it is an agent's turn. Returns what that
+    /// agent can see, None if the Game was over before any agent played (e.g. a Dragon was dealt).
+    pub fn reset(&mut self) -> Option<Observation> {
+        let config = GameConfig {
+            seed: self.config.seed.map(|seed| seed + self.games),
+            ..self.config.clone()
+        };
+        self.games += 1;
+        let mut table = Table::new(&config, &self.agents);
+        table.start();
+        self.table = Some(table);
+        self.observation()
+    }
+
+    /// What the agent whose turn it is can see, None if the Game is over.
+    pub fn observation(&self) -> Option<Observation> {
+        let table = self.table.as_ref()?;
+        if table.result().is_some() {
+            return None;
+        }
+        let view = table.turn_view();
+        Some(Observation {
+            player_id: view.player_id,
+            features: features(&view),
+            legal_actions: view.legal_plays().to_vec(),
+        })
+    }
+
+    /// Plays the legal action with this index for the agent whose turn it is, then the AI seats
+    /// until it is an agent's turn again or the Game is over.
+    pub fn step(&mut self, action: usize) -> Result<Step, EnvError> {
+        let observation = self.observation().ok_or(EnvError::GameOver)?;
+        let hand = *observation
+            .legal_actions
+            .get(action)
+            .ok_or(EnvError::NoSuchAction(action))?;
+
+        let owed_before = self.owed();
+        self.table
+            .as_mut()
+            .expect("there is an observation")
+            .play_hand(observation.player_id, hand)
+            .expect("legal actions are always playable");
+        let owed_after = self.owed();
+
+        let result = self.table.as_ref().and_then(Table::result);
+        let rewards = (0..NUM_PLAYERS)
+            .map(|seat| {
+                if !self.agents.contains(&seat) {
+                    return 0.0;
+                }
+                let shaping = match self.reward {
+                    Reward::Shaped => owed_before[seat] - owed_after[seat],
+                    _ => 0.0,
+                };
+                let outcome = match (self.reward, result) {
+                    (_, None) => 0.0,
+                    (Reward::WinLoss, Some(result)) if result.winner == seat => 1.0,
+                    (Reward::WinLoss, Some(_)) => -1.0,
+                    (_, Some(result)) => result.scores.points[seat] as f64,
+                };
+                shaping + outcome
+            })
+            .collect();
+        Ok(Step {
+            observation: self.observation(),
+            rewards,
+            done: result.is_some(),
+        })
+    }
+
+    /// The penalty each seat would owe if the Game ended now.
+    fn owed(&self) -> Vec<f64> {
+        let table = self.table.as_ref().expect("a Game has been dealt");
+        (0..NUM_PLAYERS)
+            .map(|seat| penalty(table.cards(seat), &self.config.rules) as f64)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::self_play::NUM_FEATURES;
+
+    #[test]
+    fn test_single_agent() {
+        let config = GameConfig {
+            seed: Some(3),
+            ..GameConfig::default()
+        };
+        let mut env = Env::new(&config, &[0], Reward::WinLoss);
+        assert_eq!(env.step(0), Err(EnvError::GameOver));
+
+        let mut observation = env.reset().unwrap();
+        let mut steps = 0;
+        let step = loop {
+            assert_eq!(observation.player_id, 0);
+            assert_eq!(observation.features.len(), NUM_FEATURES);
+            let action = observation.legal_actions.len();
+            assert_eq!(env.step(action), Err(EnvError::NoSuchAction(action)));
+
+            // always plays the first legal action
+            let step = env.step(0).unwrap();
+            steps += 1;
+            if step.done {
+                break step;
+            }
+            assert_eq!(step.rewards, vec![0.0; NUM_PLAYERS]);
+            observation = step.observation.unwrap();
+        };
+        assert!(steps > 1);
+        assert_eq!(step.observation, None);
+        assert!(step.rewards[0] == 1.0 || step.rewards[0] == -1.0);
+        assert_eq!(step.rewards[1..], [0.0; NUM_PLAYERS - 1]);
+        assert_eq!(env.step(0), Err(EnvError::GameOver));
+
+        // the next Game is dealt differently
+        let next = env.reset().unwrap();
+        assert_ne!(next.features, observation.features);
+    }
+
+    #[test]
+    fn test_multi_agent_shaped() {
+        let config = GameConfig {
+            seed: Some(5),
+            ..GameConfig::default()
+        };
+        let mut env = Env::new(&config, &[0, 1, 2, 3], Reward::Shaped);
+        let mut observation = env.reset().unwrap();
+        let mut total = [0.0; NUM_PLAYERS];
+        loop {
+            // every seat is an agent, so only the one who played is rewarded until the end
+            let player_id = observation.player_id;
+            let action = observation
+                .legal_actions
+                .iter()
+                .position(|hand| !matches!(hand, Hand::Pass))
+                .unwrap_or(0);
+            let step = env.step(action).unwrap();
+            for (seat, reward) in step.rewards.iter().enumerate() {
+                total[seat] += reward;
+                if seat != player_id && !step.done {
+                    assert_eq!(*reward, 0.0);
+                }
+            }
+            match step.observation {
+                Some(next) => observation = next,
+                None => break,
+            }
+        }
+        // the points sum to 0, and the shaping to the penalties shed: 13 cards tripled each at
+        // the start, less what the losers still owe (which is what the winner gained)
+        let result = env.table.as_ref().and_then(Table::result).unwrap();
+        let owed = result.scores.points[result.winner] as f64;
+        assert_eq!(total.iter().sum::<f64>(), 4.0 * 39.0 - owed);
+    }
+}
@@ -0,0 +1,166 @@
+//! The card exchange between the Games of a Match, as in Presidents: whoever lost the last Game
+//! worst gives their best cards to its winner, who gives back as many cards of their choice.
+
+use crate::card::Card;
+use crate::game::GameResult;
+use crate::output::current;
+use crate::player::Player;
+use crate::rules::Rules;
+use crate::trick::NUM_PLAYERS;
+
+/// Who gives how many cards to whom, before a Game is played.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Exchange {
+    /// The loser of the last Game, who gives their best cards.
+    pub loser: usize,
+
+    /// The winner of the last Game, who receives them and gives back cards of their choice.
+    pub winner: usize,
+
+    /// How many cards each of them gives.
+    pub num_cards: usize,
+}
+
+impl Exchange {
+    /// The exchange owed before the next Game, after this one. The loser is whoever owed the
+    /// largest penalty (then held the most cards), ignoring dead seats. None if the Rules do not
+    /// exchange cards.
+    pub fn after(result: &GameResult, rules: &Rules) -> Option<Self> {
+        if rules.exchange_cards == 0 {
+            return None;
+        }
+        let scores = &result.scores;
+        let loser = (0..scores.penalties.len())
+            .filter(|seat| *seat != result.winner && !rules.is_dead_seat(*seat))
+            .max_by_key(|seat| (scores.penalties[*seat], scores.cards_left[*seat]))?;
+        Some(Self {
+            loser,
+            winner: result.winner,
+            num_cards: rules.exchange_cards,
+        })
+    }
+}
+
+/// The num_cards highest of these cards, highest first.
+pub fn best_cards(cards: &[Card], num_cards: usize) -> Vec<Card> {
+    let mut cards = cards.to_vec();
+    cards.sort();
+    cards.into_iter().rev().take(num_cards).collect()
+}
+
+/// Returns true if these are num_cards different cards, all of them held.
+pub fn is_valid_gift(gift: &[Card], held: &[Card], num_cards: usize) -> bool {
+    let mut distinct = gift.to_vec();
+    distinct.sort();
+    distinct.dedup();
+    distinct.len() == num_cards
+        && gift.len() == num_cards
+        && gift.iter().all(|card| held.contains(card))
+}
+
+/// Moves the loser's best cards to the winner, then the cards the winner chooses (see
+/// Player::give_cards) back to the loser. Both are left with their cards sorted.
+///
+/// # Panics
+/// - If the winner chooses cards they do not hold, or the wrong number of them.
+pub(crate) fn perform_exchange(players: &mut [Player; NUM_PLAYERS], exchange: &Exchange) {
+    let Exchange {
+        loser,
+        winner,
+        num_cards,
+    } = *exchange;
+    let given = best_cards(&players[loser].cards, num_cards);
+    move_cards(players, loser, winner, &given);
+    writeln!(
+        current(),
+        "Player {loser} gives their best {} to Player {winner}",
+        cards_to_list(&given)
+    );
+
+    let given_back = (players[winner].give_cards)(&players[winner].cards, num_cards);
+    assert!(
+        is_valid_gift(&given_back, &players[winner].cards, num_cards),
+        "Player {winner} must give back {num_cards} of their own cards"
+    );
+    move_cards(players, winner, loser, &given_back);
+    writeln!(
+        current(),
+        "Player {winner} gives back {} to Player {loser}",
+        cards_to_list(&given_back)
+    );
+}
+
+/// Moves these cards from one player to the other.
+fn move_cards(players: &mut [Player; NUM_PLAYERS], from: usize, to: usize, cards: &[Card]) {
+    players[from].cards.retain(|card| !cards.contains(card));
+    players[to].cards.extend_from_slice(cards);
+    players[to].cards.sort();
+}
+
+/// Writes cards the way they are typed, e.g. "2S AH".
+fn cards_to_list(cards: &[Card]) -> String {
+    cards
+        .iter()
+        .map(|card| card.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::config::GameConfig;
+    use crate::tests::test_util::vec_card_from_str;
+
+    #[test]
+    fn test_exchange_after() {
+        let mut config = GameConfig::default();
+        let remaining = ["3D 4D", "", "5D 6D 7D 8D", "9D TD JD"].map(vec_card_from_str);
+        let result = GameResult::new(1, false, &remaining, &config);
+        assert_eq!(Exchange::after(&result, &config.rules), None);
+
+        config.rules.exchange_cards = 2;
+        assert_eq!(
+            Exchange::after(&result, &config.rules),
+            Some(Exchange {
+                loser: 2,
+                winner: 1,
+                num_cards: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_perform_exchange() {
+        let mut players = <[Player; NUM_PLAYERS]>::default();
+        players[0].cards = vec_card_from_str("3C 9D 2S");
+        players[1].cards = vec_card_from_str("4C 5D AH");
+        let exchange = Exchange {
+            loser: 0,
+            winner: 1,
+            num_cards: 2,
+        };
+        perform_exchange(&mut players, &exchange);
+        // the AI gives back its lowest cards
+        assert_eq!(players[0].cards, vec_card_from_str("3C 4C 5D"));
+        assert_eq!(players[1].cards, vec_card_from_str("9D AH 2S"));
+
+        assert_eq!(best_cards(&players[1].cards, 1), vec_card_from_str("2S"));
+        assert!(is_valid_gift(
+            &vec_card_from_str("9D"),
+            &players[1].cards,
+            1
+        ));
+        assert!(!is_valid_gift(
+            &vec_card_from_str("3C"),
+            &players[1].cards,
+            1
+        ));
+        assert!(!is_valid_gift(
+            &vec_card_from_str("9D 9D"),
+            &players[1].cards,
+            2
+        ));
+    }
+}
@@ -0,0 +1,198 @@
+//! Checks that the engine treats every seat alike, by playing the same deal again with everybody
+//! moved round the table. Rotating the players, their cards and their seat configs together must
+//! rotate the whole Game and nothing else; rotating only the players through a fixed deal shows
+//! how much sitting in a seat is worth, e.g. when benchmarking AIs against each other.
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use crate::card::Card;
+use crate::config::GameConfig;
+use crate::deck::Deck;
+use crate::game::{shuffle_and_deal_cards, GameResult};
+use crate::player::Player;
+use crate::table::Table;
+use crate::trick::NUM_PLAYERS;
+
+/// A way the same Game, played with everybody moved round the table, did not simply rotate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RotationArtifact {
+    /// How many seats everybody moved to their left.
+    pub rotation: usize,
+
+    /// What differed.
+    pub description: String,
+}
+
+/// How often each seat, and each player, won when every player played every deal from every seat.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PositionalBias {
+    /// How many Games were played.
+    pub games: usize,
+
+    /// Wins by the seat the winner sat in.
+    pub wins_by_seat: Vec<usize>,
+
+    /// Wins by player, i.e. by the seat config the player started in.
+    pub wins_by_player: Vec<usize>,
+}
+
+impl PositionalBias {
+    /// The fraction of Games won from each seat, 1 / NUM_PLAYERS each if position does not matter.
+    pub fn win_rate_by_seat(&self) -> Vec<f64> {
+        self.wins_by_seat
+            .iter()
+            .map(|wins| *wins as f64 / self.games.max(1) as f64)
+            .collect()
+    }
+
+    /// The fraction of Games won by each player.
+    pub fn win_rate_by_player(&self) -> Vec<f64> {
+        self.wins_by_player
+            .iter()
+            .map(|wins| *wins as f64 / self.games.max(1) as f64)
+            .collect()
+    }
+}
+
+/// Moves whatever sits in each seat `rotation` seats to the left.
+fn rotate<T: Clone>(seats: &[T], rotation: usize) -> Vec<T> {
+    let n = seats.len();
+    (0..n)
+        .map(|seat| seats[(seat + n - rotation % n) % n].clone())
+        .collect()
+}
+
+/// The cards a seed deals to each seat.
+pub(crate) fn seeded_deal(seed: u64) -> [Vec<Card>; NUM_PLAYERS] {
+    let mut players = <[Player; NUM_PLAYERS]>::default();
+    shuffle_and_deal_cards(&mut players, Deck::new(), &mut StdRng::seed_from_u64(seed));
+    players.map(|player| player.cards)
+}
+
+/// Plays a Game of AI players to the end, with these seats and cards.
+pub(crate) fn play_deal(config: &GameConfig, deal: [Vec<Card>; NUM_PLAYERS]) -> GameResult {
+    let mut table = Table::from_deal(config, &[], deal);
+    table.start();
+    table
+        .result()
+        .expect("AI only Games play to the end")
+        .clone()
+}
+
+/// The config with every seat spelled out, moved `rotation` seats to the left.
+fn rotated_config(config: &GameConfig, rotation: usize) -> GameConfig {
+    let seats: Vec<_> = (0..NUM_PLAYERS).map(|seat| config.seat(seat)).collect();
+    GameConfig {
+        seats: rotate(&seats, rotation),
+        ..config.clone()
+    }
+}
+
+/// Plays the config's deal (seed 0 if it has none) from every rotation, each player taking their
+/// cards and seat config with them, and returns every way the Games differ beyond the rotation:
+/// who won, the points, and who played what.
+pub fn check_rotations(config: &GameConfig) -> Vec<RotationArtifact> {
+    let deal = seeded_deal(config.seed.unwrap_or(0));
+    let original = play_deal(&rotated_config(config, 0), deal.clone());
+
+    let mut artifacts = vec![];
+    for rotation in 1..NUM_PLAYERS {
+        let rotated_deal = rotate(&deal, rotation).try_into().unwrap();
+        let result = play_deal(&rotated_config(config, rotation), rotated_deal);
+        let mut differs = |description: String| {
+            artifacts.push(RotationArtifact {
+                rotation,
+                description,
+            })
+        };
+
+        let expected_winner = (original.winner + rotation) % NUM_PLAYERS;
+        if result.winner != expected_winner {
+            differs(format!(
+                "Player {} won instead of Player {expected_winner}",
+                result.winner
+            ));
+        }
+        if result.scores.points != rotate(&original.scores.points, rotation) {
+            differs(format!(
+                "points were {:?} instead of {:?}",
+                result.scores.points,
+                rotate(&original.scores.points, rotation)
+            ));
+        }
+        let turn = original.plays.iter().zip(&result.plays).position(|(a, b)| {
+            (a.player_id + rotation) % NUM_PLAYERS != b.player_id || a.hand != b.hand
+        });
+        if let Some(turn) = turn {
+            differs(format!("the Games went differently from turn {turn}"));
+        } else if original.plays.len() != result.plays.len() {
+            differs(format!(
+                "the Game took {} turns instead of {}",
+                result.plays.len(),
+                original.plays.len()
+            ));
+        }
+    }
+    artifacts
+}
+
+/// Plays num_deals deals (seeded from the config's seed, or 0, upwards), each once with the
+/// players rotated through every seat while the cards stay where they were dealt, so every player
+/// plays every hand from every seat. Returns the wins by seat and by player.
+pub fn positional_bias(config: &GameConfig, num_deals: usize) -> PositionalBias {
+    let mut bias = PositionalBias {
+        games: 0,
+        wins_by_seat: vec![0; NUM_PLAYERS],
+        wins_by_player: vec![0; NUM_PLAYERS],
+    };
+    let first_seed = config.seed.unwrap_or(0);
+    for seed in first_seed..first_seed + num_deals as u64 {
+        let deal = seeded_deal(seed);
+        for rotation in 0..NUM_PLAYERS {
+            let result = play_deal(&rotated_config(config, rotation), deal.clone());
+            bias.games += 1;
+            bias.wins_by_seat[result.winner] += 1;
+            bias.wins_by_player[(result.winner + NUM_PLAYERS - rotation) % NUM_PLAYERS] += 1;
+        }
+    }
+    bias
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::player::AiLevel;
+
+    #[test]
+    fn test_rotate() {
+        assert_eq!(rotate(&[0, 1, 2, 3], 1), vec![3, 0, 1, 2]);
+        assert_eq!(rotate(&[0, 1, 2, 3], 4), vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_rotations_agree() {
+        for ai_level in [AiLevel::Easy, AiLevel::Hard] {
+            for seed in 0..4 {
+                let config = GameConfig {
+                    seed: Some(seed),
+                    ai_level,
+                    ..GameConfig::default()
+                };
+                assert_eq!(check_rotations(&config), vec![], "{ai_level} seed {seed}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_positional_bias() {
+        let bias = positional_bias(&GameConfig::default(), 3);
+        assert_eq!(bias.games, 3 * NUM_PLAYERS);
+        assert_eq!(bias.wins_by_seat.iter().sum::<usize>(), bias.games);
+        assert_eq!(bias.wins_by_player.iter().sum::<usize>(), bias.games);
+        // identical players win every deal from the same seat, whoever sits there
+        assert!(bias.wins_by_seat.iter().all(|wins| wins % NUM_PLAYERS == 0));
+        assert_eq!(bias.win_rate_by_seat().iter().sum::<f64>(), 1.0);
+    }
+}
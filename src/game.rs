@@ -1,49 +1,441 @@
 //! Run the entire Game Loop.
 
-use crate::card::THREE_OF_CLUBS;
+use std::panic::{catch_unwind, resume_unwind, AssertUnwindSafe};
+use std::time::Duration;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+
+use crate::analysis::deal_quality::DealQuality;
+use crate::audit::GameMeta;
+use crate::card::rank::Rank;
+use crate::card::{Card, THREE_OF_CLUBS};
+use crate::config::GameConfig;
 use crate::deck::Deck;
-use crate::player::Player;
-use crate::trick::{perform_trick, TrickResult, NUM_PLAYERS};
+use crate::exchange::{perform_exchange, Exchange};
+use crate::output::{self, current};
+use crate::play::Play;
+use crate::player::{HumanInput, Player};
+use crate::recap::{find_key_moments, KeyMoment};
+use crate::rules::{is_dragon, is_hopeless, Rules};
+use crate::sanity::InvariantError;
+use crate::scoring::{
+    captured_points, pool_team_scores, score_game, score_stalemate, settle, ScoreSheet, Settlement,
+    Stalemate,
+};
+use crate::seats::Controller;
+use crate::snapshot::Snapshotter;
+use crate::stats::{find_achievements, EarnedAchievement};
+use crate::trick::{next_leader, perform_trick, Seat, TrickResult, NUM_PLAYERS};
+
+/// Returned at the end of the Game, describes who won and how everybody scored.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameResult {
+    /// The player who went out first (or was dealt a winning Dragon). If the Game was stopped at
+    /// the turn cap, the player holding the fewest cards.
+    pub winner: usize,
+
+    /// True if the winner won immediately by being dealt a Dragon.
+    pub won_by_dragon: bool,
+
+    /// The scores of every player.
+    pub scores: ScoreSheet,
+
+    /// Who pays whom how much, at the configured stake.
+    pub settlements: Vec<Settlement>,
+
+    /// Every notable feat performed during the Game.
+    pub achievements: Vec<EarnedAchievement>,
+
+    /// The total time each player spent deciding what to play, as charged to their clocks (after
+    /// any latency allowance).
+    pub thinking_times: Vec<Duration>,
+
+    /// The total wall-clock time each player spent deciding what to play, including any time
+    /// their moves spent in transit.
+    #[serde(default)]
+    pub raw_thinking_times: Vec<Duration>,
+
+    /// Every turn of the Game in order, enough to replay it.
+    pub plays: Vec<Play>,
+
+    /// The points each player captured in the Tricks they won, all 0 unless the Rules count
+    /// point cards.
+    #[serde(default)]
+    pub captured_points: Vec<u32>,
+
+    /// Identifies the Game and what it was played with, the default for Games recorded before
+    /// they were identified.
+    #[serde(default)]
+    pub meta: GameMeta,
+
+    /// How the Game was scored if it was stopped at the turn cap (see GameConfig::max_turns), None
+    /// if somebody went out.
+    #[serde(default)]
+    pub stalemate: Option<Stalemate>,
+
+    /// The turns worth jumping to in a replay, see recap::find_key_moments.
+    #[serde(default)]
+    pub key_moments: Vec<KeyMoment>,
+}
+
+impl GameResult {
+    /// Scores the Game and settles up at the configured stake.
+    pub fn new(
+        winner: usize,
+        won_by_dragon: bool,
+        remaining: &[Vec<Card>],
+        config: &GameConfig,
+    ) -> Self {
+        let mut scores = score_game(remaining, &config.rules);
+        pool_team_scores(&mut scores, winner, &config.rules);
+        config.house_rules.score_game(&mut scores, remaining);
+        Self::from_scores(winner, won_by_dragon, scores, config)
+    }
+
+    /// Scores a Game stopped at the turn cap as the Stalemate rule says (see score_stalemate), a
+    /// Draw stays at 0 points whatever the team and house rules.
+    pub fn stalemated(stalemate: Stalemate, remaining: &[Vec<Card>], config: &GameConfig) -> Self {
+        let (winner, mut scores) = score_stalemate(remaining, stalemate, &config.rules);
+        if stalemate == Stalemate::FewestCards {
+            pool_team_scores(&mut scores, winner, &config.rules);
+            config.house_rules.score_game(&mut scores, remaining);
+        }
+        Self {
+            stalemate: Some(stalemate),
+            ..Self::from_scores(winner, false, scores, config)
+        }
+    }
+
+    /// Returns true if the Game was stopped at the turn cap and declared a Draw, nobody won.
+    pub fn is_draw(&self) -> bool {
+        self.stalemate == Some(Stalemate::Draw)
+    }
+
+    /// Returns true if the Game was played for practice, and so counts towards no ratings.
+    pub fn is_practice(&self) -> bool {
+        self.meta.practice
+    }
+
+    /// Settles the scores up at the configured stake.
+    fn from_scores(
+        winner: usize,
+        won_by_dragon: bool,
+        scores: ScoreSheet,
+        config: &GameConfig,
+    ) -> Self {
+        let remaining = scores.cards_left.len();
+        let settlements = settle(&scores, config.stake, &config.rules);
+        Self {
+            winner,
+            won_by_dragon,
+            scores,
+            settlements,
+            achievements: vec![],
+            thinking_times: vec![Duration::ZERO; remaining],
+            raw_thinking_times: vec![Duration::ZERO; remaining],
+            plays: vec![],
+            captured_points: vec![0; remaining],
+            meta: GameMeta::new(config, config.seed),
+            stalemate: None,
+            key_moments: vec![],
+        }
+    }
+}
 
 /// Run the entire Game Loop.
-/// 1. Generate 4 Players (3 NPC and 1 PC)
+/// 1. Generate 4 Players, seated as the config describes (by default 3 NPC and 1 PC)
 /// 2. Generate a Deck of 52-Standard-Playing-Cards
-/// 3. Shuffle the Deck and deal 13 cards to each player
-/// 4. Perform Tricks in a loop until a Trick returns GameOver
-/// 5. TODO: return Scores.
-pub fn perform_game() {
+/// 3. Shuffle the Deck and deal CARDS_PER_PLAYER cards to each player
+/// 4. If the Rules allow it, a player dealt a hopeless hand may call for a redeal
+/// 5. If the Rules allow it, a player dealt a Dragon wins immediately
+/// 6. Perform Tricks in a loop until a Trick returns GameOver
+/// 7. Return the GameResult with everybody's Scores.
+///
+/// # Panics
+/// - If config.check_invariants is set and the Game breaks one, see try_perform_game.
+pub fn perform_game(config: &GameConfig) -> GameResult {
+    perform_game_with_exchange(config, None)
+}
+
+/// Run the entire Game Loop as perform_game does, but return the invariant the Game broke (only
+/// checked if config.check_invariants is set, see sanity) rather than panicking.
+pub fn try_perform_game(config: &GameConfig) -> Result<GameResult, InvariantError> {
+    try_perform_game_with_exchange(config, None)
+}
+
+/// Run the entire Game Loop as perform_game does, but first exchange cards as the last Game of the
+/// Match left owing (see exchange::Exchange::after), once the cards are dealt.
+///
+/// # Panics
+/// - If config.check_invariants is set and the Game breaks one, see
+///   try_perform_game_with_exchange.
+pub fn perform_game_with_exchange(config: &GameConfig, exchange: Option<&Exchange>) -> GameResult {
+    try_perform_game_with_exchange(config, exchange)
+        .unwrap_or_else(|e| panic!("the Game broke an invariant, {e}"))
+}
+
+/// Run the entire Game Loop as perform_game_with_exchange does, but return the invariant the Game
+/// broke rather than panicking.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(skip_all, fields(preset = %config.preset, seed = ?config.seed))
+)]
+pub fn try_perform_game_with_exchange(
+    config: &GameConfig,
+    exchange: Option<&Exchange>,
+) -> Result<GameResult, InvariantError> {
+    let _output = output::install(config.output.clone());
     let mut players = <[Player; NUM_PLAYERS]>::default();
-    players[0].convert_to_stdio_user();
+    for (seat, player) in players.iter_mut().enumerate() {
+        let seat_config = config.seat(seat);
+        match seat_config.controller {
+            Controller::Ai => {
+                player.convert_to_ai(seat_config.ai_level, seat_config.aggressiveness);
+            }
+            Controller::Human => player.convert_to_human(config.human_input),
+            Controller::Scripted => player.convert_to_scripted_user(),
+            Controller::Network => panic!("seat {seat} is played over the network, use the server"),
+        }
+        if !seat_config.is_ai() {
+            player.max_attempts = config.max_attempts;
+            player.show_inferences = config.show_inferences;
+            player.show_odds = config.show_odds;
+            player.stand_in = (seat_config.ai_level, seat_config.aggressiveness);
+        } else {
+            player.time_budget = config.ai_time_budget;
+        }
+        player.time_left = config.time_bank;
+    }
+
+    // a seeded Game always deals the same cards, an unseeded one is given a seed to record
+    let seed = config.seed.unwrap_or_else(rand::random);
+    let mut rng = StdRng::seed_from_u64(seed);
+    let meta = GameMeta::new(config, Some(seed));
+    #[cfg(feature = "tracing")]
+    tracing::info!(id = %meta.id, seed, "game started");
+    deal_cards(&mut players, &config.rules, &mut rng);
 
-    shuffle_and_deal_cards(&mut players, Deck::new());
+    // in practice, each person may have their cards dealt again (see rules::house::Practice)
+    let mut mulligans = [config.house_rules.mulligans(); NUM_PLAYERS];
+    while let Some(caller) = find_player_calling_mulligan(&players, &mulligans, config) {
+        mulligans[caller] -= 1;
+        writeln!(
+            current(),
+            "Player {caller} takes a mulligan (practice), the cards are dealt again"
+        );
+        deal_again(&mut players, &mut rng);
+    }
+
+    if let Some(exchange) = exchange {
+        perform_exchange(&mut players, exchange);
+    }
+
+    // tells the person at the keyboard how good their cards are
+    for (seat, player) in players.iter().enumerate() {
+        let at_keyboard = config.seat(seat).controller == Controller::Human
+            && config.human_input == HumanInput::Interactive;
+        if at_keyboard && !config.rules.is_dead_seat(seat) {
+            writeln!(
+                current(),
+                "=== You were dealt {}",
+                DealQuality::of(&player.cards, &config.rules)
+            );
+        }
+    }
 
-    let mut starting_player_idx = find_player_with_three_of_clubs(&players);
-    println!("Player {starting_player_idx} has the Three of Clubs and may begin");
-    let mut is_first_trick_of_game = true;
+    // in practice, each person may look at an opponent's cards
+    for seat in (0..NUM_PLAYERS).filter(|seat| takes_practice(*seat, config)) {
+        let opponents: Vec<usize> = (0..NUM_PLAYERS)
+            .filter(|other| *other != seat && !config.rules.is_dead_seat(*other))
+            .collect();
+        for _ in 0..config.house_rules.peeks() {
+            let player = &players[seat];
+            let Some(peeked) = (player.choose_peek)(&player.cards, &opponents) else {
+                break;
+            };
+            writeln!(
+                current(),
+                "Player {seat} peeks at Player {peeked}'s cards (practice)"
+            );
+            writeln!(current(), "{}", peek_reveal(peeked, &players[peeked].cards));
+        }
+    }
 
-    let winner: usize = loop {
-        let trick_result = perform_trick(starting_player_idx, &mut players, is_first_trick_of_game);
-        is_first_trick_of_game = false;
+    if config.rules.dragon_is_natural_win {
+        if let Some(winner) = find_player_with_dragon(&players, &config.rules) {
+            #[cfg(feature = "tracing")]
+            tracing::info!(winner, "dragon");
+            writeln!(
+                current(),
+                "Player {winner} was dealt a Dragon and wins immediately!!"
+            );
+            let remaining = remaining_after_dragon(&players, winner);
+            return Ok(finish_game(
+                GameResult::new(winner, true, &remaining, config),
+                meta,
+                &players,
+                vec![],
+                config,
+            ));
+        }
+    }
+
+    let opening_player = find_opening_player(&players, &config.rules);
+    writeln!(
+        current(),
+        "{}",
+        opening_announcement(opening_player, &players[opening_player])
+    );
+    let mut starting_player =
+        Seat::new(opening_player, NUM_PLAYERS).expect("the opening player is seated");
+    let mut history: Vec<Play> = vec![];
+    let mut snapshotter = Snapshotter::new(config, &players);
+
+    // None once the Game reaches the turn cap
+    let winner: Option<usize> = loop {
+        let trick_result = catch_unwind(AssertUnwindSafe(|| {
+            perform_trick(
+                starting_player,
+                &mut players,
+                &mut history,
+                &config.rules,
+                &config.house_rules,
+                config.check_invariants,
+            )
+        }));
+        let trick_result = match trick_result {
+            Ok(trick_result) => trick_result,
+            Err(payload) => {
+                if let Some(snapshotter) = &snapshotter {
+                    snapshotter.after_panic(&players, &history, payload.as_ref());
+                }
+                resume_unwind(payload)
+            }
+        };
+        if let Some(snapshotter) = &mut snapshotter {
+            snapshotter.after_trick(&players, &history);
+        }
         match trick_result {
-            TrickResult::GameOver(winner) => break winner,
-            TrickResult::NewTrick(new_starting_player_idx) => {
-                starting_player_idx = new_starting_player_idx;
-                println!("Player {starting_player_idx} wins the trick (everybody else passed) and starts the next trick");
+            TrickResult::GameOver(winner) => break Some(winner.index()),
+            TrickResult::Invalid(e) => {
+                #[cfg(feature = "tracing")]
+                tracing::error!(error = %e, "invariant broken");
+                return Err(e);
+            }
+            TrickResult::NewTrick(trick_winner) => {
+                starting_player = next_leader(trick_winner, &players, &config.rules);
+                writeln!(
+                    current(),
+                    "{}",
+                    trick_won_announcement(trick_winner.index(), starting_player.index())
+                );
+                if config.reached_turn_cap(history.len()) {
+                    break None;
+                }
             }
         }
     };
 
-    println!("Game Over, Player {winner} wins!!");
+    let remaining: Vec<Vec<Card>> = players.iter().map(|player| player.cards.clone()).collect();
+    let result = match winner {
+        Some(winner) => {
+            writeln!(current(), "Game Over, Player {winner} wins!!");
+            GameResult::new(winner, false, &remaining, config)
+        }
+        None => {
+            let result = GameResult::stalemated(config.stalemate, &remaining, config);
+            writeln!(current(), "{}", stalemate_announcement(&result));
+            result
+        }
+    };
+    #[cfg(feature = "tracing")]
+    tracing::info!(winner = result.winner, stalemate = ?result.stalemate, "game over");
+    if let Some(snapshotter) = snapshotter {
+        snapshotter.finish();
+    }
+
+    Ok(finish_game(result, meta, &players, history, config))
+}
+
+/// Records every Play and what identifies the Game, totals up the clocks and captured points, and
+/// finds the Achievements earned during the Game and announces them.
+fn finish_game(
+    mut result: GameResult,
+    meta: GameMeta,
+    players: &[Player; NUM_PLAYERS],
+    history: Vec<Play>,
+    config: &GameConfig,
+) -> GameResult {
+    result.meta = meta;
+    result.thinking_times = players.iter().map(|player| player.thinking_time).collect();
+    result.raw_thinking_times = players
+        .iter()
+        .map(|player| player.raw_thinking_time)
+        .collect();
+    result.plays = history;
+    result.captured_points = players
+        .iter()
+        .map(|player| captured_points(&player.captured, &config.rules))
+        .collect();
+    if let Some(rule) = config.rules.point_cards {
+        writeln!(
+            current(),
+            "Points captured ({rule}): {:?}",
+            result.captured_points
+        );
+    }
+    result.key_moments = find_key_moments(&result.plays);
+    result.achievements = find_achievements(&result);
+    for earned in &result.achievements {
+        writeln!(
+            current(),
+            "Player {} {}!",
+            earned.player_id,
+            earned.achievement
+        );
+    }
+    result
+}
+
+/// Shuffles and deals the cards, then if the Rules allow it, deals them again for as long as a
+/// player dealt a hopeless hand calls for a redeal.
+pub(crate) fn deal_cards(players: &mut [Player; NUM_PLAYERS], rules: &Rules, rng: &mut StdRng) {
+    writeln!(current(), "Dealing Cards...");
+    shuffle_and_deal_cards(players, Deck::new(), rng);
+
+    if let Some(threshold) = rules.redeal_threshold {
+        while let Some(caller) = find_player_calling_redeal(players, threshold, rules) {
+            #[cfg(feature = "tracing")]
+            tracing::info!(caller, %threshold, "redeal");
+            writeln!(
+                current(),
+                "Player {caller} has no card higher than {threshold} and calls for a redeal"
+            );
+            deal_again(players, rng);
+        }
+    }
+}
+
+/// Takes everybody's cards back and deals again.
+pub(crate) fn deal_again(players: &mut [Player; NUM_PLAYERS], rng: &mut StdRng) {
+    for player in players.iter_mut() {
+        player.cards.clear();
+    }
+    writeln!(current(), "Dealing Cards...");
+    shuffle_and_deal_cards(players, Deck::new(), rng);
 }
 
 /// Shuffle and Deal the cards just like a regular human dealer.
-/// All players will receive 13 Cards each.
-fn shuffle_and_deal_cards(players: &mut [Player; NUM_PLAYERS], mut deck: Deck) {
-    println!("Dealing Cards...");
+/// All players will receive CARDS_PER_PLAYER Cards each.
+pub(crate) fn shuffle_and_deal_cards(
+    players: &mut [Player; NUM_PLAYERS],
+    mut deck: Deck,
+    rng: &mut StdRng,
+) {
     use rand::seq::SliceRandom;
-    use rand::thread_rng;
-    let mut rng = thread_rng();
-    deck.cards[..].shuffle(&mut rng);
+    deck.cards[..].shuffle(rng);
 
     let mut player_index: usize = 0;
     while let Some(card) = deck.cards.pop() {
@@ -55,15 +447,335 @@ fn shuffle_and_deal_cards(players: &mut [Player; NUM_PLAYERS], mut deck: Deck) {
         player.cards.sort();
     }
     assert_eq!(deck.cards.len(), 0);
+    #[cfg(feature = "tracing")]
+    tracing::debug!("dealt");
 }
 
 ///  Used to identify the player who was dealt the Three Of Clubs.
-///  The game can only begin with the player that has the Three of Clubs.
-fn find_player_with_three_of_clubs(players: &[Player; NUM_PLAYERS]) -> usize {
-    for (index, player) in players.iter().enumerate() {
-        if player.cards.contains(&THREE_OF_CLUBS) {
-            return index;
+///  The game can only begin with the player that has the Three of Clubs, or in heads-up play when
+///  it is dead, the player dealt the lowest card.
+pub(crate) fn find_opening_player(players: &[Player; NUM_PLAYERS], rules: &Rules) -> usize {
+    (0..NUM_PLAYERS)
+        .filter(|seat| !rules.is_dead_seat(*seat))
+        .min_by_key(|seat| players[*seat].cards[0])
+        .unwrap()
+}
+
+/// Announces who won the Trick, and who starts the next one (their partner, if they went out).
+pub(crate) fn trick_won_announcement(winner: usize, leader: usize) -> String {
+    if winner == leader {
+        format!("Player {winner} wins the trick (everybody else passed) and starts the next trick")
+    } else {
+        format!("Player {winner} wins the trick (everybody else passed) and their partner, Player {leader}, starts the next trick")
+    }
+}
+
+/// Announces that the Game reached the turn cap, and how it was scored.
+pub(crate) fn stalemate_announcement(result: &GameResult) -> String {
+    match result.stalemate {
+        Some(Stalemate::FewestCards) => format!(
+            "Game Over, the turn cap was reached, Player {} wins holding the fewest cards!!",
+            result.winner
+        ),
+        _ => String::from("Game Over, the turn cap was reached, the Game is a draw"),
+    }
+}
+
+/// Announces who begins the Game, and with which card.
+pub(crate) fn opening_announcement(player_id: usize, player: &Player) -> String {
+    match player.cards[0] {
+        THREE_OF_CLUBS => format!("Player {player_id} has the Three of Clubs and may begin"),
+        lowest => format!("Player {player_id} has the lowest card, {lowest}, and may begin"),
+    }
+}
+
+/// Returns true if the seat is played by a person who may take a mulligan or peek in practice.
+fn takes_practice(seat: usize, config: &GameConfig) -> bool {
+    config.house_rules.is_practice()
+        && !config.seat(seat).is_ai()
+        && !config.rules.is_dead_seat(seat)
+}
+
+/// Used to identify a person in practice with a mulligan left who chooses to take it.
+fn find_player_calling_mulligan(
+    players: &[Player; NUM_PLAYERS],
+    mulligans: &[usize; NUM_PLAYERS],
+    config: &GameConfig,
+) -> Option<usize> {
+    (0..NUM_PLAYERS).find(|seat| {
+        let player = &players[*seat];
+        mulligans[*seat] > 0
+            && takes_practice(*seat, config)
+            && (player.call_mulligan)(&player.cards, &config.rules)
+    })
+}
+
+/// Shows the player peeking in practice the cards of the player they peeked at.
+pub(crate) fn peek_reveal(peeked: usize, cards: &[Card]) -> String {
+    let cards: Vec<String> = cards.iter().map(Card::to_string).collect();
+    format!("=== Player {peeked} holds {}", cards.join(" "))
+}
+
+/// Used to identify a player who was dealt a hopeless hand and chooses to call for a redeal.
+fn find_player_calling_redeal(
+    players: &[Player; NUM_PLAYERS],
+    threshold: Rank,
+    rules: &Rules,
+) -> Option<usize> {
+    (0..NUM_PLAYERS).find(|seat| {
+        let player = &players[*seat];
+        !rules.is_dead_seat(*seat)
+            && is_hopeless(&player.cards, threshold)
+            && (player.call_redeal)(&player.cards, rules)
+    })
+}
+
+///  Used to identify a player who was dealt a Dragon (one card of every Rank).
+pub(crate) fn find_player_with_dragon(
+    players: &[Player; NUM_PLAYERS],
+    rules: &Rules,
+) -> Option<usize> {
+    (0..NUM_PLAYERS).find(|seat| !rules.is_dead_seat(*seat) && is_dragon(&players[*seat].cards))
+}
+
+/// A Dragon ends the Game before anybody plays, so the winner is treated as holding 0 cards.
+pub(crate) fn remaining_after_dragon(
+    players: &[Player; NUM_PLAYERS],
+    winner: usize,
+) -> Vec<Vec<Card>> {
+    players
+        .iter()
+        .enumerate()
+        .map(|(player_id, player)| {
+            if player_id == winner {
+                vec![]
+            } else {
+                player.cards.clone()
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::constants::CARDS_PER_PLAYER;
+    use crate::output::{Buffer, Output};
+    use crate::rules::house::Practice;
+    use crate::rules::preset::RulePreset;
+    use crate::rules::TeamPlay;
+    use crate::seats::SeatConfig;
+    use crate::tests::test_util::vec_card_from_str;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_seeded_deal_is_repeatable() {
+        let mut first = <[Player; NUM_PLAYERS]>::default();
+        shuffle_and_deal_cards(&mut first, Deck::new(), &mut StdRng::seed_from_u64(7));
+        let mut second = <[Player; NUM_PLAYERS]>::default();
+        shuffle_and_deal_cards(&mut second, Deck::new(), &mut StdRng::seed_from_u64(7));
+        let mut third = <[Player; NUM_PLAYERS]>::default();
+        shuffle_and_deal_cards(&mut third, Deck::new(), &mut StdRng::seed_from_u64(8));
+
+        for player_id in 0..NUM_PLAYERS {
+            assert_eq!(first[player_id].cards.len(), CARDS_PER_PLAYER);
+            assert_eq!(first[player_id].cards, second[player_id].cards);
+        }
+        assert!((0..NUM_PLAYERS).any(|id| first[id].cards != third[id].cards));
+    }
+
+    #[test]
+    fn test_snapshots_do_not_change_the_game() {
+        let config = GameConfig {
+            seats: vec![SeatConfig::default(); NUM_PLAYERS],
+            seed: Some(2),
+            ..GameConfig::default()
+        };
+        let snapshotted = GameConfig {
+            snapshot_every: Some(5),
+            ..config.clone()
+        };
+        let result = perform_game(&snapshotted);
+        let again = perform_game(&config);
+        assert_eq!(result.plays.len(), again.plays.len());
+        assert_eq!(result.winner, again.winner);
+
+        // the same seed, but a different Game
+        assert_eq!(result.meta.seed, Some(2));
+        assert_ne!(result.meta.id, again.meta.id);
+
+        // an unseeded Game records the seed it was dealt with
+        let unseeded = perform_game(&GameConfig {
+            seed: None,
+            ..config
+        });
+        assert!(unseeded.meta.seed.is_some());
+    }
+
+    #[test]
+    fn test_output_is_configured() {
+        let buffer = Buffer::default();
+        let config = GameConfig {
+            seats: vec![SeatConfig::default(); NUM_PLAYERS],
+            seed: Some(2),
+            output: Output::to(buffer.clone()),
+            ..GameConfig::default()
+        };
+        let result = perform_game(&config);
+        let printed = buffer.contents();
+        assert!(printed.starts_with("Dealing Cards...\n"), "{printed}");
+        let game_over = format!("Game Over, Player {} wins!!", result.winner);
+        assert!(printed.contains(&game_over), "{printed}");
+    }
+
+    #[test]
+    fn test_turn_cap() {
+        let buffer = Buffer::default();
+        let mut config = GameConfig {
+            seats: vec![SeatConfig::default(); NUM_PLAYERS],
+            seed: Some(2),
+            max_turns: Some(1),
+            output: Output::to(buffer.clone()),
+            ..GameConfig::default()
+        };
+        let result = perform_game(&config);
+        assert_eq!(result.stalemate, Some(Stalemate::Draw));
+        assert!(result.is_draw());
+        assert!(result.scores.points.iter().all(|&points| points == 0));
+        assert!(buffer
+            .contents()
+            .contains("the turn cap was reached, the Game is a draw"));
+
+        // the same first Trick, scored by who holds the fewest cards
+        config.stalemate = Stalemate::FewestCards;
+        let capped = perform_game(&config);
+        let hands = |result: &GameResult| {
+            result
+                .plays
+                .iter()
+                .map(|play| play.hand)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(hands(&capped), hands(&result));
+        let fewest = capped.scores.cards_left.iter().min().unwrap();
+        assert_eq!(capped.scores.cards_left[capped.winner], *fewest);
+        assert_eq!(capped.scores.points.iter().sum::<i32>(), 0);
+        assert!(!capped.is_draw());
+    }
+
+    #[test]
+    fn test_check_invariants() {
+        // every preset, and the variants that sit players out, keep every invariant
+        for preset in RulePreset::all() {
+            let mut config = GameConfig {
+                seats: vec![SeatConfig::default(); NUM_PLAYERS],
+                check_invariants: true,
+                ..GameConfig::from_preset(preset)
+            };
+            for seed in 0..4 {
+                config.seed = Some(seed);
+                config.rules.teams = (seed % 2 == 1).then_some(TeamPlay::BothOut);
+                config.rules.heads_up = seed == 2;
+                let result = try_perform_game(&config);
+                assert!(result.is_ok(), "{preset} {seed}: {result:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_practice() {
+        // the AI neither peeks nor takes a mulligan, so practice deals and plays the same Game
+        let mut config = GameConfig {
+            seats: vec![SeatConfig::default(); NUM_PLAYERS],
+            seed: Some(5),
+            ..GameConfig::default()
+        };
+        let rated = perform_game(&config);
+        config.house_rules.add(Arc::new(Practice));
+        let practice = perform_game(&config);
+        assert!(!rated.is_practice());
+        assert!(practice.is_practice());
+        assert_eq!(
+            practice
+                .plays
+                .iter()
+                .map(|play| play.hand)
+                .collect::<Vec<_>>(),
+            rated.plays.iter().map(|play| play.hand).collect::<Vec<_>>()
+        );
+
+        // only a person with a mulligan left may take one
+        let mut players = <[Player; NUM_PLAYERS]>::default();
+        shuffle_and_deal_cards(&mut players, Deck::new(), &mut StdRng::seed_from_u64(0));
+        for player in players.iter_mut() {
+            player.call_mulligan = |_, _| true;
+        }
+        config.seats[2].controller = Controller::Human;
+        let mulligans = [1; NUM_PLAYERS];
+        assert_eq!(
+            find_player_calling_mulligan(&players, &mulligans, &config),
+            Some(2)
+        );
+        assert_eq!(
+            find_player_calling_mulligan(&players, &[1, 1, 0, 1], &config),
+            None
+        );
+        config.house_rules = Default::default();
+        assert_eq!(
+            find_player_calling_mulligan(&players, &mulligans, &config),
+            None
+        );
+
+        assert_eq!(
+            peek_reveal(3, &vec_card_from_str("3C 4D 2S")),
+            "=== Player 3 holds 3C 4D 2S"
+        );
+    }
+
+    #[test]
+    fn test_both_out_game() {
+        let mut config = GameConfig {
+            seats: vec![SeatConfig::default(); NUM_PLAYERS],
+            ..GameConfig::default()
+        };
+        config.rules.teams = Some(TeamPlay::BothOut);
+        for seed in 0..8 {
+            config.seed = Some(seed);
+            let result = perform_game(&config);
+            let partner = (result.winner + 2) % NUM_PLAYERS;
+            assert_eq!(result.scores.cards_left[result.winner], 0);
+            assert_eq!(result.scores.cards_left[partner], 0);
+            assert_eq!(
+                result.scores.points[result.winner],
+                result.scores.points[partner]
+            );
+        }
+    }
+
+    #[test]
+    fn test_heads_up_game() {
+        let mut config = GameConfig {
+            seats: vec![SeatConfig::default(); NUM_PLAYERS],
+            ..GameConfig::default()
+        };
+        config.rules.heads_up = true;
+        for seed in 0..4 {
+            config.seed = Some(seed);
+            let result = perform_game(&config);
+            assert!(result.plays.iter().all(|play| play.player_id % 2 == 0));
+            assert_eq!(result.winner % 2, 0);
+        }
+
+        // the Three of Clubs is dead, so the lowest live card opens
+        let mut players = <[Player; NUM_PLAYERS]>::default();
+        shuffle_and_deal_cards(&mut players, Deck::new(), &mut StdRng::seed_from_u64(0));
+        let holder = find_opening_player(&players, &Rules::default());
+        let opener = find_opening_player(&players, &config.rules);
+        assert_eq!(players[holder].cards[0], THREE_OF_CLUBS);
+        assert_eq!(opener % 2, 0);
+        if holder % 2 == 1 {
+            assert!(players[opener].cards[0] < players[2 - opener].cards[0]);
         }
     }
-    unreachable!();
 }
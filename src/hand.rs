@@ -1,18 +1,22 @@
 //! Represents any one of the allowed combinations of cards (known as a "Hand").
 //! Cannot be used to represent an unrecognized / nonsensical combination.
 
+pub mod generate;
 pub mod iter;
 pub mod order;
+pub mod signature;
 pub mod try_from;
 
 use core::fmt;
 
+use serde::{Deserialize, Serialize};
+
 use crate::card::Card;
 
 /// Represents any one of the allowed combinations of cards (known as a "Hand").
 /// Cannot be used to represent an unrecognized / nonsensical combination.
 /// TODO: Fivers: Straight, Flush, FullHouse, FourPlusKicker, StraightFlush
-#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum Hand {
     /// No Hand, No Cards
     Pass,
@@ -0,0 +1,174 @@
+//! Generates every Hand that can be made from a set of Cards, and every Hand that may be played
+//! upon another.
+
+use crate::card::Card;
+use crate::hand::{order::order, Hand};
+use crate::rules::Rules;
+
+/// The number of cards in each kind of Hand, smallest first.
+pub const HAND_SIZES: [usize; 4] = [1, 2, 3, 5];
+
+/// Returns every valid Hand (excluding Pass) that can be made from these cards.
+/// Order: Lones, then Pairs, then Trips, then Fivers.
+pub fn all_hands(cards: &[Card]) -> Vec<Hand> {
+    HAND_SIZES
+        .iter()
+        .flat_map(|size| hands_of_size(cards, *size))
+        .collect()
+}
+
+/// Returns every valid Hand made of exactly this many of these cards.
+pub fn hands_of_size(cards: &[Card], size: usize) -> Vec<Hand> {
+    // Hand constructors expect cards in descending order
+    let mut cards = cards.to_vec();
+    cards.sort();
+    cards.reverse();
+    let n = cards.len();
+
+    let mut hands: Vec<Hand> = vec![];
+    match size {
+        1 => hands.extend(cards.iter().map(|card| Hand::Lone(*card))),
+        2 => {
+            for a in 0..n {
+                for b in a + 1..n {
+                    if let Ok(pair) = Hand::try_pair(cards[a], cards[b]) {
+                        hands.push(pair);
+                    }
+                }
+            }
+        }
+        3 => {
+            for a in 0..n {
+                for b in a + 1..n {
+                    if cards[a].rank != cards[b].rank {
+                        continue;
+                    }
+                    for c in b + 1..n {
+                        if let Ok(trips) = Hand::try_trips(cards[a], cards[b], cards[c]) {
+                            hands.push(trips);
+                        }
+                    }
+                }
+            }
+        }
+        5 => {
+            for a in 0..n {
+                for b in a + 1..n {
+                    for c in b + 1..n {
+                        for d in c + 1..n {
+                            for e in d + 1..n {
+                                let fiver = Hand::try_fiver(
+                                    cards[a], cards[b], cards[c], cards[d], cards[e],
+                                );
+                                if let Ok(fiver) = fiver {
+                                    hands.push(fiver);
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+    hands
+}
+
+/// Returns true if any Hand made from these cards may be played upon hand_to_beat (which must not
+/// be Pass), under these rules.
+pub fn can_beat(hand_to_beat: &Hand, cards: &[Card], rules: &Rules) -> bool {
+    hands_of_size(cards, hand_to_beat.cards().len())
+        .iter()
+        .filter(|hand| rules.allow_flush || !matches!(hand, Hand::Flush(..)))
+        .any(|hand| matches!(order(hand_to_beat, hand), Some(std::cmp::Ordering::Less)))
+}
+
+/// Returns every Hand that may be played from these cards upon hand_to_beat, under these rules.
+/// If hand_to_beat is Pass, the Trick is being started, so any Hand may be played but Pass may
+/// not. Otherwise Pass is always included (as the last option).
+pub fn playable_hands(hand_to_beat: &Hand, cards: &[Card], rules: &Rules) -> Vec<Hand> {
+    let mut hands: Vec<Hand> = all_hands(cards)
+        .into_iter()
+        .filter(|hand| rules.allow_flush || !matches!(hand, Hand::Flush(..)))
+        .filter(|hand| {
+            matches!(
+                order(hand_to_beat, hand),
+                Some(std::cmp::Ordering::Less | std::cmp::Ordering::Equal)
+            )
+        })
+        .collect();
+    if !matches!(hand_to_beat, Hand::Pass) {
+        hands.push(Hand::Pass);
+    }
+    hands
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::rules::preset::RulePreset;
+    use crate::tests::test_util::vec_card_from_str;
+
+    #[test]
+    fn test_all_hands() {
+        let cards = vec_card_from_str("3C 3D 3S 4H 5D");
+        let hands = all_hands(&cards);
+        // 5 Lones, 3 Pairs, 1 Trips, no Fivers
+        assert_eq!(hands.len(), 9);
+        assert!(hands.contains(&"3S 3D 3C".parse().unwrap()));
+        assert!(hands.contains(&"3S 3C".parse().unwrap()));
+
+        let cards = vec_card_from_str("3C 4D 5S 6H 7C 7D");
+        let hands = all_hands(&cards);
+        assert!(hands.contains(&"7C 6H 5S 4D 3C".parse().unwrap()));
+        assert!(hands.contains(&"7D 6H 5S 4D 3C".parse().unwrap()));
+        assert_eq!(hands.iter().filter(|h| h.cards().len() == 5).count(), 2);
+
+        assert!(all_hands(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_can_beat() {
+        let rules = RulePreset::HongKong.rules();
+        let cards = vec_card_from_str("3C 3D 9S 2H");
+        assert!(can_beat(&"8D".parse().unwrap(), &cards, &rules));
+        assert!(!can_beat(&"2S".parse().unwrap(), &cards, &rules));
+        assert!(can_beat(
+            &"3S 3H".parse().unwrap(),
+            &vec_card_from_str("4C 4D"),
+            &rules
+        ));
+        assert!(!can_beat(&"3S 3H".parse().unwrap(), &cards, &rules));
+
+        let flush = vec_card_from_str("3C 5C 7C 9C JC");
+        let straight: Hand = "7D 6H 5C 4H 3D".parse().unwrap();
+        assert!(can_beat(&straight, &flush, &rules));
+        assert!(!can_beat(&straight, &flush, &RulePreset::Taiwan.rules()));
+    }
+
+    #[test]
+    fn test_playable_hands() {
+        let rules = RulePreset::HongKong.rules();
+        let cards = vec_card_from_str("3C 3D 9S 2H");
+
+        // starting a trick, everything but Pass
+        let hands = playable_hands(&Hand::Pass, &cards, &rules);
+        assert_eq!(hands.len(), 5);
+        assert!(!hands.contains(&Hand::Pass));
+
+        // only higher Lones, or Pass
+        let hands = playable_hands(&"8D".parse().unwrap(), &cards, &rules);
+        assert_eq!(
+            hands,
+            vec!["2H".parse().unwrap(), "9S".parse().unwrap(), Hand::Pass]
+        );
+
+        // Flushes depend on the rules
+        let cards = vec_card_from_str("3C 5C 7C 9C JC");
+        let straight: Hand = "7D 6H 5C 4H 3D".parse().unwrap();
+        assert_eq!(playable_hands(&straight, &cards, &rules).len(), 2);
+        let rules = RulePreset::Taiwan.rules();
+        assert_eq!(playable_hands(&straight, &cards, &rules), vec![Hand::Pass]);
+    }
+}
@@ -79,6 +79,7 @@ impl<'a> Iterator for HandIterator<'a> {
 impl<'a> ExactSizeIterator for HandIterator<'a> {}
 
 impl Hand {
+    #[allow(mismatched_lifetime_syntaxes)]
     pub fn cards(&self) -> HandIterator {
         HandIterator::new(self)
     }
@@ -17,6 +17,7 @@ use crate::hand::Hand;
 ///          - AAAAB
 ///          - ABBBB
 ///        Though A may have higher Rank, we use the Quad to decide order, which might be B.
+#[allow(clippy::doc_lazy_continuation)]
 pub fn order(current: &Hand, attempt: &Hand) -> Option<std::cmp::Ordering> {
     // std::mem::discriminant is a stable way to identify enum variants
     // if both current and attempt are the same variant of Hand
@@ -47,6 +48,33 @@ pub fn order(current: &Hand, attempt: &Hand) -> Option<std::cmp::Ordering> {
     }
 }
 
+/// Ranks every Hand of the same size on one scale: first the kind of Hand (in the order the Hand
+/// variants are declared), then the cards that decide between two Hands of that kind, most
+/// important first. Wherever order compares two Hands, it agrees with comparing their strengths.
+pub fn strength(hand: &Hand) -> (usize, Vec<Card>) {
+    let kind = match hand {
+        Hand::Pass => 0,
+        Hand::Lone(..) => 1,
+        Hand::Pair(..) => 2,
+        Hand::Trips(..) => 3,
+        Hand::Straight(..) => 4,
+        Hand::Flush(..) => 5,
+        Hand::FullHouse(..) => 6,
+        Hand::FourPlusKick(..) => 7,
+        Hand::StraightFlush(..) => 8,
+    };
+    let cards = match hand {
+        Hand::FullHouse(..) => {
+            let trip = get_full_house_trip(hand);
+            let pair = get_full_house_pair(hand);
+            trip.cards().chain(pair.cards()).copied().collect()
+        }
+        Hand::FourPlusKick(..) => vec![get_fours_major(hand), get_fours_minor(hand)],
+        _ => hand.cards().copied().collect(),
+    };
+    (kind, cards)
+}
+
 /// Return an Ordering between 2 FourPlusKick
 fn order_four_plus_kick(current: &Hand, attempt: &Hand) -> std::cmp::Ordering {
     assert!(matches!(current, Hand::FourPlusKick(..)));
@@ -135,9 +163,74 @@ fn get_full_house_pair(full_house: &Hand) -> Hand {
 mod tests {
 
     use super::*;
+    use crate::card::rank::Rank;
+    use crate::deck::Deck;
+    use crate::hand::generate::hands_of_size;
     use std::cmp::Ordering;
 
+    /// Every Hand of this size made from the cards of the given Ranks.
+    fn hands_of_ranks(size: usize, ranks: &[Rank]) -> Vec<Hand> {
+        let cards: Vec<Card> = Deck::new()
+            .cards
+            .into_iter()
+            .filter(|card| ranks.contains(&card.rank))
+            .collect();
+        hands_of_size(&cards, size)
+    }
+
+    /// Checks every pair of these Hands: order is a strict total order (antisymmetric, Equal only
+    /// for the same Hand) and agrees with strength.
+    fn assert_total_order(hands: &[Hand]) {
+        let strengths: Vec<(usize, Vec<Card>)> = hands.iter().map(strength).collect();
+        for (a, strength_a) in hands.iter().zip(&strengths) {
+            for (b, strength_b) in hands.iter().zip(&strengths) {
+                let ordering = order(a, b);
+                assert_eq!(ordering, Some(strength_a.cmp(strength_b)), "{a} vs {b}");
+                assert_eq!(ordering.map(Ordering::reverse), order(b, a), "{a} vs {b}");
+                assert_eq!(ordering == Some(Ordering::Equal), a == b, "{a} vs {b}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_same_kind_is_totally_ordered() {
+        let all_ranks = Rank::all();
+        for size in [1, 2, 3] {
+            let hands = hands_of_ranks(size, &all_ranks);
+            assert!(!hands.is_empty());
+            assert_total_order(&hands);
+        }
+
+        // every Bomb in the Deck
+        let bombs: Vec<Hand> = hands_of_ranks(5, &all_ranks)
+            .into_iter()
+            .filter(|hand| matches!(hand, Hand::FourPlusKick(..) | Hand::StraightFlush(..)))
+            .collect();
+        assert_eq!(bombs.len(), 13 * 48 + 9 * 4);
+        assert_total_order(&bombs);
+
+        // every Fiver of the lowest 6 Ranks, which includes every kind of Fiver, and Full-Houses
+        // with equal Trips and different Pairs
+        let fivers = hands_of_ranks(5, &all_ranks[..6]);
+        for kind in 4..=8 {
+            assert!(fivers.iter().any(|hand| strength(hand).0 == kind));
+        }
+        assert_total_order(&fivers);
+    }
+
+    #[test]
+    fn test_full_house_with_equal_trips() {
+        // both have Trip Sevens, the Spade decides
+        let a: Hand = "7S 7H 7D 3S 3H".parse().unwrap();
+        let b: Hand = "7H 7D 7C 4S 4H".parse().unwrap();
+        assert_eq!(order(&a, &b), Some(Ordering::Greater));
+        // the same Trips, the Pair decides
+        let c: Hand = "7S 7H 7D 4S 4H".parse().unwrap();
+        assert_eq!(order(&a, &c), Some(Ordering::Less));
+    }
+
     #[test]
+    #[allow(clippy::redundant_pattern_matching)]
     fn test_check_hand_order() {
         // trivial match, Pass equals Pass
         assert!(matches!(
@@ -163,6 +256,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::redundant_pattern_matching)]
     fn test_fivers() {
         // each successive hand can beat the previous hand in the list
         let fivers = [
@@ -0,0 +1,97 @@
+//! Compares Hands by the structure of their Ranks alone, for analytics and the AI, which care
+//! that a Hand is "three Eights and two Fours" rather than exactly which Eights and Fours.
+
+use crate::card::rank::Rank;
+use crate::hand::Hand;
+
+impl Hand {
+    /// Every Rank in this Hand and how many cards of it there are, the largest group first, then
+    /// the highest Rank first, e.g. [(Eight, 3), (Four, 2)] for 8S 8H 8C 4D 4C.
+    pub fn rank_groups(&self) -> Vec<(Rank, usize)> {
+        let mut groups: Vec<(Rank, usize)> = vec![];
+        for card in self.cards() {
+            match groups.iter_mut().find(|(rank, _)| *rank == card.rank) {
+                Some((_, count)) => *count += 1,
+                None => groups.push((card.rank, 1)),
+            }
+        }
+        groups.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then(b.cmp(a)));
+        groups
+    }
+
+    /// The Ranks of this Hand as text, one group per Rank written as often as it appears, in the
+    /// order of rank_groups and joined by "-". e.g. "888-44" for a Full House of Eights over Fours,
+    /// "7-6-5-4-3" for a Straight, and "" for a Pass. The same however the suits are dealt.
+    pub fn rank_signature(&self) -> String {
+        self.rank_groups()
+            .iter()
+            .map(|(rank, count)| rank.to_string().repeat(*count))
+            .collect::<Vec<_>>()
+            .join("-")
+    }
+
+    /// Returns true if both Hands hold the same Ranks the same number of times, whatever their
+    /// suits. A Straight Flush is equal to the Straight of the same Ranks, as only the suits tell
+    /// them apart.
+    pub fn eq_ignoring_suits(&self, other: &Hand) -> bool {
+        self.rank_groups() == other.rank_groups()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::card::suit::Suit;
+    use crate::card::Card;
+
+    /// The Hand with every suit swapped for the one it maps to in suits.
+    fn with_suits(hand: &Hand, suits: [Suit; 4]) -> Hand {
+        let mut cards: Vec<Card> = hand
+            .cards()
+            .map(|card| Card {
+                rank: card.rank,
+                suit: suits[card.suit as usize],
+            })
+            .collect();
+        cards.sort_by(|a, b| b.cmp(a));
+        Hand::try_from_cards(&cards).unwrap()
+    }
+
+    #[test]
+    fn test_rank_signature() {
+        let signatures = [
+            ("", ""),
+            ("2S", "2"),
+            ("TH TC", "TT"),
+            ("8S 8H 8C 4D 4C", "888-44"),
+            ("KS KD 9S 9H 9C", "999-KK"),
+            ("7S 7H 7D 7C 3D", "7777-3"),
+            ("7D 6C 5S 4C 3H", "7-6-5-4-3"),
+            ("AH JH 9H 6H 4H", "A-J-9-6-4"),
+        ];
+        let permutations = [
+            [Suit::Clubs, Suit::Diamonds, Suit::Hearts, Suit::Spades],
+            [Suit::Spades, Suit::Hearts, Suit::Diamonds, Suit::Clubs],
+            [Suit::Diamonds, Suit::Spades, Suit::Clubs, Suit::Hearts],
+        ];
+        for (hand, signature) in signatures {
+            let hand: Hand = hand.parse().unwrap();
+            assert_eq!(hand.rank_signature(), signature, "{hand}");
+            for suits in permutations {
+                let permuted = with_suits(&hand, suits);
+                assert_eq!(permuted.rank_signature(), signature, "{permuted}");
+                assert!(permuted.eq_ignoring_suits(&hand), "{permuted} vs {hand}");
+            }
+        }
+
+        let full_house: Hand = "8S 8H 8C 4D 4C".parse().unwrap();
+        let inverted: Hand = "8S 8H 4S 4D 4C".parse().unwrap();
+        assert!(!full_house.eq_ignoring_suits(&inverted));
+        assert_eq!(full_house.rank_groups()[0], (Rank::Eight, 3));
+        let straight: Hand = "7D 6C 5S 4C 3H".parse().unwrap();
+        let straight_flush: Hand = "7H 6H 5H 4H 3H".parse().unwrap();
+        assert!(straight.eq_ignoring_suits(&straight_flush));
+        assert_ne!(straight, straight_flush);
+    }
+}
@@ -0,0 +1,209 @@
+//! Aggregates many finished Games (e.g. the results in saved replays) into a card-frequency
+//! heatmap: when in a Game each Rank tends to be played, and which kinds of Hand win Tricks. For
+//! comparing house-rule variants and tuning the AI, written as JSON or CSV.
+
+use std::io::{self, Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::card::rank::Rank;
+use crate::game::GameResult;
+use crate::hand::Hand;
+
+/// How many phases a Game is split into, each an equal stretch of its turns.
+pub const NUM_PHASES: usize = 4;
+
+/// The kinds of Hand, lowest first, named as the cheat sheet names them.
+const HAND_KINDS: [&str; 8] = [
+    "Single",
+    "Pair",
+    "Three of a Kind",
+    "Straight",
+    "Flush",
+    "Full House",
+    "Four of a Kind",
+    "Straight Flush",
+];
+
+/// How many cards of one Rank were played in each phase of the Games.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RankFrequency {
+    pub rank: Rank,
+
+    /// How many were played in each phase, earliest first.
+    pub by_phase: [usize; NUM_PHASES],
+}
+
+/// How often one kind of Hand was played, and how often it won the Trick.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HandFrequency {
+    /// What the kind of Hand is called, e.g. "Full House".
+    pub kind: String,
+
+    pub played: usize,
+
+    /// How many Tricks were won by a Hand of this kind, i.e. it was the last Hand played.
+    pub tricks_won: usize,
+}
+
+/// When each Rank was played and which Hands won Tricks, across many Games.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Heatmap {
+    /// How many Games were added.
+    pub games: usize,
+
+    /// Every Rank, lowest first.
+    pub ranks: Vec<RankFrequency>,
+
+    /// Every kind of Hand, lowest first.
+    pub hands: Vec<HandFrequency>,
+}
+
+impl Default for Heatmap {
+    fn default() -> Self {
+        Self {
+            games: 0,
+            ranks: Rank::all()
+                .into_iter()
+                .map(|rank| RankFrequency {
+                    rank,
+                    by_phase: [0; NUM_PHASES],
+                })
+                .collect(),
+            hands: HAND_KINDS
+                .into_iter()
+                .map(|kind| HandFrequency {
+                    kind: String::from(kind),
+                    played: 0,
+                    tricks_won: 0,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Where a Hand's kind sits in HAND_KINDS, None for a Pass.
+fn kind_index(hand: &Hand) -> Option<usize> {
+    match hand {
+        Hand::Pass => None,
+        Hand::Lone(..) => Some(0),
+        Hand::Pair(..) => Some(1),
+        Hand::Trips(..) => Some(2),
+        Hand::Straight(..) => Some(3),
+        Hand::Flush(..) => Some(4),
+        Hand::FullHouse(..) => Some(5),
+        Hand::FourPlusKick(..) => Some(6),
+        Hand::StraightFlush(..) => Some(7),
+    }
+}
+
+impl Heatmap {
+    /// The heatmap of these Games.
+    pub fn of<'a>(results: impl IntoIterator<Item = &'a GameResult>) -> Self {
+        let mut heatmap = Self::default();
+        for result in results {
+            heatmap.add(result);
+        }
+        heatmap
+    }
+
+    /// Adds every turn of a Game. A Game won by a Dragon has no turns, but still counts.
+    pub fn add(&mut self, result: &GameResult) {
+        self.games += 1;
+        let plays = &result.plays;
+        for (turn, play) in plays.iter().enumerate() {
+            let Some(kind) = kind_index(&play.hand) else {
+                continue;
+            };
+            let phase = turn * NUM_PHASES / plays.len();
+            for card in play.hand.cards() {
+                self.ranks[card.rank as usize].by_phase[phase] += 1;
+            }
+            self.hands[kind].played += 1;
+
+            // the last Hand of a Trick wins it
+            let wins = plays[turn + 1..]
+                .iter()
+                .take_while(|later| later.trick == play.trick)
+                .all(|later| matches!(later.hand, Hand::Pass));
+            if wins {
+                self.hands[kind].tricks_won += 1;
+            }
+        }
+    }
+
+    /// Writes the heatmap as two CSV tables separated by a blank line: the Ranks (a column per
+    /// phase), then the kinds of Hand.
+    pub fn write_csv(&self, out: &mut impl Write) -> io::Result<()> {
+        let phases: Vec<String> = (1..=NUM_PHASES)
+            .map(|phase| format!("phase {phase}"))
+            .collect();
+        writeln!(out, "rank,{}", phases.join(","))?;
+        for rank in &self.ranks {
+            let counts: Vec<String> = rank.by_phase.iter().map(usize::to_string).collect();
+            writeln!(out, "{},{}", rank.rank, counts.join(","))?;
+        }
+        writeln!(out)?;
+        writeln!(out, "hand,played,tricks won")?;
+        for hand in &self.hands {
+            writeln!(out, "{},{},{}", hand.kind, hand.played, hand.tricks_won)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the heatmap as JSON.
+    pub fn write_json(&self, out: &mut impl Write) -> io::Result<()> {
+        serde_json::to_writer_pretty(&mut *out, self)?;
+        writeln!(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::config::GameConfig;
+    use crate::play::Play;
+    use crate::tests::test_util::vec_card_from_str;
+
+    #[test]
+    fn test_heatmap() {
+        let config = GameConfig::default();
+        let remaining = ["", "3D", "4D 5D", "6D"].map(vec_card_from_str);
+        let mut result = GameResult::new(0, false, &remaining, &config);
+        result.plays = [
+            (0, "3C", 0),
+            (1, "5S", 0),
+            (2, "", 0),
+            (3, "", 0),
+            (0, "", 0),
+            (1, "7S 7H", 1),
+            (2, "", 1),
+            (3, "", 1),
+            (0, "2S", 2),
+        ]
+        .map(|(player_id, hand, trick)| Play::now(player_id, hand.parse().unwrap(), trick))
+        .to_vec();
+        let dragon = GameResult::new(2, true, &remaining, &config);
+
+        let heatmap = Heatmap::of([&result, &dragon]);
+        assert_eq!(heatmap.games, 2);
+        let by_phase = |rank: Rank| heatmap.ranks[rank as usize].by_phase;
+        assert_eq!(by_phase(Rank::Three), [1, 0, 0, 0]);
+        assert_eq!(by_phase(Rank::Seven), [0, 0, 2, 0]);
+        assert_eq!(by_phase(Rank::Two), [0, 0, 0, 1]);
+        assert_eq!(heatmap.hands[0].played, 3);
+        assert_eq!(heatmap.hands[0].tricks_won, 2);
+        assert_eq!(heatmap.hands[1].tricks_won, 1);
+
+        let mut csv = vec![];
+        heatmap.write_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert!(csv.starts_with("rank,phase 1,phase 2,phase 3,phase 4\n3,1,0,0,0\n"));
+        assert!(csv.contains("\nhand,played,tricks won\nSingle,3,2\nPair,1,1\n"));
+
+        let mut json = vec![];
+        heatmap.write_json(&mut json).unwrap();
+        assert_eq!(serde_json::from_slice::<Heatmap>(&json).unwrap(), heatmap);
+    }
+}
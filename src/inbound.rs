@@ -0,0 +1,280 @@
+//! Guards a Table from misbehaving network clients: every line a client sends is checked before it
+//! reaches the Table, so one client flooding the connection, sending huge lines or sending
+//! something that is not text cannot slow the Game down for everybody else. A client that keeps
+//! misbehaving is disconnected.
+
+use std::fmt::{Display, Formatter};
+use std::io::{self, BufRead, Read};
+use std::time::{Duration, Instant};
+
+/// How much a client may send.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Limits {
+    /// The longest line accepted, in bytes, not counting the line ending.
+    pub max_line_bytes: usize,
+
+    /// How many lines a client may send in a burst.
+    pub burst: u32,
+
+    /// How long it takes for one more line to be allowed after a burst.
+    pub refill: Duration,
+
+    /// How many lines may be rejected before the client is disconnected.
+    pub max_strikes: usize,
+}
+
+impl Default for Limits {
+    /// Plenty for a person typing or a bot playing at full speed, 256 bytes (the longest Hand is
+    /// 14 bytes), 10 lines at once and then 5 a second, and 20 strikes.
+    fn default() -> Self {
+        Self {
+            max_line_bytes: 256,
+            burst: 10,
+            refill: Duration::from_millis(200),
+            max_strikes: 20,
+        }
+    }
+}
+
+/// Represents the ways a line can be rejected before it reaches the Table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rejected {
+    /// The line was longer than Limits::max_line_bytes, the rest of it was thrown away.
+    TooLong,
+
+    /// The line was not UTF-8 text.
+    NotText,
+
+    /// The line held a control character (other than a tab).
+    ControlCharacter,
+
+    /// The client sent more lines than Limits allows.
+    TooFast,
+}
+
+impl Display for Rejected {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            Rejected::TooLong => write!(f, "error: the line is too long"),
+            Rejected::NotText => write!(f, "error: the line is not text"),
+            Rejected::ControlCharacter => write!(f, "error: the line holds a control character"),
+            Rejected::TooFast => write!(f, "error: too many lines, slow down"),
+        }
+    }
+}
+
+/// Reads one client's lines, checking each against the Limits.
+pub struct Inbound<R> {
+    reader: R,
+    limits: Limits,
+
+    /// How many more lines may be sent right now, refilled over time.
+    allowance: f64,
+
+    /// When the allowance was last refilled.
+    refilled: Option<Instant>,
+
+    /// How many lines have been rejected.
+    strikes: usize,
+}
+
+impl<R: BufRead> Inbound<R> {
+    /// Starts reading a client, with a full allowance.
+    pub fn new(reader: R, limits: Limits) -> Self {
+        Self {
+            reader,
+            limits,
+            allowance: limits.burst as f64,
+            refilled: None,
+            strikes: 0,
+        }
+    }
+
+    /// Reads the next line, without its line ending, then asks the clock when it was received.
+    /// Returns None once the client has hung up or has been rejected too often (see
+    /// is_struck_out), otherwise the line or why it was rejected.
+    pub fn next_line(
+        &mut self,
+        clock: impl FnOnce() -> Instant,
+    ) -> io::Result<Option<Result<String, Rejected>>> {
+        if self.is_struck_out() {
+            return Ok(None);
+        }
+        let mut bytes = vec![];
+        let limit = self.limits.max_line_bytes as u64 + 2;
+        if self
+            .reader
+            .by_ref()
+            .take(limit)
+            .read_until(b'\n', &mut bytes)?
+            == 0
+        {
+            return Ok(None);
+        }
+        // timed once it has arrived, not from when the wait for it began
+        let now = clock();
+        let line = if !bytes.ends_with(b"\n") && bytes.len() as u64 == limit {
+            // throw away the rest of the line, however long it is
+            self.skip_line()?;
+            Err(Rejected::TooLong)
+        } else {
+            check(&bytes, self.limits.max_line_bytes)
+        };
+        let line = line.and_then(|line| match self.take_allowance(now) {
+            true => Ok(line),
+            false => Err(Rejected::TooFast),
+        });
+        if line.is_err() {
+            self.strikes += 1;
+        }
+        Ok(Some(line))
+    }
+
+    /// Returns true once the client has had more lines rejected than Limits::max_strikes.
+    pub fn is_struck_out(&self) -> bool {
+        self.strikes > self.limits.max_strikes
+    }
+
+    /// Reads up to and including the next line ending.
+    fn skip_line(&mut self) -> io::Result<()> {
+        loop {
+            let buffer = self.reader.fill_buf()?;
+            if buffer.is_empty() {
+                return Ok(());
+            }
+            match buffer.iter().position(|byte| *byte == b'\n') {
+                Some(end) => {
+                    self.reader.consume(end + 1);
+                    return Ok(());
+                }
+                None => {
+                    let len = buffer.len();
+                    self.reader.consume(len);
+                }
+            }
+        }
+    }
+
+    /// Refills the allowance for the time since it was last refilled, then takes one line from
+    /// it. Returns false if there was none to take.
+    fn take_allowance(&mut self, now: Instant) -> bool {
+        if let Some(refilled) = self.refilled {
+            let elapsed = now.saturating_duration_since(refilled);
+            let refill = elapsed.as_secs_f64() / self.limits.refill.as_secs_f64().max(f64::EPSILON);
+            self.allowance = (self.allowance + refill).min(self.limits.burst as f64);
+        }
+        self.refilled = Some(now);
+        if self.allowance < 1.0 {
+            return false;
+        }
+        self.allowance -= 1.0;
+        true
+    }
+}
+
+/// Checks a whole line, with or without its line ending.
+fn check(bytes: &[u8], max_line_bytes: usize) -> Result<String, Rejected> {
+    let bytes = bytes.strip_suffix(b"\n").unwrap_or(bytes);
+    let bytes = bytes.strip_suffix(b"\r").unwrap_or(bytes);
+    if bytes.len() > max_line_bytes {
+        return Err(Rejected::TooLong);
+    }
+    let line = std::str::from_utf8(bytes).map_err(|_| Rejected::NotText)?;
+    if line.chars().any(|c| c.is_control() && c != '\t') {
+        return Err(Rejected::ControlCharacter);
+    }
+    Ok(line.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::cell::Cell;
+    use std::io::{BufReader, Read};
+
+    use super::*;
+
+    /// Reads from the bytes, and notes that it has.
+    struct Noted<'a>(&'a [u8], &'a Cell<bool>);
+
+    impl Read for Noted<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.1.set(true);
+            self.0.read(buf)
+        }
+    }
+
+    fn lines(input: &[u8], limits: Limits) -> Vec<Result<String, Rejected>> {
+        let mut inbound = Inbound::new(input, limits);
+        let now = Instant::now();
+        std::iter::from_fn(|| inbound.next_line(|| now).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_garbage_is_rejected() {
+        let mut input = b"3C 3D\r\npass\n".to_vec();
+        input.extend(vec![b'A'; 10_000]);
+        input.extend(b"\n\xff\xfe\ncards\x07\n\tpass\ncards");
+        let lines = lines(&input, Limits::default());
+        assert_eq!(
+            lines,
+            [
+                Ok(String::from("3C 3D")),
+                Ok(String::from("pass")),
+                Err(Rejected::TooLong),
+                Err(Rejected::NotText),
+                Err(Rejected::ControlCharacter),
+                Ok(String::from("\tpass")),
+                Ok(String::from("cards")),
+            ]
+        );
+
+        // exactly as long as allowed is fine
+        let limits = Limits {
+            max_line_bytes: 4,
+            ..Limits::default()
+        };
+        assert_eq!(
+            self::lines(b"pass\r\npasss\n", limits),
+            [Ok(String::from("pass")), Err(Rejected::TooLong)]
+        );
+    }
+
+    #[test]
+    fn test_flooding_is_limited() {
+        let limits = Limits {
+            burst: 2,
+            refill: Duration::from_secs(1),
+            max_strikes: 1,
+            ..Limits::default()
+        };
+        let mut inbound = Inbound::new(&b"a\nb\nc\nd\ne\nf\n"[..], limits);
+        let start = Instant::now();
+        let mut next = |seconds: u64| {
+            inbound
+                .next_line(|| start + Duration::from_secs(seconds))
+                .unwrap()
+        };
+        assert_eq!(next(0), Some(Ok(String::from("a"))));
+        assert_eq!(next(0), Some(Ok(String::from("b"))));
+        assert_eq!(next(0), Some(Err(Rejected::TooFast)));
+
+        // the allowance refills over time
+        assert_eq!(next(1), Some(Ok(String::from("d"))));
+        assert_eq!(next(1), Some(Err(Rejected::TooFast)));
+
+        // and the second strike is one too many
+        assert_eq!(next(5), None);
+    }
+
+    #[test]
+    fn test_timed_once_read() {
+        let read = Cell::new(false);
+        let mut inbound = Inbound::new(BufReader::new(Noted(b"pass\n", &read)), Limits::default());
+        let line = inbound.next_line(|| {
+            assert!(read.get(), "the line was timed before it was read");
+            Instant::now()
+        });
+        assert_eq!(line.unwrap(), Some(Ok(String::from("pass"))));
+    }
+}
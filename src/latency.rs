@@ -0,0 +1,109 @@
+//! Measures how long a client's connection takes to answer, so remote players on slow links are
+//! not charged for the time their moves spend in transit. Every so often the server sends
+//! "ping <n>", the client answers "pong <n>" straight away, and the typical round trip (capped at
+//! MAX_ALLOWANCE) is taken off each of their turns before it is charged to their clock (see
+//! Player::latency_allowance).
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// The most taken off a turn, however slow the connection, so a client can't stall by answering
+/// pings late.
+pub const MAX_ALLOWANCE: Duration = Duration::from_secs(1);
+
+/// How many round trips are remembered, the allowance is the median of them.
+const ROUND_TRIPS: usize = 5;
+
+/// Pings one client and times its answers.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Heartbeat {
+    /// Numbers the pings, so late answers can be told apart.
+    sent: u64,
+
+    /// The ping waiting to be answered, and when it was sent.
+    waiting: Option<(u64, Instant)>,
+
+    /// The most recent round trips, oldest first.
+    round_trips: VecDeque<Duration>,
+}
+
+impl Heartbeat {
+    /// The next ping to send at `now`, e.g. "ping 3". A ping still waiting for its answer is given
+    /// up on.
+    pub fn ping(&mut self, now: Instant) -> String {
+        self.sent += 1;
+        self.waiting = Some((self.sent, now));
+        format!("ping {}", self.sent)
+    }
+
+    /// Handles a line received at `now`. Returns true if it was the answer to a ping (whether or
+    /// not it was the latest), so it goes no further.
+    pub fn pong(&mut self, line: &str, now: Instant) -> bool {
+        let Some(number) = line
+            .trim()
+            .strip_prefix("pong ")
+            .and_then(|number| number.trim().parse::<u64>().ok())
+        else {
+            return false;
+        };
+        if let Some((waiting, sent)) = self.waiting {
+            if waiting == number {
+                self.waiting = None;
+                if self.round_trips.len() == ROUND_TRIPS {
+                    self.round_trips.pop_front();
+                }
+                self.round_trips
+                    .push_back(now.saturating_duration_since(sent));
+            }
+        }
+        true
+    }
+
+    /// The typical round trip, the median of the most recent ones, None until a ping is answered.
+    pub fn round_trip(&self) -> Option<Duration> {
+        let mut round_trips: Vec<Duration> = self.round_trips.iter().copied().collect();
+        round_trips.sort();
+        round_trips.get(round_trips.len() / 2).copied()
+    }
+
+    /// How much to take off each turn: the typical round trip, at most MAX_ALLOWANCE.
+    pub fn allowance(&self) -> Duration {
+        self.round_trip().unwrap_or_default().min(MAX_ALLOWANCE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_heartbeat() {
+        let mut heartbeat = Heartbeat::default();
+        let start = Instant::now();
+        let at = |millis: u64| start + Duration::from_millis(millis);
+        assert_eq!(heartbeat.allowance(), Duration::ZERO);
+        assert!(!heartbeat.pong("3C 3D", at(0)), "not a pong");
+
+        assert_eq!(heartbeat.ping(at(0)), "ping 1");
+        assert!(heartbeat.pong("pong 1", at(100)));
+        assert_eq!(heartbeat.round_trip(), Some(Duration::from_millis(100)));
+
+        // a late answer to an older ping is swallowed but not timed
+        heartbeat.ping(at(1000));
+        heartbeat.ping(at(2000));
+        assert!(heartbeat.pong("pong 2", at(2050)));
+        assert!(heartbeat.pong("pong 3", at(2300)));
+        assert_eq!(heartbeat.round_trip(), Some(Duration::from_millis(300)));
+
+        // the median shrugs off one slow answer, and the allowance is capped
+        heartbeat.ping(at(3000));
+        heartbeat.pong("pong 4", at(3200));
+        assert_eq!(heartbeat.round_trip(), Some(Duration::from_millis(200)));
+        for number in 5..10 {
+            heartbeat.ping(at(number * 10_000));
+            heartbeat.pong(&format!("pong {number}"), at(number * 10_000 + 5000));
+        }
+        assert_eq!(heartbeat.allowance(), MAX_ALLOWANCE);
+    }
+}
@@ -1,7 +1,41 @@
+pub mod analysis;
+pub mod audit;
 pub mod card;
+pub mod commentary;
+pub mod config;
+pub mod constants;
+pub mod daily;
 pub mod deck;
+pub mod env;
+pub mod exchange;
+pub mod fairness;
 pub mod game;
 pub mod hand;
+pub mod heatmap;
+pub mod inbound;
+pub mod latency;
+pub mod match_play;
+pub mod matrix;
+pub mod output;
+pub mod play;
 pub mod player;
+pub mod profile;
+pub mod puzzle;
+pub mod ratings;
+pub mod recap;
+pub mod replay;
+pub mod room;
+pub mod rules;
+pub mod sanity;
+pub mod scoring;
+pub mod seats;
+pub mod self_play;
+pub mod server;
+pub mod snapshot;
+pub mod solver;
+pub mod spectate;
+pub mod stats;
+pub mod table;
 pub mod tests;
 pub mod trick;
+pub mod views;
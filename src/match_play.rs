@@ -0,0 +1,337 @@
+//! Run a Match, a series of Games played at the same table, keeping a running total.
+
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::config::GameConfig;
+use crate::deck::Deck;
+use crate::exchange::Exchange;
+use crate::game::{perform_game_with_exchange, GameResult};
+use crate::output::{self, current};
+use crate::player::AdaptiveAi;
+use crate::scoring::{net_settlements, Settlement};
+use crate::stats::PlayerStats;
+use crate::trick::NUM_PLAYERS;
+
+/// How the players configured in GameConfig::seats find their seats at the start of a Match.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SeatDraw {
+    /// Everybody sits where they were configured, e.g. the human in seat 0.
+    #[default]
+    Fixed,
+
+    /// The seats are shuffled.
+    Shuffle,
+
+    /// Everybody draws a card, the highest card sits in seat 0, the next highest in seat 1, and
+    /// so on.
+    DrawCards,
+}
+
+impl Display for SeatDraw {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            SeatDraw::Fixed => write!(f, "fixed"),
+            SeatDraw::Shuffle => write!(f, "shuffle"),
+            SeatDraw::DrawCards => write!(f, "draw"),
+        }
+    }
+}
+
+/// Returned when a string is not the name of a SeatDraw.
+#[derive(Debug)]
+pub enum ParseSeatDrawError {
+    Unknown(String),
+}
+
+impl FromStr for SeatDraw {
+    type Err = ParseSeatDrawError;
+    fn from_str(draw_str: &str) -> Result<Self, Self::Err> {
+        match draw_str.to_lowercase().as_str() {
+            "fixed" => Ok(SeatDraw::Fixed),
+            "shuffle" => Ok(SeatDraw::Shuffle),
+            "draw" => Ok(SeatDraw::DrawCards),
+            _ => Err(ParseSeatDrawError::Unknown(draw_str.to_string())),
+        }
+    }
+}
+
+/// Seats the configured players as the config's SeatDraw says, announcing any cards drawn.
+/// Returns which configured player sits in each seat.
+pub fn draw_seats<R: Rng>(config: &GameConfig, rng: &mut R) -> Vec<usize> {
+    let mut seating: Vec<usize> = (0..NUM_PLAYERS).collect();
+    match config.seat_draw {
+        SeatDraw::Fixed => {}
+        SeatDraw::Shuffle => seating.shuffle(rng),
+        SeatDraw::DrawCards => {
+            let mut deck = Deck::new().cards;
+            deck.shuffle(rng);
+            for (player, card) in deck.iter().take(NUM_PLAYERS).enumerate() {
+                let name = config.seat(player).display_name(player);
+                writeln!(current(), "{name} draws the {card}");
+            }
+            seating.sort_by_key(|player| std::cmp::Reverse(deck[*player]));
+        }
+    }
+    seating
+}
+
+/// The config with each configured player moved to the seat they drew.
+fn seated_config(config: &GameConfig, seating: &[usize]) -> GameConfig {
+    GameConfig {
+        seats: seating.iter().map(|player| config.seat(*player)).collect(),
+        ..config.clone()
+    }
+}
+
+/// Returned at the end of the Match, the results of every Game and the running totals.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MatchSummary {
+    /// The result of every Game, in the order they were played.
+    pub results: Vec<GameResult>,
+
+    /// Total points of every player across all Games.
+    pub totals: Vec<i32>,
+
+    /// Who pays whom how much across all Games, at most one Settlement per pair of players.
+    pub settlements: Vec<Settlement>,
+
+    /// Stats (including Achievements) of every player across all Games.
+    pub stats: Vec<PlayerStats>,
+
+    /// Which of the players configured in GameConfig::seats sat in each seat for the whole Match,
+    /// see SeatDraw. Everything else in the summary is by seat.
+    #[serde(default)]
+    pub seating: Vec<usize>,
+
+    /// Where the adaptive AI stood once the Match was over, None if it was not used (see
+    /// GameConfig::adaptive).
+    #[serde(default)]
+    pub adaptive: Option<AdaptiveAi>,
+}
+
+impl MatchSummary {
+    /// Totals up the results of a series of Games.
+    pub fn from_results(results: Vec<GameResult>) -> Self {
+        let num_players = results
+            .first()
+            .map_or(0, |result| result.scores.points.len());
+        let mut totals = vec![0; num_players];
+        let mut stats = vec![PlayerStats::default(); num_players];
+        for result in &results {
+            for (total, points) in totals.iter_mut().zip(&result.scores.points) {
+                *total += points;
+            }
+            for (player_id, player_stats) in stats.iter_mut().enumerate() {
+                player_stats.record(player_id, result);
+            }
+        }
+
+        let all_settlements: Vec<Settlement> = results
+            .iter()
+            .flat_map(|result| result.settlements.iter().cloned())
+            .collect();
+
+        Self {
+            results,
+            totals,
+            settlements: net_settlements(&all_settlements),
+            stats,
+            seating: (0..num_players).collect(),
+            adaptive: None,
+        }
+    }
+
+    /// The seat the configured player sat in.
+    ///
+    /// # Panics
+    /// - If there is no such player.
+    pub fn seat_of(&self, player: usize) -> usize {
+        self.seating
+            .iter()
+            .position(|seated| *seated == player)
+            .expect("every configured player has a seat")
+    }
+}
+
+/// Run the entire Match, performing num_games Games in a row with the same GameConfig, once the
+/// players have found their seats (see SeatDraw). If the Rules exchange cards, every Game after
+/// the first begins with the exchange the last one left owing.
+pub fn perform_match(config: &GameConfig, num_games: usize) -> MatchSummary {
+    let _output = output::install(config.output.clone());
+    let mut rng = match config.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+    let seating = draw_seats(config, &mut rng);
+    if config.seat_draw != SeatDraw::Fixed {
+        for (seat, player) in seating.iter().enumerate() {
+            let name = config.seat(*player).display_name(*player);
+            writeln!(current(), "{name} sits in seat {seat}");
+        }
+    }
+    let mut config = seated_config(config, &seating);
+    let mut adaptive = config.adaptive.clone();
+
+    let mut results: Vec<GameResult> = vec![];
+    for game_number in 1..=num_games {
+        writeln!(current(), "=== Game {game_number} of {num_games}");
+        if let Some(adaptive) = &adaptive {
+            adaptive.apply(&mut config);
+            writeln!(current(), "=== The AI plays {}", adaptive.difficulty());
+        }
+        let exchange = results
+            .last()
+            .and_then(|last| Exchange::after(last, &config.rules));
+        let result = perform_game_with_exchange(&config, exchange.as_ref());
+        if let Some(adaptive) = &mut adaptive {
+            adaptive.record(&config, &result);
+        }
+        results.push(result);
+    }
+    MatchSummary {
+        seating,
+        adaptive,
+        ..MatchSummary::from_results(results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::output::{Buffer, Output};
+    use crate::player::{AiLevel, RECENT_GAMES};
+    use crate::seats::SeatConfig;
+    use crate::tests::test_util::vec_card_from_str;
+    use crate::trick::NUM_PLAYERS;
+
+    #[test]
+    fn test_summary_from_results() {
+        let config = GameConfig::default();
+        let first = [
+            "3C 4D 5S",
+            "",
+            "6C 7D 8S 9H JC KD AS 3D 5C 7S",
+            "3H 4C 5D 6S 8C 9D TS JH QC KS AD 6H 8D",
+        ];
+        let second = ["", "TD JS QH KH AC", "4S", "TC JD"];
+        let results = vec![
+            GameResult::new(1, false, &first.map(vec_card_from_str), &config),
+            GameResult::new(0, false, &second.map(vec_card_from_str), &config),
+        ];
+        let summary = MatchSummary::from_results(results);
+        assert_eq!(summary.totals, vec![-3 + 8, 62 - 5, -20 - 1, -39 - 2]);
+        assert_eq!(summary.totals.iter().sum::<i32>(), 0);
+        assert_eq!(summary.stats[0].games_won, 1);
+        assert_eq!(summary.stats[1].games_won, 1);
+        assert_eq!(summary.stats[2].games_played, 2);
+
+        // player 3 owes player 0 the difference in penalty from both Games
+        let three_to_zero = summary
+            .settlements
+            .iter()
+            .find(|s| s.payer == 3 && s.payee == 0)
+            .unwrap();
+        assert_eq!(three_to_zero.amount, (39 - 3) + 2);
+    }
+
+    #[test]
+    fn test_draw_seats() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut config = GameConfig::default();
+        assert_eq!(draw_seats(&config, &mut rng), [0, 1, 2, 3]);
+
+        for seat_draw in [SeatDraw::Shuffle, SeatDraw::DrawCards] {
+            config.seat_draw = seat_draw;
+            let mut seating = draw_seats(&config, &mut rng);
+            seating.sort();
+            assert_eq!(seating, [0, 1, 2, 3], "{seat_draw}");
+            assert_eq!(
+                seat_draw.to_string().parse::<SeatDraw>().unwrap(),
+                seat_draw
+            );
+        }
+        assert!(matches!(
+            "musical chairs".parse::<SeatDraw>(),
+            Err(ParseSeatDrawError::Unknown(_))
+        ));
+
+        // the players configured in seats 0 and 1 play hard, and keep their levels wherever
+        // they sit
+        let mut seats = vec![SeatConfig::default(); NUM_PLAYERS];
+        seats[0].ai_level = AiLevel::Hard;
+        seats[1].ai_level = AiLevel::Hard;
+        let config = GameConfig {
+            seats,
+            seed: Some(9),
+            seat_draw: SeatDraw::DrawCards,
+            ..GameConfig::default()
+        };
+        let summary = perform_match(&config, 1);
+        assert_ne!(summary.seating, [0, 1, 2, 3]);
+        for player in 0..NUM_PLAYERS {
+            assert_eq!(summary.seating[summary.seat_of(player)], player);
+        }
+        assert_eq!(
+            seated_config(&config, &summary.seating).seats[summary.seat_of(1)].ai_level,
+            AiLevel::Hard
+        );
+    }
+
+    #[test]
+    fn test_match_with_exchange() {
+        let mut config = GameConfig {
+            seats: vec![SeatConfig::default(); NUM_PLAYERS],
+            seed: Some(3),
+            ..GameConfig::default()
+        };
+        config.rules.exchange_cards = 2;
+        let summary = perform_match(&config, 3);
+        assert_eq!(summary.results.len(), 3);
+        assert_eq!(summary.totals.iter().sum::<i32>(), 0);
+
+        // every Game is dealt the same cards, so the exchange is what changes the later Games
+        let first = &summary.results[0].plays;
+        assert!(summary.results[1..]
+            .iter()
+            .any(|result| result.plays != *first));
+    }
+
+    #[test]
+    fn test_adaptive_match() {
+        // with nobody but the AI at the table, the people never win, so it gets easier
+        let buffer = Buffer::default();
+        let config = GameConfig {
+            seats: vec![SeatConfig::default(); NUM_PLAYERS],
+            seed: Some(3),
+            adaptive: Some(AdaptiveAi::default()),
+            output: Output::to(buffer.clone()),
+            ..GameConfig::default()
+        };
+        let summary = perform_match(&config, RECENT_GAMES + 1);
+        assert_eq!(summary.adaptive.unwrap().step, 0);
+        let printed = buffer.contents();
+        assert_eq!(
+            printed.matches("=== The AI plays hard, reckless").count(),
+            RECENT_GAMES
+        );
+        assert!(printed.contains("=== The AI plays easy\n"), "{printed}");
+        assert_eq!(
+            perform_match(
+                &GameConfig {
+                    adaptive: None,
+                    ..config
+                },
+                1
+            )
+            .adaptive,
+            None
+        );
+    }
+}
@@ -0,0 +1,298 @@
+//! A correctness gate for the rule variants: plays seeded Games between AI players for every rule
+//! preset at every AI level, and reports whatever looks wrong. The engine panicking, the rules
+//! turning down a Hand that was played (or their list of legal plays disagreeing with their
+//! check, or with why a player passed), and Games that end in a state they never should, e.g.
+//! running past a turn cap or points that do not add up. Run it (see --matrix) whenever a variant
+//! is added.
+
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::time::Duration;
+
+use crate::card::Card;
+use crate::config::GameConfig;
+use crate::fairness::{play_deal, seeded_deal};
+use crate::game::GameResult;
+use crate::hand::Hand;
+use crate::play::hand_to_beat;
+use crate::player::{AiLevel, TurnView};
+use crate::rules::preset::RulePreset;
+use crate::rules::validate::validate_play;
+use crate::rules::StartRule;
+use crate::seats::SeatConfig;
+use crate::trick::NUM_PLAYERS;
+
+/// More turns than any sensible Game takes, a Game running past it has gone round in circles.
+pub const DEFAULT_TURN_CAP: usize = 500;
+
+/// Something wrong with one Game.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Finding {
+    /// The engine panicked, with this message.
+    Panicked(String),
+
+    /// The rules disagree with themselves, or with what was played, on this turn.
+    Disagreement { turn: usize, description: String },
+
+    /// The Game ended in a state it never should.
+    Degenerate(String),
+}
+
+impl Display for Finding {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            Finding::Panicked(message) => write!(f, "panicked: {message}"),
+            Finding::Disagreement { turn, description } => {
+                write!(f, "turn {turn}: {description}")
+            }
+            Finding::Degenerate(description) => write!(f, "{description}"),
+        }
+    }
+}
+
+/// How the Games of one preset at one AI level went.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CellReport {
+    pub preset: RulePreset,
+    pub ai_level: AiLevel,
+
+    /// How many Games were played.
+    pub games: usize,
+
+    /// How many turns they took altogether.
+    pub turns: usize,
+
+    /// Everything wrong, with the seed of the Game it was found in.
+    pub findings: Vec<(u64, Finding)>,
+}
+
+impl Display for CellReport {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{} at {}: {} Games, {} turns, ",
+            self.preset, self.ai_level, self.games, self.turns
+        )?;
+        if self.findings.is_empty() {
+            return write!(f, "ok");
+        }
+        write!(f, "{} findings", self.findings.len())?;
+        for (seed, finding) in &self.findings {
+            write!(f, "\n  seed {seed}: {finding}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Every AI seat at this level, thinking as briefly as it can so the matrix runs quickly, and
+/// stopped at the turn cap so a Game going round in circles can't hang it.
+fn matrix_config(preset: RulePreset, ai_level: AiLevel, turn_cap: usize) -> GameConfig {
+    let seat = SeatConfig {
+        ai_level,
+        ..SeatConfig::default()
+    };
+    GameConfig {
+        ai_level,
+        ai_time_budget: Some(Duration::ZERO),
+        max_turns: Some(turn_cap),
+        seats: vec![seat; NUM_PLAYERS],
+        ..GameConfig::from_preset(preset)
+    }
+}
+
+/// The message a panic was raised with.
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    match (
+        payload.downcast_ref::<&str>(),
+        payload.downcast_ref::<String>(),
+    ) {
+        (Some(message), _) => message.to_string(),
+        (_, Some(message)) => message.clone(),
+        _ => String::from("(no message)"),
+    }
+}
+
+/// Plays the Game every seed deals with this preset at this AI level, and checks each one.
+pub fn run_cell(
+    preset: RulePreset,
+    ai_level: AiLevel,
+    seeds: Range<u64>,
+    turn_cap: usize,
+) -> CellReport {
+    let config = matrix_config(preset, ai_level, turn_cap);
+    let mut report = CellReport {
+        preset,
+        ai_level,
+        games: 0,
+        turns: 0,
+        findings: vec![],
+    };
+    for seed in seeds {
+        let deal = seeded_deal(seed);
+        report.games += 1;
+        match catch_unwind(AssertUnwindSafe(|| play_deal(&config, deal.clone()))) {
+            Ok(result) => {
+                report.turns += result.plays.len();
+                let findings = check_game(&config, deal, &result, turn_cap);
+                report
+                    .findings
+                    .extend(findings.into_iter().map(|finding| (seed, finding)));
+            }
+            Err(payload) => report
+                .findings
+                .push((seed, Finding::Panicked(panic_message(payload.as_ref())))),
+        }
+    }
+    report
+}
+
+/// Runs every cell of the matrix, every rule preset at every AI level.
+pub fn run_matrix(seeds: Range<u64>, turn_cap: usize) -> Vec<CellReport> {
+    RulePreset::all()
+        .into_iter()
+        .flat_map(|preset| AiLevel::all().map(|ai_level| (preset, ai_level)))
+        .map(|(preset, ai_level)| run_cell(preset, ai_level, seeds.clone(), turn_cap))
+        .collect()
+}
+
+/// Replays a finished Game from its deal, checking every turn against the rules, then checks how
+/// it ended.
+pub fn check_game(
+    config: &GameConfig,
+    deal: [Vec<Card>; NUM_PLAYERS],
+    result: &GameResult,
+    turn_cap: usize,
+) -> Vec<Finding> {
+    let mut findings = vec![];
+    let mut cards = deal;
+    let mut trick_start = 0;
+    for (turn, play) in result.plays.iter().enumerate() {
+        if turn > 0 && play.trick != result.plays[turn - 1].trick {
+            trick_start = turn;
+        }
+        let trick = &result.plays[trick_start..turn];
+        let player_id = play.player_id;
+        let mut view = TurnView::new(&cards[player_id], hand_to_beat(trick), NUM_PLAYERS);
+        view.player_id = player_id;
+        view.must_play_three_of_clubs =
+            turn == 0 && config.rules.start_rule == StartRule::MustPlayLowestCard;
+        view.trick = trick;
+        view.history = &result.plays[..trick_start];
+        view.card_counts = cards.iter().map(|cards| cards.len()).collect();
+        view.rules = config.rules;
+        let mut disagree =
+            |description: String| findings.push(Finding::Disagreement { turn, description });
+        if let Err(e) = validate_play(&view, &play.hand) {
+            disagree(format!(
+                "Player {player_id} played {}, which the rules turn down ({e})",
+                play.hand
+            ));
+        } else if !view.is_legal(&play.hand) {
+            disagree(format!(
+                "Player {player_id} played {}, which is not among the legal plays",
+                play.hand
+            ));
+        }
+        if matches!(play.hand, Hand::Pass) && play.pass_reason != Some(view.pass_reason()) {
+            let recorded = play
+                .pass_reason
+                .map_or(String::from("without a reason"), |reason| {
+                    format!("as {reason}")
+                });
+            disagree(format!(
+                "Player {player_id} passed, recorded {recorded}, but the pass was {}",
+                view.pass_reason()
+            ));
+        }
+        if let Some((legal, e)) = view
+            .legal_plays()
+            .iter()
+            .find_map(|legal| validate_play(&view, legal).err().map(|e| (legal, e)))
+        {
+            disagree(format!(
+                "{legal} is among the legal plays, but the rules turn it down ({e})"
+            ));
+        }
+
+        if !matches!(play.hand, Hand::Pass) {
+            cards[player_id].retain(|card| !play.hand.cards().any(|played| played == card));
+        }
+    }
+
+    if result.plays.len() > turn_cap {
+        findings.push(Finding::Degenerate(format!(
+            "the Game took {} turns, more than the cap of {turn_cap}",
+            result.plays.len()
+        )));
+    } else if result.stalemate.is_some() {
+        findings.push(Finding::Degenerate(format!(
+            "the Game was stopped at the turn cap after {} turns",
+            result.plays.len()
+        )));
+    }
+    let cards_left = cards[result.winner].len();
+    if !result.won_by_dragon && result.stalemate.is_none() && cards_left > 0 {
+        findings.push(Finding::Degenerate(format!(
+            "Player {} won holding {cards_left} cards",
+            result.winner
+        )));
+    }
+    if result.scores.cards_left != cards.iter().map(Vec::len).collect::<Vec<_>>() {
+        findings.push(Finding::Degenerate(format!(
+            "the scores count {:?} cards left, but the players hold {:?}",
+            result.scores.cards_left,
+            cards.iter().map(Vec::len).collect::<Vec<_>>()
+        )));
+    }
+    let total: i32 = result.scores.points.iter().sum();
+    if total != 0 {
+        findings.push(Finding::Degenerate(format!(
+            "the points {:?} add up to {total}, not 0",
+            result.scores.points
+        )));
+    }
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_matrix_is_clean() {
+        let reports = run_matrix(0..3, DEFAULT_TURN_CAP);
+        assert_eq!(
+            reports.len(),
+            RulePreset::all().len() * AiLevel::all().len()
+        );
+        for report in reports {
+            assert_eq!(report.games, 3);
+            assert!(report.findings.is_empty(), "{report}");
+        }
+    }
+
+    #[test]
+    fn test_findings() {
+        let config = matrix_config(RulePreset::HongKong, AiLevel::Easy, DEFAULT_TURN_CAP);
+        let deal = seeded_deal(1);
+        let mut result = play_deal(&config, deal.clone());
+        assert_eq!(
+            check_game(&config, deal.clone(), &result, DEFAULT_TURN_CAP),
+            []
+        );
+
+        // a cap the Game ran past, points that do not add up, and a first play that breaks the
+        // rules
+        let findings = check_game(&config, deal.clone(), &result, 3);
+        assert!(matches!(&findings[..], [Finding::Degenerate(_)]));
+        result.scores.points[0] += 1;
+        result.plays[0].hand = Hand::Pass;
+        let findings = check_game(&config, deal, &result, DEFAULT_TURN_CAP);
+        assert!(matches!(findings[0], Finding::Disagreement { turn: 0, .. }));
+        assert!(findings
+            .iter()
+            .any(|finding| finding.to_string().contains("add up to 1")));
+    }
+}
@@ -0,0 +1,154 @@
+//! Where the CLI frontends write: the narration of a Game, the prompts of a stdio User and the
+//! lines a script reads. An Output is chosen when the Game is set up (GameConfig::output, stdout
+//! by default), e.g. a file for a log, or a Buffer a test can read back. While a Game is played
+//! its Output is installed for the thread playing it, and everything the frontends print goes to
+//! current().
+
+use std::cell::RefCell;
+use std::fmt::{Arguments, Debug, Formatter};
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+
+/// Somewhere to write, shared by every clone.
+#[derive(Clone)]
+pub struct Output {
+    writer: Arc<Mutex<dyn Write + Send>>,
+}
+
+impl Default for Output {
+    /// Writes to stdout.
+    fn default() -> Self {
+        Self::to(io::stdout())
+    }
+}
+
+impl Debug for Output {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "Output")
+    }
+}
+
+/// Outputs are not part of what is compared, any two are equal (as with HouseRules, two
+/// GameConfigs writing to different places describe the same Game).
+impl PartialEq for Output {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for Output {}
+
+impl Output {
+    /// Writes to the writer, e.g. a File.
+    pub fn to<W: Write + Send + 'static>(writer: W) -> Self {
+        Self {
+            writer: Arc::new(Mutex::new(writer)),
+        }
+    }
+
+    /// Writes the formatted text and flushes, so a prompt is seen before the input it asks for.
+    /// Like print!, this is what write!(output, ...) calls. A writer that fails (e.g. a closed
+    /// pipe) loses the text, the Game goes on.
+    pub fn write_fmt(&self, args: Arguments) {
+        let mut writer = self.writer.lock().unwrap_or_else(|e| e.into_inner());
+        let _ = writer.write_fmt(args).and_then(|_| writer.flush());
+    }
+}
+
+/// Keeps everything written to it, for reading back.
+#[derive(Debug, Clone, Default)]
+pub struct Buffer {
+    bytes: Arc<Mutex<Vec<u8>>>,
+}
+
+impl Buffer {
+    /// Everything written so far.
+    pub fn contents(&self) -> String {
+        String::from_utf8_lossy(&self.bytes.lock().unwrap()).into_owned()
+    }
+}
+
+impl Write for Buffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.bytes.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Writes everything to both writers, e.g. stdout and a log file.
+#[derive(Debug)]
+pub struct Both<A: Write, B: Write>(pub A, pub B);
+
+impl<A: Write, B: Write> Write for Both<A, B> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write_all(buf)?;
+        self.1.write_all(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()?;
+        self.1.flush()
+    }
+}
+
+thread_local! {
+    static CURRENT: RefCell<Output> = RefCell::new(Output::default());
+}
+
+/// Where the frontends on this thread write, stdout unless an Output is installed.
+pub fn current() -> Output {
+    CURRENT.with(|current| current.borrow().clone())
+}
+
+/// Makes the Output current on this thread until the returned Installed is dropped, when the one
+/// it replaced is current again.
+pub fn install(output: Output) -> Installed {
+    let replaced = CURRENT.with(|current| current.replace(output));
+    Installed { replaced }
+}
+
+/// Keeps an Output installed, see install.
+#[must_use = "the Output is only installed until this is dropped"]
+pub struct Installed {
+    replaced: Output,
+}
+
+impl Drop for Installed {
+    fn drop(&mut self) {
+        let replaced = std::mem::take(&mut self.replaced);
+        CURRENT.with(|current| current.replace(replaced));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_install() {
+        let outer = Buffer::default();
+        let inner = Buffer::default();
+        let copy = Buffer::default();
+        {
+            let _outer = install(Output::to(outer.clone()));
+            writeln!(current(), "Dealing Cards...");
+            {
+                let _inner = install(Output::to(Both(inner.clone(), copy.clone())));
+                write!(current(), "=== > ");
+            }
+            writeln!(current(), "Game Over, Player {} wins!!", 2);
+        }
+        assert_eq!(
+            outer.contents(),
+            "Dealing Cards...\nGame Over, Player 2 wins!!\n"
+        );
+        assert_eq!(inner.contents(), "=== > ");
+        assert_eq!(copy.contents(), "=== > ");
+    }
+}
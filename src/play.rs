@@ -0,0 +1,220 @@
+//! A single turn of a Game, remembering who played what and when.
+
+use std::fmt::{Display, Formatter};
+use std::time::{Duration, SystemTime};
+
+use serde::{Deserialize, Serialize};
+
+use crate::hand::Hand;
+
+/// Why a player passed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PassReason {
+    /// They could have played something, and held back.
+    Voluntary,
+
+    /// Nothing they held could be played.
+    Forced,
+}
+
+impl Display for PassReason {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            PassReason::Voluntary => write!(f, "voluntary"),
+            PassReason::Forced => write!(f, "forced"),
+        }
+    }
+}
+
+/// A single turn of a Game, remembering who played what and when.
+/// Every turn is recorded, including Passes, so a Game can be replayed from its Plays.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Play {
+    /// The player who took the turn.
+    pub player_id: usize,
+
+    /// The Hand they played, or Pass.
+    pub hand: Hand,
+
+    /// Which Trick of the Game the turn was taken in, counting from 0.
+    pub trick: usize,
+
+    /// When the turn was taken.
+    pub timestamp: SystemTime,
+
+    /// How long after the previous turn of the Game this one was taken, i.e. the real pace of
+    /// play (0 for the first turn), so replays can be watched at their original speed.
+    #[serde(default)]
+    pub elapsed: Duration,
+
+    /// Identifies the public state of the Game after this turn, i.e. every turn so far (see
+    /// next_state_hash). Two copies of a Game with the same hash have not diverged.
+    #[serde(default)]
+    pub state_hash: u64,
+
+    /// Why the player passed, None if they played a Hand (or the Game was recorded before
+    /// reasons were kept). Worked out from their legal plays, so it is not part of the state hash.
+    #[serde(default)]
+    pub pass_reason: Option<PassReason>,
+}
+
+impl Play {
+    /// Records a turn taken just now, on its own, e.g. for tests.
+    pub fn now(player_id: usize, hand: Hand, trick: usize) -> Self {
+        let mut play = Self {
+            player_id,
+            hand,
+            trick,
+            timestamp: SystemTime::now(),
+            elapsed: Duration::ZERO,
+            state_hash: 0,
+            pass_reason: None,
+        };
+        play.state_hash = next_state_hash(INITIAL_STATE_HASH, &play);
+        play
+    }
+
+    /// Records a turn taken just now, following the previous turn of the Game (None if this is
+    /// the first turn).
+    pub fn following(previous: Option<&Play>, player_id: usize, hand: Hand, trick: usize) -> Self {
+        let mut play = Self::now(player_id, hand, trick);
+        play.state_hash = next_state_hash(state_hash(previous), &play);
+        if let Some(previous) = previous {
+            play.elapsed = play
+                .timestamp
+                .duration_since(previous.timestamp)
+                .unwrap_or_default();
+        }
+        play
+    }
+
+    /// The same turn, recording why the player passed (if they did).
+    pub fn with_pass_reason(self, pass_reason: Option<PassReason>) -> Self {
+        Self {
+            pass_reason,
+            ..self
+        }
+    }
+
+    /// Returns true if the player passed because nothing they held could be played.
+    pub fn is_forced_pass(&self) -> bool {
+        self.pass_reason == Some(PassReason::Forced)
+    }
+}
+
+/// The state hash of a Game before anybody has played.
+pub const INITIAL_STATE_HASH: u64 = 0xcbf2_9ce4_8422_2325;
+
+/// The state hash of a Game whose last turn was this Play, INITIAL_STATE_HASH if there was none.
+pub fn state_hash(last: Option<&Play>) -> u64 {
+    last.map_or(INITIAL_STATE_HASH, |play| play.state_hash)
+}
+
+/// Hashes the public state after a turn from the state before it: who played what, in which
+/// Trick. Uses 64 bit FNV-1a over a fixed encoding (never the timestamp), so every platform and
+/// every build agrees.
+pub fn next_state_hash(previous: u64, play: &Play) -> u64 {
+    let mut hash = INITIAL_STATE_HASH;
+    let bytes = previous
+        .to_le_bytes()
+        .into_iter()
+        .chain((play.player_id as u64).to_le_bytes())
+        .chain((play.trick as u64).to_le_bytes())
+        .chain(play.hand.to_string().into_bytes());
+    for byte in bytes {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    hash
+}
+
+/// Recomputes the state hash after every Play, returning the index of the first Play whose
+/// recorded hash is wrong, i.e. where a replay (or a client's copy) diverged.
+pub fn find_desync(plays: &[Play]) -> Option<usize> {
+    let mut previous = INITIAL_STATE_HASH;
+    for (index, play) in plays.iter().enumerate() {
+        let expected = next_state_hash(previous, play);
+        if play.state_hash != expected {
+            return Some(index);
+        }
+        previous = expected;
+    }
+    None
+}
+
+/// Returns the Hand that must be beaten after these Plays of a Trick, i.e. the last one that is
+/// not a Pass, or Pass if nothing has been played yet.
+pub fn hand_to_beat(plays: &[Play]) -> Hand {
+    plays
+        .iter()
+        .rev()
+        .map(|play| play.hand)
+        .find(|hand| !matches!(hand, Hand::Pass))
+        .unwrap_or(Hand::Pass)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_hand_to_beat() {
+        assert_eq!(hand_to_beat(&[]), Hand::Pass);
+
+        let plays = [
+            Play::now(0, "3C".parse().unwrap(), 0),
+            Play::now(1, "5D".parse().unwrap(), 0),
+            Play::now(2, Hand::Pass, 0),
+        ];
+        assert_eq!(hand_to_beat(&plays), "5D".parse().unwrap());
+        assert_eq!(hand_to_beat(&plays[..1]), "3C".parse().unwrap());
+    }
+
+    #[test]
+    fn test_state_hash() {
+        let first = Play::following(None, 0, "3C".parse().unwrap(), 0);
+        let second = Play::following(Some(&first), 1, "5D".parse().unwrap(), 0);
+        let mut plays = vec![first, second];
+        assert_eq!(find_desync(&plays), None);
+        assert_eq!(first.elapsed, Duration::ZERO);
+        assert_eq!(
+            first.timestamp + second.elapsed,
+            second.timestamp,
+            "the pace of play is recorded"
+        );
+        assert_ne!(first.state_hash, second.state_hash);
+
+        // the same turns at another time hash the same
+        let again = Play::following(None, 0, "3C".parse().unwrap(), 0);
+        assert_eq!(again.state_hash, first.state_hash);
+
+        // a different turn diverges from there on
+        plays[1].hand = "6D".parse().unwrap();
+        assert_eq!(find_desync(&plays), Some(1));
+        plays[1].hand = "5D".parse().unwrap();
+        plays[0].player_id = 2;
+        assert_eq!(find_desync(&plays), Some(0));
+    }
+
+    #[test]
+    fn test_serialize_play() {
+        let play = Play::now(3, "3S 3C".parse().unwrap(), 7);
+        let json = serde_json::to_string(&play).unwrap();
+        assert_eq!(serde_json::from_str::<Play>(&json).unwrap(), play);
+
+        let pass = Play::now(1, Hand::Pass, 7).with_pass_reason(Some(PassReason::Forced));
+        let json = serde_json::to_string(&pass).unwrap();
+        assert!(serde_json::from_str::<Play>(&json)
+            .unwrap()
+            .is_forced_pass());
+        assert_eq!(pass.state_hash, Play::now(1, Hand::Pass, 7).state_hash);
+
+        // Plays recorded before pass reasons were kept
+        let json = json.replace(r#","pass_reason":"Forced""#, "");
+        assert_eq!(
+            serde_json::from_str::<Play>(&json).unwrap().pass_reason,
+            None
+        );
+    }
+}
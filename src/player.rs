@@ -1,27 +1,90 @@
 //! Represents a player in the game, could be AI or User.
 
+mod adaptive;
+mod control_cards;
 mod get_ai_input;
 mod get_cli_user_input;
+mod opponent_model;
+mod turn_view;
 
 use std::collections::BTreeSet;
-use std::fmt::Display;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use std::time::Duration;
 
+pub use adaptive::{AdaptiveAi, Difficulty, DIFFICULTIES, RECENT_GAMES};
+pub use control_cards::{
+    count_controls, is_control, should_spend, Aggressiveness, ParseAggressivenessError,
+};
 use get_ai_input::{
-    PLAY_SMALLEST_SINGLE_OR_PASS, START_TRICK_WITH_SMALLEST_SINGLE, USE_THREE_OF_CLUBS,
+    ALWAYS_CALL_REDEAL, ALWAYS_PASS, CALL_REDEAL_IF_WEAK, GIVE_LOWEST_CARDS, NEVER_CALL_MULLIGAN,
+    NEVER_PEEK, PLAY_SMALLEST_SINGLE_OR_PASS, PLAY_WHAT_OPPONENTS_CANNOT_BEAT,
+    PLAY_WHAT_OPPONENTS_CANNOT_BEAT_CAUTIOUSLY, PLAY_WHAT_OPPONENTS_CANNOT_BEAT_RECKLESSLY,
+    START_TRICK_WITH_SMALLEST_SINGLE, USE_LOWEST_CARD,
+};
+pub use get_cli_user_input::InputError;
+use get_cli_user_input::{
+    get_cli_user_cards, get_cli_user_confirmation, get_cli_user_input, get_cli_user_seat,
+    get_scripted_user_input,
 };
-use get_cli_user_input::get_cli_user_input;
+pub use opponent_model::{OpponentModel, Void};
+use serde::{Deserialize, Serialize};
+pub use turn_view::TurnView;
 
+use crate::analysis::deal_quality::DealQuality;
+use crate::analysis::partition::{partition, Objective, Partition};
+use crate::hand::generate::can_beat;
+use crate::output::current;
+use crate::rules::Rules;
 use crate::{card::Card, hand::Hand};
 
+/// How many deals of the unseen cards are sampled for the odds of each play being beaten.
+const ODDS_SAMPLES: usize = 100;
+
 /// Represents a player in the game, could be AI or User.
 /// We use settable Function-Pointers / Closures to change from AI to User.
 /// NOTE: Using settable Function-Pointers instead of Traits/Generics because
 /// it's just a bit easier for me right now.
 pub struct Player {
     pub cards: Vec<Card>,
-    pub submit_hand: fn(&Hand, &Vec<Card>) -> Hand,
-    pub start_game: fn(&Vec<Card>) -> Hand,
-    pub start_trick: fn(&Vec<Card>) -> Hand,
+    /// Total time this Player has spent deciding what to play this Game, as charged to their clock
+    /// (after the latency_allowance).
+    pub thinking_time: Duration,
+    /// Total wall-clock time this Player has spent deciding what to play this Game, including any
+    /// time their moves spent in transit.
+    pub raw_thinking_time: Duration,
+    /// Taken off each turn's time before it is charged, to make up for a slow connection (see
+    /// latency::Heartbeat). Zero for local players.
+    pub latency_allowance: Duration,
+    /// Thinking time left on this Player's clock, None if the Game is untimed.
+    pub time_left: Option<Duration>,
+    /// How long this Player may think about each decision, None for as long as they like. Only
+    /// the AI strategies that search (see TurnView::time_budget) pay attention to it.
+    pub time_budget: Option<Duration>,
+    /// Strict mode, how many invalid attempts this Player gets each turn before they forfeit.
+    /// None lets them retry forever.
+    pub max_attempts: Option<usize>,
+    /// Tells this Player, on their turn, what each opponent has shown they cannot beat.
+    pub show_inferences: bool,
+    /// Tells this Player, on their turn, how likely each of their legal plays is to be beaten.
+    pub show_odds: bool,
+    /// True once this Player has given up, or run out of attempts in strict mode.
+    pub forfeited: bool,
+    /// How the AI plays for this Player if it takes over, e.g. once a User's input has closed.
+    pub stand_in: (AiLevel, Aggressiveness),
+    /// Every card played in the Tricks this Player has won, for point-card variants.
+    pub captured: Vec<Card>,
+    pub submit_hand: fn(&TurnView) -> Hand,
+    pub start_game: fn(&TurnView) -> Hand,
+    pub start_trick: fn(&TurnView) -> Hand,
+    pub call_redeal: fn(&[Card], &Rules) -> bool,
+    /// Chooses which of its cards (the first argument) to give back in a card exchange, exactly
+    /// as many as the second argument.
+    pub give_cards: fn(&[Card], usize) -> Vec<Card>,
+    /// In practice (see rules::house::Practice), whether to have the cards dealt again.
+    pub call_mulligan: fn(&[Card], &Rules) -> bool,
+    /// In practice, which of the opponents (the second argument) to look at the cards of, if any.
+    pub choose_peek: fn(&[Card], &[usize]) -> Option<usize>,
 }
 
 impl Default for Player {
@@ -29,9 +92,134 @@ impl Default for Player {
     fn default() -> Self {
         Self {
             cards: vec![],
+            thinking_time: Duration::ZERO,
+            raw_thinking_time: Duration::ZERO,
+            latency_allowance: Duration::ZERO,
+            time_left: None,
+            time_budget: None,
+            max_attempts: None,
+            show_inferences: false,
+            show_odds: false,
+            forfeited: false,
+            stand_in: (AiLevel::default(), Aggressiveness::default()),
+            captured: vec![],
             submit_hand: PLAY_SMALLEST_SINGLE_OR_PASS,
-            start_game: USE_THREE_OF_CLUBS,
+            start_game: USE_LOWEST_CARD,
             start_trick: START_TRICK_WITH_SMALLEST_SINGLE,
+            call_redeal: ALWAYS_CALL_REDEAL,
+            give_cards: GIVE_LOWEST_CARDS,
+            call_mulligan: NEVER_CALL_MULLIGAN,
+            choose_peek: NEVER_PEEK,
+        }
+    }
+}
+
+/// How strongly the AI players play.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AiLevel {
+    /// Plays its smallest single card, and passes on anything else.
+    #[default]
+    Easy,
+    /// Infers what each opponent cannot beat from their passes, and plays accordingly.
+    Hard,
+}
+
+impl AiLevel {
+    /// A convenience for iterating through AiLevel's variants.
+    pub fn all() -> [AiLevel; 2] {
+        [AiLevel::Easy, AiLevel::Hard]
+    }
+}
+
+impl Display for AiLevel {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            AiLevel::Easy => write!(f, "easy"),
+            AiLevel::Hard => write!(f, "hard"),
+        }
+    }
+}
+
+/// Represents the possible errors from attempting to parse an AiLevel from a string.
+#[derive(Debug)]
+pub enum ParseAiLevelError {
+    /// Not the name of any AiLevel.
+    Unknown(String),
+}
+
+impl FromStr for AiLevel {
+    type Err = ParseAiLevelError;
+    fn from_str(level_str: &str) -> Result<Self, Self::Err> {
+        match level_str.to_lowercase().as_str() {
+            "easy" => Ok(AiLevel::Easy),
+            "hard" => Ok(AiLevel::Hard),
+            _ => Err(Self::Err::Unknown(level_str.to_string())),
+        }
+    }
+}
+
+/// Where the human seat's moves come from.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum HumanInput {
+    /// Typed at a prompt.
+    #[default]
+    Interactive,
+    /// Read from stdin one Hand per line (a file or a pipe), with a machine-readable line
+    /// describing the turn printed before each move.
+    Scripted,
+}
+
+/// useful for telling a User how their cards could be played out
+fn partition_hint(view: &TurnView) -> Partition {
+    partition(view.cards, Objective::FewestPlays, &view.rules)
+}
+
+/// useful for telling a User what the opponents have shown they cannot beat, leaving out
+/// whatever nothing unseen could beat anyway
+fn print_inferences(view: &TurnView) {
+    if !view.show_inferences {
+        return;
+    }
+    let model = OpponentModel::from_view(view);
+    let unseen = view.knowledge().unseen_cards();
+    for void in view.opponent_ids().flat_map(|id| model.voids(id)) {
+        if can_beat(&void.passed_on, &unseen, &view.rules) {
+            writeln!(current(), "=== {void}");
+        }
+    }
+}
+
+/// useful for telling a User how likely each of their plays is to be beaten, by sampling deals of
+/// the cards they cannot see
+fn print_odds(view: &TurnView) {
+    if !view.show_odds {
+        return;
+    }
+    let knowledge = view.knowledge();
+    let mut rng = rand::thread_rng();
+    for hand in view.legal_plays() {
+        if !matches!(hand, Hand::Pass) {
+            let odds = knowledge.beat_odds(hand, &view.rules, ODDS_SAMPLES, &mut rng);
+            writeln!(
+                current(),
+                "=== {hand} is beaten {:.0}% of the time",
+                odds * 100.0
+            );
+        }
+    }
+}
+
+/// useful for stopping when a User's input has closed, or giving up after too many attempts
+fn or_give_up(view: &TurnView, hand: Result<Hand, InputError>) -> Hand {
+    match hand {
+        Ok(hand) => hand,
+        Err(InputError::Closed) => {
+            view.close_input();
+            Hand::Pass
+        }
+        Err(InputError::GaveUp) => {
+            view.resign();
+            Hand::Pass
         }
     }
 }
@@ -42,27 +230,173 @@ fn cards_to_string(cards: &[Card]) -> String {
 }
 
 impl Player {
+    /// Use this to set how strongly an AI Player plays, and (for the hard AI) how freely it
+    /// spends its control cards.
+    pub fn convert_to_ai(&mut self, level: AiLevel, aggressiveness: Aggressiveness) {
+        let hard = match aggressiveness {
+            Aggressiveness::Cautious => PLAY_WHAT_OPPONENTS_CANNOT_BEAT_CAUTIOUSLY,
+            Aggressiveness::Balanced => PLAY_WHAT_OPPONENTS_CANNOT_BEAT,
+            Aggressiveness::Reckless => PLAY_WHAT_OPPONENTS_CANNOT_BEAT_RECKLESSLY,
+        };
+        let (submit_hand, start_game, start_trick, call_redeal) = match level {
+            AiLevel::Easy => (
+                PLAY_SMALLEST_SINGLE_OR_PASS,
+                USE_LOWEST_CARD,
+                START_TRICK_WITH_SMALLEST_SINGLE,
+                ALWAYS_CALL_REDEAL,
+            ),
+            AiLevel::Hard => (hard, hard, hard, CALL_REDEAL_IF_WEAK),
+        };
+        self.submit_hand = submit_hand;
+        self.start_game = start_game;
+        self.start_trick = start_trick;
+        self.call_redeal = call_redeal;
+    }
+
     /// Use this to transform any player from default AI into a User that
     /// accepts inputs from stdin.
     pub fn convert_to_stdio_user(&mut self) {
-        self.submit_hand = |_, cards| {
-            println!("=== Your Turn.");
-            println!("=== {}", cards_to_string(cards));
-            get_cli_user_input(&mut std::io::stdin().lock())
+        self.submit_hand = |view| {
+            writeln!(current(), "=== Your Turn.");
+            writeln!(current(), "=== {}", cards_to_string(view.cards));
+            print_inferences(view);
+            print_odds(view);
+            or_give_up(
+                view,
+                get_cli_user_input(&mut std::io::stdin().lock(), view.max_attempts),
+            )
+        };
+        self.start_game = |view| {
+            if let Some(card) = view.opening_card() {
+                writeln!(current(), "=== Please start the game using the |{card}|.");
+            }
+            writeln!(current(), "=== {}", cards_to_string(view.cards));
+            writeln!(
+                current(),
+                "=== Your cards break into: {}",
+                partition_hint(view)
+            );
+            print_odds(view);
+            or_give_up(
+                view,
+                get_cli_user_input(&mut std::io::stdin().lock(), view.max_attempts),
+            )
+        };
+        self.start_trick = |view| {
+            writeln!(
+                current(),
+                "=== Please start the trick by playing any valid hand."
+            );
+            writeln!(current(), "=== {}", cards_to_string(view.cards));
+            writeln!(
+                current(),
+                "=== Your cards break into: {}",
+                partition_hint(view)
+            );
+            print_inferences(view);
+            print_odds(view);
+            or_give_up(
+                view,
+                get_cli_user_input(&mut std::io::stdin().lock(), view.max_attempts),
+            )
+        };
+        self.call_redeal = |cards, rules| {
+            writeln!(
+                current(),
+                "=== Your cards are hopeless, would you like to call for a redeal? (y/n)"
+            );
+            writeln!(current(), "=== {}", cards_to_string(cards));
+            writeln!(
+                current(),
+                "=== You were dealt {}",
+                DealQuality::of(cards, rules)
+            );
+            get_cli_user_confirmation(&mut std::io::stdin().lock())
+        };
+        self.give_cards = |cards, num_cards| {
+            writeln!(current(), "=== Choose {num_cards} card(s) to give back.");
+            writeln!(current(), "=== {}", cards_to_string(cards));
+            get_cli_user_cards(&mut std::io::stdin().lock(), cards, num_cards, true)
+                .unwrap_or_else(|_| GIVE_LOWEST_CARDS(cards, num_cards))
         };
-        self.start_game = |cards| {
-            println!("=== Please start the game using the |3C|.");
-            println!("=== {}", cards_to_string(cards));
-            get_cli_user_input(&mut std::io::stdin().lock())
+        self.call_mulligan = |cards, rules| {
+            writeln!(
+                current(),
+                "=== Practice: would you like these cards dealt again? (y/n)"
+            );
+            writeln!(current(), "=== {}", cards_to_string(cards));
+            writeln!(
+                current(),
+                "=== You were dealt {}",
+                DealQuality::of(cards, rules)
+            );
+            get_cli_user_confirmation(&mut std::io::stdin().lock())
         };
-        self.start_trick = |cards| {
-            println!("=== Please start the trick by playing any valid hand.");
-            println!("=== {}", cards_to_string(cards));
-            get_cli_user_input(&mut std::io::stdin().lock())
+        self.choose_peek = |_, opponents| {
+            let seats: Vec<String> = opponents.iter().map(usize::to_string).collect();
+            writeln!(
+                current(),
+                "=== Practice: type the seat of an opponent to see their cards ({}), or nothing",
+                seats.join(", ")
+            );
+            get_cli_user_seat(&mut std::io::stdin().lock(), opponents, true)
         };
     }
 }
 
+impl Player {
+    /// Use this to transform any player into a User driven by a script: before each move a line
+    /// "STATE <json>" (see TurnView::to_json) is printed, then one line is read from stdin, e.g.
+    /// "3C 3D", or an empty line to Pass. Before a possible redeal "REDEAL <json cards>" is
+    /// printed, and "y" or "n" is read. Before a card exchange "GIVE <n> <json cards>" is printed,
+    /// and the n cards to give back are read, e.g. "3C 4D". In practice, "MULLIGAN <json cards>"
+    /// is answered "y" or "n", and "PEEK <json seats>" with one of the seats or an empty line.
+    pub fn convert_to_scripted_user(&mut self) {
+        let next_hand = |view: &TurnView| {
+            writeln!(current(), "STATE {}", view.to_json());
+            or_give_up(
+                view,
+                get_scripted_user_input(&mut std::io::stdin().lock(), view.max_attempts),
+            )
+        };
+        self.submit_hand = next_hand;
+        self.start_game = next_hand;
+        self.start_trick = next_hand;
+        self.call_redeal = |cards, _| {
+            let cards: Vec<String> = cards.iter().map(|card| card.to_string()).collect();
+            writeln!(current(), "REDEAL {}", serde_json::json!(cards));
+            get_cli_user_confirmation(&mut std::io::stdin().lock())
+        };
+        self.give_cards = |cards, num_cards| {
+            let card_strings: Vec<String> = cards.iter().map(|card| card.to_string()).collect();
+            writeln!(
+                current(),
+                "GIVE {num_cards} {}",
+                serde_json::json!(card_strings)
+            );
+            get_cli_user_cards(&mut std::io::stdin().lock(), cards, num_cards, false)
+                .unwrap_or_else(|_| GIVE_LOWEST_CARDS(cards, num_cards))
+        };
+        self.call_mulligan = |cards, _| {
+            let cards: Vec<String> = cards.iter().map(|card| card.to_string()).collect();
+            writeln!(current(), "MULLIGAN {}", serde_json::json!(cards));
+            get_cli_user_confirmation(&mut std::io::stdin().lock())
+        };
+        self.choose_peek = |_, opponents| {
+            writeln!(current(), "PEEK {}", serde_json::json!(opponents));
+            get_cli_user_seat(&mut std::io::stdin().lock(), opponents, false)
+        };
+    }
+
+    /// Use this to transform the human seat's Player into whichever kind of User it is.
+    pub fn convert_to_human(&mut self, input: HumanInput) {
+        match input {
+            HumanInput::Interactive => self.convert_to_stdio_user(),
+            HumanInput::Scripted => self.convert_to_scripted_user(),
+        }
+    }
+}
+
 impl Display for Player {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         write!(f, "{}", cards_to_string(&self.cards))
@@ -84,6 +418,60 @@ impl Player {
         }
     }
 
+    /// Used by the caller / game logic to charge a turn's thinking time to this Player's clock,
+    /// less their latency_allowance. Returns true if this turn used up the last of their time, they
+    /// are then flagged: from now on they pass, or start tricks with their smallest card.
+    pub fn record_thinking_time(&mut self, elapsed: Duration) -> bool {
+        self.raw_thinking_time += elapsed;
+        let elapsed = elapsed.saturating_sub(self.latency_allowance);
+        self.thinking_time += elapsed;
+        match self.time_left {
+            Some(time_left) if !self.is_flagged() => {
+                let time_left = time_left.saturating_sub(elapsed);
+                self.time_left = Some(time_left);
+                if time_left.is_zero() {
+                    self.play_automatically();
+                }
+                time_left.is_zero()
+            }
+            _ => false,
+        }
+    }
+
+    /// Hands this Player to the AI for the rest of the Game, playing as stand_in says.
+    pub fn hand_to_ai(&mut self) {
+        let (level, aggressiveness) = self.stand_in;
+        self.convert_to_ai(level, aggressiveness);
+    }
+
+    /// Used by the caller / game logic when this Player gives up, or runs out of attempts in
+    /// strict mode: from now on they pass, or start tricks with their smallest card.
+    pub fn forfeit(&mut self) {
+        self.forfeited = true;
+        self.play_automatically();
+    }
+
+    /// Passes, or starts tricks with the smallest card, for a Player who can no longer choose.
+    fn play_automatically(&mut self) {
+        self.submit_hand = ALWAYS_PASS;
+        self.start_game = USE_LOWEST_CARD;
+        self.start_trick = START_TRICK_WITH_SMALLEST_SINGLE;
+    }
+
+    /// How long this Player may think about this decision: their time_budget, cut short by what is
+    /// left on their clock. None if neither limits them.
+    pub fn decision_budget(&self) -> Option<Duration> {
+        match (self.time_budget, self.time_left) {
+            (Some(budget), Some(time_left)) => Some(budget.min(time_left)),
+            (budget, time_left) => budget.or(time_left),
+        }
+    }
+
+    /// Returns true if this Player has run out of thinking time.
+    pub fn is_flagged(&self) -> bool {
+        self.time_left == Some(Duration::ZERO)
+    }
+
     /// Used to make sure the Player actually has the cards they tried to play.
     pub fn has_cards(&self, hand: &Hand) -> bool {
         let cards: BTreeSet<&Card> = BTreeSet::from_iter(&self.cards);
@@ -95,9 +483,11 @@ impl Player {
 mod tests {
 
     use super::*;
+    use crate::output::{self, Buffer, Output};
     use crate::tests::test_util::vec_card_from_str;
 
     #[test]
+    #[allow(clippy::field_reassign_with_default)]
     fn test_has_cards() {
         let cards = vec_card_from_str("3C 3S 4H 4D 4S");
         let mut player = Player::default();
@@ -120,6 +510,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::field_reassign_with_default)]
     fn test_remove_cards_from_hand() {
         let mut player = Player::default();
         player.cards = vec_card_from_str("3D 3S 5S 6S");
@@ -129,4 +520,80 @@ mod tests {
         assert!(player.cards.contains(&"5S".parse().unwrap()));
         assert!(player.cards.contains(&"6S".parse().unwrap()));
     }
+
+    #[test]
+    fn test_record_thinking_time() {
+        let mut player = Player {
+            cards: vec_card_from_str("3D 5S"),
+            ..Default::default()
+        };
+        assert!(!player.record_thinking_time(Duration::from_secs(100)));
+        assert!(!player.is_flagged());
+
+        player.time_left = Some(Duration::from_secs(10));
+        assert!(!player.record_thinking_time(Duration::from_secs(6)));
+        assert!(player.record_thinking_time(Duration::from_secs(6)));
+        assert!(player.is_flagged());
+        assert!(!player.record_thinking_time(Duration::from_secs(1)));
+        assert_eq!(player.thinking_time, Duration::from_secs(113));
+
+        // a flagged Player passes, or leads their smallest card
+        let hand = (player.submit_hand)(&TurnView::new(&player.cards, "4D".parse().unwrap(), 4));
+        assert_eq!(hand, Hand::Pass);
+        let hand = (player.start_trick)(&TurnView::new(&player.cards, Hand::Pass, 4));
+        assert_eq!(hand, "3D".parse().unwrap());
+
+        // the clock cuts the budget short
+        let mut player = Player::default();
+        assert_eq!(player.decision_budget(), None);
+        player.time_budget = Some(Duration::from_secs(2));
+        assert_eq!(player.decision_budget(), Some(Duration::from_secs(2)));
+        player.time_left = Some(Duration::from_secs(1));
+        assert_eq!(player.decision_budget(), Some(Duration::from_secs(1)));
+
+        // a slow connection is not charged for
+        let mut player = Player {
+            time_left: Some(Duration::from_secs(10)),
+            latency_allowance: Duration::from_millis(300),
+            ..Default::default()
+        };
+        player.record_thinking_time(Duration::from_secs(2));
+        player.record_thinking_time(Duration::from_millis(100));
+        assert_eq!(player.time_left, Some(Duration::from_millis(8300)));
+        assert_eq!(player.thinking_time, Duration::from_millis(1700));
+        assert_eq!(player.raw_thinking_time, Duration::from_millis(2100));
+    }
+
+    #[test]
+    fn test_ai_level_to_from_string() {
+        for level in AiLevel::all() {
+            assert_eq!(level.to_string().parse::<AiLevel>().unwrap(), level);
+        }
+        assert!(matches!("HARD".parse::<AiLevel>(), Ok(AiLevel::Hard)));
+        assert!(matches!(
+            "impossible".parse::<AiLevel>(),
+            Err(ParseAiLevelError::Unknown(_))
+        ));
+    }
+
+    #[test]
+    fn test_print_odds() {
+        let cards = vec_card_from_str("3C 4D 4S 2S");
+        let mut view = TurnView::new(&cards, "5H".parse().unwrap(), 4);
+        let printed = Buffer::default();
+        {
+            let _output = output::install(Output::to(printed.clone()));
+            print_odds(&view);
+            view.show_odds = true;
+            print_odds(&view);
+        }
+        // every legal play but the Pass, nothing can beat the 2S
+        assert_eq!(
+            printed.contents().lines().count(),
+            1,
+            "{}",
+            printed.contents()
+        );
+        assert!(printed.contents().contains("2S is beaten 0% of the time"));
+    }
 }
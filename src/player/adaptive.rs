@@ -0,0 +1,184 @@
+//! Keeps single-player Matches competitive: the AI seats play at a Difficulty that is nudged up
+//! when the people at the table keep winning, and down when they keep losing, between Games.
+//!
+//! With four players an even Game is won a quarter of the time, so the AI plays harder once the
+//! people have won half of the last few Games, and easier once they have won none of them.
+
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::GameConfig;
+use crate::game::GameResult;
+use crate::player::{Aggressiveness, AiLevel};
+
+/// How many Games are looked back on before the Difficulty is nudged down.
+pub const RECENT_GAMES: usize = 4;
+
+/// How strongly the AI seats play: its level, how freely it spends controls, and how long it may
+/// search each decision.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Difficulty {
+    pub ai_level: AiLevel,
+    pub aggressiveness: Aggressiveness,
+    pub time_budget: Option<Duration>,
+}
+
+impl Display for Difficulty {
+    /// e.g. "hard, balanced, 100ms per decision".
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self.ai_level {
+            AiLevel::Easy => write!(f, "{}", self.ai_level)?,
+            AiLevel::Hard => write!(f, "{}, {}", self.ai_level, self.aggressiveness)?,
+        }
+        match self.time_budget {
+            Some(budget) if self.ai_level == AiLevel::Hard => {
+                write!(f, ", {budget:?} per decision")
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Every Difficulty the AI is nudged between, easiest first.
+pub const DIFFICULTIES: [Difficulty; 4] = [
+    Difficulty {
+        ai_level: AiLevel::Easy,
+        aggressiveness: Aggressiveness::Balanced,
+        time_budget: None,
+    },
+    Difficulty {
+        ai_level: AiLevel::Hard,
+        aggressiveness: Aggressiveness::Reckless,
+        time_budget: Some(Duration::from_millis(10)),
+    },
+    Difficulty {
+        ai_level: AiLevel::Hard,
+        aggressiveness: Aggressiveness::Balanced,
+        time_budget: Some(Duration::from_millis(100)),
+    },
+    Difficulty {
+        ai_level: AiLevel::Hard,
+        aggressiveness: Aggressiveness::Balanced,
+        time_budget: None,
+    },
+];
+
+/// Where the AI stands on DIFFICULTIES, and how the people have done since it last moved. Kept in
+/// the Profile so it carries over from one Match to the next.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AdaptiveAi {
+    /// The index of the current Difficulty.
+    pub step: usize,
+
+    /// Whether the people won each Game since the Difficulty last changed, oldest first, at most
+    /// RECENT_GAMES of them.
+    pub recent: Vec<bool>,
+}
+
+impl Default for AdaptiveAi {
+    /// Starts one step up from the easiest.
+    fn default() -> Self {
+        Self {
+            step: 1,
+            recent: vec![],
+        }
+    }
+}
+
+impl AdaptiveAi {
+    /// The Difficulty the AI seats play at now.
+    pub fn difficulty(&self) -> Difficulty {
+        DIFFICULTIES[self.step.min(DIFFICULTIES.len() - 1)]
+    }
+
+    /// Sets every AI seat of the config to play at the current Difficulty.
+    pub fn apply(&self, config: &mut GameConfig) {
+        let difficulty = self.difficulty();
+        config.ai_level = difficulty.ai_level;
+        config.aggressiveness = difficulty.aggressiveness;
+        config.ai_time_budget = difficulty.time_budget;
+        for seat in config.seats.iter_mut().filter(|seat| seat.is_ai()) {
+            seat.ai_level = difficulty.ai_level;
+            seat.aggressiveness = difficulty.aggressiveness;
+        }
+    }
+
+    /// Remembers how a Game played with this config went for the people at the table (a draw is
+    /// not a win), and nudges the Difficulty if they have been winning or losing too often. A Game
+    /// played for practice is not remembered.
+    pub fn record(&mut self, config: &GameConfig, result: &GameResult) {
+        if result.is_practice() {
+            return;
+        }
+        let won = !result.is_draw() && config.people().contains(&result.winner);
+        self.recent.push(won);
+        if self.recent.len() > RECENT_GAMES {
+            self.recent.remove(0);
+        }
+        let wins = self.recent.iter().filter(|won| **won).count();
+        if 2 * wins >= RECENT_GAMES && self.step + 1 < DIFFICULTIES.len() {
+            self.step += 1;
+            self.recent.clear();
+        } else if wins == 0 && self.recent.len() == RECENT_GAMES && self.step > 0 {
+            self.step -= 1;
+            self.recent.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::seats::{Controller, SeatConfig};
+    use crate::tests::test_util::vec_card_from_str;
+    use crate::trick::NUM_PLAYERS;
+
+    fn won_by(winner: usize, config: &GameConfig) -> GameResult {
+        let mut remaining = ["3D", "4D", "5D", "6D"].map(vec_card_from_str);
+        remaining[winner].clear();
+        GameResult::new(winner, false, &remaining, config)
+    }
+
+    #[test]
+    fn test_adaptive_ai() {
+        let mut config = GameConfig::default();
+        let mut adaptive = AdaptiveAi::default();
+        adaptive.apply(&mut config);
+        assert_eq!(config.seat(1).aggressiveness, Aggressiveness::Reckless);
+        assert_eq!(
+            adaptive.difficulty().to_string(),
+            "hard, reckless, 10ms per decision"
+        );
+
+        // two wins in a row makes it harder
+        adaptive.record(&config, &won_by(0, &config));
+        assert_eq!(adaptive.step, 1);
+        adaptive.record(&config, &won_by(0, &config));
+        assert_eq!(adaptive.step, 2);
+        assert!(adaptive.recent.is_empty());
+
+        // a win in every RECENT_GAMES keeps it where it is, none makes it easier
+        for winner in [1, 0, 2, 3, 1, 0, 2, 3, 1] {
+            adaptive.record(&config, &won_by(winner, &config));
+            assert_eq!(adaptive.step, 2);
+        }
+        adaptive.record(&config, &won_by(2, &config));
+        assert_eq!(adaptive.step, 1);
+
+        // every AI seat is set, the people are left alone
+        config.seats = vec![SeatConfig::default(); NUM_PLAYERS];
+        config.seats[0].controller = Controller::Human;
+        adaptive.step = 0;
+        adaptive.apply(&mut config);
+        assert_eq!(config.seats[2].ai_level, AiLevel::Easy);
+        assert_eq!(config.ai_time_budget, None);
+        adaptive.step = 3;
+        adaptive.apply(&mut config);
+        assert_eq!(config.seats[2].ai_level, AiLevel::Hard);
+        assert_eq!(config.seats[0].ai_level, AiLevel::Easy);
+        assert_eq!(adaptive.difficulty().to_string(), "hard, balanced");
+    }
+}
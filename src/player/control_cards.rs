@@ -0,0 +1,182 @@
+//! Recognizes "control cards", the Hands that win a Trick outright and so give their player the
+//! lead, and schedules when the AI spends them.
+//!
+//! Controls are 2s, Bombs (Four-Plus-Kick and Straight-Flush) and Full-Houses nothing unseen can
+//! beat. Spent early they win a Trick that does not matter, kept back they win the Trick that lets
+//! the player go out.
+
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::card::{rank::Rank, Card};
+use crate::constants::CARDS_PER_PLAYER;
+use crate::hand::generate::hands_of_size;
+use crate::hand::Hand;
+use crate::player::TurnView;
+use crate::scoring::{count_bombs, count_twos};
+
+/// How freely the AI spends its control cards.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Aggressiveness {
+    /// Keeps two controls back, only spends them early when an opponent is about to go out.
+    Cautious,
+    /// Keeps one control back for the final Trick.
+    #[default]
+    Balanced,
+    /// Spends controls whenever they are the best play.
+    Reckless,
+}
+
+impl Aggressiveness {
+    /// How many controls to keep back until the player can go out.
+    pub fn reserve(&self) -> usize {
+        match self {
+            Aggressiveness::Cautious => 2,
+            Aggressiveness::Balanced => 1,
+            Aggressiveness::Reckless => 0,
+        }
+    }
+
+    /// An opponent holding this many cards or fewer is about to go out, and must be stopped.
+    pub fn danger(&self) -> usize {
+        match self {
+            Aggressiveness::Cautious => 1,
+            Aggressiveness::Balanced => 2,
+            Aggressiveness::Reckless => CARDS_PER_PLAYER,
+        }
+    }
+}
+
+impl Display for Aggressiveness {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            Aggressiveness::Cautious => write!(f, "cautious"),
+            Aggressiveness::Balanced => write!(f, "balanced"),
+            Aggressiveness::Reckless => write!(f, "reckless"),
+        }
+    }
+}
+
+/// Represents the possible errors from attempting to parse an Aggressiveness from a string.
+#[derive(Debug)]
+pub enum ParseAggressivenessError {
+    /// Not the name of any Aggressiveness.
+    Unknown(String),
+}
+
+impl FromStr for Aggressiveness {
+    type Err = ParseAggressivenessError;
+    fn from_str(aggressiveness_str: &str) -> Result<Self, Self::Err> {
+        match aggressiveness_str.to_lowercase().as_str() {
+            "cautious" => Ok(Aggressiveness::Cautious),
+            "balanced" => Ok(Aggressiveness::Balanced),
+            "reckless" => Ok(Aggressiveness::Reckless),
+            _ => Err(Self::Err::Unknown(aggressiveness_str.to_string())),
+        }
+    }
+}
+
+/// Returns true if this Hand is a control, i.e. it holds a 2, is a Bomb, or is a Full-House that
+/// no Trips among the unseen cards can top.
+pub fn is_control(hand: &Hand, unseen: &[Card]) -> bool {
+    match hand {
+        Hand::Pass => false,
+        Hand::FourPlusKick(..) | Hand::StraightFlush(..) => true,
+        Hand::FullHouse(..) => {
+            let (trips, _) = hand.rank_groups()[0];
+            let higher_trips = Rank::all()
+                .into_iter()
+                .filter(|rank| *rank > trips)
+                .any(|rank| unseen.iter().filter(|card| card.rank == rank).count() >= 3);
+            !higher_trips && count_bombs(unseen) == 0
+        }
+        Hand::Straight(..) | Hand::Flush(..) => false,
+        _ => hand.cards().any(|card| card.rank == Rank::Two),
+    }
+}
+
+/// Counts the controls held in these cards, every 2 and every Bomb.
+pub fn count_controls(cards: &[Card]) -> usize {
+    (count_twos(cards) + count_bombs(cards)) as usize
+}
+
+/// Returns true if the AI may play this Hand now: it is not a control, or enough controls are
+/// left over afterwards, or the rest of the cards go out in one Hand, or an opponent is about to
+/// go out.
+pub fn should_spend(view: &TurnView, hand: &Hand, aggressiveness: Aggressiveness) -> bool {
+    if !is_control(hand, &view.knowledge().unseen_cards()) {
+        return true;
+    }
+    let left: Vec<Card> = view
+        .cards
+        .iter()
+        .filter(|card| !hand.cards().any(|c| c == *card))
+        .copied()
+        .collect();
+    let goes_out = left.is_empty() || !hands_of_size(&left, left.len()).is_empty();
+    let danger = view
+        .opponent_ids()
+        .any(|id| view.card_counts[id] <= aggressiveness.danger());
+    goes_out || danger || count_controls(&left) >= aggressiveness.reserve()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::tests::test_util::vec_card_from_str;
+
+    #[test]
+    fn test_aggressiveness_to_from_string() {
+        for aggressiveness in [
+            Aggressiveness::Cautious,
+            Aggressiveness::Balanced,
+            Aggressiveness::Reckless,
+        ] {
+            let parsed: Aggressiveness = aggressiveness.to_string().parse().unwrap();
+            assert_eq!(parsed, aggressiveness);
+        }
+        assert!(matches!(
+            "timid".parse::<Aggressiveness>(),
+            Err(ParseAggressivenessError::Unknown(_))
+        ));
+    }
+
+    #[test]
+    fn test_is_control() {
+        let unseen = vec_card_from_str("KS KH KD 9C");
+        assert!(is_control(&"2D".parse().unwrap(), &unseen));
+        assert!(!is_control(&"AS".parse().unwrap(), &unseen));
+        assert!(is_control(&"6S 6H 6D 6C 3D".parse().unwrap(), &unseen));
+        // the unseen Kings could top Queens, but not Aces
+        assert!(!is_control(&"QS QH QD 4S 4H".parse().unwrap(), &unseen));
+        assert!(is_control(&"AS AH AD 4S 4H".parse().unwrap(), &unseen));
+    }
+
+    #[test]
+    fn test_should_spend() {
+        let cards = vec_card_from_str("4D 6C 9H 2S");
+        let two: Hand = "2S".parse().unwrap();
+        let mut view = TurnView::new(&cards, "KD".parse().unwrap(), 4);
+        // the only 2 is kept for the end, unless the AI is reckless
+        assert!(!should_spend(&view, &two, Aggressiveness::Balanced));
+        assert!(should_spend(&view, &two, Aggressiveness::Reckless));
+        assert!(should_spend(
+            &view,
+            &"9H".parse().unwrap(),
+            Aggressiveness::Cautious
+        ));
+
+        // an opponent with 2 cards left must be stopped
+        view.card_counts = vec![4, 13, 2, 13];
+        assert!(should_spend(&view, &two, Aggressiveness::Balanced));
+        assert!(!should_spend(&view, &two, Aggressiveness::Cautious));
+
+        // the rest goes out as one Pair
+        let cards = vec_card_from_str("9D 9H 2S");
+        let view = TurnView::new(&cards, "KD".parse().unwrap(), 4);
+        assert!(should_spend(&view, &two, Aggressiveness::Cautious));
+    }
+}
@@ -1,12 +1,43 @@
-use crate::card::{Card, THREE_OF_CLUBS};
-use crate::hand::Hand;
+use std::cmp::Reverse;
 
-pub const USE_THREE_OF_CLUBS: fn(&Vec<Card>) -> Hand = |cards| {
-    assert_eq!(cards[0], THREE_OF_CLUBS);
+use crate::analysis::deal_quality::{DealQuality, DealStrength};
+use crate::card::Card;
+use crate::hand::{order::order, Hand};
+use crate::player::control_cards::{should_spend, Aggressiveness};
+use crate::player::opponent_model::{seeded_rng, OpponentModel};
+use crate::player::TurnView;
+use crate::rules::Rules;
 
-    match cards[..] {
-        [a, b, c, ..] => {
-            if let Ok(trips) = Hand::try_trips(c, b, a) {
+/// How many deals of the unseen cards the opponent-modeling AI samples each turn.
+const OPPONENT_MODEL_SAMPLES: usize = 48;
+
+/// The opponent-modeling AI considers a Hand safe if it is beaten at most this often.
+const SAFE_ODDS: f64 = 0.3;
+
+/// An opponent holding this many cards or fewer is close to going out, the hard AI plays to stop
+/// them rather than for itself.
+const THREAT_CARDS: usize = 3;
+
+/// The AI's choice if it is one of the legal plays, otherwise the weakest legal play (a Pass if
+/// it may pass), e.g. when a house rule forbids what the AI would rather play.
+fn legal_or_weakest(view: &TurnView, hand: Hand) -> Hand {
+    if view.is_legal(&hand) {
+        return hand;
+    }
+    view.legal_plays()
+        .iter()
+        .min_by_key(|hand| (hand.cards().count(), hand.cards().max().copied()))
+        .copied()
+        .unwrap_or(Hand::Pass)
+}
+
+/// Opens the Game with the lowest card in play (the Three of Clubs, unless it is dead or was not
+/// dealt), in the biggest Hand of a kind it makes.
+pub const USE_LOWEST_CARD: fn(&TurnView) -> Hand = |view| {
+    let hand = match view.cards[..] {
+        [a, b, ..] => {
+            let trips = view.cards.get(2).map(|c| Hand::try_trips(*c, b, a));
+            if let Some(Ok(trips)) = trips {
                 trips
             } else if let Ok(pair) = Hand::try_pair(b, a) {
                 pair
@@ -14,35 +45,149 @@ pub const USE_THREE_OF_CLUBS: fn(&Vec<Card>) -> Hand = |cards| {
                 Hand::Lone(a)
             }
         }
-        _ => panic!("oop"),
-    }
+        [a] => Hand::Lone(a),
+        [] => panic!("a player with no cards cannot open the Game"),
+    };
+    legal_or_weakest(view, hand)
 };
 
-pub const PLAY_SMALLEST_SINGLE_OR_PASS: fn(&Hand, &Vec<Card>) -> Hand = |hand, cards| {
-    if let Hand::Lone(c) = hand {
-        for card in cards {
-            if card > c {
-                return Hand::Lone(*card);
+/// Plays the smallest single that beats hand_to_beat, but never beats its partner.
+pub const PLAY_SMALLEST_SINGLE_OR_PASS: fn(&TurnView) -> Hand = |view| {
+    if view.partner_is_winning() {
+        return Hand::Pass;
+    }
+    if let Hand::Lone(c) = view.hand_to_beat {
+        for card in view.cards {
+            if *card > c {
+                return legal_or_weakest(view, Hand::Lone(*card));
             }
         }
     }
-    Hand::Pass
+    legal_or_weakest(view, Hand::Pass)
 };
 
-pub const START_TRICK_WITH_SMALLEST_SINGLE: fn(&Vec<Card>) -> Hand = |cards| Hand::Lone(cards[0]);
+pub const START_TRICK_WITH_SMALLEST_SINGLE: fn(&TurnView) -> Hand =
+    |view| legal_or_weakest(view, Hand::Lone(view.cards[0]));
+
+pub const ALWAYS_PASS: fn(&TurnView) -> Hand = |_| Hand::Pass;
+
+/// Plays the biggest Hand (then the lowest) the opponents are unlikely to beat, judging from what
+/// they have passed on, keeping one control card back for the final Trick. When an opponent is
+/// close to going out, it plays whatever that opponent is least likely to beat instead. If nothing is safe, it
+/// plays the weakest Hand that beats hand_to_beat, or leads the biggest Hand it can. Only passes
+/// when it cannot (or will not yet) beat hand_to_beat. In partnerships it lets its partner's Hand
+/// stand, unless it can go out.
+pub const PLAY_WHAT_OPPONENTS_CANNOT_BEAT: fn(&TurnView) -> Hand =
+    |view| play_what_opponents_cannot_beat(view, Aggressiveness::Balanced);
+
+/// As PLAY_WHAT_OPPONENTS_CANNOT_BEAT, but keeps two control cards back.
+pub const PLAY_WHAT_OPPONENTS_CANNOT_BEAT_CAUTIOUSLY: fn(&TurnView) -> Hand =
+    |view| play_what_opponents_cannot_beat(view, Aggressiveness::Cautious);
+
+/// As PLAY_WHAT_OPPONENTS_CANNOT_BEAT, but spends control cards as soon as they are useful.
+pub const PLAY_WHAT_OPPONENTS_CANNOT_BEAT_RECKLESSLY: fn(&TurnView) -> Hand =
+    |view| play_what_opponents_cannot_beat(view, Aggressiveness::Reckless);
+
+fn play_what_opponents_cannot_beat(view: &TurnView, aggressiveness: Aggressiveness) -> Hand {
+    let mut candidates: Vec<Hand> = view
+        .legal_plays()
+        .iter()
+        .filter(|hand| !matches!(hand, Hand::Pass))
+        .copied()
+        .collect();
+    // let a partner's Hand stand, unless this player can go out on it
+    if view.partner_is_winning() {
+        candidates.retain(|hand| hand.cards().count() == view.cards.len());
+    }
+    // hold the controls back, unless leading and there is nothing else
+    let scheduled: Vec<Hand> = candidates
+        .iter()
+        .filter(|hand| should_spend(view, hand, aggressiveness))
+        .copied()
+        .collect();
+    if !scheduled.is_empty() || !matches!(view.hand_to_beat, Hand::Pass) {
+        candidates = scheduled;
+    }
+    if candidates.is_empty() {
+        return Hand::Pass;
+    }
+    candidates.sort_by_key(|hand| (Reverse(hand.cards().count()), hand.cards().max().copied()));
+
+    let model = OpponentModel::from_view(view);
+    let deadline = view.deadline();
+
+    // stop whoever is closest to going out, by playing what they are least likely to beat
+    let threat = view
+        .opponent_ids()
+        .filter(|id| view.is_rival(*id) && (1..=THREAT_CARDS).contains(&view.card_counts[*id]))
+        .min_by_key(|id| view.card_counts[*id]);
+    if let Some(threat) = threat {
+        let odds = model.beat_probabilities_by(
+            view,
+            &candidates,
+            &[threat],
+            OPPONENT_MODEL_SAMPLES,
+            deadline,
+            &mut seeded_rng(view),
+        );
+        let lowest = odds.iter().copied().fold(1.0, f64::min);
+        if lowest < 1.0 {
+            let stopper = odds.iter().position(|odds| *odds <= lowest).unwrap();
+            return candidates[stopper];
+        }
+    }
+
+    let odds = model.beat_probabilities(
+        view,
+        &candidates,
+        OPPONENT_MODEL_SAMPLES,
+        deadline,
+        &mut seeded_rng(view),
+    );
+    if let Some(safest) = odds.iter().position(|odds| *odds <= SAFE_ODDS) {
+        return candidates[safest];
+    }
+    match view.hand_to_beat {
+        Hand::Pass => candidates[0],
+        _ => candidates
+            .into_iter()
+            .min_by(|a, b| order(a, b).unwrap_or(std::cmp::Ordering::Equal))
+            .unwrap(),
+    }
+}
+
+pub const ALWAYS_CALL_REDEAL: fn(&[Card], &Rules) -> bool = |_, _| true;
+
+/// Calls for a redeal only if the cards are weak, see DealQuality.
+pub const CALL_REDEAL_IF_WEAK: fn(&[Card], &Rules) -> bool =
+    |cards, rules| DealQuality::of(cards, rules).strength() == DealStrength::Weak;
+
+/// Keeps the cards it was dealt, the AI never takes a mulligan.
+pub const NEVER_CALL_MULLIGAN: fn(&[Card], &Rules) -> bool = |_, _| false;
+
+/// Never looks at an opponent's cards, even when it may.
+pub const NEVER_PEEK: fn(&[Card], &[usize]) -> Option<usize> = |_, _| None;
+
+/// Gives away the lowest cards in a card exchange.
+pub const GIVE_LOWEST_CARDS: fn(&[Card], usize) -> Vec<Card> = |cards, num_cards| {
+    let mut cards = cards.to_vec();
+    cards.sort();
+    cards.truncate(num_cards);
+    cards
+};
 
 #[cfg(test)]
 mod tests {
 
     use super::*;
-    use crate::card::{rank::Rank, suit::Suit};
+    use crate::card::{rank::Rank, suit::Suit, THREE_OF_CLUBS};
     use crate::tests::test_util::vec_card_from_str;
 
     #[test]
     fn test_play_smallest_single_or_pass() {
         let hand_to_beat: Hand = "4H".parse().unwrap();
         let player_cards = vec_card_from_str("4D 4S 5C");
-        let hand = (PLAY_SMALLEST_SINGLE_OR_PASS)(&hand_to_beat, &player_cards);
+        let hand = (PLAY_SMALLEST_SINGLE_OR_PASS)(&TurnView::new(&player_cards, hand_to_beat, 4));
         assert!(matches!(
             hand,
             Hand::Lone(Card {
@@ -53,27 +198,99 @@ mod tests {
 
         let hand_to_beat: Hand = "4H 4C".parse().unwrap();
         let player_cards = vec_card_from_str("4D 4S 5C");
-        let hand = (PLAY_SMALLEST_SINGLE_OR_PASS)(&hand_to_beat, &player_cards);
+        let hand = (PLAY_SMALLEST_SINGLE_OR_PASS)(&TurnView::new(&player_cards, hand_to_beat, 4));
         assert!(matches!(hand, Hand::Pass));
 
         let hand_to_beat: Hand = "6C".parse().unwrap();
         let player_cards = vec_card_from_str("4D 4S 5C");
-        let hand = (PLAY_SMALLEST_SINGLE_OR_PASS)(&hand_to_beat, &player_cards);
+        let hand = (PLAY_SMALLEST_SINGLE_OR_PASS)(&TurnView::new(&player_cards, hand_to_beat, 4));
         assert!(matches!(hand, Hand::Pass));
     }
 
     #[test]
-    fn test_use_three_of_clubs() {
+    fn test_use_lowest_card() {
         let cards = vec_card_from_str("3C 4C 5D 2S");
-        let hand = USE_THREE_OF_CLUBS(&cards);
+        let hand = USE_LOWEST_CARD(&TurnView::new(&cards, Hand::Pass, 4));
         assert!(matches!(hand, Hand::Lone(a) if a == THREE_OF_CLUBS));
 
         let cards = vec_card_from_str("3C 3D 5D 2S");
-        let hand = USE_THREE_OF_CLUBS(&cards);
+        let hand = USE_LOWEST_CARD(&TurnView::new(&cards, Hand::Pass, 4));
         assert!(matches!(hand, Hand::Pair(_, a) if a == THREE_OF_CLUBS));
 
         let cards = vec_card_from_str("3C 3D 3S 2S");
-        let hand = USE_THREE_OF_CLUBS(&cards);
+        let hand = USE_LOWEST_CARD(&TurnView::new(&cards, Hand::Pass, 4));
         assert!(matches!(hand, Hand::Trips(_, _, a) if a == THREE_OF_CLUBS));
+
+        // without the Three of Clubs, and with fewer than three cards
+        let cards = vec_card_from_str("5H 5S");
+        let hand = USE_LOWEST_CARD(&TurnView::new(&cards, Hand::Pass, 4));
+        assert_eq!(hand, "5S 5H".parse().unwrap());
+        let cards = vec_card_from_str("9D");
+        let hand = USE_LOWEST_CARD(&TurnView::new(&cards, Hand::Pass, 4));
+        assert_eq!(hand, "9D".parse().unwrap());
+    }
+
+    #[test]
+    fn test_play_what_opponents_cannot_beat() {
+        // nothing beats the Ace
+        let cards = vec_card_from_str("4D 5C");
+        let hand =
+            PLAY_WHAT_OPPONENTS_CANNOT_BEAT(&TurnView::new(&cards, "AH".parse().unwrap(), 4));
+        assert_eq!(hand, Hand::Pass);
+
+        // the very first Hand must include the 3C
+        let cards = vec_card_from_str("3C 3S 9D 2S");
+        let mut view = TurnView::new(&cards, Hand::Pass, 4);
+        view.must_play_three_of_clubs = true;
+        let hand = PLAY_WHAT_OPPONENTS_CANNOT_BEAT(&view);
+        assert!(hand.cards().any(|card| *card == THREE_OF_CLUBS));
+
+        // with no time to think, it still plays legally
+        view.time_budget = Some(std::time::Duration::ZERO);
+        let hand = PLAY_WHAT_OPPONENTS_CANNOT_BEAT(&view);
+        assert!(hand.cards().any(|card| *card == THREE_OF_CLUBS));
+
+        // the only card that beats is the only control, kept back for the end
+        let mut view = TurnView::new(&cards, "AH".parse().unwrap(), 4);
+        assert_eq!(PLAY_WHAT_OPPONENTS_CANNOT_BEAT(&view), Hand::Pass);
+        assert_eq!(
+            PLAY_WHAT_OPPONENTS_CANNOT_BEAT_RECKLESSLY(&view),
+            "2S".parse().unwrap()
+        );
+
+        // unless an opponent is about to go out
+        view.card_counts = vec![4, 4, 1, 4];
+        assert_eq!(
+            PLAY_WHAT_OPPONENTS_CANNOT_BEAT(&view),
+            "2S".parse().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_play_to_stop_the_threat() {
+        // player 1 has a single card left, so the AI leads high rather than low
+        let cards = vec_card_from_str("4C 9D");
+        let mut view = TurnView::new(&cards, Hand::Pass, 4);
+        view.card_counts = vec![2, 1, 13, 13];
+        assert_eq!(
+            PLAY_WHAT_OPPONENTS_CANNOT_BEAT(&view),
+            "9D".parse().unwrap()
+        );
+
+        // and beats a low card high, not with the weakest card that beats it
+        let cards = vec_card_from_str("6C AS");
+        let mut view = TurnView::new(&cards, "5D".parse().unwrap(), 4);
+        view.card_counts = vec![2, 1, 13, 13];
+        assert_eq!(
+            PLAY_WHAT_OPPONENTS_CANNOT_BEAT(&view),
+            "AS".parse().unwrap()
+        );
+
+        // nobody is close to going out, so the AI plays low
+        view.card_counts = vec![2, 13, 13, 13];
+        assert_eq!(
+            PLAY_WHAT_OPPONENTS_CANNOT_BEAT(&view),
+            "6C".parse().unwrap()
+        );
     }
 }
@@ -1,48 +1,211 @@
-use std::io::BufRead;
-use std::io::{self, Write};
+use std::io::{BufRead, ErrorKind};
 
 use crate::card::Card;
+use crate::exchange::is_valid_gift;
 use crate::hand::Hand;
+use crate::output::current;
 
-pub fn get_cli_user_input<Input: BufRead>(f: &mut Input) -> Hand {
+/// Why no Hand was read from a User's input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputError {
+    /// The input has closed, e.g. a piped file has run out or the terminal has gone away.
+    Closed,
+
+    /// Strict mode, and max_attempts lines in a row were not Hands.
+    GaveUp,
+}
+
+/// Reads the next line. Err(InputError::Closed) once the input has reached its end or can no
+/// longer be read, None for a line that is not text.
+fn next_line<Input: BufRead>(f: &mut Input) -> Result<Option<String>, InputError> {
+    let mut line = String::new();
     loop {
-        let mut line = String::new();
-        print!("=== > ");
-
-        io::stdout().flush().unwrap();
-        f.read_line(&mut line).unwrap();
-
-        let mut cards = vec![];
-        let mut card_errs = vec![];
-
-        let card_strs: Vec<&str> = line.split_whitespace().collect();
-        for card_str in card_strs {
-            let maybe_card = card_str.to_uppercase().parse::<Card>();
-            match maybe_card {
-                Err(e) => {
-                    println!("error: could not understand {card_str}, {:?}", e);
-                    card_errs.push(e);
-                }
-                Ok(c) => cards.push(c),
+        match f.read_line(&mut line) {
+            Ok(0) => {
+                writeln!(current(), "error: no more input");
+                return Err(InputError::Closed);
+            }
+            Ok(_) => return Ok(Some(line)),
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) if e.kind() == ErrorKind::InvalidData => {
+                writeln!(
+                    current(),
+                    "error: could not understand that line, it is not text"
+                );
+                return Ok(None);
+            }
+            Err(e) => {
+                writeln!(current(), "error: could not read input, {e}");
+                return Err(InputError::Closed);
             }
         }
+    }
+}
+
+/// Prompts for a Hand until one is typed. Fails if the input has closed, or (in strict mode)
+/// after max_attempts lines that are not Hands.
+pub fn get_cli_user_input<Input: BufRead>(
+    f: &mut Input,
+    max_attempts: Option<usize>,
+) -> Result<Hand, InputError> {
+    read_user_hand(f, max_attempts, true)
+}
+
+/// Reads one Hand per line, without prompting, for scripts driving a seat through a file or pipe.
+/// Fails as get_cli_user_input does.
+pub fn get_scripted_user_input<Input: BufRead>(
+    f: &mut Input,
+    max_attempts: Option<usize>,
+) -> Result<Hand, InputError> {
+    read_user_hand(f, max_attempts, false)
+}
 
-        if card_errs.is_empty() {
-            cards.sort();
-            cards.reverse();
-            if let Err(e) = Hand::sanitize_cards(&cards) {
-                println!("error: sanitize cards failed {:?}", e);
+fn read_user_hand<Input: BufRead>(
+    f: &mut Input,
+    max_attempts: Option<usize>,
+    prompt: bool,
+) -> Result<Hand, InputError> {
+    let mut attempts = 0;
+    loop {
+        if prompt {
+            write!(current(), "=== > ");
+        }
+        if let Some(hand) = next_line(f)?.and_then(|line| parse_user_hand(&line)) {
+            return Ok(hand);
+        }
+        attempts += 1;
+        if max_attempts.is_some_and(|max| attempts >= max) {
+            writeln!(current(), "error: giving up after {attempts} attempts");
+            return Err(InputError::GaveUp);
+        }
+    }
+}
+
+/// Parses a typed Hand, printing what was wrong with it if it is not one.
+fn parse_user_hand(line: &str) -> Option<Hand> {
+    let mut cards = vec![];
+    let mut card_errs = vec![];
+
+    let card_strs: Vec<&str> = line.split_whitespace().collect();
+    for card_str in card_strs {
+        let maybe_card = card_str.to_uppercase().parse::<Card>();
+        match maybe_card {
+            Err(e) => {
+                writeln!(current(), "error: could not understand {card_str}, {:?}", e);
+                card_errs.push(e);
             }
-            let maybe_hand = Hand::try_from_cards(&cards);
-            if let Ok(hand) = maybe_hand {
-                break hand;
-            } else {
-                println!("error: invalid hand {:?}", maybe_hand.err());
+            Ok(c) => cards.push(c),
+        }
+    }
+
+    if card_errs.is_empty() {
+        cards.sort();
+        cards.reverse();
+        if let Err(e) = Hand::sanitize_cards(&cards) {
+            writeln!(current(), "error: sanitize cards failed {:?}", e);
+        }
+        let maybe_hand = Hand::try_from_cards(&cards);
+        if let Ok(hand) = maybe_hand {
+            return Some(hand);
+        } else {
+            writeln!(current(), "error: invalid hand {:?}", maybe_hand.err());
+        }
+    }
+    None
+}
+
+/// Reads lines until one names exactly num_cards different cards, all of them held. Fails if the
+/// input has closed.
+pub fn get_cli_user_cards<Input: BufRead>(
+    f: &mut Input,
+    held: &[Card],
+    num_cards: usize,
+    prompt: bool,
+) -> Result<Vec<Card>, InputError> {
+    loop {
+        if prompt {
+            write!(current(), "=== > ");
+        }
+        let Some(line) = next_line(f)? else {
+            continue;
+        };
+
+        let cards: Result<Vec<Card>, _> = line
+            .split_whitespace()
+            .map(|card_str| card_str.to_uppercase().parse::<Card>())
+            .collect();
+        match cards {
+            Ok(cards) if is_valid_gift(&cards, held, num_cards) => return Ok(cards),
+            Ok(_) => writeln!(
+                current(),
+                "error: please give {num_cards} different cards that you hold"
+            ),
+            Err(e) => writeln!(
+                current(),
+                "error: could not understand {}, {:?}",
+                line.trim(),
+                e
+            ),
+        }
+    }
+}
+
+/// Prompts until one of the seats is typed, or an empty line for none of them. The input closing
+/// counts as none.
+pub fn get_cli_user_seat<Input: BufRead>(
+    f: &mut Input,
+    seats: &[usize],
+    prompt: bool,
+) -> Option<usize> {
+    loop {
+        if prompt {
+            write!(current(), "=== > ");
+        }
+        let line = match next_line(f) {
+            Ok(Some(line)) => line,
+            Ok(None) => continue,
+            Err(_) => break None,
+        };
+
+        let line = line.trim();
+        if line.is_empty() {
+            break None;
+        }
+        match line.parse() {
+            Ok(seat) if seats.contains(&seat) => break Some(seat),
+            _ => {
+                let seats: Vec<String> = seats.iter().map(usize::to_string).collect();
+                writeln!(
+                    current(),
+                    "error: could not understand {line}, please type one of {} or nothing",
+                    seats.join(", ")
+                );
             }
         }
     }
 }
 
+/// Prompts until y or n is typed, the input closing counts as n.
+pub fn get_cli_user_confirmation<Input: BufRead>(f: &mut Input) -> bool {
+    loop {
+        write!(current(), "=== > ");
+        let line = match next_line(f) {
+            Ok(Some(line)) => line,
+            Ok(None) => continue,
+            Err(_) => break false,
+        };
+
+        match line.trim().to_lowercase().as_str() {
+            "y" | "yes" => break true,
+            "n" | "no" => break false,
+            other => writeln!(
+                current(),
+                "error: could not understand {other}, please type y or n"
+            ),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -53,7 +216,7 @@ mod tests {
     #[test]
     fn test_get_user_input() {
         let mut input = "3C".as_bytes();
-        let hand = get_cli_user_input(&mut input);
+        let hand = get_cli_user_input(&mut input, None).unwrap();
         assert!(matches!(hand, Hand::Lone(c) if c == THREE_OF_CLUBS));
 
         const THREE_OF_DIAMONDS: Card = Card {
@@ -66,13 +229,13 @@ mod tests {
         };
 
         let mut input = "3C 3S 3D".as_bytes();
-        let hand = get_cli_user_input(&mut input);
+        let hand = get_cli_user_input(&mut input, None).unwrap();
         assert!(
             matches!(hand, Hand::Trips(a, b, c) if a == THREE_OF_SPADES && b == THREE_OF_DIAMONDS && c == THREE_OF_CLUBS,)
         );
 
         let mut input = "3G\n3S 4D\n3C 3S 3D".as_bytes();
-        let hand = get_cli_user_input(&mut input);
+        let hand = get_cli_user_input(&mut input, None).unwrap();
         assert!(
             matches!(hand, Hand::Trips(a, b, c) if a == THREE_OF_SPADES && b == THREE_OF_DIAMONDS && c == THREE_OF_CLUBS,)
         );
@@ -97,7 +260,7 @@ mod tests {
             THREE_OF_SPADES,
         ];
         let mut input = "3G\n3S 4D\n7C 6D 5H 4D 3S".as_bytes();
-        let hand = get_cli_user_input(&mut input);
+        let hand = get_cli_user_input(&mut input, None).unwrap();
         for (idx, card) in hand.cards().enumerate() {
             assert_eq!(*card, expected_cards[idx]);
         }
@@ -125,9 +288,102 @@ mod tests {
             },
         ];
         let mut input = "3G\n3S 4D\nTD 8D 6D 4D 3D".as_bytes();
-        let hand = get_cli_user_input(&mut input);
+        let hand = get_cli_user_input(&mut input, None).unwrap();
         for (idx, card) in hand.cards().enumerate() {
             assert_eq!(*card, expected_cards[idx]);
         }
     }
+
+    #[test]
+    fn test_get_scripted_user_input() {
+        let mut input = "3G\n3S 3C\n\n".as_bytes();
+        let hand = get_scripted_user_input(&mut input, None);
+        assert_eq!(hand, Ok("3S 3C".parse().unwrap()));
+        let hand = get_scripted_user_input(&mut input, None);
+        assert_eq!(hand, Ok(Hand::Pass));
+        // the input has run out
+        assert_eq!(
+            get_scripted_user_input(&mut input, None),
+            Err(InputError::Closed)
+        );
+    }
+
+    /// Input that can no longer be read, e.g. from a terminal that has gone away.
+    struct Broken;
+
+    impl std::io::Read for Broken {
+        fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+            Err(ErrorKind::BrokenPipe.into())
+        }
+    }
+
+    #[test]
+    fn test_input_closed() {
+        let mut input = std::io::BufReader::new(Broken);
+        assert_eq!(
+            get_cli_user_input(&mut input, None),
+            Err(InputError::Closed)
+        );
+        assert_eq!(
+            get_cli_user_cards(&mut input, &[THREE_OF_CLUBS], 1, false),
+            Err(InputError::Closed)
+        );
+        assert!(!get_cli_user_confirmation(&mut input));
+
+        // a line that is not text is read past
+        let mut input = b"\xff\xfe\n3C\n".as_slice();
+        assert_eq!(
+            get_scripted_user_input(&mut input, None),
+            Ok(Hand::Lone(THREE_OF_CLUBS))
+        );
+    }
+
+    #[test]
+    fn test_strict_user_input() {
+        let mut input = "3G\n4Z\n3C\n".as_bytes();
+        assert_eq!(
+            get_cli_user_input(&mut input, Some(2)),
+            Err(InputError::GaveUp)
+        );
+
+        let mut input = "3G\n4Z\n3C\n".as_bytes();
+        assert_eq!(
+            get_cli_user_input(&mut input, Some(3)),
+            Ok(Hand::Lone(THREE_OF_CLUBS))
+        );
+    }
+
+    #[test]
+    fn test_get_user_cards() {
+        let held = crate::tests::test_util::vec_card_from_str("3C 4D 9S");
+        let mut input = "3G\n3C 3D\n4d 4d\n3C\n9s 4d\n".as_bytes();
+        assert_eq!(
+            get_cli_user_cards(&mut input, &held, 2, false),
+            Ok(crate::tests::test_util::vec_card_from_str("9S 4D"))
+        );
+        assert_eq!(
+            get_cli_user_cards(&mut input, &held, 2, false),
+            Err(InputError::Closed)
+        );
+    }
+
+    #[test]
+    fn test_get_user_seat() {
+        let mut input = "0\nthree\n3\n\n".as_bytes();
+        assert_eq!(get_cli_user_seat(&mut input, &[1, 2, 3], false), Some(3));
+        assert_eq!(get_cli_user_seat(&mut input, &[1, 2, 3], false), None);
+        assert_eq!(get_cli_user_seat(&mut input, &[1, 2, 3], false), None);
+    }
+
+    #[test]
+    fn test_get_user_confirmation() {
+        let mut input = "y".as_bytes();
+        assert!(get_cli_user_confirmation(&mut input));
+
+        let mut input = "maybe\nNO".as_bytes();
+        assert!(!get_cli_user_confirmation(&mut input));
+
+        let mut input = "maybe".as_bytes();
+        assert!(!get_cli_user_confirmation(&mut input));
+    }
 }
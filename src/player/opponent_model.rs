@@ -0,0 +1,372 @@
+//! Models what each opponent is likely to hold, from what they have passed on.
+//!
+//! Cards only ever leave a player's hand, so a player who passed on a single Eight (because they
+//! could not beat it) can never beat a single Eight for the rest of the Game. Passing is not always
+//! forced though, a player may hold back on purpose. A pass recorded as forced (see PassReason) is
+//! certain, one recorded as voluntary shows nothing, and one recorded without a reason is treated
+//! as very likely rather than certain.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Formatter};
+use std::hash::{Hash, Hasher};
+use std::time::Instant;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::card::Card;
+use crate::hand::generate::{can_beat, hands_of_size};
+use crate::hand::{order::order, Hand};
+use crate::play::{PassReason, Play};
+use crate::player::TurnView;
+use crate::rules::Rules;
+
+/// How much less likely a deal is for every opponent who would have chosen to pass while holding
+/// something that beats the Hand they passed on.
+const VOLUNTARY_PASS_WEIGHT: f64 = 0.1;
+
+/// How much less likely a deal is for every opponent who would have held something that beats a
+/// Hand they were forced to pass on. Such a deal is impossible, but still counts for a little so
+/// there is an estimate even when every sample is.
+const FORCED_PASS_WEIGHT: f64 = 1e-6;
+
+/// Something a player has shown they (very likely) cannot beat, by passing on it: nothing of the
+/// same size beats passed_on, e.g. no single above the JH.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Void {
+    /// The player who passed.
+    pub player_id: usize,
+
+    /// The lowest Hand of its size they passed on.
+    pub passed_on: Hand,
+
+    /// They were forced to pass on it, so certainly cannot beat it.
+    pub forced: bool,
+}
+
+impl Display for Void {
+    /// e.g. "Player 3 likely has no singles above JH", or "Player 3 has no ..." if it is certain.
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        let kind = match self.passed_on.cards().len() {
+            1 => "singles",
+            2 => "pairs",
+            3 => "trips",
+            _ => "five-card hands",
+        };
+        let likely = if self.forced { "" } else { "likely " };
+        write!(
+            f,
+            "Player {} {likely}has no {kind} above {}",
+            self.player_id, self.passed_on
+        )
+    }
+}
+
+/// What each player has shown they (very likely) cannot beat, by passing.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct OpponentModel {
+    /// For each player id, the lowest Hand of each size (number of cards) they passed on, leaving
+    /// out voluntary passes.
+    passed_on: Vec<BTreeMap<usize, Hand>>,
+
+    /// As passed_on, only the passes that were forced.
+    forced: Vec<BTreeMap<usize, Hand>>,
+}
+
+impl OpponentModel {
+    /// Builds the model from every Play of the Game so far, in order.
+    pub fn from_plays<'a>(plays: impl IntoIterator<Item = &'a Play>, num_players: usize) -> Self {
+        let mut passed_on: Vec<BTreeMap<usize, Hand>> = vec![BTreeMap::new(); num_players];
+        let mut forced = passed_on.clone();
+        let mut trick = None;
+        let mut hand_to_beat = Hand::Pass;
+        for play in plays {
+            if trick != Some(play.trick) {
+                trick = Some(play.trick);
+                hand_to_beat = Hand::Pass;
+            }
+            match (play.hand, play.pass_reason) {
+                (Hand::Pass, Some(PassReason::Voluntary)) => {}
+                (Hand::Pass, reason) => {
+                    keep_lowest(&mut passed_on[play.player_id], hand_to_beat);
+                    if reason == Some(PassReason::Forced) {
+                        keep_lowest(&mut forced[play.player_id], hand_to_beat);
+                    }
+                }
+                (hand, _) => hand_to_beat = hand,
+            }
+        }
+        Self { passed_on, forced }
+    }
+
+    /// Builds the model from everything the player can see on their turn.
+    pub fn from_view(view: &TurnView) -> Self {
+        Self::from_plays(view.plays(), view.card_counts.len())
+    }
+
+    /// The lowest Hand of this size the player passed on, if they ever did.
+    pub fn passed_on(&self, player_id: usize, size: usize) -> Option<&Hand> {
+        self.passed_on.get(player_id)?.get(&size)
+    }
+
+    /// Everything the player has shown they cannot beat, the smallest Hands first.
+    pub fn voids(&self, player_id: usize) -> Vec<Void> {
+        self.passed_on
+            .get(player_id)
+            .into_iter()
+            .flat_map(|passed_on| passed_on.iter())
+            .map(|(size, passed_on)| Void {
+                player_id,
+                passed_on: *passed_on,
+                forced: self.forced[player_id].get(size) == Some(passed_on),
+            })
+            .collect()
+    }
+
+    /// Returns true if holding these cards agrees with every pass the player made, i.e. none of
+    /// their passes would have been voluntary.
+    pub fn is_consistent(&self, player_id: usize, cards: &[Card], rules: &Rules) -> bool {
+        self.passed_on[player_id]
+            .values()
+            .all(|passed_on| !can_beat(passed_on, cards, rules))
+    }
+
+    /// How likely the player is to hold these cards, given their passes: 1 if it agrees with all
+    /// of them, less if one of them would have been voluntary, next to nothing if one of their
+    /// forced passes would not have been.
+    fn weight(&self, player_id: usize, cards: &[Card], rules: &Rules) -> f64 {
+        if self.is_consistent(player_id, cards, rules) {
+            1.0
+        } else if self.forced[player_id]
+            .values()
+            .any(|forced_on| can_beat(forced_on, cards, rules))
+        {
+            FORCED_PASS_WEIGHT
+        } else {
+            VOLUNTARY_PASS_WEIGHT
+        }
+    }
+
+    /// Estimates, for each candidate Hand, the probability that some opponent could beat it, by
+    /// sampling deals of the unseen cards and weighting each deal by how well it agrees with the
+    /// opponents' passes. Stops sampling early once the deadline (if any) has passed, though
+    /// always takes at least one sample.
+    pub fn beat_probabilities<R: Rng>(
+        &self,
+        view: &TurnView,
+        candidates: &[Hand],
+        samples: usize,
+        deadline: Option<Instant>,
+        rng: &mut R,
+    ) -> Vec<f64> {
+        // dead seats in heads-up play hold cards, but never play them, and a partner beating the
+        // candidate is no loss
+        let opponent_ids: Vec<usize> = view
+            .opponent_ids()
+            .filter(|id| view.is_rival(*id))
+            .collect();
+        self.beat_probabilities_by(view, candidates, &opponent_ids, samples, deadline, rng)
+    }
+
+    /// As beat_probabilities, but only counts the candidate as beaten if one of these opponents
+    /// could beat it.
+    pub fn beat_probabilities_by<R: Rng>(
+        &self,
+        view: &TurnView,
+        candidates: &[Hand],
+        beaten_by: &[usize],
+        samples: usize,
+        deadline: Option<Instant>,
+        rng: &mut R,
+    ) -> Vec<f64> {
+        let opponent_ids: Vec<usize> = view.opponent_ids().collect();
+        let knowledge = view.knowledge();
+        let sizes: BTreeSet<usize> = candidates.iter().map(|hand| hand.cards().len()).collect();
+
+        let mut total_weight = 0.0;
+        let mut beaten_weight = vec![0.0; candidates.len()];
+        for sample in 0..samples {
+            if sample > 0 && deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                break;
+            }
+            let deal = knowledge.sample_opponent_hands(rng);
+            let weight: f64 = opponent_ids
+                .iter()
+                .zip(&deal)
+                .map(|(id, cards)| self.weight(*id, cards, &view.rules))
+                .product();
+            total_weight += weight;
+
+            // every Hand each opponent could make, of the sizes being considered
+            let opponent_hands: Vec<BTreeMap<usize, Vec<Hand>>> = deal
+                .iter()
+                .map(|cards| {
+                    sizes
+                        .iter()
+                        .map(|size| (*size, hands_of_size(cards, *size)))
+                        .collect()
+                })
+                .collect();
+            for (candidate, beaten) in candidates.iter().zip(beaten_weight.iter_mut()) {
+                let size = candidate.cards().len();
+                let can_beat = opponent_hands
+                    .iter()
+                    .zip(&opponent_ids)
+                    .filter(|(_, id)| beaten_by.contains(id))
+                    .any(|(hands, _)| {
+                        hands[&size]
+                            .iter()
+                            .filter(|hand| {
+                                view.rules.allow_flush || !matches!(hand, Hand::Flush(..))
+                            })
+                            .any(|hand| order(candidate, hand) == Some(std::cmp::Ordering::Less))
+                    });
+                if can_beat {
+                    *beaten += weight;
+                }
+            }
+        }
+        beaten_weight
+            .into_iter()
+            .map(|beaten| beaten / total_weight)
+            .collect()
+    }
+}
+
+/// Replaces the lowest Hand of this one's size if this one is lower.
+fn keep_lowest(passed_on: &mut BTreeMap<usize, Hand>, hand: Hand) {
+    let lowest = passed_on.entry(hand.cards().len()).or_insert(hand);
+    if order(&hand, lowest) == Some(std::cmp::Ordering::Less) {
+        *lowest = hand;
+    }
+}
+
+/// Seeds the sampling from what the player can see, so the same turn is always played the same
+/// way (seeded Games stay repeatable).
+pub(crate) fn seeded_rng(view: &TurnView) -> StdRng {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    view.cards.hash(&mut hasher);
+    view.plays().count().hash(&mut hasher);
+    StdRng::seed_from_u64(hasher.finish())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::deck::Deck;
+    use crate::tests::test_util::{plays, vec_card_from_str};
+
+    #[test]
+    fn test_passed_on() {
+        let history = plays(&[
+            (0, "3C", 0),
+            (1, "8D", 0),
+            (2, "", 0),
+            (3, "KS", 0),
+            (0, "", 0),
+            (1, "", 0),
+            (2, "", 0),
+            (3, "4S 4H", 1),
+            (0, "", 1),
+        ]);
+        let model = OpponentModel::from_plays(&history, 4);
+        assert_eq!(model.passed_on(2, 1), Some(&"8D".parse().unwrap()));
+        assert_eq!(model.passed_on(0, 1), Some(&"KS".parse().unwrap()));
+        assert_eq!(model.passed_on(0, 2), Some(&"4S 4H".parse().unwrap()));
+        assert_eq!(model.passed_on(3, 1), None);
+        assert_eq!(model.voids(3), []);
+        let voids = model.voids(0);
+        assert_eq!(voids.len(), 2);
+        assert_eq!(
+            voids[0].to_string(),
+            "Player 0 likely has no singles above KS"
+        );
+        assert_eq!(
+            voids[1].to_string(),
+            "Player 0 likely has no pairs above 4S 4H"
+        );
+
+        let rules = Rules::default();
+        assert!(model.is_consistent(2, &vec_card_from_str("3D 5H 8C"), &rules));
+        assert!(!model.is_consistent(2, &vec_card_from_str("3D 5H 9C"), &rules));
+        assert!(model.is_consistent(0, &vec_card_from_str("4C 4D QS"), &rules));
+        assert!(!model.is_consistent(0, &vec_card_from_str("5C 5D"), &rules));
+    }
+
+    #[test]
+    fn test_pass_reasons() {
+        let mut history = plays(&[(0, "3C", 0), (1, "", 0), (2, "8D", 0), (3, "", 0)]);
+        history[1].pass_reason = Some(PassReason::Voluntary);
+        history[3].pass_reason = Some(PassReason::Forced);
+        let model = OpponentModel::from_plays(&history, 4);
+
+        // holding back shows nothing, being forced to pass is certain
+        assert_eq!(model.voids(1), []);
+        let voids = model.voids(3);
+        assert!(voids[0].forced);
+        assert_eq!(voids[0].to_string(), "Player 3 has no singles above 8D");
+
+        let rules = Rules::default();
+        assert_eq!(model.weight(1, &vec_card_from_str("2S"), &rules), 1.0);
+        assert_eq!(
+            model.weight(3, &vec_card_from_str("2S"), &rules),
+            FORCED_PASS_WEIGHT
+        );
+
+        // an earlier pass without a reason is still only likely
+        let mut history = plays(&[(0, "3C", 0), (3, "", 0), (0, "5C", 1), (3, "", 1)]);
+        history[3].pass_reason = Some(PassReason::Forced);
+        let model = OpponentModel::from_plays(&history, 4);
+        assert!(!model.voids(3)[0].forced);
+        assert_eq!(
+            model.weight(3, &vec_card_from_str("4D"), &rules),
+            VOLUNTARY_PASS_WEIGHT
+        );
+        assert_eq!(
+            model.weight(3, &vec_card_from_str("6D"), &rules),
+            FORCED_PASS_WEIGHT
+        );
+    }
+
+    #[test]
+    fn test_beat_probabilities() {
+        // everything but these cards has been played, the last of it an AS which player 1 passed on
+        let cards = vec_card_from_str("3C KD");
+        let unseen = vec_card_from_str("3D 3H 3S 4C 5D 2S");
+        let ace: Card = "AS".parse().unwrap();
+        let mut history: Vec<Play> = Deck::new()
+            .cards
+            .into_iter()
+            .filter(|card| !cards.contains(card) && !unseen.contains(card) && *card != ace)
+            .enumerate()
+            .map(|(trick, card)| Play::now(3, Hand::Lone(card), trick))
+            .collect();
+        let trick = history.len();
+        history.extend(plays(&[(3, "AS", trick), (0, "", trick), (1, "", trick)]));
+        let mut view = TurnView::new(&cards, Hand::Pass, 4);
+        view.player_id = 0;
+        view.history = &history;
+        view.card_counts = vec![2, 1, 1, 1];
+        let candidates = ["KD".parse().unwrap(), "3C".parse().unwrap()];
+
+        let mut rng = StdRng::seed_from_u64(0);
+        let modeled = OpponentModel::from_view(&view);
+        let odds = modeled.beat_probabilities(&view, &candidates, 400, None, &mut rng);
+        // any card beats the 3C
+        assert_eq!(odds[1], 1.0);
+
+        // knowing player 1 did not beat the AS makes the 2S less likely to be out there
+        let naive = OpponentModel::from_plays(&[], 4);
+        let naive_odds = naive.beat_probabilities(&view, &candidates, 400, None, &mut rng);
+        assert!(
+            0.0 < odds[0] && odds[0] < naive_odds[0],
+            "{odds:?} {naive_odds:?}"
+        );
+
+        // out of time, a single deal decides
+        let deadline = Some(Instant::now());
+        let odds = modeled.beat_probabilities(&view, &candidates, 400, deadline, &mut rng);
+        assert!(odds[0] == 0.0 || odds[0] == 1.0);
+    }
+}
@@ -0,0 +1,271 @@
+//! Everything a Player may see when it is their turn, handed to their input functions.
+
+use std::cell::{Cell, OnceCell};
+use std::time::{Duration, Instant};
+
+use crate::analysis::Knowledge;
+use crate::card::Card;
+use crate::hand::Hand;
+use crate::play::{PassReason, Play};
+use crate::rules::validate::{self, Rejection};
+use crate::rules::Rules;
+
+/// Everything a Player may see when it is their turn: their own cards, what must be beaten, every
+/// Play made so far, and how many cards everybody holds. Never reveals an opponent's cards.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TurnView<'a> {
+    /// The player whose turn it is.
+    pub player_id: usize,
+
+    /// The cards held by the player whose turn it is, sorted.
+    pub cards: &'a [Card],
+
+    /// The Hand that must be beaten, Pass if the player is starting a new Trick.
+    pub hand_to_beat: Hand,
+
+    /// True if this is the very first Hand of the Game, and it must include the Three of Clubs
+    /// (in heads-up play, when the Three of Clubs is dead, the lowest card dealt to either player).
+    /// Either way that is the player's lowest card, see opening_card.
+    pub must_play_three_of_clubs: bool,
+
+    /// Every Play of the current Trick so far, including Passes.
+    pub trick: &'a [Play],
+
+    /// Every Play of the earlier Tricks of this Game.
+    pub history: &'a [Play],
+
+    /// How many cards each player holds, indexed by player id.
+    pub card_counts: Vec<usize>,
+
+    /// The rules of the variant being played.
+    pub rules: Rules,
+
+    /// Strict mode, how many invalid attempts the player gets this turn before they forfeit.
+    pub max_attempts: Option<usize>,
+
+    /// The player's last attempt this turn and why it was turned down, None on their first
+    /// attempt. Together with legal_plays, lets a controller correct itself on the retry.
+    pub rejection: Option<Rejection>,
+
+    /// How long the player may take to decide, None if there is no limit (see
+    /// Player::decision_budget). The AI strategies that search stop once it is spent, so the same
+    /// strategies keep to a turn clock, or decide almost at once given Duration::ZERO.
+    pub time_budget: Option<Duration>,
+
+    /// True if the player asked to be told what each opponent has shown they cannot beat.
+    pub show_inferences: bool,
+
+    /// True if the player asked to be told how likely each of their legal plays is to be beaten.
+    pub show_odds: bool,
+
+    /// Set by resign(), checked by the engine after each attempt.
+    pub resigned: Cell<bool>,
+
+    /// Set by close_input(), checked by the engine after each attempt.
+    pub input_closed: Cell<bool>,
+
+    /// Every Hand the player may play, see legal_plays. The engine fills it in at the start of
+    /// each turn (see with_legal_plays), otherwise it is worked out on first use.
+    legal: OnceCell<Vec<Hand>>,
+
+    /// True if the engine filled in legal with every house rule applied, so a Hand chosen from
+    /// it needs no further checks, see HouseRules::check_play.
+    checked: bool,
+}
+
+impl<'a> TurnView<'a> {
+    /// A view with no history, every opponent holding as many cards as the player, useful when
+    /// only the player's own cards matter.
+    pub fn new(cards: &'a [Card], hand_to_beat: Hand, num_players: usize) -> Self {
+        Self {
+            player_id: 0,
+            cards,
+            hand_to_beat,
+            must_play_three_of_clubs: false,
+            trick: &[],
+            history: &[],
+            card_counts: vec![cards.len(); num_players],
+            rules: Rules::default(),
+            max_attempts: None,
+            rejection: None,
+            time_budget: None,
+            show_inferences: false,
+            show_odds: false,
+            resigned: Cell::new(false),
+            input_closed: Cell::new(false),
+            legal: OnceCell::new(),
+            checked: false,
+        }
+    }
+
+    /// The same view with the legal plays already worked out, by the engine with every house rule
+    /// applied (see HouseRules::legal_plays). The view must not be changed after.
+    pub(crate) fn with_legal_plays(self, legal: Vec<Hand>) -> Self {
+        Self {
+            legal: OnceCell::from(legal),
+            checked: true,
+            ..self
+        }
+    }
+
+    /// Returns true if the legal plays were worked out by the engine with every house rule
+    /// applied, see with_legal_plays.
+    pub(crate) fn is_checked(&self) -> bool {
+        self.checked
+    }
+
+    /// Every Hand the player may play on this turn, Pass included if they may pass. Worked out
+    /// on first use, so the view's fields must be set before.
+    pub fn legal_plays(&self) -> &[Hand] {
+        self.legal.get_or_init(|| validate::legal_plays(self))
+    }
+
+    /// Why passing now would be a Pass, Forced if passing is all the player may do.
+    pub fn pass_reason(&self) -> PassReason {
+        if self
+            .legal_plays()
+            .iter()
+            .all(|hand| matches!(hand, Hand::Pass))
+        {
+            PassReason::Forced
+        } else {
+            PassReason::Voluntary
+        }
+    }
+
+    /// Returns true if the attempt is one of the legal plays.
+    pub fn is_legal(&self, attempt: &Hand) -> bool {
+        self.legal_plays().contains(attempt)
+    }
+
+    /// When a decision started now must be made by, None if there is no time_budget.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.time_budget.map(|budget| Instant::now() + budget)
+    }
+
+    /// The card the first Hand of the Game must include, None if this is not the first Hand.
+    pub fn opening_card(&self) -> Option<Card> {
+        if self.must_play_three_of_clubs {
+            self.cards.first().copied()
+        } else {
+            None
+        }
+    }
+
+    /// Gives up instead of submitting a Hand, e.g. after too many attempts in strict mode. The
+    /// engine then treats the player as forfeited, and plays for them (passing) from then on.
+    pub fn resign(&self) {
+        self.resigned.set(true);
+    }
+
+    /// Returns true if the player gave up this turn.
+    pub fn has_resigned(&self) -> bool {
+        self.resigned.get()
+    }
+
+    /// Says that the player's input has closed instead of submitting a Hand, e.g. stdin has
+    /// reached its end. The engine then hands the seat to the AI (see Player::stand_in), or stops
+    /// whatever it was doing if there is no Game to carry on with.
+    pub fn close_input(&self) {
+        self.input_closed.set(true);
+    }
+
+    /// Returns true if the player's input closed this turn.
+    pub fn has_input_closed(&self) -> bool {
+        self.input_closed.get()
+    }
+
+    /// Every Play of the Game so far, the earlier Tricks then the current one.
+    pub fn plays(&self) -> impl Iterator<Item = &Play> {
+        self.history.iter().chain(self.trick)
+    }
+
+    /// What the player knows about the cards: their own, those played, and the opponents' counts.
+    pub fn knowledge(&self) -> Knowledge {
+        Knowledge {
+            hand: self.cards.to_vec(),
+            played: self
+                .plays()
+                .flat_map(|play| play.hand.cards().copied().collect::<Vec<_>>())
+                .collect(),
+            opponent_card_counts: self.opponent_ids().map(|id| self.card_counts[id]).collect(),
+        }
+    }
+
+    /// The view as a single line of JSON, for scripts driving a seat. Cards and Hands are written
+    /// the way they are typed, e.g. "3C" or "4S 4H", a Pass is "".
+    pub fn to_json(&self) -> String {
+        let hand_strings = |plays: &[Play]| -> Vec<serde_json::Value> {
+            plays
+                .iter()
+                .map(|play| {
+                    serde_json::json!({
+                        "player_id": play.player_id,
+                        "hand": play.hand.to_string(),
+                    })
+                })
+                .collect()
+        };
+        serde_json::json!({
+            "player_id": self.player_id,
+            "cards": self.cards.iter().map(|card| card.to_string()).collect::<Vec<_>>(),
+            "hand_to_beat": self.hand_to_beat.to_string(),
+            "must_play_three_of_clubs": self.must_play_three_of_clubs,
+            "opening_card": self.opening_card().map(|card| card.to_string()),
+            "trick": hand_strings(self.trick),
+            "card_counts": self.card_counts,
+        })
+        .to_string()
+    }
+
+    /// Returns true if, in partnerships, this player's partner played the Hand to beat.
+    pub fn partner_is_winning(&self) -> bool {
+        let last = self
+            .trick
+            .iter()
+            .rfind(|play| !matches!(play.hand, Hand::Pass));
+        last.is_some_and(|play| self.rules.partner(self.player_id) == Some(play.player_id))
+    }
+
+    /// Returns true if this player is trying to beat that player, i.e. they are not a dead seat in
+    /// heads-up play, nor this player's partner.
+    pub fn is_rival(&self, player_id: usize) -> bool {
+        player_id != self.player_id
+            && !self.rules.is_dead_seat(player_id)
+            && self.rules.partner(self.player_id) != Some(player_id)
+    }
+
+    /// The ids of the other players, in turn order starting after this player.
+    pub fn opponent_ids(&self) -> impl Iterator<Item = usize> + '_ {
+        let num_players = self.card_counts.len();
+        (1..num_players).map(move |offset| (self.player_id + offset) % num_players)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::tests::test_util::vec_card_from_str;
+
+    #[test]
+    fn test_turn_view() {
+        let cards = vec_card_from_str("3C 4D");
+        let history = [Play::now(1, "3D".parse().unwrap(), 0)];
+        let trick = [Play::now(2, "5S".parse().unwrap(), 1)];
+        let mut view = TurnView::new(&cards, "5S".parse().unwrap(), 4);
+        view.player_id = 3;
+        view.history = &history;
+        view.trick = &trick;
+        assert_eq!(view.plays().count(), 2);
+        assert_eq!(view.opponent_ids().collect::<Vec<_>>(), vec![0, 1, 2]);
+        assert_eq!(view.knowledge().played.len(), 2);
+        assert_eq!(view.knowledge().unseen_cards().len(), 48);
+
+        let json: serde_json::Value = serde_json::from_str(&view.to_json()).unwrap();
+        assert_eq!(json["player_id"], 3);
+        assert_eq!(json["cards"], serde_json::json!(["3C", "4D"]));
+        assert_eq!(json["hand_to_beat"], "5S");
+        assert_eq!(json["trick"][0]["hand"], "5S");
+    }
+}
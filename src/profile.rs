@@ -0,0 +1,253 @@
+//! A local player profile, stored as JSON, remembering how the human has done over time, and every
+//! Match they played (see MatchRecord), so they can be listed and replayed later.
+
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::audit::GameId;
+use crate::daily::format_day;
+use crate::game::GameResult;
+use crate::match_play::MatchSummary;
+use crate::player::AdaptiveAi;
+use crate::ratings::{Ratings, INITIAL_RATING};
+use crate::stats::PlayerStats;
+
+/// A local player profile, stored as JSON, remembering how the human has done over time.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Profile {
+    pub name: String,
+    pub rating: f64,
+    pub stats: PlayerStats,
+
+    /// Points scored in each Daily Challenge, keyed by day number, only the first attempt counts.
+    pub daily_scores: BTreeMap<u64, i32>,
+
+    /// Every Match played, oldest first.
+    #[serde(default)]
+    pub matches: Vec<MatchRecord>,
+
+    /// How hard the adaptive AI plays against this player, and how they have done since it last
+    /// changed, see --adaptive.
+    #[serde(default)]
+    pub adaptive: AdaptiveAi,
+}
+
+/// A Match played with a Profile, enough to list it and find its replay.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MatchRecord {
+    /// The day the Match was played, as a day number (see daily::day_number).
+    pub day: u64,
+
+    /// The seat the human sat in.
+    pub seat: usize,
+
+    /// The names shown for every seat, including the human's.
+    pub names: Vec<String>,
+
+    /// The total points of every seat.
+    pub totals: Vec<i32>,
+
+    /// Every Game of the Match, in order.
+    pub games: Vec<GameId>,
+
+    /// Where the Match was saved to be replayed (see replay::save_match), None if it was not.
+    pub replay: Option<PathBuf>,
+}
+
+impl MatchRecord {
+    /// Records a Match played on this day, by the human sitting in this seat.
+    pub fn new(summary: &MatchSummary, seat: usize, day: u64, replay: Option<&Path>) -> Self {
+        let names = summary
+            .results
+            .first()
+            .map(|result| {
+                result
+                    .meta
+                    .participants
+                    .iter()
+                    .map(|participant| participant.name.clone())
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self {
+            day,
+            seat,
+            names,
+            totals: summary.totals.clone(),
+            games: summary
+                .results
+                .iter()
+                .map(|result| result.meta.id)
+                .collect(),
+            replay: replay.map(Path::to_path_buf),
+        }
+    }
+
+    /// The names of everybody but the human.
+    pub fn opponents(&self) -> Vec<&str> {
+        self.names
+            .iter()
+            .enumerate()
+            .filter(|(seat, _)| *seat != self.seat)
+            .map(|(_, name)| name.as_str())
+            .collect()
+    }
+}
+
+impl Display for MatchRecord {
+    /// e.g. "2023-05-17, 3 Games vs Player 1, Player 2, Player 3, scores [12, -4, -3, -5] (you
+    /// 12), replay /home/me/replays/1.json"
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        let games = if self.games.len() == 1 {
+            "Game"
+        } else {
+            "Games"
+        };
+        write!(
+            f,
+            "{}, {} {games} vs {}, scores {:?}",
+            format_day(self.day),
+            self.games.len(),
+            self.opponents().join(", "),
+            self.totals
+        )?;
+        if let Some(points) = self.totals.get(self.seat) {
+            write!(f, " (you {points})")?;
+        }
+        match &self.replay {
+            Some(path) => write!(f, ", replay {}", path.display()),
+            None => write!(f, ", no replay"),
+        }
+    }
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self {
+            name: String::from("You"),
+            rating: INITIAL_RATING,
+            stats: PlayerStats::default(),
+            daily_scores: BTreeMap::new(),
+            matches: vec![],
+            adaptive: AdaptiveAi::default(),
+        }
+    }
+}
+
+impl Profile {
+    /// Reads a Profile from a JSON file, a missing file is a brand new Profile.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(json) => serde_json::from_str(&json).map_err(io::Error::from),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Writes this Profile to a JSON file.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(path, json)
+    }
+
+    /// Records the score of a Daily Challenge, returns false (and records nothing) if this day
+    /// was already attempted.
+    pub fn record_daily(&mut self, day: u64, player_id: usize, result: &GameResult) -> bool {
+        if self.daily_scores.contains_key(&day) {
+            return false;
+        }
+        self.daily_scores
+            .insert(day, result.scores.points[player_id]);
+        true
+    }
+
+    /// Moves the rating by the result of a Game the human played in this seat (see
+    /// ratings::Ratings::update), the AI opponents are taken to be rated INITIAL_RATING. A Game
+    /// played for practice changes nothing.
+    pub fn record_rating(&mut self, player_id: usize, result: &GameResult) {
+        // keyed by seat, so an opponent cannot share the human's name
+        let seats: Vec<String> = (0..result.scores.penalties.len())
+            .map(|seat| seat.to_string())
+            .collect();
+        let mut ratings = Ratings::default();
+        ratings
+            .ratings
+            .insert(seats[player_id].clone(), self.rating);
+        ratings.update(&seats, result);
+        self.rating = ratings.get(&seats[player_id]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::config::GameConfig;
+    use crate::tests::test_util::vec_card_from_str;
+
+    #[test]
+    fn test_save_and_load() {
+        let path = std::env::temp_dir().join(format!("bigtwo_profile_{}.json", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let profile = Profile::load(&path).unwrap();
+        assert_eq!(profile, Profile::default());
+
+        let config = GameConfig::default();
+        let remaining = ["", "3D", "4D 5D", "6D"].map(vec_card_from_str);
+        let result = GameResult::new(0, false, &remaining, &config);
+
+        let mut profile = profile;
+        assert!(profile.record_daily(19000, 0, &result));
+        assert!(!profile.record_daily(19000, 0, &result));
+        profile.stats.record(0, &result);
+        profile.save(&path).unwrap();
+
+        let loaded = Profile::load(&path).unwrap();
+        assert_eq!(loaded, profile);
+        assert_eq!(loaded.daily_scores[&19000], 4);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_record_rating() {
+        let config = GameConfig::default();
+        let remaining = ["", "3D", "4D 5D", "6D"].map(vec_card_from_str);
+        let result = GameResult::new(0, false, &remaining, &config);
+
+        let mut profile = Profile::default();
+        profile.record_rating(0, &result);
+        assert!(profile.rating > INITIAL_RATING);
+        let won = profile.rating;
+        profile.record_rating(2, &result);
+        assert!(profile.rating < won);
+
+        // a Game played for practice is not rated
+        let mut practice = result.clone();
+        practice.meta.practice = true;
+        let before = profile.rating;
+        profile.record_rating(0, &practice);
+        assert_eq!(profile.rating, before);
+    }
+
+    #[test]
+    fn test_match_record() {
+        let config = GameConfig::default();
+        let remaining = ["3D", "", "4D 5D", "6D"].map(vec_card_from_str);
+        let result = GameResult::new(1, false, &remaining, &config);
+        let summary = MatchSummary::from_results(vec![result.clone(), result]);
+        let record = MatchRecord::new(&summary, 0, 19000, Some(Path::new("/tmp/replay.json")));
+        assert_eq!(record.games.len(), 2);
+        assert_eq!(record.opponents(), ["Player 1", "Player 2", "Player 3"]);
+        assert_eq!(
+            record.to_string(),
+            "2022-01-08, 2 Games vs Player 1, Player 2, Player 3, scores [-2, 8, -4, -2] (you -2), \
+             replay /tmp/replay.json"
+        );
+    }
+}
@@ -0,0 +1,552 @@
+//! Puzzle mode, find the winning line. A constructed Position is loaded and the human must force a
+//! win within a number of plays, against opponents who defend perfectly (using the solver).
+//!
+//! Puzzles are written one "key: value" per line, blank lines and lines starting with '#' are
+//! ignored. The numbered keys are the cards held by each player, in seat order:
+//!
+//! ```text
+//! name: Lead the Deuce
+//! plays: 2
+//! # optional, defaults: hero 0, turn = hero, trick empty (start a new Trick), nobody passed
+//! hero: 0
+//! turn: 0
+//! trick: 9D
+//! passed: 2 3
+//! # optional, defaults to the README rules
+//! preset: taiwan
+//! 0: 2S 3C
+//! 1: 2H
+//! 2: 4D
+//! 3: 5D
+//! ```
+//!
+//! A Position may also be shared without revealing the whole deal: each "?" stands for a card
+//! nobody has been shown ("?5" for five of them), and the optional "unseen" key lists the cards the
+//! hidden ones are among (by default every card not shown anywhere else). The hero's own cards are
+//! always known. Such a Puzzle is played (and solved) by dealing the hidden cards at random, see
+//! Puzzle::reveal.
+//!
+//! ```text
+//! 1: 2H ? ?
+//! 2: ?5
+//! unseen: 4D 5D 6C 7C 8H 9S TD
+//! ```
+
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+use crate::card::Card;
+use crate::deck::Deck;
+use crate::hand::Hand;
+use crate::output::current;
+use crate::player::{Player, TurnView};
+use crate::rules::{preset::RulePreset, Rules};
+use crate::solver::{can_force_win, find_refutation, Position};
+
+/// A constructed Position, and how many plays the hero has to force a win.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Puzzle {
+    pub name: String,
+
+    /// How many turns the hero may take to force a win.
+    pub plays: usize,
+
+    /// The player the human controls.
+    pub hero: usize,
+
+    pub rules: Rules,
+
+    /// Every player's cards, only the known ones while any are hidden.
+    pub position: Position,
+
+    /// How many of each player's cards nobody has been shown, all 0 once every card is known.
+    pub hidden: Vec<usize>,
+
+    /// The cards the hidden ones are among, lowest first, none unless cards are hidden.
+    pub unseen: Vec<Card>,
+}
+
+/// How many random deals of the hidden cards a partial Puzzle must be solvable in.
+const SOLVABLE_DEALS: u64 = 8;
+
+/// Puzzles that come with the game, in increasing difficulty.
+pub const BUILT_IN_PUZZLES: [&str; 4] = [
+    "name: Lead the Deuce
+plays: 2
+0: 2S 3C
+1: 2H
+2: 4D
+3: 5D",
+    "name: Straight to the Point
+plays: 2
+0: 8C 7D 6S 5H 4C 2S
+1: 2H AD
+2: KS KH KD
+3: 9C 9D",
+    "name: Pairs Away
+plays: 3
+0: QH QD 9S 9H 5S 5H
+1: 9D 7H 7C
+2: 2D QS 6H
+3: AC KD JC",
+    "name: Save the Deuces
+plays: 3
+0: 2S 2H 8H 8D 6D 6C
+1: KH 6H 3D
+2: QS 9C 4D
+3: QH QC 8S",
+];
+
+/// Represents the possible ways a string can fail to parse into a Puzzle.
+#[derive(Debug)]
+pub enum ParsePuzzleError {
+    /// A line that is not "key: value".
+    BadLine(String),
+    /// A key that is not part of the format.
+    UnknownKey(String),
+    /// A value that could not be understood, for the given key.
+    BadValue(String),
+    /// A required key was not provided.
+    Missing(&'static str),
+    /// The Position is not possible, e.g. a card held twice or a player id out of range.
+    Inconsistent(&'static str),
+}
+
+/// Parses a player's cards, each "?" (or "?<n>") counting cards nobody has been shown. Returns the
+/// known cards, lowest first, and how many are hidden.
+fn parse_held(key: &str, value: &str) -> Result<(Vec<Card>, usize), ParsePuzzleError> {
+    let mut hidden = 0;
+    let mut known = vec![];
+    for token in value.split_whitespace() {
+        match token.strip_prefix('?') {
+            Some("") => hidden += 1,
+            Some(count) => hidden += parse_number(key, count)?,
+            None => known.push(token),
+        }
+    }
+    Ok((parse_cards(key, &known.join(" "))?, hidden))
+}
+
+/// Parses a space separated list of cards.
+fn parse_cards(key: &str, value: &str) -> Result<Vec<Card>, ParsePuzzleError> {
+    let mut cards: Vec<Card> = value
+        .split_whitespace()
+        .map(|card| card.parse())
+        .collect::<Result<_, _>>()
+        .map_err(|_| ParsePuzzleError::BadValue(key.to_string()))?;
+    cards.sort();
+    Ok(cards)
+}
+
+/// Parses a single number.
+fn parse_number(key: &str, value: &str) -> Result<usize, ParsePuzzleError> {
+    value
+        .parse()
+        .map_err(|_| ParsePuzzleError::BadValue(key.to_string()))
+}
+
+impl FromStr for Puzzle {
+    type Err = ParsePuzzleError;
+
+    fn from_str(puzzle_str: &str) -> Result<Self, Self::Err> {
+        let mut name = None;
+        let mut plays = None;
+        let mut hero = 0;
+        let mut turn = None;
+        let mut hand_to_beat = Hand::Pass;
+        let mut passed = BTreeSet::new();
+        let mut rules = Rules::default();
+        let mut hands: Vec<Option<(Vec<Card>, usize)>> = vec![];
+        let mut unseen = None;
+
+        for line in puzzle_str.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line
+                .split_once(':')
+                .ok_or_else(|| ParsePuzzleError::BadLine(line.to_string()))?;
+            let (key, value) = (key.trim(), value.trim());
+            match key {
+                "name" => name = Some(value.to_string()),
+                "plays" => plays = Some(parse_number(key, value)?),
+                "hero" => hero = parse_number(key, value)?,
+                "turn" => turn = Some(parse_number(key, value)?),
+                "trick" => {
+                    hand_to_beat = Hand::try_from_cards(&{
+                        let mut cards = parse_cards(key, value)?;
+                        cards.reverse();
+                        cards
+                    })
+                    .map_err(|_| ParsePuzzleError::BadValue(key.to_string()))?
+                }
+                "passed" => {
+                    for id in value.split_whitespace() {
+                        passed.insert(parse_number(key, id)?);
+                    }
+                }
+                "preset" => {
+                    let preset: RulePreset = value
+                        .parse()
+                        .map_err(|_| ParsePuzzleError::BadValue(key.to_string()))?;
+                    rules = preset.rules();
+                }
+                "unseen" => unseen = Some(parse_cards(key, value)?),
+                _ => {
+                    let player_id: usize = key
+                        .parse()
+                        .map_err(|_| ParsePuzzleError::UnknownKey(key.to_string()))?;
+                    if hands.len() <= player_id {
+                        hands.resize(player_id + 1, None);
+                    }
+                    hands[player_id] = Some(parse_held(key, value)?);
+                }
+            }
+        }
+
+        let (hands, hidden): (Vec<Vec<Card>>, Vec<usize>) = hands
+            .into_iter()
+            .collect::<Option<Vec<_>>>()
+            .ok_or(ParsePuzzleError::Missing("the cards of every player"))?
+            .into_iter()
+            .unzip();
+        if hands.len() < 2 {
+            return Err(ParsePuzzleError::Inconsistent("needs at least 2 players"));
+        }
+        let all_cards: BTreeSet<&Card> = hands.iter().flatten().collect();
+        if all_cards.len() != hands.iter().map(|cards| cards.len()).sum::<usize>() {
+            return Err(ParsePuzzleError::Inconsistent("a card is held twice"));
+        }
+        if hands
+            .iter()
+            .zip(&hidden)
+            .any(|(cards, hidden)| cards.is_empty() && *hidden == 0)
+        {
+            return Err(ParsePuzzleError::Inconsistent("the game is already over"));
+        }
+        let shown: BTreeSet<&Card> = all_cards
+            .iter()
+            .copied()
+            .chain(hand_to_beat.cards())
+            .collect();
+        let unseen = match unseen {
+            Some(unseen) if unseen.iter().any(|card| shown.contains(card)) => {
+                return Err(ParsePuzzleError::Inconsistent("an unseen card is shown"))
+            }
+            Some(unseen) => unseen,
+            None if hidden.iter().all(|hidden| *hidden == 0) => vec![],
+            None => {
+                let mut unseen: Vec<Card> = Deck::new()
+                    .cards
+                    .into_iter()
+                    .filter(|card| !shown.contains(card))
+                    .collect();
+                unseen.sort();
+                unseen
+            }
+        };
+        if hidden.iter().sum::<usize>() > unseen.len() {
+            return Err(ParsePuzzleError::Inconsistent("too few unseen cards"));
+        }
+        let current_player = turn.unwrap_or(hero);
+        if hero >= hands.len()
+            || current_player >= hands.len()
+            || passed.iter().any(|id| *id >= hands.len())
+        {
+            return Err(ParsePuzzleError::Inconsistent("player id out of range"));
+        }
+        if passed.contains(&current_player) || passed.len() >= hands.len() - 1 {
+            return Err(ParsePuzzleError::Inconsistent("too many players passed"));
+        }
+        if hidden[hero] > 0 {
+            return Err(ParsePuzzleError::Inconsistent(
+                "the hero's cards are hidden",
+            ));
+        }
+
+        Ok(Puzzle {
+            name: name.ok_or(ParsePuzzleError::Missing("name"))?,
+            plays: plays.ok_or(ParsePuzzleError::Missing("plays"))?,
+            hero,
+            rules,
+            position: Position {
+                hands,
+                hand_to_beat,
+                current_player,
+                passed,
+            },
+            hidden,
+            unseen,
+        })
+    }
+}
+
+impl Display for Puzzle {
+    /// Writes the Puzzle in the format it is read from, hidden cards as "?". Rules that are not one
+    /// of the presets are written as the README rules.
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        writeln!(f, "name: {}", self.name)?;
+        writeln!(f, "plays: {}", self.plays)?;
+        writeln!(f, "hero: {}", self.hero)?;
+        writeln!(f, "turn: {}", self.position.current_player)?;
+        if self.position.hand_to_beat != Hand::Pass {
+            writeln!(f, "trick: {}", self.position.hand_to_beat)?;
+        }
+        if !self.position.passed.is_empty() {
+            let passed: Vec<String> = self.position.passed.iter().map(usize::to_string).collect();
+            writeln!(f, "passed: {}", passed.join(" "))?;
+        }
+        if let Some(preset) = RulePreset::all()
+            .into_iter()
+            .find(|preset| preset.rules() == self.rules)
+        {
+            writeln!(f, "preset: {preset}")?;
+        }
+        for (player_id, (cards, hidden)) in self.position.hands.iter().zip(&self.hidden).enumerate()
+        {
+            let held: Vec<String> = cards
+                .iter()
+                .map(Card::to_string)
+                .chain((0..*hidden).map(|_| String::from("?")))
+                .collect();
+            writeln!(f, "{player_id}: {}", held.join(" "))?;
+        }
+        if self.is_partial() {
+            let unseen: Vec<String> = self.unseen.iter().map(Card::to_string).collect();
+            writeln!(f, "unseen: {}", unseen.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+impl Puzzle {
+    /// Returns true while any player's cards are hidden.
+    pub fn is_partial(&self) -> bool {
+        self.hidden.iter().any(|hidden| *hidden > 0)
+    }
+
+    /// Deals the hidden cards at random from the unseen ones, the cards left over stay unseen.
+    pub fn reveal<R: Rng>(&self, rng: &mut R) -> Puzzle {
+        let mut revealed = self.clone();
+        revealed.unseen.shuffle(rng);
+        for (cards, hidden) in revealed.position.hands.iter_mut().zip(&mut revealed.hidden) {
+            cards.extend(revealed.unseen.drain(..*hidden));
+            cards.sort();
+            *hidden = 0;
+        }
+        revealed.unseen.sort();
+        revealed
+    }
+
+    /// Hides the cards of these players, e.g. to share a Position reached in a Game without
+    /// revealing the deal. Their cards join the unseen ones.
+    pub fn hiding(&self, player_ids: &[usize]) -> Puzzle {
+        let mut hiding = self.clone();
+        for player_id in player_ids {
+            let cards = std::mem::take(&mut hiding.position.hands[*player_id]);
+            hiding.hidden[*player_id] += cards.len();
+            hiding.unseen.extend(cards);
+        }
+        hiding.unseen.sort();
+        hiding
+    }
+
+    /// Returns true if the hero really can force a win, i.e. the Puzzle has a solution. While
+    /// cards are hidden, the hero must be able to force a win in each of a few random (but
+    /// repeatable) deals of them.
+    pub fn is_solvable(&self) -> bool {
+        if self.is_partial() {
+            return (0..SOLVABLE_DEALS)
+                .all(|seed| self.reveal(&mut StdRng::seed_from_u64(seed)).is_solvable());
+        }
+        can_force_win(&self.position, self.hero, self.plays, &self.rules)
+    }
+}
+
+/// Plays through a Puzzle, the human controls the hero via stdin, the opponents defend perfectly.
+/// Returns true if the human found the winning line.
+pub fn perform_puzzle(puzzle: &Puzzle) -> bool {
+    let mut human = Player::default();
+    human.convert_to_stdio_user();
+
+    writeln!(current(), "Puzzle: {}", puzzle.name);
+    writeln!(current(), "Force a win within {} plays.", puzzle.plays);
+    let revealed;
+    let puzzle = match puzzle.is_partial() {
+        true => {
+            writeln!(
+                current(),
+                "The hidden cards are dealt at random from the unseen ones."
+            );
+            revealed = puzzle.reveal(&mut rand::thread_rng());
+            &revealed
+        }
+        false => puzzle,
+    };
+
+    let mut position = puzzle.position.clone();
+    let mut plays_left = puzzle.plays;
+    loop {
+        if let Some(winner) = position.winner() {
+            let solved = winner == puzzle.hero;
+            if solved {
+                writeln!(current(), "Solved!");
+            } else {
+                writeln!(current(), "Player {winner} went out first, try again.");
+            }
+            return solved;
+        }
+
+        if position.current_player == puzzle.hero {
+            if plays_left == 0 {
+                writeln!(current(), "Out of plays, try again.");
+                return false;
+            }
+            for (player_id, cards) in position.hands.iter().enumerate() {
+                if player_id != puzzle.hero {
+                    writeln!(current(), "Player {player_id} holds {} cards", cards.len());
+                }
+            }
+            human.cards = position.hands[puzzle.hero].clone();
+            let playable = position.playable_hands(&puzzle.rules);
+            let mut view = TurnView::new(&human.cards, position.hand_to_beat, position.hands.len());
+            view.player_id = puzzle.hero;
+            view.card_counts = position.hands.iter().map(|cards| cards.len()).collect();
+            view.rules = puzzle.rules;
+            let attempt = loop {
+                let attempt = if let Hand::Pass = position.hand_to_beat {
+                    (human.start_trick)(&view)
+                } else {
+                    (human.submit_hand)(&view)
+                };
+                if view.has_resigned() || view.has_input_closed() {
+                    writeln!(current(), "Puzzle abandoned.");
+                    return false;
+                }
+                if playable.contains(&attempt) {
+                    break attempt;
+                }
+                writeln!(current(), "{attempt}: that hand cannot be played here");
+            };
+            plays_left -= 1;
+            position = position.play(&attempt);
+        } else {
+            let hand = find_refutation(&position, puzzle.hero, plays_left, &puzzle.rules);
+            if let Hand::Pass = hand {
+                writeln!(current(), "Player {} passed", position.current_player);
+            } else {
+                writeln!(
+                    current(),
+                    "Player {} played {}",
+                    position.current_player,
+                    hand
+                );
+            }
+            position = position.play(&hand);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_built_in_puzzles_are_solvable() {
+        for puzzle_str in BUILT_IN_PUZZLES {
+            let puzzle: Puzzle = puzzle_str.parse().unwrap();
+            assert!(puzzle.is_solvable(), "{}", puzzle.name);
+
+            // the number of plays is tight, one fewer is not enough
+            let mut harder = puzzle.clone();
+            harder.plays -= 1;
+            assert!(!harder.is_solvable(), "{}", puzzle.name);
+        }
+    }
+
+    #[test]
+    fn test_parse_puzzle() {
+        let puzzle: Puzzle = "
+            # a comment
+            name: Example
+            plays: 3
+            hero: 1
+            turn: 2
+            trick: 9D
+            passed: 3
+            preset: taiwan
+            0: 2S 3C
+            1: 2H
+            2: 4D TC
+            3: 5D"
+            .parse()
+            .unwrap();
+        assert_eq!(puzzle.name, "Example");
+        assert_eq!(puzzle.plays, 3);
+        assert_eq!(puzzle.hero, 1);
+        assert_eq!(puzzle.position.current_player, 2);
+        assert_eq!(puzzle.position.hand_to_beat, "9D".parse().unwrap());
+        assert_eq!(puzzle.position.passed, BTreeSet::from([3]));
+        assert!(!puzzle.rules.allow_flush);
+        assert_eq!(puzzle.position.hands[2].len(), 2);
+        assert!(!puzzle.is_partial());
+    }
+
+    #[test]
+    fn test_partial_puzzle() {
+        let full: Puzzle = BUILT_IN_PUZZLES[1].parse().unwrap();
+        let partial = full.hiding(&[2, 3]);
+        assert_eq!(partial.hidden, [0, 0, 3, 2]);
+        assert_eq!(partial.unseen.len(), 5);
+        let text = partial.to_string();
+        assert!(text.contains("2: ? ? ?\n"), "{text}");
+        assert!(text.contains("unseen: 9C 9D KD KH KS"), "{text}");
+
+        // the notation reads back, and "?<n>" counts several cards
+        assert_eq!(text.parse::<Puzzle>().unwrap(), partial);
+        let short = text.replace("2: ? ? ?", "2: ?3");
+        assert_eq!(short.parse::<Puzzle>().unwrap(), partial);
+
+        // a deal of the hidden cards is a full Puzzle again
+        let revealed = partial.reveal(&mut rand::thread_rng());
+        assert!(!revealed.is_partial());
+        assert_eq!(revealed.position.hands[2].len(), 3);
+        assert!(revealed.unseen.is_empty());
+        assert_eq!(full.to_string().parse::<Puzzle>().unwrap(), full);
+
+        // without an unseen key, the hidden cards are among every card not shown
+        let puzzle: Puzzle = "name: x\nplays: 1\n0: 2S\n1: 3C ?4".parse().unwrap();
+        assert_eq!(puzzle.unseen.len(), 50);
+        assert!(
+            puzzle.is_solvable(),
+            "the 2S wins whatever the hidden cards"
+        );
+    }
+
+    #[test]
+    fn test_bad_puzzles() {
+        let bad = [
+            "name: x\nplays: 1\n0: 3C\n1: 3C",
+            "name: x\n0: 3C\n1: 4C",
+            "name: x\nplays: 1\n0: 3C\n2: 4C",
+            "name: x\nplays: 1\nhero: 5\n0: 3C\n1: 4C",
+            "name: x\nplays: 1\ncolour: red\n0: 3C\n1: 4C",
+            "name: x\nplays: one\n0: 3C\n1: 4C",
+            "name x",
+            // unseen cards that are shown, too few unseen cards, or the hero's cards hidden
+            "name: x\nplays: 1\n0: 3C\n1: ?\nunseen: 3C 4C",
+            "name: x\nplays: 1\n0: 3C\n1: ?3\nunseen: 4C 5C",
+            "name: x\nplays: 1\n0: 3C ?\n1: 4C",
+        ];
+        for puzzle_str in bad {
+            assert!(puzzle_str.parse::<Puzzle>().is_err(), "{puzzle_str}");
+        }
+    }
+}
@@ -0,0 +1,179 @@
+//! Elo-style ratings, updated from the GameResult of every Game.
+//!
+//! A 4 player Game is treated as every pair of players playing head-to-head: whoever finished
+//! with the smaller penalty beat the other (equal penalties are a draw). Each player's rating
+//! moves by the average of their head-to-head adjustments. In a heads up Game nobody plays the dead
+//! seats, so they are neither rated nor rated against.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::audit::GameId;
+use crate::game::GameResult;
+
+/// The rating given to a player the first time they are seen.
+pub const INITIAL_RATING: f64 = 1500.0;
+
+/// How far a single Game can move a rating.
+pub const DEFAULT_K_FACTOR: f64 = 32.0;
+
+/// Ratings of every known player, keyed by player name.
+/// Serializable so they can be stored alongside local profiles or a leaderboard.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Ratings {
+    /// How far a single Game can move a rating.
+    pub k_factor: f64,
+
+    /// The current rating of every known player.
+    pub ratings: BTreeMap<String, f64>,
+
+    /// Every Game the ratings were updated from, in order.
+    #[serde(default)]
+    pub games: Vec<GameId>,
+}
+
+impl Default for Ratings {
+    fn default() -> Self {
+        Self {
+            k_factor: DEFAULT_K_FACTOR,
+            ratings: BTreeMap::new(),
+            games: vec![],
+        }
+    }
+}
+
+/// The expected score (0.0 to 1.0) of a player rated `rating` against a player rated `opponent`.
+pub fn expected_score(rating: f64, opponent: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf((opponent - rating) / 400.0))
+}
+
+impl Ratings {
+    /// Returns the rating of a player, or INITIAL_RATING if they have never played.
+    pub fn get(&self, name: &str) -> f64 {
+        *self.ratings.get(name).unwrap_or(&INITIAL_RATING)
+    }
+
+    /// Updates the ratings of every participant from the result of a Game.
+    /// participants[i] is the name of the player sitting at player id i. A Game played for
+    /// practice changes nothing, and the dead seats of a heads up Game are left out.
+    ///
+    /// # Panics
+    /// - If the number of participants does not match the number of players in the result.
+    pub fn update(&mut self, participants: &[String], result: &GameResult) {
+        if result.is_practice() {
+            return;
+        }
+        let penalties = &result.scores.penalties;
+        assert_eq!(
+            participants.len(),
+            penalties.len(),
+            "every player in the GameResult must have a name"
+        );
+        let rules = &result.meta.rules;
+        let rated: Vec<usize> = (0..participants.len())
+            .filter(|player_id| !rules.is_dead_seat(*player_id))
+            .collect();
+        let num_opponents = rated.len().saturating_sub(1).max(1) as f64;
+
+        let before: Vec<f64> = participants.iter().map(|name| self.get(name)).collect();
+        for &player_id in &rated {
+            let name = &participants[player_id];
+            let mut adjustment = 0.0;
+            for &opponent_id in &rated {
+                if opponent_id == player_id {
+                    continue;
+                }
+                let opponent_rating = before[opponent_id];
+                let actual = match penalties[player_id].cmp(&penalties[opponent_id]) {
+                    std::cmp::Ordering::Less => 1.0,
+                    std::cmp::Ordering::Equal => 0.5,
+                    std::cmp::Ordering::Greater => 0.0,
+                };
+                adjustment += actual - expected_score(before[player_id], opponent_rating);
+            }
+            let rating = before[player_id] + self.k_factor * adjustment / num_opponents;
+            self.ratings.insert(name.clone(), rating);
+        }
+        self.games.push(result.meta.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::config::GameConfig;
+    use crate::tests::test_util::vec_card_from_str;
+
+    fn names() -> Vec<String> {
+        ["ana", "bo", "cy", "di"].map(String::from).to_vec()
+    }
+
+    #[test]
+    fn test_expected_score() {
+        assert_eq!(expected_score(1500.0, 1500.0), 0.5);
+        assert!(expected_score(1700.0, 1500.0) > 0.75);
+        let sum = expected_score(1600.0, 1400.0) + expected_score(1400.0, 1600.0);
+        assert!((sum - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_update() {
+        let config = GameConfig::default();
+        let remaining = ["", "3C", "3D 4D", "3H 4H 5H"].map(vec_card_from_str);
+        let result = GameResult::new(0, false, &remaining, &config);
+
+        let mut ratings = Ratings::default();
+        ratings.update(&names(), &result);
+
+        // finishing order is preserved in the new ratings
+        let new: Vec<f64> = names().iter().map(|name| ratings.get(name)).collect();
+        assert!(new[0] > new[1] && new[1] > new[2] && new[2] > new[3]);
+
+        // ratings are zero sum when everybody starts equal
+        let total: f64 = new.iter().sum();
+        assert!((total - 4.0 * INITIAL_RATING).abs() < 1e-9);
+
+        // the winner beat all 3 opponents, each worth half of K
+        assert!((new[0] - (INITIAL_RATING + DEFAULT_K_FACTOR * 0.5)).abs() < 1e-9);
+
+        // and can be traced back to the Game
+        assert_eq!(ratings.games, [result.meta.id]);
+
+        // a Game played for practice is not rated
+        let mut practice = result.clone();
+        practice.meta.practice = true;
+        let before = ratings.clone();
+        ratings.update(&names(), &practice);
+        assert_eq!(ratings, before);
+    }
+
+    #[test]
+    fn test_update_draws() {
+        let config = GameConfig::default();
+        let remaining = ["", "3C", "3D", "3H"].map(vec_card_from_str);
+        let result = GameResult::new(0, false, &remaining, &config);
+
+        let mut ratings = Ratings::default();
+        ratings.update(&names(), &result);
+        assert_eq!(ratings.get("bo"), ratings.get("cy"));
+        assert_eq!(ratings.get("cy"), ratings.get("di"));
+    }
+
+    #[test]
+    fn test_update_heads_up() {
+        let mut config = GameConfig::default();
+        config.rules.heads_up = true;
+        let remaining = ["", "3D 4D 5D", "3C", "3H 4H 5H"].map(vec_card_from_str);
+        let result = GameResult::new(0, false, &remaining, &config);
+
+        let mut ratings = Ratings::default();
+        ratings.update(&names(), &result);
+
+        // the dead seats are neither rated nor rated against, so the winner beat their only opponent
+        assert_eq!(ratings.ratings.keys().collect::<Vec<_>>(), ["ana", "cy"]);
+        assert!((ratings.get("ana") - (INITIAL_RATING + DEFAULT_K_FACTOR * 0.5)).abs() < 1e-9);
+        assert!((ratings.get("cy") - (INITIAL_RATING - DEFAULT_K_FACTOR * 0.5)).abs() < 1e-9);
+    }
+}
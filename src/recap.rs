@@ -0,0 +1,191 @@
+//! Picks the key moments out of a finished Game, the turns worth jumping to in a replay: every
+//! time control changed hands, the largest Hand played, and the Bomb that decided the Game.
+
+use std::fmt::{Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+use crate::commentary::describe_hand;
+use crate::hand::Hand;
+use crate::play::Play;
+
+/// What made a turn a key moment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MomentKind {
+    /// The player won a Trick, taking control from the player who won the Trick before (or who
+    /// opened the Game).
+    ControlChanged { from: usize },
+
+    /// The largest Hand played all Game, the most cards and then the strongest.
+    LargestHand,
+
+    /// The last Bomb (Four-Plus-Kick or Straight-Flush) that won its Trick.
+    DecisiveBomb,
+}
+
+/// A turn worth remembering, see find_key_moments.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyMoment {
+    /// The index of the turn in GameResult::plays.
+    pub turn: usize,
+    pub trick: usize,
+    pub player_id: usize,
+    pub hand: Hand,
+    pub kind: MomentKind,
+}
+
+impl Display for KeyMoment {
+    /// e.g. "Turn 12: Player 2 takes control from Player 0 with a pair of kings".
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        let turn = self.turn + 1;
+        let player_id = self.player_id;
+        let described = describe_hand(&self.hand);
+        match self.kind {
+            MomentKind::ControlChanged { from } => write!(
+                f,
+                "Turn {turn}: Player {player_id} takes control from Player {from} with {described}"
+            ),
+            MomentKind::LargestHand => write!(
+                f,
+                "Turn {turn}: Player {player_id} plays the largest Hand of the Game, {described}"
+            ),
+            MomentKind::DecisiveBomb => write!(
+                f,
+                "Turn {turn}: Player {player_id} wins the Trick with a Bomb, {described}"
+            ),
+        }
+    }
+}
+
+fn is_bomb(hand: &Hand) -> bool {
+    matches!(hand, Hand::FourPlusKick(..) | Hand::StraightFlush(..))
+}
+
+/// Finds the key moments of a Game from every turn played, in the order they happened. Control
+/// only changes hands in a Trick somebody won, so not in the last Trick, which ends with the
+/// winner going out.
+pub fn find_key_moments(plays: &[Play]) -> Vec<KeyMoment> {
+    let moment = |turn: usize, kind: MomentKind| KeyMoment {
+        turn,
+        trick: plays[turn].trick,
+        player_id: plays[turn].player_id,
+        hand: plays[turn].hand,
+        kind,
+    };
+
+    // the last Hand played in each Trick won it
+    let mut winning_turns: Vec<usize> = vec![];
+    for (turn, play) in plays.iter().enumerate() {
+        if matches!(play.hand, Hand::Pass) {
+            continue;
+        }
+        match winning_turns.last_mut() {
+            Some(last) if plays[*last].trick == play.trick => *last = turn,
+            _ => winning_turns.push(turn),
+        }
+    }
+
+    let mut moments = vec![];
+    if let Some(opener) = plays.first() {
+        let mut control = opener.player_id;
+        let finished = winning_turns.len().saturating_sub(1);
+        for turn in &winning_turns[..finished] {
+            let winner = plays[*turn].player_id;
+            if winner != control {
+                moments.push(moment(*turn, MomentKind::ControlChanged { from: control }));
+                control = winner;
+            }
+        }
+    }
+
+    // the first of the largest, if there is more than one
+    let largest = (0..plays.len())
+        .filter(|turn| !matches!(plays[*turn].hand, Hand::Pass))
+        .reduce(
+            |largest, turn| match plays[turn].hand > plays[largest].hand {
+                true => turn,
+                false => largest,
+            },
+        );
+    if let Some(turn) = largest {
+        moments.push(moment(turn, MomentKind::LargestHand));
+    }
+    if let Some(turn) = winning_turns
+        .iter()
+        .rev()
+        .find(|turn| is_bomb(&plays[**turn].hand))
+    {
+        moments.push(moment(*turn, MomentKind::DecisiveBomb));
+    }
+
+    moments.sort_by_key(|moment| moment.turn);
+    moments
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::tests::test_util::plays;
+
+    #[test]
+    fn test_find_key_moments() {
+        let history = plays(&[
+            (0, "3C", 0),
+            (1, "2S", 0),
+            (2, "", 0),
+            (3, "", 0),
+            (0, "", 0),
+            (1, "6D 6C", 1),
+            (2, "KS KH", 1),
+            (3, "", 1),
+            (0, "", 1),
+            (1, "", 1),
+            (2, "8S 8H 8C 4D 4C", 2),
+            (3, "7S 7H 7D 7C 3D", 2),
+            (0, "", 2),
+            (1, "", 2),
+            (2, "", 2),
+            (3, "5S", 3),
+            (0, "JD", 3),
+        ]);
+        let moments = find_key_moments(&history);
+        let described: Vec<String> = moments.iter().map(KeyMoment::to_string).collect();
+        assert_eq!(
+            described,
+            [
+                "Turn 2: Player 1 takes control from Player 0 with the two of spades",
+                "Turn 7: Player 2 takes control from Player 1 with a pair of kings",
+                "Turn 12: Player 3 takes control from Player 2 with four sevens",
+                "Turn 12: Player 3 plays the largest Hand of the Game, four sevens",
+                "Turn 12: Player 3 wins the Trick with a Bomb, four sevens",
+            ]
+        );
+        assert_eq!(moments[2].trick, 2);
+        assert_eq!(moments[2].kind, MomentKind::ControlChanged { from: 2 });
+
+        // a Bomb that is beaten decides nothing, and the last Trick changes no control
+        let history = plays(&[
+            (0, "7S 7H 7D 7C 3D", 0),
+            (1, "9H 8H 7H 6H 5H", 0),
+            (2, "", 0),
+            (3, "", 0),
+            (0, "", 0),
+            (1, "3C", 1),
+            (2, "4C", 1),
+        ]);
+        let kinds: Vec<MomentKind> = find_key_moments(&history)
+            .iter()
+            .map(|moment| moment.kind)
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                MomentKind::ControlChanged { from: 0 },
+                MomentKind::LargestHand,
+                MomentKind::DecisiveBomb,
+            ]
+        );
+        assert!(find_key_moments(&[]).is_empty());
+    }
+}
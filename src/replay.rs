@@ -0,0 +1,252 @@
+//! Plays a finished Game back turn by turn: at the pace it was played (see Play::elapsed),
+//! faster, or one turn each time the viewer presses enter. Key moments (see recap) are pointed
+//! out as they come, and when stepping the viewer may type "next" to jump to the next one.
+
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::hand::Hand;
+use crate::match_play::MatchSummary;
+use crate::play::Play;
+use crate::recap::KeyMoment;
+
+/// Writes a Match (every Game, and who sat where) to a JSON file, to be replayed later.
+pub fn save_match(path: &Path, summary: &MatchSummary) -> io::Result<()> {
+    let json = serde_json::to_string_pretty(summary)?;
+    fs::write(path, json)
+}
+
+/// Reads the Match written by save_match.
+pub fn load_match(path: &Path) -> io::Result<MatchSummary> {
+    let json = fs::read_to_string(path)?;
+    serde_json::from_str(&json).map_err(io::Error::from)
+}
+
+/// How quickly a replay is played back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pacing {
+    /// Waits as long between turns as the players took.
+    RealTime,
+
+    /// Waits this many times less between turns than the players took.
+    FastForward(u32),
+
+    /// Waits for the viewer to press enter before every turn.
+    Step,
+}
+
+impl Pacing {
+    /// How long to wait before showing a turn taken this long after the one before, None if the
+    /// viewer is waited for instead.
+    pub fn delay(&self, elapsed: Duration) -> Option<Duration> {
+        match self {
+            Pacing::RealTime => Some(elapsed),
+            Pacing::FastForward(speed) => Some(elapsed / (*speed).max(1)),
+            Pacing::Step => None,
+        }
+    }
+}
+
+impl Display for Pacing {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            Pacing::RealTime => write!(f, "real-time"),
+            Pacing::FastForward(speed) => write!(f, "{speed}x"),
+            Pacing::Step => write!(f, "step"),
+        }
+    }
+}
+
+/// Represents the possible errors from attempting to parse a Pacing from a string.
+#[derive(Debug)]
+pub enum ParsePacingError {
+    /// Not "real-time", "step", or a speed such as "4x".
+    Unknown(String),
+}
+
+impl FromStr for Pacing {
+    type Err = ParsePacingError;
+    fn from_str(pacing_str: &str) -> Result<Self, Self::Err> {
+        let lowercase = pacing_str.to_lowercase();
+        match lowercase.as_str() {
+            "real-time" => Ok(Pacing::RealTime),
+            "step" => Ok(Pacing::Step),
+            speed => speed
+                .strip_suffix('x')
+                .and_then(|speed| speed.parse().ok())
+                .filter(|speed| *speed > 0)
+                .map(Pacing::FastForward)
+                .ok_or_else(|| Self::Err::Unknown(pacing_str.to_string())),
+        }
+    }
+}
+
+/// Describes a turn the way the Game announced it.
+fn describe(play: &Play) -> String {
+    match play.hand {
+        Hand::Pass if play.is_forced_pass() => {
+            format!("Player {} passed, having nothing to play", play.player_id)
+        }
+        Hand::Pass => format!("Player {} passed", play.player_id),
+        hand => format!("Player {} played {hand}", play.player_id),
+    }
+}
+
+/// Writes every turn to out, each after the delay the pacing asks for (waiting with `wait`), or
+/// in step mode after a line is read from input, pointing out the key moments. In step mode a
+/// line of "next" plays every turn up to the next key moment without stopping. If the input runs
+/// out, the rest is played without stopping.
+pub fn play_back(
+    plays: &[Play],
+    moments: &[KeyMoment],
+    pacing: Pacing,
+    input: &mut impl BufRead,
+    out: &mut impl Write,
+    mut wait: impl FnMut(Duration),
+) -> io::Result<()> {
+    let mut stepping = pacing == Pacing::Step;
+    if stepping && !moments.is_empty() {
+        writeln!(out, "Type \"next\" to jump to the next key moment")?;
+    }
+    let mut jump_to = None;
+    let mut trick = None;
+    for (turn, play) in plays.iter().enumerate() {
+        if jump_to == Some(turn) {
+            jump_to = None;
+        }
+        match pacing.delay(play.elapsed) {
+            Some(delay) => wait(delay),
+            None if stepping && jump_to.is_none() => {
+                write!(out, "(press enter) ")?;
+                out.flush()?;
+                let mut line = String::new();
+                stepping = input.read_line(&mut line)? > 0;
+                if line.trim().eq_ignore_ascii_case("next") {
+                    jump_to = moments
+                        .iter()
+                        .map(|moment| moment.turn)
+                        .find(|moment| *moment > turn);
+                }
+            }
+            None => {}
+        }
+        if trick != Some(play.trick) {
+            trick = Some(play.trick);
+            writeln!(out, "--- Trick {}", play.trick + 1)?;
+        }
+        for moment in moments.iter().filter(|moment| moment.turn == turn) {
+            writeln!(out, "*** {moment}")?;
+        }
+        writeln!(out, "{} (after {:.1?})", describe(play), play.elapsed)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::play::PassReason;
+    use crate::recap::find_key_moments;
+
+    fn plays() -> Vec<Play> {
+        let mut plays = vec![
+            Play::now(0, "3C".parse().unwrap(), 0),
+            Play::now(1, Hand::Pass, 0).with_pass_reason(Some(PassReason::Forced)),
+            Play::now(2, "5D".parse().unwrap(), 1),
+        ];
+        for (seconds, play) in plays.iter_mut().enumerate() {
+            play.elapsed = Duration::from_secs(seconds as u64 * 2);
+        }
+        plays
+    }
+
+    #[test]
+    fn test_pacing_to_from_string() {
+        for pacing in [Pacing::RealTime, Pacing::FastForward(4), Pacing::Step] {
+            assert_eq!(pacing.to_string().parse::<Pacing>().unwrap(), pacing);
+        }
+        assert!(matches!(
+            "0x".parse::<Pacing>(),
+            Err(ParsePacingError::Unknown(_))
+        ));
+        assert!(matches!(
+            "slow".parse::<Pacing>(),
+            Err(ParsePacingError::Unknown(_))
+        ));
+    }
+
+    #[test]
+    fn test_play_back() {
+        let mut waits = vec![];
+        let mut out = vec![];
+        let pacing = Pacing::FastForward(2);
+        play_back(
+            &plays(),
+            &[],
+            pacing,
+            &mut "".as_bytes(),
+            &mut out,
+            |delay| waits.push(delay),
+        )
+        .unwrap();
+        assert_eq!(
+            waits,
+            [0, 1, 2].map(Duration::from_secs),
+            "half as long as they took"
+        );
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(
+            out.lines().collect::<Vec<_>>(),
+            [
+                "--- Trick 1",
+                "Player 0 played 3C (after 0.0ns)",
+                "Player 1 passed, having nothing to play (after 2.0s)",
+                "--- Trick 2",
+                "Player 2 played 5D (after 4.0s)",
+            ]
+        );
+
+        // one turn per line read, then the rest once the input runs out
+        let mut out = vec![];
+        let mut input = "\n".as_bytes();
+        play_back(&plays(), &[], Pacing::Step, &mut input, &mut out, |_| {
+            panic!("step mode never waits")
+        })
+        .unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(out.matches("(press enter)").count(), 2);
+    }
+
+    #[test]
+    fn test_jump_to_key_moment() {
+        let plays = plays();
+        let moments = find_key_moments(&plays);
+        assert_eq!(moments[0].turn, 2);
+
+        // "next" plays the pass without stopping, and stops again at the key moment
+        let mut out = vec![];
+        let mut input = "next\n".as_bytes();
+        play_back(&plays, &moments, Pacing::Step, &mut input, &mut out, |_| {
+            panic!("step mode never waits")
+        })
+        .unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(
+            out.lines().collect::<Vec<_>>(),
+            [
+                "Type \"next\" to jump to the next key moment",
+                "(press enter) --- Trick 1",
+                "Player 0 played 3C (after 0.0ns)",
+                "Player 1 passed, having nothing to play (after 2.0s)",
+                "(press enter) --- Trick 2",
+                &format!("*** {}", moments[0]),
+                "Player 2 played 5D (after 4.0s)",
+            ]
+        );
+    }
+}
@@ -0,0 +1,305 @@
+//! Host controls for a network room. Whoever sits down first is the host, and may send commands
+//! the Table never sees: "kick <seat>" (the AI plays the seat from then on, and whoever sat there
+//! may not come back), "lock <password>" and "unlock" (who may join), "preset <name>" (the rules,
+//! only before the Game starts) and "host <seat>" (hands the host to another person). The Room
+//! decides who may do what, the server carries the commands out.
+
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter};
+use std::net::IpAddr;
+use std::str::FromStr;
+
+use crate::rules::preset::RulePreset;
+
+/// Something only the host may do.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HostCommand {
+    /// Hands the seat to the AI, and bans whoever sat there.
+    Kick(usize),
+
+    /// Asks everybody who joins from now on for the password.
+    Lock(String),
+
+    /// Lets anybody join again.
+    Unlock,
+
+    /// Changes the rules, before the Game starts.
+    Preset(RulePreset),
+
+    /// Makes the person in the seat the host.
+    Host(usize),
+}
+
+impl Display for HostCommand {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            HostCommand::Kick(seat) => write!(f, "kick {seat}"),
+            HostCommand::Lock(password) => write!(f, "lock {password}"),
+            HostCommand::Unlock => write!(f, "unlock"),
+            HostCommand::Preset(preset) => write!(f, "preset {preset}"),
+            HostCommand::Host(seat) => write!(f, "host {seat}"),
+        }
+    }
+}
+
+/// Represents the possible errors from attempting to parse a HostCommand from a string.
+#[derive(Debug)]
+pub enum ParseHostCommandError {
+    /// Not one of the host commands, e.g. a Hand.
+    Unknown(String),
+}
+
+impl FromStr for HostCommand {
+    type Err = ParseHostCommandError;
+    fn from_str(command_str: &str) -> Result<Self, Self::Err> {
+        let unknown = || Self::Err::Unknown(command_str.to_string());
+        let (command, argument) = match command_str.trim().split_once(' ') {
+            Some((command, argument)) => (command, Some(argument.trim())),
+            None => (command_str.trim(), None),
+        };
+        let seat = || {
+            argument
+                .and_then(|seat| seat.parse().ok())
+                .ok_or_else(unknown)
+        };
+        match (command.to_lowercase().as_str(), argument) {
+            ("kick", _) => Ok(HostCommand::Kick(seat()?)),
+            ("lock", Some(password)) if !password.is_empty() => {
+                Ok(HostCommand::Lock(password.to_string()))
+            }
+            ("unlock", None) => Ok(HostCommand::Unlock),
+            ("preset", Some(preset)) => preset
+                .parse()
+                .map(HostCommand::Preset)
+                .map_err(|_| unknown()),
+            ("host", _) => Ok(HostCommand::Host(seat()?)),
+            _ => Err(unknown()),
+        }
+    }
+}
+
+/// Represents the reasons a HostCommand is turned down.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HostError {
+    /// Only the host may send host commands.
+    NotHost,
+
+    /// The rules may only be changed before the Game starts.
+    AlreadyStarted,
+
+    /// Nobody is sitting in the seat, or it is played by the AI.
+    NoSuchPerson(usize),
+
+    /// The host may not kick themself.
+    Yourself,
+}
+
+impl Display for HostError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            HostError::NotHost => write!(f, "error: only the host may do that"),
+            HostError::AlreadyStarted => write!(f, "error: the Game has already started"),
+            HostError::NoSuchPerson(seat) => write!(f, "error: nobody is sitting in seat {seat}"),
+            HostError::Yourself => write!(f, "error: the host may not kick themself"),
+        }
+    }
+}
+
+/// Represents the reasons somebody may not join the room.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Refusal {
+    /// They were kicked earlier.
+    Banned,
+
+    /// The room is locked and they did not know the password.
+    WrongPassword,
+}
+
+impl Display for Refusal {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            Refusal::Banned => write!(f, "You have been banned from this room."),
+            Refusal::WrongPassword => write!(f, "Wrong password."),
+        }
+    }
+}
+
+/// Who is in the room, who is the host, and who may join.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Room {
+    /// The seat of the host, None until somebody sits down.
+    host: Option<usize>,
+
+    /// The seats taken by people.
+    people: BTreeSet<usize>,
+
+    /// Set once the Game has started.
+    started: bool,
+
+    /// Asked of everybody who joins, if set.
+    password: Option<String>,
+
+    /// The addresses of everybody who was kicked.
+    banned: BTreeSet<IpAddr>,
+}
+
+impl Room {
+    /// The seat of the host, None until somebody sits down.
+    pub fn host(&self) -> Option<usize> {
+        self.host
+    }
+
+    /// Returns true while somebody is sitting in the seat.
+    pub fn is_seated(&self, seat: usize) -> bool {
+        self.people.contains(&seat)
+    }
+
+    /// Returns true if everybody joining is asked for the password.
+    pub fn is_locked(&self) -> bool {
+        self.password.is_some()
+    }
+
+    /// Somebody sat down in the seat, the first becomes the host.
+    pub fn sit(&mut self, seat: usize) {
+        self.people.insert(seat);
+        self.host.get_or_insert(seat);
+    }
+
+    /// Somebody left the seat, if it was the host the next person along becomes the host.
+    pub fn leave(&mut self, seat: usize) {
+        self.people.remove(&seat);
+        if self.host == Some(seat) {
+            self.host = self.people.first().copied();
+        }
+    }
+
+    /// The Game has started, the rules may no longer be changed.
+    pub fn start(&mut self) {
+        self.started = true;
+    }
+
+    /// Keeps whoever was kicked from this address out from now on.
+    pub fn ban(&mut self, address: IpAddr) {
+        self.banned.insert(address);
+    }
+
+    /// Checks whether somebody may join from this address, with this password (None if they
+    /// were not asked).
+    pub fn admit(&self, address: IpAddr, password: Option<&str>) -> Result<(), Refusal> {
+        if self.banned.contains(&address) {
+            return Err(Refusal::Banned);
+        }
+        match &self.password {
+            Some(expected) if password != Some(expected.as_str()) => Err(Refusal::WrongPassword),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks that the person in this seat may send the command, and remembers what it changes
+    /// about the room. Carrying it out (e.g. handing a kicked seat to the AI) is left to the
+    /// caller.
+    pub fn apply(&mut self, seat: usize, command: &HostCommand) -> Result<(), HostError> {
+        if self.host != Some(seat) {
+            return Err(HostError::NotHost);
+        }
+        match command {
+            HostCommand::Kick(kicked) if *kicked == seat => return Err(HostError::Yourself),
+            HostCommand::Kick(kicked) | HostCommand::Host(kicked) if !self.is_seated(*kicked) => {
+                return Err(HostError::NoSuchPerson(*kicked))
+            }
+            HostCommand::Kick(kicked) => {
+                self.people.remove(kicked);
+            }
+            HostCommand::Lock(password) => self.password = Some(password.clone()),
+            HostCommand::Unlock => self.password = None,
+            HostCommand::Preset(_) if self.started => return Err(HostError::AlreadyStarted),
+            HostCommand::Preset(_) => {}
+            HostCommand::Host(host) => self.host = Some(*host),
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_host_command_to_from_string() {
+        for command in [
+            HostCommand::Kick(2),
+            HostCommand::Lock(String::from("secret")),
+            HostCommand::Unlock,
+            HostCommand::Preset(RulePreset::Taiwan),
+            HostCommand::Host(1),
+        ] {
+            assert_eq!(command.to_string().parse::<HostCommand>().unwrap(), command);
+        }
+        for not_a_command in ["3C 3D", "pass", "kick", "kick me", "lock", "preset mars"] {
+            assert!(matches!(
+                not_a_command.parse::<HostCommand>(),
+                Err(ParseHostCommandError::Unknown(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_only_the_host_may() {
+        let mut room = Room::default();
+        room.sit(1);
+        room.sit(3);
+        assert_eq!(room.host(), Some(1));
+        let lock = HostCommand::Lock(String::from("secret"));
+        assert_eq!(room.apply(3, &lock), Err(HostError::NotHost));
+        assert_eq!(
+            room.apply(1, &HostCommand::Kick(1)),
+            Err(HostError::Yourself)
+        );
+        assert_eq!(
+            room.apply(1, &HostCommand::Host(2)),
+            Err(HostError::NoSuchPerson(2))
+        );
+
+        room.apply(1, &HostCommand::Preset(RulePreset::Taiwan))
+            .unwrap();
+        room.start();
+        assert_eq!(
+            room.apply(1, &HostCommand::Preset(RulePreset::Taiwan)),
+            Err(HostError::AlreadyStarted)
+        );
+
+        room.apply(1, &HostCommand::Host(3)).unwrap();
+        assert_eq!(room.apply(1, &lock), Err(HostError::NotHost));
+        room.apply(3, &HostCommand::Kick(1)).unwrap();
+        assert!(!room.is_seated(1));
+
+        // the host leaving hands the host on
+        room.sit(0);
+        room.leave(3);
+        assert_eq!(room.host(), Some(0));
+    }
+
+    #[test]
+    fn test_admit() {
+        let mut room = Room::default();
+        room.sit(0);
+        let address: IpAddr = "10.0.0.1".parse().unwrap();
+        assert_eq!(room.admit(address, None), Ok(()));
+
+        room.apply(0, &HostCommand::Lock(String::from("secret")))
+            .unwrap();
+        assert!(room.is_locked());
+        assert_eq!(room.admit(address, None), Err(Refusal::WrongPassword));
+        assert_eq!(
+            room.admit(address, Some("guess")),
+            Err(Refusal::WrongPassword)
+        );
+        assert_eq!(room.admit(address, Some("secret")), Ok(()));
+
+        room.ban(address);
+        assert_eq!(room.admit(address, Some("secret")), Err(Refusal::Banned));
+        room.apply(0, &HostCommand::Unlock).unwrap();
+        assert_eq!(room.admit("10.0.0.2".parse().unwrap(), None), Ok(()));
+    }
+}
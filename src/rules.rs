@@ -0,0 +1,285 @@
+//! Describes the rules of the variant of Big Two being played.
+//! Big Two has many regional variations, the common ones are bundled as a RulePreset.
+
+pub mod cheat_sheet;
+pub mod house;
+pub mod preset;
+pub mod validate;
+
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::card::{rank::Rank, suit::Suit, Card};
+use crate::trick::NUM_PLAYERS;
+
+/// Decides how the very first Trick of the Game is started. Whoever holds the lowest card in play
+/// begins, that is the Three of Clubs unless it is dead (in heads-up play) or was not dealt (a
+/// custom deal, see Table::from_deal), then it is the lowest card anybody playing holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StartRule {
+    /// The holder of the lowest card begins, and must include it in their first Hand.
+    #[serde(alias = "MustPlayThreeOfClubs")]
+    MustPlayLowestCard,
+
+    /// The holder of the lowest card begins, but may start with any valid Hand.
+    #[serde(alias = "ThreeOfClubsHolderLeads")]
+    LowestCardHolderLeads,
+}
+
+/// Some families also count the point cards each player captures, i.e. the cards played in the
+/// Tricks they win.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PointCardRule {
+    /// Every 5 is worth 5 points, every 10 and King is worth 10.
+    FivesTensKings,
+
+    /// Every Heart is worth 1 point.
+    Hearts,
+}
+
+impl PointCardRule {
+    /// How many points capturing this card is worth.
+    pub fn points(&self, card: &Card) -> u32 {
+        match self {
+            PointCardRule::FivesTensKings => match card.rank {
+                Rank::Five => 5,
+                Rank::Ten | Rank::King => 10,
+                _ => 0,
+            },
+            PointCardRule::Hearts => (card.suit == Suit::Hearts) as u32,
+        }
+    }
+}
+
+impl Display for PointCardRule {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            PointCardRule::FivesTensKings => write!(f, "fives-tens-kings"),
+            PointCardRule::Hearts => write!(f, "hearts"),
+        }
+    }
+}
+
+/// Returned when a string is not the name of a PointCardRule.
+#[derive(Debug)]
+pub enum ParsePointCardRuleError {
+    Unknown(String),
+}
+
+impl FromStr for PointCardRule {
+    type Err = ParsePointCardRuleError;
+    fn from_str(rule_str: &str) -> Result<Self, Self::Err> {
+        match rule_str.to_lowercase().as_str() {
+            "fives-tens-kings" => Ok(PointCardRule::FivesTensKings),
+            "hearts" => Ok(PointCardRule::Hearts),
+            _ => Err(ParsePointCardRuleError::Unknown(rule_str.to_string())),
+        }
+    }
+}
+
+/// Partnership play, seats 0 and 2 play against seats 1 and 3, and each team's scores are pooled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TeamPlay {
+    /// The Game ends as soon as any player goes out, their team wins.
+    FirstOut,
+
+    /// A player who goes out sits out the rest of the Game while their partner plays on, the
+    /// Game ends once both partners of a team are out.
+    BothOut,
+}
+
+impl Display for TeamPlay {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            TeamPlay::FirstOut => write!(f, "first-out"),
+            TeamPlay::BothOut => write!(f, "both-out"),
+        }
+    }
+}
+
+/// Returned when a string is not the name of a TeamPlay.
+#[derive(Debug)]
+pub enum ParseTeamPlayError {
+    Unknown(String),
+}
+
+impl FromStr for TeamPlay {
+    type Err = ParseTeamPlayError;
+    fn from_str(team_str: &str) -> Result<Self, Self::Err> {
+        match team_str.to_lowercase().as_str() {
+            "first-out" => Ok(TeamPlay::FirstOut),
+            "both-out" => Ok(TeamPlay::BothOut),
+            _ => Err(ParseTeamPlayError::Unknown(team_str.to_string())),
+        }
+    }
+}
+
+/// The set of rules that vary between the regional variations of Big Two.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rules {
+    /// Some regions do not recognize a Flush as a valid Five Card Hand.
+    pub allow_flush: bool,
+
+    /// A loser holding at least this many cards has their penalty doubled.
+    pub double_penalty_at: usize,
+
+    /// A loser holding at least this many cards has their penalty tripled.
+    pub triple_penalty_at: usize,
+
+    /// Common variant, every unplayed 2 and every undetonated Bomb (Four-Of-A-Kind or
+    /// Straight-Flush) left in a loser's cards doubles their penalty.
+    pub twos_and_bombs_double_penalty: bool,
+
+    /// A "Dragon" is being dealt one card of every Rank (Three through Two), some regions let the
+    /// holder win the Game immediately.
+    pub dragon_is_natural_win: bool,
+
+    /// How the first Trick of the Game begins.
+    pub start_rule: StartRule,
+
+    /// Optional rule, a player dealt no card ranked higher than this may call for a redeal
+    /// before the first play. e.g. Nine for "nothing above a 9", Ten for "no faces, Aces or 2s".
+    pub redeal_threshold: Option<Rank>,
+
+    /// Optional rule, the cards played in each Trick are captured by its winner and scored.
+    /// None in every preset.
+    #[serde(default)]
+    pub point_cards: Option<PointCardRule>,
+
+    /// Heads-up play, only seats 0 and 2 play. Seats 1 and 3 are still dealt 13 cards each, the
+    /// 26 dead cards, but never play and are not scored. If the Three of Clubs is dead, whoever
+    /// holds the lowest card begins, and must include it.
+    #[serde(default)]
+    pub heads_up: bool,
+
+    /// Optional rule, partners sitting opposite each other play as a team (not with heads_up).
+    /// None in every preset.
+    #[serde(default)]
+    pub teams: Option<TeamPlay>,
+
+    /// Optional rule, between the Games of a Match whoever lost the last Game worst gives this
+    /// many of their best cards to its winner, who gives back as many cards of their choice.
+    /// 0 (no exchange) in every preset.
+    #[serde(default)]
+    pub exchange_cards: usize,
+}
+
+impl Rules {
+    /// Returns true if nobody plays this seat, i.e. its cards are dead.
+    pub fn is_dead_seat(&self, seat: usize) -> bool {
+        self.heads_up && seat % 2 == 1
+    }
+
+    /// The seats nobody plays, out of this many.
+    pub fn dead_seats(&self, num_players: usize) -> BTreeSet<usize> {
+        (0..num_players)
+            .filter(|seat| self.is_dead_seat(*seat))
+            .collect()
+    }
+
+    /// The seat opposite this one, if playing in partnerships.
+    pub fn partner(&self, seat: usize) -> Option<usize> {
+        self.teams.map(|_| (seat + NUM_PLAYERS / 2) % NUM_PLAYERS)
+    }
+
+    /// Returns true if the Game is over now that this seat has gone out (has no cards left).
+    /// When both partners must go out, that is only once their partner is out too.
+    pub fn ends_game(&self, seat: usize, cards_left: &[usize]) -> bool {
+        match (self.teams, self.partner(seat)) {
+            (Some(TeamPlay::BothOut), Some(partner)) => cards_left[partner] == 0,
+            _ => true,
+        }
+    }
+}
+
+impl Default for Rules {
+    /// Returns the rules described in the README.
+    fn default() -> Self {
+        preset::RulePreset::default().rules()
+    }
+}
+
+/// Returns true if these cards contain one card of every Rank, i.e. a Dragon.
+pub fn is_dragon(cards: &[Card]) -> bool {
+    Rank::all()
+        .iter()
+        .all(|rank| cards.iter().any(|card| card.rank == *rank))
+}
+
+/// Returns true if none of these cards is ranked higher than the threshold, i.e. the holder may
+/// call for a redeal.
+pub fn is_hopeless(cards: &[Card], threshold: Rank) -> bool {
+    cards.iter().all(|card| card.rank <= threshold)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::tests::test_util::vec_card_from_str;
+
+    #[test]
+    fn test_is_dragon() {
+        let cards = vec_card_from_str("3C 4D 5S 6H 7C 8C 9D TS JH QC KD AS 2H");
+        assert!(is_dragon(&cards));
+
+        let cards = vec_card_from_str("3C 3D 5S 6H 7C 8C 9D TS JH QC KD AS 2H");
+        assert!(!is_dragon(&cards));
+    }
+
+    #[test]
+    fn test_start_rule_old_names() {
+        // configs and results saved before the rules were renamed still load
+        let rule: StartRule = serde_json::from_str("\"MustPlayThreeOfClubs\"").unwrap();
+        assert_eq!(rule, StartRule::MustPlayLowestCard);
+        let rule: StartRule = serde_json::from_str("\"ThreeOfClubsHolderLeads\"").unwrap();
+        assert_eq!(rule, StartRule::LowestCardHolderLeads);
+    }
+
+    #[test]
+    fn test_point_cards() {
+        let cards = vec_card_from_str("5C TD KH 2H 3S");
+        let points = |rule: PointCardRule| -> u32 { cards.iter().map(|c| rule.points(c)).sum() };
+        assert_eq!(points(PointCardRule::FivesTensKings), 25);
+        assert_eq!(points(PointCardRule::Hearts), 2);
+
+        for rule in [PointCardRule::FivesTensKings, PointCardRule::Hearts] {
+            assert_eq!(rule.to_string().parse::<PointCardRule>().unwrap(), rule);
+        }
+        assert!("spades".parse::<PointCardRule>().is_err());
+    }
+
+    #[test]
+    fn test_teams() {
+        let mut rules = Rules::default();
+        assert_eq!(rules.partner(0), None);
+        assert!(rules.ends_game(0, &[0, 3, 5, 7]));
+
+        rules.teams = Some(TeamPlay::FirstOut);
+        assert_eq!(rules.partner(0), Some(2));
+        assert_eq!(rules.partner(3), Some(1));
+        assert!(rules.ends_game(0, &[0, 3, 5, 7]));
+
+        rules.teams = Some(TeamPlay::BothOut);
+        assert!(!rules.ends_game(0, &[0, 3, 5, 7]));
+        assert!(rules.ends_game(2, &[0, 3, 0, 7]));
+
+        for rule in [TeamPlay::FirstOut, TeamPlay::BothOut] {
+            assert_eq!(rule.to_string().parse::<TeamPlay>().unwrap(), rule);
+        }
+        assert!("last-out".parse::<TeamPlay>().is_err());
+    }
+
+    #[test]
+    fn test_is_hopeless() {
+        let cards = vec_card_from_str("3C 4D 5S 6H 7C 8C 9D 9S 3H 4C 5D 6S 7H");
+        assert!(is_hopeless(&cards, Rank::Nine));
+
+        let cards = vec_card_from_str("3C 4D 5S 6H 7C 8C 9D TS 3H 4C 5D 6S 7H");
+        assert!(!is_hopeless(&cards, Rank::Nine));
+        assert!(is_hopeless(&cards, Rank::Ten));
+    }
+}
@@ -0,0 +1,182 @@
+//! A cheat sheet of the Hands that may be played under a set of Rules, lowest first, as data a UI
+//! can lay out however it likes (a help command, a sidebar, a web page). It follows the Rules, so
+//! e.g. a variant that does not recognize Flushes leaves them out.
+
+use std::fmt::{Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+use crate::card::{rank::Rank, suit::Suit};
+use crate::hand::Hand;
+use crate::rules::house::HouseRules;
+use crate::rules::Rules;
+
+/// One kind of Hand, as the cheat sheet lists it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HandKind {
+    /// What the kind of Hand is called, e.g. "Full House".
+    pub name: String,
+
+    /// How many cards a Hand of this kind has.
+    pub num_cards: usize,
+
+    /// A Hand of this kind.
+    pub example: Hand,
+
+    /// What decides between two Hands of this kind.
+    pub decided_by: String,
+
+    /// The names of the other kinds of Hand this kind beats, whatever their cards.
+    pub beats: Vec<String>,
+}
+
+/// Everything a player needs to remember about which Hands beat which.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CheatSheet {
+    /// Every Rank, lowest first.
+    pub ranks: Vec<Rank>,
+
+    /// Every Suit, lowest first, deciding between cards of the same Rank.
+    pub suits: Vec<Suit>,
+
+    /// Every kind of Hand that may be played, by size and then lowest first. A Hand may only be
+    /// beaten by a Hand of the same size.
+    pub kinds: Vec<HandKind>,
+
+    /// The names of the house rules in effect, which may forbid some plays.
+    pub house_rules: Vec<String>,
+}
+
+impl CheatSheet {
+    /// The cheat sheet for a Game played under these rules.
+    pub fn of(rules: &Rules, house_rules: &HouseRules) -> Self {
+        let mut kinds = vec![
+            kind("Single", "KH", "the higher card"),
+            kind("Pair", "7S 7D", "the higher card"),
+            kind("Three of a Kind", "9S 9H 9C", "the Rank"),
+        ];
+        let mut five_card_kinds = vec![kind("Straight", "7C 6S 5H 4C 3D", "the highest card")];
+        if rules.allow_flush {
+            five_card_kinds.push(kind("Flush", "KH JH 9H 6H 3H", "the highest card"));
+        }
+        five_card_kinds.extend([
+            kind(
+                "Full House",
+                "8S 8D 8C 4S 4H",
+                "the Rank of the Three of a Kind",
+            ),
+            kind(
+                "Four of a Kind",
+                "JS JH JD JC 3D",
+                "the Rank of the Four of a Kind",
+            ),
+            kind("Straight Flush", "9S 8S 7S 6S 5S", "the highest card"),
+        ]);
+        // every five card Hand beats the kinds listed before it
+        for index in 0..five_card_kinds.len() {
+            five_card_kinds[index].beats = five_card_kinds[..index]
+                .iter()
+                .map(|lower| lower.name.clone())
+                .collect();
+        }
+        kinds.extend(five_card_kinds);
+
+        Self {
+            ranks: Rank::all().to_vec(),
+            suits: Suit::all().to_vec(),
+            kinds,
+            house_rules: house_rules.names().into_iter().map(String::from).collect(),
+        }
+    }
+}
+
+fn kind(name: &str, example: &str, decided_by: &str) -> HandKind {
+    let example: Hand = example.parse().expect("the examples are valid Hands");
+    HandKind {
+        name: String::from(name),
+        num_cards: example.cards().count(),
+        example,
+        decided_by: String::from(decided_by),
+        beats: vec![],
+    }
+}
+
+/// Joins things with spaces, e.g. "3 4 5".
+fn spaced<T: Display>(things: &[T]) -> String {
+    things
+        .iter()
+        .map(|thing| thing.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+impl Display for CheatSheet {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Ranks, lowest first: {}, then Suits: {}",
+            spaced(&self.ranks),
+            spaced(&self.suits)
+        )?;
+        for kind in &self.kinds {
+            let cards = if kind.num_cards == 1 { "card" } else { "cards" };
+            write!(
+                f,
+                "{} ({} {cards}) e.g. {}, decided by {}",
+                kind.name, kind.num_cards, kind.example, kind.decided_by
+            )?;
+            if !kind.beats.is_empty() {
+                write!(f, ", beats any {}", kind.beats.join(", "))?;
+            }
+            writeln!(f)?;
+        }
+        if !self.house_rules.is_empty() {
+            writeln!(f, "House rules: {}", self.house_rules.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::hand::order::order;
+    use crate::rules::preset::RulePreset;
+
+    #[test]
+    fn test_cheat_sheet() {
+        let sheet = CheatSheet::of(&Rules::default(), &HouseRules::default());
+        assert_eq!(sheet.ranks.first(), Some(&Rank::Three));
+        assert_eq!(sheet.suits.last(), Some(&Suit::Spades));
+        assert!(sheet.house_rules.is_empty());
+
+        // the examples of each size really do beat the kinds listed as beaten
+        for kind in &sheet.kinds {
+            for beaten in &kind.beats {
+                let beaten = sheet.kinds.iter().find(|k| k.name == *beaten).unwrap();
+                assert_eq!(kind.num_cards, beaten.num_cards);
+                assert_eq!(
+                    order(&beaten.example, &kind.example),
+                    Some(std::cmp::Ordering::Less),
+                    "{} beats {}",
+                    kind.name,
+                    beaten.name
+                );
+            }
+        }
+        let straight_flush = sheet.kinds.last().unwrap();
+        assert_eq!(straight_flush.beats.len(), 4);
+        assert!(sheet.to_string().contains("Full House (5 cards) e.g."));
+
+        // a variant without Flushes leaves them out
+        let rules = RulePreset::all()
+            .into_iter()
+            .map(|preset| preset.rules())
+            .find(|rules| !rules.allow_flush)
+            .unwrap();
+        let sheet = CheatSheet::of(&rules, &HouseRules::default());
+        assert!(sheet.kinds.iter().all(|kind| kind.name != "Flush"));
+        assert_eq!(sheet.kinds.last().unwrap().beats.len(), 3);
+    }
+}
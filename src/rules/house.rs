@@ -0,0 +1,306 @@
+//! House rules beyond what Rules can describe, written as hooks the engine calls at fixed points
+//! of the Game, so exotic variants can live in their own crates without forking the engine.
+//!
+//! ```
+//! use std::sync::Arc;
+//! use bigtwo::config::GameConfig;
+//! use bigtwo::rules::house::SevenResetsTheTrick;
+//!
+//! let mut config = GameConfig::default();
+//! config.house_rules.add(Arc::new(SevenResetsTheTrick));
+//! ```
+
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+
+use crate::card::{rank::Rank, Card};
+use crate::hand::Hand;
+use crate::play::Play;
+use crate::player::TurnView;
+use crate::rules::validate::{self, PlayError};
+use crate::scoring::ScoreSheet;
+
+/// A house rule, every callback does nothing unless overridden.
+pub trait RulesHook: Send + Sync {
+    /// Names the house rule, e.g. for announcing it at the start of the Game.
+    fn name(&self) -> &str;
+
+    /// Extra checks on an attempt the standard rules allow.
+    fn validate_play(&self, _view: &TurnView, _attempt: &Hand) -> Result<(), PlayError> {
+        Ok(())
+    }
+
+    /// Called after every turn with the Plays of the Trick so far, returns true to end the Trick
+    /// now, won by whoever played last (rather than waiting for everybody else to pass).
+    fn ends_trick(&self, _trick: &[Play]) -> bool {
+        false
+    }
+
+    /// Called once a Trick is over, with its Plays and its winner.
+    fn on_trick_end(&self, _trick: &[Play], _winner: usize) {}
+
+    /// Adjusts the scores of a finished Game, given the cards each player was still holding.
+    fn score_game(&self, _sheet: &mut ScoreSheet, _remaining: &[Vec<Card>]) {}
+
+    /// Returns true if a Game played under this house rule is only practice: it is marked as such
+    /// in its result, and counts for no ratings or stats.
+    fn is_practice(&self) -> bool {
+        false
+    }
+
+    /// How many times in a Game each person may look at an opponent's cards once they are dealt.
+    fn peeks(&self) -> usize {
+        0
+    }
+
+    /// How many times in a Game each person may have the cards dealt again.
+    fn mulligans(&self) -> usize {
+        0
+    }
+}
+
+/// Every house rule in effect, in the order they were added. Compared by name, and never
+/// serialized, a shared Game must agree on its house rules some other way.
+#[derive(Clone, Default)]
+pub struct HouseRules {
+    pub hooks: Vec<Arc<dyn RulesHook>>,
+}
+
+impl HouseRules {
+    /// Puts another house rule into effect.
+    pub fn add(&mut self, hook: Arc<dyn RulesHook>) {
+        self.hooks.push(hook);
+    }
+
+    /// The names of the house rules in effect.
+    pub fn names(&self) -> Vec<&str> {
+        self.hooks.iter().map(|hook| hook.name()).collect()
+    }
+
+    /// The first objection any house rule has to the attempt.
+    pub fn validate_play(&self, view: &TurnView, attempt: &Hand) -> Result<(), PlayError> {
+        self.hooks
+            .iter()
+            .try_for_each(|hook| hook.validate_play(view, attempt))
+    }
+
+    /// Every Hand the player may play on this turn, under the rules and every house rule.
+    pub fn legal_plays(&self, view: &TurnView) -> Vec<Hand> {
+        validate::legal_plays(view)
+            .into_iter()
+            .filter(|hand| self.validate_play(view, hand).is_ok())
+            .collect()
+    }
+
+    /// Checks the attempt against the rules and every house rule. One of the legal plays the
+    /// engine worked out (see TurnView::with_legal_plays) is accepted without any checks, anything
+    /// else goes through them to find out what is wrong with it.
+    pub fn check_play(&self, view: &TurnView, attempt: &Hand) -> Result<(), PlayError> {
+        if view.is_checked() && view.is_legal(attempt) {
+            return Ok(());
+        }
+        validate::validate_play(view, attempt)?;
+        self.validate_play(view, attempt)
+    }
+
+    /// Returns true if any house rule ends the Trick now.
+    pub fn ends_trick(&self, trick: &[Play]) -> bool {
+        self.hooks.iter().any(|hook| hook.ends_trick(trick))
+    }
+
+    /// Tells every house rule the Trick is over.
+    pub fn on_trick_end(&self, trick: &[Play], winner: usize) {
+        for hook in &self.hooks {
+            hook.on_trick_end(trick, winner);
+        }
+    }
+
+    /// Lets every house rule adjust the scores, in order.
+    pub fn score_game(&self, sheet: &mut ScoreSheet, remaining: &[Vec<Card>]) {
+        for hook in &self.hooks {
+            hook.score_game(sheet, remaining);
+        }
+    }
+
+    /// Returns true if any house rule makes the Game practice.
+    pub fn is_practice(&self) -> bool {
+        self.hooks.iter().any(|hook| hook.is_practice())
+    }
+
+    /// How many peeks each person has in a Game, under every house rule together.
+    pub fn peeks(&self) -> usize {
+        self.hooks.iter().map(|hook| hook.peeks()).sum()
+    }
+
+    /// How many mulligans each person has in a Game, under every house rule together.
+    pub fn mulligans(&self) -> usize {
+        self.hooks.iter().map(|hook| hook.mulligans()).sum()
+    }
+}
+
+impl Debug for HouseRules {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        f.debug_list().entries(self.names()).finish()
+    }
+}
+
+impl PartialEq for HouseRules {
+    fn eq(&self, other: &Self) -> bool {
+        self.names() == other.names()
+    }
+}
+
+impl Eq for HouseRules {}
+
+/// A house rule: any Hand including a Seven ends the Trick at once, and whoever played it starts
+/// the next one.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SevenResetsTheTrick;
+
+impl RulesHook for SevenResetsTheTrick {
+    fn name(&self) -> &str {
+        "seven resets the trick"
+    }
+
+    fn ends_trick(&self, trick: &[Play]) -> bool {
+        trick
+            .last()
+            .is_some_and(|play| play.hand.cards().any(|card| card.rank == Rank::Seven))
+    }
+}
+
+/// Practice mode: once the cards are dealt, each person may have them dealt again once, and look
+/// at one opponent's cards once. The Game is marked as practice in its result, and counts for no
+/// ratings or stats.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Practice;
+
+impl RulesHook for Practice {
+    fn name(&self) -> &str {
+        "practice"
+    }
+
+    fn is_practice(&self) -> bool {
+        true
+    }
+
+    fn peeks(&self) -> usize {
+        1
+    }
+
+    fn mulligans(&self) -> usize {
+        1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::config::GameConfig;
+    use crate::constants::NUM_PLAYERS;
+    use crate::game::perform_game;
+    use crate::output::{Buffer, Output};
+    use crate::seats::SeatConfig;
+    use crate::table::Table;
+
+    /// Forbids leading with a Pair.
+    struct NoPairLeads;
+
+    impl RulesHook for NoPairLeads {
+        fn name(&self) -> &str {
+            "no pair leads"
+        }
+
+        fn validate_play(&self, view: &TurnView, attempt: &Hand) -> Result<(), PlayError> {
+            match (view.hand_to_beat, attempt) {
+                (Hand::Pass, Hand::Pair(..)) => Err(PlayError::HouseRule("no pair leads")),
+                _ => Ok(()),
+            }
+        }
+    }
+
+    #[test]
+    fn test_house_rules() {
+        let mut house_rules = HouseRules::default();
+        house_rules.add(Arc::new(SevenResetsTheTrick));
+        house_rules.add(Arc::new(NoPairLeads));
+        assert_eq!(
+            house_rules.names(),
+            ["seven resets the trick", "no pair leads"]
+        );
+        assert!(!house_rules.is_practice());
+        assert_eq!(house_rules.peeks(), 0);
+
+        let trick = [
+            Play::now(0, "5C".parse().unwrap(), 0),
+            Play::now(1, Hand::Pass, 0),
+        ];
+        assert!(!house_rules.ends_trick(&trick));
+        let trick = [trick[0], Play::now(1, "7D".parse().unwrap(), 0)];
+        assert!(house_rules.ends_trick(&trick));
+
+        let cards = [];
+        let view = TurnView::new(&cards, Hand::Pass, 4);
+        let pair = "4S 4H".parse().unwrap();
+        let res = house_rules.validate_play(&view, &pair);
+        assert_eq!(res, Err(PlayError::HouseRule("no pair leads")));
+        assert!(house_rules
+            .validate_play(&view, &"4S".parse().unwrap())
+            .is_ok());
+
+        // the pair is a legal play, until the house rules are consulted
+        let cards = crate::tests::test_util::vec_card_from_str("4H 4S");
+        let view = TurnView::new(&cards, Hand::Pass, 4);
+        assert!(view.is_legal(&pair));
+        assert_eq!(
+            house_rules.legal_plays(&view),
+            ["4S".parse().unwrap(), "4H".parse().unwrap()]
+        );
+        assert_eq!(
+            house_rules.check_play(&view, &pair),
+            Err(PlayError::HouseRule("no pair leads"))
+        );
+
+        // the legal plays the engine worked out are trusted, and nothing else is
+        let legal = house_rules.legal_plays(&view);
+        let view = view.with_legal_plays(legal);
+        assert_eq!(
+            house_rules.check_play(&view, &"4S".parse().unwrap()),
+            Ok(())
+        );
+        assert_eq!(
+            house_rules.check_play(&view, &pair),
+            Err(PlayError::HouseRule("no pair leads"))
+        );
+        let view = view.with_legal_plays(vec![pair]);
+        assert_eq!(house_rules.check_play(&view, &pair), Ok(()));
+    }
+
+    #[test]
+    fn test_ai_keeps_to_house_rules() {
+        // the AI would open with the 3C in a Pair for some of these seeds
+        for seed in 0..8 {
+            let mut config = GameConfig {
+                seats: vec![SeatConfig::default(); NUM_PLAYERS],
+                seed: Some(seed),
+                output: Output::to(Buffer::default()),
+                ..GameConfig::default()
+            };
+            config.house_rules.add(Arc::new(NoPairLeads));
+
+            let result = perform_game(&config);
+            let leads =
+                result.plays.iter().enumerate().filter(|(turn, play)| {
+                    *turn == 0 || result.plays[turn - 1].trick != play.trick
+                });
+            assert!(leads
+                .into_iter()
+                .all(|(_, play)| !matches!(play.hand, Hand::Pair(..))));
+
+            let mut table = Table::new(&config, &[]);
+            table.start();
+            assert!(table.result().is_some(), "seed {seed}");
+        }
+    }
+}
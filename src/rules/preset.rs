@@ -0,0 +1,151 @@
+//! Named bundles of Rules, one for each of the common regional variations of Big Two.
+
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::constants::CARDS_PER_PLAYER;
+use crate::rules::{Rules, StartRule};
+
+/// Named bundles of Rules, one for each of the common regional variations of Big Two.
+/// Serializable so that it can be agreed upon when players connect to a shared Game.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum RulePreset {
+    /// Flushes allowed, penalty doubled at 10 cards and tripled at 13, must start with the |3C|.
+    #[default]
+    HongKong,
+
+    /// No Flushes, penalty doubled at 8 cards and tripled at 13, and doubled again for every 2 or
+    /// Bomb left unplayed, Dragons win immediately.
+    Taiwan,
+
+    /// Flushes allowed, penalty doubled at 10 cards and tripled at 13, Dragons win immediately,
+    /// the holder of the |3C| may start with any Hand.
+    Singapore,
+}
+
+/// A convenience for iterating through RulePreset's variants.
+const RULE_PRESETS: [RulePreset; 3] = [
+    RulePreset::HongKong,
+    RulePreset::Taiwan,
+    RulePreset::Singapore,
+];
+
+impl RulePreset {
+    /// A convenience for iterating through RulePreset's variants.
+    pub fn all() -> [RulePreset; 3] {
+        RULE_PRESETS
+    }
+
+    /// Returns the bundle of Rules this preset represents.
+    pub fn rules(&self) -> Rules {
+        match self {
+            RulePreset::HongKong => Rules {
+                allow_flush: true,
+                double_penalty_at: 10,
+                triple_penalty_at: CARDS_PER_PLAYER,
+                twos_and_bombs_double_penalty: false,
+                dragon_is_natural_win: false,
+                start_rule: StartRule::MustPlayLowestCard,
+                redeal_threshold: None,
+                point_cards: None,
+                heads_up: false,
+                teams: None,
+                exchange_cards: 0,
+            },
+            RulePreset::Taiwan => Rules {
+                allow_flush: false,
+                double_penalty_at: 8,
+                triple_penalty_at: CARDS_PER_PLAYER,
+                twos_and_bombs_double_penalty: true,
+                dragon_is_natural_win: true,
+                start_rule: StartRule::MustPlayLowestCard,
+                redeal_threshold: None,
+                point_cards: None,
+                heads_up: false,
+                teams: None,
+                exchange_cards: 0,
+            },
+            RulePreset::Singapore => Rules {
+                allow_flush: true,
+                double_penalty_at: 10,
+                triple_penalty_at: CARDS_PER_PLAYER,
+                twos_and_bombs_double_penalty: false,
+                dragon_is_natural_win: true,
+                start_rule: StartRule::LowestCardHolderLeads,
+                redeal_threshold: None,
+                point_cards: None,
+                heads_up: false,
+                teams: None,
+                exchange_cards: 0,
+            },
+        }
+    }
+}
+
+impl Display for RulePreset {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            RulePreset::HongKong => write!(f, "hongkong"),
+            RulePreset::Taiwan => write!(f, "taiwan"),
+            RulePreset::Singapore => write!(f, "singapore"),
+        }
+    }
+}
+
+/// Represents the possible errors from attempting to parse a RulePreset from a string.
+#[derive(Debug)]
+pub enum ParseRulePresetError {
+    /// Not the name of any RulePreset.
+    Unknown(String),
+}
+
+impl FromStr for RulePreset {
+    type Err = ParseRulePresetError;
+    fn from_str(preset_str: &str) -> Result<Self, Self::Err> {
+        match preset_str
+            .to_lowercase()
+            .replace(['-', '_', ' '], "")
+            .as_str()
+        {
+            "hongkong" | "hk" => Ok(RulePreset::HongKong),
+            "taiwan" | "tw" => Ok(RulePreset::Taiwan),
+            "singapore" | "sg" => Ok(RulePreset::Singapore),
+            _ => Err(Self::Err::Unknown(preset_str.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_preset_to_from_string() {
+        for preset in RulePreset::all() {
+            let result = preset.to_string().parse::<RulePreset>();
+            assert!(matches!(result, Ok(p) if p == preset));
+        }
+        assert!(matches!(
+            "Hong-Kong".parse::<RulePreset>(),
+            Ok(RulePreset::HongKong)
+        ));
+        assert!(matches!(
+            "mars".parse::<RulePreset>(),
+            Err(ParseRulePresetError::Unknown(_))
+        ));
+    }
+
+    #[test]
+    fn test_preset_rules() {
+        assert!(RulePreset::HongKong.rules().allow_flush);
+        assert!(!RulePreset::Taiwan.rules().allow_flush);
+        assert!(RulePreset::Singapore.rules().dragon_is_natural_win);
+        assert_eq!(
+            RulePreset::Singapore.rules().start_rule,
+            StartRule::LowestCardHolderLeads
+        );
+    }
+}
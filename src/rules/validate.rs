@@ -0,0 +1,239 @@
+//! Checks whether a Hand may be played on a turn, the same checks the engine enforces, so
+//! clients, servers and UIs can validate a move before submitting it.
+
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter};
+
+use crate::card::{Card, THREE_OF_CLUBS};
+use crate::hand::{generate::playable_hands, order::order, Hand};
+use crate::player::TurnView;
+
+/// Represents the different ways a Player's attempted Hand is not playable
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlayError {
+    /// Attempted Hand must be the same number of cards as previous played Hand.
+    NotMatching,
+
+    /// Highest card of attempted Hand must be higher than the highest card of the previously
+    /// played Hand.
+    TooLow,
+
+    /// Attempted Hand has cards not found in the player's cards.
+    StolenCards,
+
+    /// Attempted Hand is not recognized by the rules of the variant being played.
+    NotAllowed,
+
+    /// A Trick cannot be started with a Pass.
+    MustLead,
+
+    /// The first Hand of the Game must include the Three of Clubs (or in heads-up play, maybe the
+    /// lowest card dealt).
+    MissingOpeningCard(Card),
+
+    /// A house rule forbids the attempt, for the given reason (see rules::house).
+    HouseRule(&'static str),
+}
+
+impl Display for PlayError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            Self::NotMatching => write!(f, "wrong number of cards"),
+            Self::TooLow => write!(f, "highest is not high enough"),
+            Self::StolenCards => write!(f, "these cards are not in the players hand"),
+            Self::NotAllowed => write!(f, "this hand is not allowed by the rules"),
+            Self::MustLead => write!(f, "Starting Hand cannot be Pass."),
+            Self::MissingOpeningCard(card) if *card == THREE_OF_CLUBS => {
+                write!(f, "Must play a hand that includes the Three of Clubs.")
+            }
+            Self::MissingOpeningCard(card) => {
+                write!(f, "Must play a hand that includes the {card}.")
+            }
+            Self::HouseRule(reason) => write!(f, "house rule: {reason}"),
+        }
+    }
+}
+
+/// An attempt the engine turned down, handed back to the player on their retry (see
+/// TurnView::rejection) so they can correct themselves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rejection {
+    /// The Hand the player attempted.
+    pub attempt: Hand,
+
+    /// Why it could not be played.
+    pub error: PlayError,
+}
+
+impl Display for Rejection {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "{}: {}", self.attempt, self.error)
+    }
+}
+
+/// Checks if the player whose turn it is can actually play the Hand they are attempting to play.
+/// Returns () if the Hand is playable, otherwise returns a specific PlayError.
+pub fn validate_play(view: &TurnView, attempt: &Hand) -> Result<(), PlayError> {
+    // player may only play cards they possess
+    let cards: BTreeSet<&Card> = view.cards.iter().collect();
+    if !attempt.cards().all(|card| cards.contains(card)) {
+        return Err(PlayError::StolenCards);
+    }
+
+    // some variants do not recognize every kind of Hand
+    if !view.rules.allow_flush && matches!(attempt, Hand::Flush(..)) {
+        return Err(PlayError::NotAllowed);
+    }
+
+    // a new Trick must be started with a Hand, the very first one including the Three of Clubs
+    if let Hand::Pass = view.hand_to_beat {
+        if let Hand::Pass = attempt {
+            return Err(PlayError::MustLead);
+        }
+        if let Some(opening) = view.opening_card() {
+            if !attempt.cards().any(|card| *card == opening) {
+                return Err(PlayError::MissingOpeningCard(opening));
+            }
+        }
+    }
+
+    // use non-derived custom order to decide if Hand is playable
+    let ordering = order(&view.hand_to_beat, attempt);
+    match ordering {
+        Some(std::cmp::Ordering::Greater) => Err(PlayError::TooLow),
+        None => Err(PlayError::NotMatching),
+        _ => Ok(()),
+    }
+}
+
+/// Every Hand the player may play on this turn (Pass included, if they may pass), i.e. every
+/// Hand from their cards that validate_play accepts. House rules are not consulted, see
+/// HouseRules::legal_plays.
+pub fn legal_plays(view: &TurnView) -> Vec<Hand> {
+    playable_hands(&view.hand_to_beat, view.cards, &view.rules)
+        .into_iter()
+        .filter(|hand| validate_play(view, hand).is_ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::rules::{preset::RulePreset, Rules};
+    use crate::tests::test_util::vec_card_from_str;
+
+    /// Validates the attempt of a player holding these cards.
+    fn check(
+        hand_to_beat: &Hand,
+        cards: &[Card],
+        attempt: &Hand,
+        rules: Rules,
+    ) -> Result<(), PlayError> {
+        let mut view = TurnView::new(cards, *hand_to_beat, 4);
+        view.rules = rules;
+        validate_play(&view, attempt)
+    }
+
+    #[test]
+    fn test_validate_play() {
+        let rules = Rules::default();
+
+        // new trick begins with a Three of Clubs (ostensibly by player 0),
+        let hand_to_beat = "3C".parse().unwrap();
+
+        // player has a few cards
+        let cards = vec_card_from_str("3D 3S 4H 4D 4S");
+
+        // plays a Three of Spades
+        let hand: Hand = "3S".parse().unwrap();
+        let res = check(&hand_to_beat, &cards, &hand, rules);
+        assert!(matches!(res, Ok(())));
+
+        // update hand
+        let hand_to_beat = hand;
+
+        // incorrectly plays a Three of Diamonds, reject
+        let hand: Hand = "3D".parse().unwrap();
+        let res = check(&hand_to_beat, &cards, &hand, rules);
+        assert!(matches!(res, Err(PlayError::TooLow)));
+
+        // incorrectly plays a Pair of Fours, reject
+        let hand: Hand = "4H 4D".parse().unwrap();
+        let res = check(&hand_to_beat, &cards, &hand, rules);
+        assert!(matches!(res, Err(PlayError::NotMatching)));
+
+        // incorrectly plays cards they don't have
+        let hand: Hand = "2S".parse().unwrap();
+        let res = check(&hand_to_beat, &cards, &hand, rules);
+        assert!(matches!(res, Err(PlayError::StolenCards)));
+
+        // passes
+        let hand: Hand = "".parse().unwrap();
+        let res = check(&hand_to_beat, &cards, &hand, rules);
+        assert!(res.is_ok());
+
+        // loses
+        let hand_to_beat: Hand = "7D 6H 5C 4H 3D".parse().unwrap();
+        let cards = vec_card_from_str("7C 6D 5H 4D 3S");
+        let hand = Hand::try_from_cards(&cards[..]).unwrap();
+        let res = check(&hand_to_beat, &cards, &hand, rules);
+        assert!(matches!(res, Err(PlayError::TooLow)));
+
+        // wins
+        let cards = vec_card_from_str("7S 6D 5H 4D 3S");
+        let hand = Hand::try_from_cards(&cards[..]).unwrap();
+        let res = check(&hand_to_beat, &cards, &hand, rules);
+        assert!(res.is_ok());
+    }
+
+    #[test]
+    fn test_leading() {
+        let cards = vec_card_from_str("3C 3S 4H");
+        let mut view = TurnView::new(&cards, Hand::Pass, 4);
+        assert_eq!(validate_play(&view, &Hand::Pass), Err(PlayError::MustLead));
+        assert_eq!(validate_play(&view, &"4H".parse().unwrap()), Ok(()));
+
+        view.must_play_three_of_clubs = true;
+        let res = validate_play(&view, &"4H".parse().unwrap());
+        assert_eq!(res, Err(PlayError::MissingOpeningCard(THREE_OF_CLUBS)));
+        assert_eq!(validate_play(&view, &"3S 3C".parse().unwrap()), Ok(()));
+    }
+
+    #[test]
+    fn test_legal_plays() {
+        // the first Hand of the Game must include the 3C, and cannot be a Pass
+        let cards = vec_card_from_str("3C 3S 4H");
+        let mut view = TurnView::new(&cards, Hand::Pass, 4);
+        view.must_play_three_of_clubs = true;
+        let legal = legal_plays(&view);
+        assert_eq!(legal.len(), 2);
+        assert!(legal.contains(&"3C".parse().unwrap()));
+        assert!(legal.contains(&"3S 3C".parse().unwrap()));
+        assert_eq!(view.legal_plays(), legal);
+
+        // every Lone that beats the 3D, or a Pass
+        let view = TurnView::new(&cards, "3D".parse().unwrap(), 4);
+        let legal = legal_plays(&view);
+        assert_eq!(legal.len(), 3);
+        assert!(legal.contains(&"3S".parse().unwrap()));
+        assert!(legal.contains(&"4H".parse().unwrap()));
+        assert!(legal.contains(&Hand::Pass));
+        assert!(legal.iter().all(|hand| validate_play(&view, hand).is_ok()));
+    }
+
+    #[test]
+    fn test_flush_not_allowed() {
+        let hand_to_beat: Hand = "7D 6H 5C 4H 3D".parse().unwrap();
+        let cards = vec_card_from_str("TC 8C 7C 5C 4C");
+        let hand = Hand::try_from_cards(&cards[..]).unwrap();
+
+        let rules = RulePreset::HongKong.rules();
+        let res = check(&hand_to_beat, &cards, &hand, rules);
+        assert!(res.is_ok());
+
+        let rules = RulePreset::Taiwan.rules();
+        let res = check(&hand_to_beat, &cards, &hand, rules);
+        assert!(matches!(res, Err(PlayError::NotAllowed)));
+    }
+}
@@ -0,0 +1,249 @@
+//! An optional invariant checker for the engines (see GameConfig::check_invariants). After every
+//! turn it checks what must always hold, whatever the rules: all 52 cards are accounted for
+//! exactly once between the hands and the Plays, turns are taken in a sensible order, and the
+//! players marked as having passed are the ones who did. A broken invariant comes back as an
+//! InvariantError the caller can report, rather than an assert! taking the whole process down.
+
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter};
+
+use crate::card::Card;
+use crate::constants::NUM_CARDS_IN_DECK;
+use crate::hand::Hand;
+use crate::play::Play;
+
+/// An invariant the engine broke, i.e. a bug in the engine (or a house rule), not a bad move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InvariantError {
+    /// The hands and the Plays hold this many cards altogether, rather than a whole deck.
+    CardCount { count: usize },
+
+    /// The card is held or played more than once.
+    DuplicateCard(Card),
+
+    /// A turn was taken by, or given to, a seat that is not at the table.
+    UnknownSeat { player_id: usize },
+
+    /// The turn's Trick is neither the one before it nor the next one.
+    TrickOutOfOrder { turn: usize },
+
+    /// The turn started a Trick by passing.
+    TrickStartsWithPass { turn: usize },
+
+    /// The player took this turn as well as the one before it, in the same Trick.
+    PlayedTwice { turn: usize, player_id: usize },
+
+    /// The player took this turn after passing earlier in the same Trick.
+    PlayedAfterPassing { turn: usize, player_id: usize },
+
+    /// The player passed this Trick, but is not marked as having passed.
+    PassForgotten { player_id: usize },
+
+    /// It is the player's turn, but they are marked as having passed.
+    CurrentPlayerPassed { player_id: usize },
+}
+
+impl Display for InvariantError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            InvariantError::CardCount { count } => write!(
+                f,
+                "error: the hands and the plays hold {count} cards, not {NUM_CARDS_IN_DECK}"
+            ),
+            InvariantError::DuplicateCard(card) => {
+                write!(f, "error: the {card} is held or played more than once")
+            }
+            InvariantError::UnknownSeat { player_id } => {
+                write!(f, "error: there is no seat {player_id} at the table")
+            }
+            InvariantError::TrickOutOfOrder { turn } => {
+                write!(f, "error: turn {turn} was taken in a Trick out of order")
+            }
+            InvariantError::TrickStartsWithPass { turn } => {
+                write!(f, "error: turn {turn} started a Trick with a pass")
+            }
+            InvariantError::PlayedTwice { turn, player_id } => write!(
+                f,
+                "error: Player {player_id} took turn {turn} as well as the turn before it"
+            ),
+            InvariantError::PlayedAfterPassing { turn, player_id } => write!(
+                f,
+                "error: Player {player_id} took turn {turn} after passing in the same Trick"
+            ),
+            InvariantError::PassForgotten { player_id } => write!(
+                f,
+                "error: Player {player_id} passed this Trick, but is not marked as having passed"
+            ),
+            InvariantError::CurrentPlayerPassed { player_id } => write!(
+                f,
+                "error: it is Player {player_id}'s turn, but they are marked as having passed"
+            ),
+        }
+    }
+}
+
+/// Checks the state of a Game between turns: every player's cards, every Play so far, the index
+/// of the Trick being played, who is marked as having passed in it, and whose turn it is (None
+/// once the Trick is over).
+pub fn check_invariants(
+    cards: &[Vec<Card>],
+    plays: &[Play],
+    trick: usize,
+    passed: &BTreeSet<usize>,
+    current_player: Option<usize>,
+) -> Result<(), InvariantError> {
+    let mut all: Vec<Card> = cards.iter().flatten().copied().collect();
+    all.extend(plays.iter().flat_map(|play| play.hand.cards().copied()));
+    all.sort();
+    if let Some(pair) = all.windows(2).find(|pair| pair[0] == pair[1]) {
+        return Err(InvariantError::DuplicateCard(pair[0]));
+    }
+    if all.len() != NUM_CARDS_IN_DECK {
+        return Err(InvariantError::CardCount { count: all.len() });
+    }
+
+    let mut passed_this_trick = BTreeSet::new();
+    for (turn, play) in plays.iter().enumerate() {
+        let player_id = play.player_id;
+        if player_id >= cards.len() {
+            return Err(InvariantError::UnknownSeat { player_id });
+        }
+        let previous = turn.checked_sub(1).map(|previous| &plays[previous]);
+        let starts_trick = previous.is_none_or(|previous| previous.trick != play.trick);
+        let expected_trick = previous.map_or(0, |previous| previous.trick + 1);
+        if starts_trick && play.trick != expected_trick {
+            return Err(InvariantError::TrickOutOfOrder { turn });
+        }
+        if starts_trick {
+            passed_this_trick.clear();
+            if matches!(play.hand, Hand::Pass) {
+                return Err(InvariantError::TrickStartsWithPass { turn });
+            }
+        } else if previous.is_some_and(|previous| previous.player_id == player_id) {
+            return Err(InvariantError::PlayedTwice { turn, player_id });
+        } else if passed_this_trick.contains(&player_id) {
+            return Err(InvariantError::PlayedAfterPassing { turn, player_id });
+        }
+        if matches!(play.hand, Hand::Pass) {
+            passed_this_trick.insert(player_id);
+        }
+    }
+
+    if let Some(player_id) = plays
+        .iter()
+        .filter(|play| play.trick == trick && matches!(play.hand, Hand::Pass))
+        .map(|play| play.player_id)
+        .find(|player_id| !passed.contains(player_id))
+    {
+        return Err(InvariantError::PassForgotten { player_id });
+    }
+    match current_player {
+        Some(player_id) if player_id >= cards.len() => {
+            Err(InvariantError::UnknownSeat { player_id })
+        }
+        Some(player_id) if passed.contains(&player_id) => {
+            Err(InvariantError::CurrentPlayerPassed { player_id })
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::deck::Deck;
+    use crate::tests::test_util::plays;
+
+    /// The whole deck dealt out in order, less the cards played.
+    fn dealt(plays: &[Play]) -> Vec<Vec<Card>> {
+        let played: Vec<Card> = plays
+            .iter()
+            .flat_map(|play| play.hand.cards().copied())
+            .collect();
+        let unplayed: Vec<Card> = Deck::new()
+            .cards
+            .into_iter()
+            .filter(|card| !played.contains(card))
+            .collect();
+        unplayed.chunks(13).map(<[Card]>::to_vec).collect()
+    }
+
+    #[test]
+    fn test_check_invariants() {
+        let history = plays(&[(0, "3C", 0), (1, "", 0), (2, "5D", 0), (3, "", 0)]);
+        let cards = dealt(&history);
+        let passed = BTreeSet::from([1, 3]);
+        assert_eq!(
+            check_invariants(&cards, &history, 0, &passed, Some(0)),
+            Ok(())
+        );
+
+        // the passed set and whose turn it is
+        assert_eq!(
+            check_invariants(&cards, &history, 0, &BTreeSet::from([1]), Some(0)),
+            Err(InvariantError::PassForgotten { player_id: 3 })
+        );
+        assert_eq!(
+            check_invariants(&cards, &history, 0, &passed, Some(1)),
+            Err(InvariantError::CurrentPlayerPassed { player_id: 1 })
+        );
+        assert_eq!(
+            check_invariants(&cards, &history, 1, &BTreeSet::new(), Some(2)),
+            Ok(()),
+            "a new Trick starts with nobody having passed"
+        );
+
+        // the cards
+        let mut lost = cards.clone();
+        lost[2].pop();
+        assert_eq!(
+            check_invariants(&lost, &history, 0, &passed, Some(0)),
+            Err(InvariantError::CardCount { count: 51 })
+        );
+        let mut duplicated = cards.clone();
+        duplicated[1].push("3C".parse().unwrap());
+        assert_eq!(
+            check_invariants(&duplicated, &history, 0, &passed, Some(0)),
+            Err(InvariantError::DuplicateCard("3C".parse().unwrap()))
+        );
+
+        // the order of the turns
+        let broken = [
+            (
+                plays(&[(0, "3C", 0), (0, "5D", 0)]),
+                InvariantError::PlayedTwice {
+                    turn: 1,
+                    player_id: 0,
+                },
+            ),
+            (
+                plays(&[(0, "3C", 0), (1, "", 0), (2, "5D", 0), (1, "6D", 0)]),
+                InvariantError::PlayedAfterPassing {
+                    turn: 3,
+                    player_id: 1,
+                },
+            ),
+            (
+                plays(&[(0, "3C", 0), (1, "", 1)]),
+                InvariantError::TrickStartsWithPass { turn: 1 },
+            ),
+            (
+                plays(&[(0, "3C", 0), (1, "5D", 2)]),
+                InvariantError::TrickOutOfOrder { turn: 1 },
+            ),
+            (
+                plays(&[(7, "3C", 0)]),
+                InvariantError::UnknownSeat { player_id: 7 },
+            ),
+        ];
+        for (history, error) in broken {
+            let cards = dealt(&history);
+            assert_eq!(
+                check_invariants(&cards, &history, 0, &BTreeSet::new(), Some(2)),
+                Err(error),
+                "{error}"
+            );
+        }
+    }
+}
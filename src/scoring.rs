@@ -0,0 +1,564 @@
+//! Scores a finished Game, every loser pays the winner a penalty for each card they still hold.
+//! A Game stopped at the turn cap (see GameConfig::max_turns) is scored as its Stalemate rule
+//! says.
+
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::card::{rank::Rank, suit::Suit, Card};
+use crate::rules::Rules;
+
+/// The outcome of scoring a single Game, indexed by player id.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScoreSheet {
+    /// How many cards each player was still holding when the Game ended.
+    pub cards_left: Vec<usize>,
+
+    /// The penalty each player owes the winner (the winner's penalty is 0).
+    pub penalties: Vec<u32>,
+
+    /// Net points for the Game, losers lose their penalty and the winner gains the sum.
+    pub points: Vec<i32>,
+}
+
+/// One player paying another, in units of the stake.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Settlement {
+    /// The player who pays.
+    pub payer: usize,
+
+    /// The player who is paid.
+    pub payee: usize,
+
+    /// How much is paid, i.e. the difference in penalty multiplied by the stake.
+    pub amount: u64,
+}
+
+/// What happens to a Game stopped at the turn cap, before anybody has gone out.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Stalemate {
+    /// Nobody wins, and nobody pays.
+    #[default]
+    Draw,
+
+    /// The player holding the fewest cards wins (the first of them round the table from seat 0),
+    /// and everybody else pays for the cards they hold as if that player had gone out.
+    FewestCards,
+}
+
+impl Display for Stalemate {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            Stalemate::Draw => write!(f, "draw"),
+            Stalemate::FewestCards => write!(f, "fewest-cards"),
+        }
+    }
+}
+
+/// Represents the possible errors from attempting to parse a Stalemate from a string.
+#[derive(Debug)]
+pub enum ParseStalemateError {
+    /// Not the name of any Stalemate rule.
+    Unknown(String),
+}
+
+impl FromStr for Stalemate {
+    type Err = ParseStalemateError;
+    fn from_str(stalemate_str: &str) -> Result<Self, Self::Err> {
+        match stalemate_str.to_lowercase().as_str() {
+            "draw" => Ok(Stalemate::Draw),
+            "fewest-cards" => Ok(Stalemate::FewestCards),
+            _ => Err(Self::Err::Unknown(stalemate_str.to_string())),
+        }
+    }
+}
+
+/// Returns the penalty owed for finishing the Game still holding these cards,
+/// applying the multipliers described by the Rules.
+pub fn penalty(cards: &[Card], rules: &Rules) -> u32 {
+    let cards_left = cards.len();
+    let multiplier = if cards_left >= rules.triple_penalty_at {
+        3
+    } else if cards_left >= rules.double_penalty_at {
+        2
+    } else {
+        1
+    };
+
+    let doublings = if rules.twos_and_bombs_double_penalty {
+        count_twos(cards) + count_bombs(cards)
+    } else {
+        0
+    };
+
+    cards_left as u32 * multiplier * 2u32.pow(doublings)
+}
+
+/// Counts the unplayed 2s in these cards.
+pub(crate) fn count_twos(cards: &[Card]) -> u32 {
+    cards.iter().filter(|card| card.rank == Rank::Two).count() as u32
+}
+
+/// Counts the undetonated Bombs in these cards, Four-Of-A-Kinds and Straight-Flushes (5
+/// consecutive Ranks in the same Suit). 2s are left out, each already doubles by itself, so four 2s
+/// or J-Q-K-A-2 are not Bombs. No card is counted in two Bombs, the cards are split into as many
+/// Bombs as they can be.
+pub(crate) fn count_bombs(cards: &[Card]) -> u32 {
+    let quads: Vec<Rank> = Rank::all()
+        .into_iter()
+        .filter(|rank| *rank != Rank::Two)
+        .filter(|rank| cards.iter().filter(|card| card.rank == *rank).count() == 4)
+        .collect();
+
+    // every choice of Four-Of-A-Kinds, with Straight-Flushes made from the rest
+    (0..1u32 << quads.len())
+        .map(|chosen| {
+            let used: Vec<Rank> = quads
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| chosen & (1 << index) != 0)
+                .map(|(_, rank)| *rank)
+                .collect();
+            used.len() as u32 + count_straight_flushes(cards, &used)
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+/// Counts the non-overlapping Straight-Flushes up to the Ace in these cards, leaving out the
+/// Ranks already used.
+fn count_straight_flushes(cards: &[Card], used: &[Rank]) -> u32 {
+    let mut straight_flushes = 0;
+    for suit in Suit::all() {
+        let mut run = 0;
+        for rank in Rank::all() {
+            if rank != Rank::Two && !used.contains(&rank) && cards.contains(&Card { rank, suit }) {
+                run += 1;
+                if run == 5 {
+                    straight_flushes += 1;
+                    run = 0;
+                }
+            } else {
+                run = 0;
+            }
+        }
+    }
+    straight_flushes
+}
+
+/// Returns the points captured with these cards under the Rules' point-card rule, 0 if the Rules
+/// do not count point cards.
+pub fn captured_points(captured: &[Card], rules: &Rules) -> u32 {
+    match rules.point_cards {
+        Some(rule) => captured.iter().map(|card| rule.points(card)).sum(),
+        None => 0,
+    }
+}
+
+/// Scores a finished Game given the cards each player was still holding.
+///
+/// # Panics
+/// - If nobody has 0 cards, i.e. the Game is not over.
+pub fn score_game(remaining: &[Vec<Card>], rules: &Rules) -> ScoreSheet {
+    let winner = remaining
+        .iter()
+        .position(|cards| cards.is_empty())
+        .expect("a scored game must have a winner with 0 cards");
+    score_with_winner(remaining, winner, rules)
+}
+
+/// Scores a Game stopped at the turn cap given the cards each player was still holding, and
+/// returns who is counted as the winner: the player holding the fewest cards (dead seats in
+/// heads-up play never count). In a Draw every penalty and every point is 0.
+pub fn score_stalemate(
+    remaining: &[Vec<Card>],
+    stalemate: Stalemate,
+    rules: &Rules,
+) -> (usize, ScoreSheet) {
+    let winner = (0..remaining.len())
+        .filter(|seat| !rules.is_dead_seat(*seat))
+        .min_by_key(|seat| remaining[*seat].len())
+        .expect("somebody is playing");
+    let mut sheet = score_with_winner(remaining, winner, rules);
+    if stalemate == Stalemate::Draw {
+        sheet.penalties.fill(0);
+        sheet.points.fill(0);
+    }
+    (winner, sheet)
+}
+
+/// Scores the Game as if the winner had gone out, whatever they still hold.
+fn score_with_winner(remaining: &[Vec<Card>], winner: usize, rules: &Rules) -> ScoreSheet {
+    let cards_left: Vec<usize> = remaining.iter().map(|cards| cards.len()).collect();
+
+    // dead seats in heads-up play are not playing, so owe nothing
+    let penalties: Vec<u32> = remaining
+        .iter()
+        .enumerate()
+        .map(
+            |(seat, cards)| match rules.is_dead_seat(seat) || seat == winner {
+                true => 0,
+                false => penalty(cards, rules),
+            },
+        )
+        .collect();
+
+    let total: u32 = penalties.iter().sum();
+    let points = penalties
+        .iter()
+        .enumerate()
+        .map(|(player_id, penalty)| {
+            if player_id == winner {
+                total as i32
+            } else {
+                -(*penalty as i32)
+            }
+        })
+        .collect();
+
+    ScoreSheet {
+        cards_left,
+        penalties,
+        points,
+    }
+}
+
+/// In partnerships, pools each team's scores: the winning team owes nothing, every member of the
+/// losing team owes the total of their team's penalties, and every member of the winning team
+/// gains it. Does nothing otherwise.
+pub fn pool_team_scores(sheet: &mut ScoreSheet, winner: usize, rules: &Rules) {
+    let Some(partner) = rules.partner(winner) else {
+        return;
+    };
+    let winners = [winner, partner];
+    let pooled: u32 = (0..sheet.penalties.len())
+        .filter(|seat| !winners.contains(seat))
+        .map(|seat| sheet.penalties[seat])
+        .sum();
+    for seat in 0..sheet.penalties.len() {
+        if winners.contains(&seat) {
+            sheet.penalties[seat] = 0;
+            sheet.points[seat] = pooled as i32;
+        } else {
+            sheet.penalties[seat] = pooled;
+            sheet.points[seat] = -(pooled as i32);
+        }
+    }
+}
+
+/// Computes who pays whom, the way the Game is commonly played for small stakes:
+/// between every pair of players, the one with the larger penalty pays the other the difference,
+/// multiplied by the per-point stake. Pairs with equal penalties do not settle. The winner is paid
+/// exactly their points, the losers also settle amongst themselves.
+///
+/// In partnerships the penalties are already pooled (see pool_team_scores), so the teams settle
+/// rather than every pair: each member of the losing team pays their penalty to the opponent
+/// sitting after them, and everybody is paid or pays exactly their points.
+pub fn settle(sheet: &ScoreSheet, stake: u64, rules: &Rules) -> Vec<Settlement> {
+    let num_players = sheet.penalties.len();
+    if rules.teams.is_some() {
+        return (0..num_players)
+            .filter(|payer| sheet.penalties[*payer] > 0)
+            .map(|payer| Settlement {
+                payer,
+                payee: (payer + 1) % num_players,
+                amount: sheet.penalties[payer] as u64 * stake,
+            })
+            .collect();
+    }
+    let mut settlements = vec![];
+    for (a, penalty_a) in sheet.penalties.iter().enumerate() {
+        for (b, penalty_b) in sheet.penalties.iter().enumerate().skip(a + 1) {
+            let amount = penalty_a.abs_diff(*penalty_b) as u64 * stake;
+            if amount == 0 {
+                continue;
+            }
+            let (payer, payee) = if penalty_a > penalty_b {
+                (a, b)
+            } else {
+                (b, a)
+            };
+            settlements.push(Settlement {
+                payer,
+                payee,
+                amount,
+            });
+        }
+    }
+    settlements
+}
+
+/// Combines the settlements of many Games into at most one Settlement per pair of players,
+/// e.g. for a match summary.
+pub fn net_settlements(settlements: &[Settlement]) -> Vec<Settlement> {
+    // positive balance means the lower id player is owed by the higher id player
+    let mut balances: BTreeMap<(usize, usize), i64> = BTreeMap::new();
+    for settlement in settlements {
+        let (low, high) = if settlement.payer < settlement.payee {
+            (settlement.payer, settlement.payee)
+        } else {
+            (settlement.payee, settlement.payer)
+        };
+        let signed = if settlement.payee == low {
+            settlement.amount as i64
+        } else {
+            -(settlement.amount as i64)
+        };
+        *balances.entry((low, high)).or_default() += signed;
+    }
+
+    balances
+        .into_iter()
+        .filter(|(_, balance)| *balance != 0)
+        .map(|((low, high), balance)| Settlement {
+            payer: if balance > 0 { high } else { low },
+            payee: if balance > 0 { low } else { high },
+            amount: balance.unsigned_abs(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::rules::{preset::RulePreset, PointCardRule, TeamPlay};
+    use crate::tests::test_util::vec_card_from_str;
+
+    /// Deals the lowest cards of a Deck without 2s, Four-Of-A-Kinds, or Straight-Flushes
+    /// (every Rank alternates between Clubs/Hearts and Diamonds/Spades), up to 24 cards.
+    fn hands_with_counts(counts: &[usize]) -> Vec<Vec<Card>> {
+        let mut pool = Rank::all()[..12]
+            .iter()
+            .enumerate()
+            .flat_map(|(idx, rank)| {
+                let suits = if idx % 2 == 0 {
+                    [Suit::Clubs, Suit::Hearts]
+                } else {
+                    [Suit::Diamonds, Suit::Spades]
+                };
+                suits.map(|suit| Card { rank: *rank, suit })
+            })
+            .collect::<Vec<Card>>()
+            .into_iter();
+        counts
+            .iter()
+            .map(|count| pool.by_ref().take(*count).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_penalty() {
+        let hong_kong = RulePreset::HongKong.rules();
+        let taiwan = RulePreset::Taiwan.rules();
+        let ten_clubs = "3C 4C 5C 6C 7C 8C 9C TC JC QC";
+        let table = [
+            ("", &hong_kong, 0),
+            ("3C 5D 7S", &hong_kong, 3),
+            ("3C 5D 7S 9H JC KD 2S 3D 5S 7H", &hong_kong, 20),
+            ("3C 5D 7S 9H JC KD 2S 3D 5S 7H 9C JD KS", &hong_kong, 39),
+            // 2s and Bombs only count when the rule is on
+            ("2S 2H 3C", &hong_kong, 3),
+            ("2S 3C 5D", &taiwan, 6),
+            ("2S 2H 3C", &taiwan, 12),
+            ("7C 7D 7H 7S 3C", &taiwan, 10),
+            ("7C 7D 7H 3C", &taiwan, 4),
+            ("3C 4C 5C 6C 7C", &taiwan, 10),
+            ("3C 4C 5C 6C 8C", &taiwan, 5),
+            // two Straight-Flushes, and the tier multiplier
+            (ten_clubs, &taiwan, 80),
+            (ten_clubs, &hong_kong, 20),
+        ];
+        for (cards, rules, expected) in table {
+            let cards = vec_card_from_str(cards);
+            assert_eq!(penalty(&cards, rules), expected, "{cards:?}");
+        }
+    }
+
+    #[test]
+    fn test_count_bombs() {
+        let table = [
+            ("", 0),
+            ("7C 7D 7H 3C", 0),
+            ("7C 7D 7H 7S 3C", 1),
+            ("3C 4C 5C 6C 7C", 1),
+            ("3C 4C 5C 6C 8C", 0),
+            ("3C 4C 5C 6C 7C 8C 9C TC JC QC", 2),
+            // 2s double by themselves
+            ("2C 2D 2H 2S", 0),
+            ("JS QS KS AS 2S", 0),
+            ("TS JS QS KS AS 2S", 1),
+            // the 5C is in the Four-Of-A-Kind or the Straight-Flush, not both
+            ("3C 4C 5C 6C 7C 5D 5H 5S", 1),
+            // the 5s are better used in two Straight-Flushes
+            ("3C 4C 5C 6C 7C 3D 4D 5D 6D 7D 5H 5S", 2),
+            ("3C 4C 5C 6C 7C 8C 8D 8H 8S", 2),
+        ];
+        for (cards, expected) in table {
+            assert_eq!(count_bombs(&vec_card_from_str(cards)), expected, "{cards}");
+        }
+    }
+
+    #[test]
+    fn test_captured_points() {
+        let mut rules = RulePreset::HongKong.rules();
+        let captured = vec_card_from_str("5C 5D TH KS 2S");
+        assert_eq!(captured_points(&captured, &rules), 0);
+        rules.point_cards = Some(PointCardRule::FivesTensKings);
+        assert_eq!(captured_points(&captured, &rules), 30);
+        rules.point_cards = Some(PointCardRule::Hearts);
+        assert_eq!(captured_points(&captured, &rules), 1);
+    }
+
+    #[test]
+    fn test_score_game() {
+        let rules = RulePreset::HongKong.rules();
+        let sheet = score_game(&hands_with_counts(&[3, 0, 8, 13]), &rules);
+        assert_eq!(sheet.cards_left, vec![3, 0, 8, 13]);
+        assert_eq!(sheet.penalties, vec![3, 0, 8, 39]);
+        assert_eq!(sheet.points, vec![-3, 50, -8, -39]);
+        assert_eq!(sheet.points.iter().sum::<i32>(), 0);
+    }
+
+    #[test]
+    fn test_score_stalemate() {
+        let rules = RulePreset::HongKong.rules();
+        let remaining = hands_with_counts(&[3, 2, 8, 2]);
+        let (winner, sheet) = score_stalemate(&remaining, Stalemate::FewestCards, &rules);
+        assert_eq!(winner, 1);
+        assert_eq!(sheet.penalties, vec![3, 0, 8, 2]);
+        assert_eq!(sheet.points, vec![-3, 13, -8, -2]);
+
+        let (winner, sheet) = score_stalemate(&remaining, Stalemate::Draw, &rules);
+        assert_eq!(winner, 1);
+        assert_eq!(sheet.cards_left, vec![3, 2, 8, 2]);
+        assert_eq!(sheet.points, vec![0; 4]);
+
+        for stalemate in [Stalemate::Draw, Stalemate::FewestCards] {
+            assert_eq!(
+                stalemate.to_string().parse::<Stalemate>().unwrap(),
+                stalemate
+            );
+        }
+        assert!("forever".parse::<Stalemate>().is_err());
+    }
+
+    #[test]
+    fn test_pool_team_scores() {
+        let mut rules = RulePreset::HongKong.rules();
+        let mut sheet = score_game(&hands_with_counts(&[3, 0, 8, 13]), &rules);
+        let unpooled = sheet.clone();
+        pool_team_scores(&mut sheet, 1, &rules);
+        assert_eq!(sheet, unpooled);
+
+        // seat 3 still held 13 cards, but their partner went out
+        rules.teams = Some(TeamPlay::FirstOut);
+        pool_team_scores(&mut sheet, 1, &rules);
+        assert_eq!(sheet.penalties, vec![11, 0, 11, 0]);
+        assert_eq!(sheet.points, vec![-11, 11, -11, 11]);
+
+        // the teams settle, each loser paying the pooled penalty once, so everybody's net is
+        // exactly their points
+        let settlements = settle(&sheet, 2, &rules);
+        assert_eq!(
+            settlements,
+            vec![
+                Settlement {
+                    payer: 0,
+                    payee: 1,
+                    amount: 22,
+                },
+                Settlement {
+                    payer: 2,
+                    payee: 3,
+                    amount: 22,
+                },
+            ]
+        );
+        for seat in 0..sheet.points.len() {
+            let paid: u64 = settlements
+                .iter()
+                .filter(|s| s.payee == seat)
+                .map(|s| s.amount)
+                .sum();
+            let paying: u64 = settlements
+                .iter()
+                .filter(|s| s.payer == seat)
+                .map(|s| s.amount)
+                .sum();
+            let net = paid as i64 - paying as i64;
+            assert_eq!(net, sheet.points[seat] as i64 * 2);
+        }
+    }
+
+    #[test]
+    fn test_settle() {
+        let rules = RulePreset::HongKong.rules();
+        let sheet = score_game(&hands_with_counts(&[3, 0, 8, 13]), &rules);
+        let settlements = settle(&sheet, 2, &rules);
+        let expected = [
+            (0, 1, 6),
+            (2, 0, 10),
+            (3, 0, 72),
+            (2, 1, 16),
+            (3, 1, 78),
+            (3, 2, 62),
+        ];
+        assert_eq!(settlements.len(), expected.len());
+        for (settlement, (payer, payee, amount)) in settlements.iter().zip(expected) {
+            assert_eq!(settlement.payer, payer);
+            assert_eq!(settlement.payee, payee);
+            assert_eq!(settlement.amount, amount);
+        }
+
+        // the winner is paid exactly their points
+        let paid_to_winner: u64 = settlements
+            .iter()
+            .filter(|s| s.payee == 1)
+            .map(|s| s.amount)
+            .sum();
+        assert_eq!(paid_to_winner as i32, sheet.points[1] * 2);
+
+        // equal penalties do not settle
+        let sheet = score_game(&hands_with_counts(&[3, 0, 3, 3]), &rules);
+        assert_eq!(settle(&sheet, 1, &rules).len(), 3);
+    }
+
+    #[test]
+    fn test_net_settlements() {
+        let settlements = [
+            Settlement {
+                payer: 0,
+                payee: 1,
+                amount: 5,
+            },
+            Settlement {
+                payer: 1,
+                payee: 0,
+                amount: 8,
+            },
+            Settlement {
+                payer: 2,
+                payee: 3,
+                amount: 4,
+            },
+            Settlement {
+                payer: 3,
+                payee: 2,
+                amount: 4,
+            },
+        ];
+        let netted = net_settlements(&settlements);
+        assert_eq!(
+            netted,
+            vec![Settlement {
+                payer: 1,
+                payee: 0,
+                amount: 3,
+            }]
+        );
+    }
+}
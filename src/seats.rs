@@ -0,0 +1,219 @@
+//! Describes who sits in each seat, and the rules they play by, loaded from a TOML file so a
+//! table (a test setup, or a house-rule game) can be set up again without code changes.
+//!
+//! ```toml
+//! preset = "taiwan"
+//! stake = 5
+//! time_bank = 300
+//!
+//! [[seats]]
+//! name = "Alice"
+//! controller = "human"
+//!
+//! [[seats]]
+//! controller = "ai"
+//! ai_level = "hard"
+//! aggressiveness = "cautious"
+//!
+//! [[seats]]
+//! controller = "network"
+//!
+//! [[seats]]
+//! controller = "ai"
+//! ```
+//!
+//! Everything but the seats may be left out, `rules` (every field of Rules) replaces the preset's
+//! rules for house-rule tables.
+
+use std::fmt::Debug;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::config::GameConfig;
+use crate::player::{Aggressiveness, AiLevel};
+use crate::rules::{preset::RulePreset, Rules};
+use crate::trick::NUM_PLAYERS;
+
+/// Who makes the decisions for a seat.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Controller {
+    /// A person at the keyboard (or the human seat's script, see HumanInput).
+    Human,
+    /// The AI, at the seat's ai_level and aggressiveness.
+    #[default]
+    Ai,
+    /// A person connected to the server.
+    Network,
+    /// A script, reading one Hand per line from stdin.
+    Scripted,
+}
+
+/// Who sits in one seat.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SeatConfig {
+    /// Shown alongside the seat number, None for "Player <seat>".
+    #[serde(default)]
+    pub name: Option<String>,
+
+    #[serde(default)]
+    pub controller: Controller,
+
+    /// How strongly the seat plays, if played by the AI.
+    #[serde(default, deserialize_with = "by_name")]
+    pub ai_level: AiLevel,
+
+    /// How freely the seat spends its control cards, if played by the hard AI.
+    #[serde(default, deserialize_with = "by_name")]
+    pub aggressiveness: Aggressiveness,
+}
+
+impl SeatConfig {
+    /// Returns true if the AI plays this seat.
+    pub fn is_ai(&self) -> bool {
+        self.controller == Controller::Ai
+    }
+
+    /// The seat's name, or "Player <seat>" if it has none.
+    pub fn display_name(&self, seat: usize) -> String {
+        self.name
+            .clone()
+            .unwrap_or_else(|| format!("Player {seat}"))
+    }
+}
+
+/// A whole table as written in a TOML file: the rules, stakes and clock, and every seat.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+pub struct TableFile {
+    #[serde(default, deserialize_with = "by_name")]
+    pub preset: RulePreset,
+
+    /// House rules, replacing the preset's rules.
+    #[serde(default)]
+    pub rules: Option<Rules>,
+
+    #[serde(default)]
+    pub stake: Option<u64>,
+
+    #[serde(default)]
+    pub seed: Option<u64>,
+
+    /// Each player's thinking time for the whole Game, in seconds.
+    #[serde(default)]
+    pub time_bank: Option<u64>,
+
+    pub seats: Vec<SeatConfig>,
+}
+
+/// Reads one of the enums that are named the way they are typed on the command line.
+fn by_name<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: Debug,
+{
+    let name = String::deserialize(deserializer)?;
+    name.parse()
+        .map_err(|e| D::Error::custom(format!("could not understand {name}, {e:?}")))
+}
+
+impl FromStr for TableFile {
+    type Err = io::Error;
+    fn from_str(toml_str: &str) -> Result<Self, Self::Err> {
+        let file: TableFile =
+            toml::from_str(toml_str).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        if file.seats.len() != NUM_PLAYERS {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("expected {NUM_PLAYERS} seats, found {}", file.seats.len()),
+            ));
+        }
+        Ok(file)
+    }
+}
+
+impl TableFile {
+    /// Reads a TableFile from a TOML file.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        fs::read_to_string(path)?.parse()
+    }
+
+    /// The GameConfig this table plays with.
+    pub fn config(&self) -> GameConfig {
+        let mut config = GameConfig::from_preset(self.preset);
+        if let Some(rules) = self.rules {
+            config.rules = rules;
+        }
+        if let Some(stake) = self.stake {
+            config.stake = stake;
+        }
+        config.seed = self.seed;
+        config.time_bank = self.time_bank.map(Duration::from_secs);
+        config.seats = self.seats.clone();
+        config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_parse_table_file() {
+        let file: TableFile = r#"
+            preset = "taiwan"
+            stake = 5
+            time_bank = 300
+
+            [[seats]]
+            name = "Alice"
+            controller = "human"
+
+            [[seats]]
+            controller = "ai"
+            ai_level = "hard"
+            aggressiveness = "cautious"
+
+            [[seats]]
+            controller = "network"
+
+            [[seats]]
+        "#
+        .parse()
+        .unwrap();
+        let config = file.config();
+        assert_eq!(config.preset, RulePreset::Taiwan);
+        assert_eq!(config.rules, RulePreset::Taiwan.rules());
+        assert_eq!(config.stake, 5);
+        assert_eq!(config.time_bank, Some(Duration::from_secs(300)));
+
+        assert_eq!(config.seat(0).display_name(0), "Alice");
+        assert_eq!(config.seat(0).controller, Controller::Human);
+        assert_eq!(config.seat(1).ai_level, AiLevel::Hard);
+        assert_eq!(config.seat(1).aggressiveness, Aggressiveness::Cautious);
+        assert_eq!(config.seat(2).controller, Controller::Network);
+        assert!(config.seat(3).is_ai());
+        assert_eq!(config.seat(3).display_name(3), "Player 3");
+    }
+
+    #[test]
+    fn test_bad_table_files() {
+        // every seat must be described
+        assert!("[[seats]]".parse::<TableFile>().is_err());
+        // names must be understood
+        let file = r#"
+            preset = "atlantis"
+            seats = [{}, {}, {}, {}]
+        "#;
+        assert!(file.parse::<TableFile>().is_err());
+        let file = r#"seats = [{ controller = "robot" }, {}, {}, {}]"#;
+        assert!(file.parse::<TableFile>().is_err());
+    }
+}
@@ -0,0 +1,190 @@
+//! Headless self-play for training policies: plays seeded Games between AI players, and records
+//! every turn as (state features, chosen action, final outcome), written one JSON object per line.
+//! Every array has a fixed length, so a file stacks straight into arrays (e.g. for an .npz).
+
+use std::io::{self, Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::audit::GameId;
+use crate::card::Card;
+use crate::config::GameConfig;
+use crate::constants::{NUM_CARDS_IN_DECK, NUM_PLAYERS};
+use crate::fairness::{play_deal, seeded_deal};
+use crate::game::GameResult;
+use crate::hand::Hand;
+use crate::player::TurnView;
+
+/// How many cards there are, the length of every card mask.
+pub const NUM_CARDS: usize = NUM_CARDS_IN_DECK;
+
+/// The length of Sample::features: the player's cards, every card played so far, and the Hand to
+/// beat as card masks, then how many cards each player holds (starting with the player, in turn
+/// order), then 1 if the player is starting a Trick.
+pub const NUM_FEATURES: usize = 3 * NUM_CARDS + NUM_PLAYERS + 1;
+
+/// One turn of a self-play Game, seen by the player who took it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Sample {
+    /// The seed that dealt the Game.
+    pub seed: u64,
+
+    /// Identifies the Game, see audit.
+    #[serde(default)]
+    pub game_id: GameId,
+
+    /// The player who took the turn.
+    pub player_id: usize,
+
+    /// What the player could see, NUM_FEATURES numbers laid out as described there.
+    pub features: Vec<u8>,
+
+    /// The cards the player chose to play as a card mask, all 0 for a Pass.
+    pub action: Vec<u8>,
+
+    /// The same Hand as it is typed, e.g. "4S 4H", a Pass is "".
+    pub hand: String,
+
+    /// The player's net points once the Game was over.
+    pub outcome: i32,
+
+    /// True if the player won the Game.
+    pub won: bool,
+}
+
+/// Where a card sits in a card mask, from 0 for the Three of Clubs to 51 for the Two of Spades.
+pub fn card_index(card: &Card) -> usize {
+    card.rank as usize * 4 + card.suit as usize
+}
+
+/// NUM_CARDS 0s and 1s, the 1s at the card_index of each of these cards.
+pub fn card_mask<'a>(cards: impl IntoIterator<Item = &'a Card>) -> Vec<u8> {
+    let mut mask = vec![0; NUM_CARDS];
+    for card in cards {
+        mask[card_index(card)] = 1;
+    }
+    mask
+}
+
+/// What the player can see on their turn, NUM_FEATURES numbers laid out as described there.
+pub fn features(view: &TurnView) -> Vec<u8> {
+    let played: Vec<&Card> = view.plays().flat_map(|play| play.hand.cards()).collect();
+    let mut features = card_mask(view.cards);
+    features.extend(card_mask(played));
+    features.extend(card_mask(view.hand_to_beat.cards()));
+    features.push(view.card_counts[view.player_id] as u8);
+    features.extend(view.opponent_ids().map(|id| view.card_counts[id] as u8));
+    features.push(matches!(view.hand_to_beat, Hand::Pass) as u8);
+    features
+}
+
+/// Plays num_games Games of AI players (dealt by the config's seed, or 0, upwards) and returns
+/// every turn of every Game.
+pub fn self_play(config: &GameConfig, num_games: usize) -> Vec<Sample> {
+    let first_seed = config.seed.unwrap_or(0);
+    (first_seed..first_seed + num_games as u64)
+        .flat_map(|seed| {
+            let deal = seeded_deal(seed);
+            let result = play_deal(config, deal.clone());
+            samples(seed, deal, &result)
+        })
+        .collect()
+}
+
+/// Replays a Game from its deal, recording what each player saw on each of their turns.
+fn samples(seed: u64, deal: [Vec<Card>; NUM_PLAYERS], result: &GameResult) -> Vec<Sample> {
+    let mut cards = deal;
+    let mut hand_to_beat = Hand::Pass;
+    let mut trick = 0;
+    let mut samples = vec![];
+    for (turn, play) in result.plays.iter().enumerate() {
+        if play.trick != trick {
+            trick = play.trick;
+            hand_to_beat = Hand::Pass;
+        }
+
+        let player_id = play.player_id;
+        let mut view = TurnView::new(&cards[player_id], hand_to_beat, NUM_PLAYERS);
+        view.player_id = player_id;
+        view.history = &result.plays[..turn];
+        view.card_counts = cards.iter().map(|cards| cards.len()).collect();
+        samples.push(Sample {
+            seed,
+            game_id: result.meta.id,
+            player_id,
+            features: features(&view),
+            action: card_mask(play.hand.cards()),
+            hand: play.hand.to_string(),
+            outcome: result.scores.points[player_id],
+            won: result.winner == player_id,
+        });
+
+        if !matches!(play.hand, Hand::Pass) {
+            cards[player_id].retain(|card| !play.hand.cards().any(|c| c == card));
+            hand_to_beat = play.hand;
+        }
+    }
+    samples
+}
+
+/// Writes the samples one JSON object per line.
+pub fn write_jsonl(samples: &[Sample], out: &mut impl Write) -> io::Result<()> {
+    for sample in samples {
+        serde_json::to_writer(&mut *out, sample)?;
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::card::THREE_OF_CLUBS;
+    use crate::constants::CARDS_PER_PLAYER;
+
+    #[test]
+    fn test_card_mask() {
+        assert_eq!(card_index(&THREE_OF_CLUBS), 0);
+        assert_eq!(card_index(&"2S".parse().unwrap()), NUM_CARDS - 1);
+        let mask = card_mask(&crate::tests::test_util::vec_card_from_str("3C 3D"));
+        assert_eq!(mask.iter().filter(|bit| **bit == 1).count(), 2);
+        assert_eq!(&mask[..3], [1, 1, 0]);
+    }
+
+    #[test]
+    fn test_self_play() {
+        let config = GameConfig {
+            seed: Some(7),
+            ..GameConfig::default()
+        };
+        let samples = self_play(&config, 2);
+        assert!(samples.iter().any(|sample| sample.seed == 8));
+        assert!(samples.iter().all(
+            |sample| sample.features.len() == NUM_FEATURES && sample.action.len() == NUM_CARDS
+        ));
+
+        // the first turn leads the 3C, from a full hand
+        let first = &samples[0];
+        assert_eq!(
+            first.features[..NUM_CARDS].iter().sum::<u8>() as usize,
+            CARDS_PER_PLAYER
+        );
+        assert_eq!(first.action[card_index(&THREE_OF_CLUBS)], 1);
+        assert_eq!(first.features[NUM_FEATURES - 1], 1);
+
+        // every player who won has a positive outcome
+        assert!(samples
+            .iter()
+            .all(|sample| !sample.won || sample.outcome > 0));
+
+        let mut out = vec![];
+        write_jsonl(&samples, &mut out).unwrap();
+        let lines: Vec<&str> = std::str::from_utf8(&out).unwrap().lines().collect();
+        assert_eq!(lines.len(), samples.len());
+        assert_eq!(
+            serde_json::from_str::<Sample>(lines[0]).unwrap(),
+            samples[0]
+        );
+    }
+}
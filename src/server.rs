@@ -0,0 +1,567 @@
+//! A plain-text server, players join with netcat or telnet and type the same card strings as the
+//! local CLI, e.g. "nc localhost 7878". Every line sent by the server is human readable. The
+//! server binary runs serve, with its flags as ServerOptions.
+//!
+//! Whoever sits down first is the host, and may also type "kick <seat>", "lock <password>",
+//! "unlock", "preset <name>" (before the Game starts) and "host <seat>", see room. The welcome
+//! names the preset in play, which only the host may change, the players joining do not choose
+//! their own. Until every seat is taken anything else is answered "The Game has not started,
+//! waiting for <n> more players...", after that everybody else who connects is a spectator.
+//!
+//! Once the Game starts every line goes to the Table (see table::Table::handle). Every time the
+//! Table says something to everybody, it is followed by "Sync <hash> at <seq>", the state hash and
+//! the seq of the last Message, so a client can tell it missed something and ask for a "sync" or
+//! everything "since <seq>". Once a Game is over the players may type "ready", and once all of
+//! them have the next Game of the Match is dealt at the same table, carrying the scores. A player
+//! who leaves ends the Match for everybody.
+//!
+//! Every line a player sends is checked first (see inbound): lines that are too long, are not
+//! text or come too quickly are answered with an error, and a player who sends too many of them
+//! is disconnected.
+//!
+//! What the server logs, e.g. who joined and how each Game ended, is written to output::current
+//! on the thread that called serve, and on every thread it starts.
+//!
+//! tests/protocol.rs drives a server with scripted clients, and is the specification of what it
+//! says when.
+
+use std::io::{BufReader, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::config::GameConfig;
+use crate::constants::NUM_PLAYERS;
+use crate::inbound::{Inbound, Limits};
+use crate::latency::Heartbeat;
+use crate::output::{self, current};
+use crate::ratings::Ratings;
+use crate::room::{HostCommand, Room};
+use crate::seats::{Controller, SeatConfig};
+use crate::spectate::SpectatorFeed;
+use crate::table::{Message, Recipient, Table};
+use crate::trick::Seat;
+
+/// How far behind the players the spectators watch, unless ServerOptions says otherwise.
+pub const DEFAULT_SPECTATOR_DELAY: Duration = Duration::from_secs(30);
+
+/// How a server runs, beyond the GameConfig its Tables are dealt with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ServerOptions {
+    /// How far behind the players the spectators watch.
+    pub spectator_delay: Duration,
+
+    /// Whether the spectators are told what happens in commentary (see commentary) rather than
+    /// what the Table says.
+    pub commentary: bool,
+
+    /// How often to send each player "ping <n>", None to never. A client that answers "pong <n>"
+    /// straight away has the time its moves spend in transit kept off its clock, see latency.
+    pub heartbeat: Option<Duration>,
+}
+
+impl Default for ServerOptions {
+    fn default() -> Self {
+        Self {
+            spectator_delay: DEFAULT_SPECTATOR_DELAY,
+            commentary: false,
+            heartbeat: None,
+        }
+    }
+}
+
+/// Explains the commands only the host may send.
+const HOST_HELP: &str = "You are the host. You may \"kick <seat>\" (the AI takes over the seat), \
+                         \"lock <password>\", \"unlock\", \"preset <name>\" before the Game starts, \
+                         and \"host <seat>\" to hand the host to somebody else.";
+
+/// How often new spectators are let in while nothing else is happening.
+const SPECTATOR_POLL: Duration = Duration::from_millis(500);
+
+/// Sends a line of text in a single write, a player who has gone away simply stops receiving.
+fn send(stream: &mut impl Write, text: &str) {
+    let _ = stream.write_all(format!("{text}\n").as_bytes());
+}
+
+/// A player's connection, written to both by the main loop and by the thread reading their lines.
+/// Each write holds the lock, so the lines sent by the two never interleave.
+#[derive(Debug, Clone)]
+struct Connection(Arc<Mutex<TcpStream>>);
+
+impl Connection {
+    fn peer_addr(&self) -> std::io::Result<SocketAddr> {
+        self.0.lock().unwrap().peer_addr()
+    }
+
+    fn shutdown(&self) {
+        let _ = self.0.lock().unwrap().shutdown(Shutdown::Both);
+    }
+}
+
+impl Write for Connection {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> std::io::Result<()> {
+        self.0.lock().unwrap().write_all(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.0.lock().unwrap().flush()
+    }
+}
+
+/// Sends the Table's Messages, to everybody or to the player sitting in the seat. Every broadcast
+/// ends with the state hash and the seq of the last Message, "Sync <hash> at <seq>", so clients
+/// can tell they have missed something and ask for a "sync" or everything "since <seq>". The
+/// broadcasts are also held back for the spectators.
+fn deliver(
+    messages: Vec<Message>,
+    streams: &mut [(usize, Connection)],
+    table: &Table,
+    feed: &mut SpectatorFeed,
+) {
+    let now = Instant::now();
+    feed.record(now, &messages);
+    feed.narrate(now, table.plays(), table.result());
+    let broadcast = messages.iter().any(|m| m.to == Recipient::Everybody);
+    for message in messages {
+        for (seat, stream) in streams.iter_mut() {
+            if message.to == Recipient::Everybody || message.to == Recipient::Seat(*seat) {
+                send(stream, &message.text);
+            }
+        }
+    }
+    if broadcast {
+        let sync = format!("Sync {:016x} at {}", table.state_hash(), table.last_seq());
+        for (_, stream) in streams.iter_mut() {
+            send(stream, &sync);
+        }
+        feed.push(now, sync);
+    }
+}
+
+/// Lets in the spectators who connected since last time, and sends them whatever the feed no
+/// longer holds back.
+fn watch(
+    feed: &mut SpectatorFeed,
+    joining: &mpsc::Receiver<TcpStream>,
+    spectators: &mut Vec<TcpStream>,
+) {
+    for mut stream in joining.try_iter() {
+        writeln!(
+            current(),
+            "A spectator joined from {:?}",
+            stream.peer_addr()
+        );
+        send(
+            &mut stream,
+            &format!(
+                "Welcome to Big Two! You are watching, {} seconds behind the players.",
+                feed.delay().as_secs()
+            ),
+        );
+        spectators.push(stream);
+    }
+    for text in feed.due(Instant::now()) {
+        for stream in spectators.iter_mut() {
+            send(stream, &text);
+        }
+    }
+}
+
+/// Says something to the players and the spectators, e.g. that the Game is over.
+fn announce(text: &str, streams: &mut [(usize, Connection)], feed: &mut SpectatorFeed) {
+    for (_, stream) in streams.iter_mut() {
+        send(stream, text);
+    }
+    feed.push(Instant::now(), text.to_string());
+}
+
+/// Keeps the spectators watching until they have seen everything the feed holds back.
+fn finish_watching(
+    feed: &mut SpectatorFeed,
+    joining: &mpsc::Receiver<TcpStream>,
+    spectators: &mut Vec<TcpStream>,
+) {
+    while let Some(next) = feed.next_due() {
+        thread::sleep(next.saturating_duration_since(Instant::now()));
+        watch(feed, joining, spectators);
+    }
+}
+
+/// Lets somebody into the room unless they were kicked, or the room is locked and they do not
+/// know the password, and hands them to the main thread.
+fn admit(mut stream: TcpStream, room: &Mutex<Room>, joining: &mpsc::Sender<TcpStream>) {
+    let Ok(address) = stream.peer_addr().map(|address| address.ip()) else {
+        return;
+    };
+    let locked = room.lock().unwrap().is_locked();
+    let password = match locked {
+        true => {
+            send(&mut stream, "This room is locked, type the password.");
+            // read a byte at a time, so nothing sent after the password is lost
+            let clone = stream.try_clone().expect("could not clone stream");
+            let mut inbound = Inbound::new(BufReader::with_capacity(1, clone), Limits::default());
+            match inbound.next_line(Instant::now) {
+                Ok(Some(Ok(password))) => Some(password),
+                _ => None,
+            }
+        }
+        false => None,
+    };
+    let admitted = room.lock().unwrap().admit(address, password.as_deref());
+    match admitted {
+        Ok(()) => {
+            let _ = joining.send(stream);
+        }
+        Err(refusal) => {
+            writeln!(current(), "Refused {address}, {refusal}");
+            send(&mut stream, &refusal.to_string());
+            let _ = stream.shutdown(Shutdown::Both);
+        }
+    }
+}
+
+/// Seats a player, and starts reading the lines they type.
+fn sit(
+    seat: usize,
+    mut stream: TcpStream,
+    config: &GameConfig,
+    room: &Mutex<Room>,
+    streams: &mut Vec<(usize, Connection)>,
+    sender: &mpsc::Sender<(usize, Option<String>)>,
+) {
+    let name = config.seat(seat).display_name(seat);
+    writeln!(
+        current(),
+        "{name} (seat {seat}) joined from {:?}",
+        stream.peer_addr()
+    );
+    send(
+        &mut stream,
+        &format!(
+            "Welcome to Big Two! You are Player {seat} ({name}), playing {} rules.",
+            config.preset
+        ),
+    );
+    room.lock().unwrap().sit(seat);
+    if room.lock().unwrap().host() == Some(seat) {
+        send(&mut stream, HOST_HELP);
+    }
+    for (_, other) in streams.iter_mut() {
+        send(other, &format!("Player {seat} ({name}) joined."));
+    }
+
+    // lines are checked before they reach the Table, rejections are answered straight away
+    let reader = BufReader::new(stream.try_clone().expect("could not clone stream"));
+    let connection = Connection(Arc::new(Mutex::new(stream)));
+    let mut replies = connection.clone();
+    let mut inbound = Inbound::new(reader, Limits::default());
+    let sender = sender.clone();
+    let log = current();
+    thread::spawn(move || {
+        let _output = output::install(log);
+        while let Ok(Some(line)) = inbound.next_line(Instant::now) {
+            match line {
+                Ok(line) => {
+                    if sender.send((seat, Some(line))).is_err() {
+                        return;
+                    }
+                }
+                Err(rejected) => send(&mut replies, &rejected.to_string()),
+            }
+        }
+        if inbound.is_struck_out() {
+            writeln!(
+                current(),
+                "Player {seat} was disconnected for sending too many bad lines"
+            );
+            send(&mut replies, "Disconnected for sending too many bad lines.");
+            replies.shutdown();
+        }
+        let _ = sender.send((seat, None));
+    });
+    streams.push((seat, connection));
+}
+
+/// Sends a line of text to the player sitting in the seat.
+fn tell(streams: &mut [(usize, Connection)], seat: usize, text: &str) {
+    for (_, stream) in streams.iter_mut().filter(|(other, _)| *other == seat) {
+        send(stream, text);
+    }
+}
+
+/// Carries out a host command sent from the seat, once the Room has checked that it may be. A
+/// kicked seat is handed to the AI, at the Table if the Game has started.
+fn host_command(
+    seat: usize,
+    command: HostCommand,
+    room: &Mutex<Room>,
+    config: &mut GameConfig,
+    streams: &mut Vec<(usize, Connection)>,
+    table: Option<&mut Table>,
+    feed: &mut SpectatorFeed,
+) {
+    let applied = room.lock().unwrap().apply(seat, &command);
+    if let Err(e) = applied {
+        tell(streams, seat, &e.to_string());
+        return;
+    }
+    writeln!(current(), "Player {seat} (the host) sent {command}");
+    match command {
+        HostCommand::Kick(kicked) => {
+            if let Some(index) = streams.iter().position(|(other, _)| *other == kicked) {
+                let (_, mut stream) = streams.remove(index);
+                if let Ok(address) = stream.peer_addr() {
+                    room.lock().unwrap().ban(address.ip());
+                }
+                send(&mut stream, "You have been kicked from this room.");
+                stream.shutdown();
+            }
+            config.seats = (0..NUM_PLAYERS)
+                .map(|other| SeatConfig {
+                    controller: match other == kicked {
+                        true => Controller::Ai,
+                        false => config.seat(other).controller,
+                    },
+                    ..config.seat(other)
+                })
+                .collect();
+            let text = format!("Player {kicked} was kicked by the host.");
+            announce(&text, streams, feed);
+            if let Some(table) = table {
+                let seat = Seat::new(kicked, NUM_PLAYERS).expect("only a seated person is kicked");
+                let messages = table.replace_with_ai(seat);
+                deliver(messages, streams, table, feed);
+            }
+        }
+        HostCommand::Lock(_) => announce("The host locked the room.", streams, feed),
+        HostCommand::Unlock => announce("The host unlocked the room.", streams, feed),
+        HostCommand::Preset(preset) => {
+            config.preset = preset;
+            config.rules = preset.rules();
+            let text = format!("The host changed the rules to {preset}.");
+            announce(&text, streams, feed);
+        }
+        HostCommand::Host(host) => {
+            announce(&format!("Player {host} is now the host."), streams, feed)
+        }
+    }
+}
+
+/// Runs a room on the listener: seats the people the config is waiting for as they connect, then
+/// plays Game after Game at the same Table for as long as they all agree to a rematch. Returns once
+/// a player leaves, and the spectators have seen everything.
+///
+/// # Panics
+/// - If every seat of the config is played by the AI.
+pub fn serve(listener: TcpListener, mut config: GameConfig, options: ServerOptions) {
+    assert!(
+        !config.people().is_empty(),
+        "at least one seat must not be played by the AI"
+    );
+
+    // everybody let into the room arrives here, the first to fill the seats and then spectators
+    let room = Arc::new(Mutex::new(Room::default()));
+    let (joining_sender, joining) = mpsc::channel();
+    let gate = Arc::clone(&room);
+    let log = current();
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let room = Arc::clone(&gate);
+            let joining = joining_sender.clone();
+            let log = log.clone();
+            thread::spawn(move || {
+                let _output = output::install(log);
+                admit(stream, &room, &joining)
+            });
+        }
+    });
+
+    // every line a player types arrives here, None when they disconnect
+    let (sender, receiver) = mpsc::channel::<(usize, Option<String>)>();
+    let mut streams: Vec<(usize, Connection)> = vec![];
+    let mut feed = SpectatorFeed::new(options.spectator_delay);
+    if options.commentary {
+        feed = feed.with_commentary();
+    }
+    loop {
+        let mut open_seats: Vec<usize> = config
+            .people()
+            .into_iter()
+            .filter(|seat| !room.lock().unwrap().is_seated(*seat))
+            .collect();
+        let Some(&seat) = open_seats.first() else {
+            break;
+        };
+        if let Ok(stream) = joining.try_recv() {
+            open_seats.remove(0);
+            sit(seat, stream, &config, &room, &mut streams, &sender);
+            if !open_seats.is_empty() {
+                let waiting = format!("Waiting for {} more players...", open_seats.len());
+                announce(&waiting, &mut streams, &mut feed);
+            }
+            continue;
+        }
+        match receiver.recv_timeout(SPECTATOR_POLL) {
+            Ok((seat, Some(line))) => match line.parse::<HostCommand>() {
+                Ok(command) => host_command(
+                    seat,
+                    command,
+                    &room,
+                    &mut config,
+                    &mut streams,
+                    None,
+                    &mut feed,
+                ),
+                Err(_) => tell(
+                    &mut streams,
+                    seat,
+                    &format!(
+                        "The Game has not started, waiting for {} more players...",
+                        open_seats.len()
+                    ),
+                ),
+            },
+            Ok((seat, None)) => {
+                if room.lock().unwrap().is_seated(seat) {
+                    room.lock().unwrap().leave(seat);
+                    streams.retain(|(other, _)| *other != seat);
+                    writeln!(current(), "Player {seat} left before the Game started");
+                    announce(&format!("Player {seat} left."), &mut streams, &mut feed);
+                }
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+            Err(mpsc::RecvTimeoutError::Disconnected) => panic!("every reader hung up"),
+        }
+    }
+    room.lock().unwrap().start();
+
+    // once every seat is taken, everybody else who connects is a spectator
+    let mut spectators = vec![];
+
+    // times how long each player's connection takes to answer, if asked to
+    let heartbeat_every = options.heartbeat;
+    let mut heartbeats = <[Heartbeat; NUM_PLAYERS]>::default();
+    let mut last_ping = Instant::now();
+
+    // everybody's rating, by name, moved by every Game of the Match
+    let mut ratings = Ratings::default();
+
+    // the same Table plays Game after Game, for as long as everybody agrees to a rematch
+    let mut table = Table::new(&config, &config.people());
+    loop {
+        for (seat, heartbeat) in heartbeats.iter().enumerate() {
+            table.set_latency_allowance(seat, heartbeat.allowance());
+        }
+        let meta = table.meta();
+        writeln!(
+            current(),
+            "Game {} started, {} rules, seed {:?}, version {}",
+            meta.id,
+            meta.preset,
+            meta.seed,
+            meta.version
+        );
+        let messages = table.start();
+        deliver(messages, &mut streams, &table, &mut feed);
+
+        let mut reported = false;
+        while !table.rematch_agreed() {
+            if let (Some(result), false) = (table.result(), reported) {
+                reported = true;
+                writeln!(
+                    current(),
+                    "Game {} Over, Scores: {:?}, thinking times {:.1?} (raw {:.1?}), clients: {:?}",
+                    result.meta.id,
+                    result.scores.points,
+                    result.thinking_times,
+                    result.raw_thinking_times,
+                    result
+                        .meta
+                        .participants
+                        .iter()
+                        .map(|participant| participant.client.as_deref())
+                        .collect::<Vec<_>>()
+                );
+                let names: Vec<String> = result
+                    .meta
+                    .participants
+                    .iter()
+                    .map(|participant| participant.name.clone())
+                    .collect();
+                ratings.update(&names, result);
+                writeln!(
+                    current(),
+                    "Ratings after Game {}: {}",
+                    result.meta.id,
+                    names
+                        .iter()
+                        .map(|name| format!("{name} {:.0}", ratings.get(name)))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                );
+            }
+            if heartbeat_every.is_some_and(|every| last_ping.elapsed() >= every) {
+                last_ping = Instant::now();
+                for (seat, stream) in streams.iter_mut() {
+                    send(stream, &heartbeats[*seat].ping(last_ping));
+                }
+            }
+            watch(&mut feed, &joining, &mut spectators);
+            // wake up in time to show the spectators the next thing held back
+            let wait = feed
+                .next_due()
+                .map_or(SPECTATOR_POLL, |next| {
+                    next.saturating_duration_since(Instant::now())
+                })
+                .min(SPECTATOR_POLL);
+            match receiver.recv_timeout(wait) {
+                Ok((seat, Some(line))) if heartbeats[seat].pong(&line, Instant::now()) => {
+                    table.set_latency_allowance(seat, heartbeats[seat].allowance());
+                }
+                Ok((seat, Some(line))) => match line.parse::<HostCommand>() {
+                    Ok(command) => host_command(
+                        seat,
+                        command,
+                        &room,
+                        &mut config,
+                        &mut streams,
+                        Some(&mut table),
+                        &mut feed,
+                    ),
+                    Err(_) => {
+                        let messages = table.handle(seat, &line);
+                        deliver(messages, &mut streams, &table, &mut feed);
+                    }
+                },
+                // whoever was kicked is no longer missed
+                Ok((seat, None)) if !room.lock().unwrap().is_seated(seat) => {}
+                Ok((seat, None)) => {
+                    let text = match table.result() {
+                        Some(_) => format!("Player {seat} left, there is no rematch."),
+                        None => format!("Player {seat} left, the Game is abandoned."),
+                    };
+                    announce(&text, &mut streams, &mut feed);
+                    match table.result() {
+                        Some(_) => writeln!(current(), "Player {seat} left, the room is closed"),
+                        None => writeln!(
+                            current(),
+                            "Player {seat} left, Game {} abandoned",
+                            table.meta().id
+                        ),
+                    }
+                    finish_watching(&mut feed, &joining, &mut spectators);
+                    return;
+                }
+                Err(mpsc::RecvTimeoutError::Timeout) => {}
+                Err(mpsc::RecvTimeoutError::Disconnected) => panic!("every reader hung up"),
+            }
+        }
+        table = table.rematch().expect("everybody agreed to a rematch");
+        feed.next_game();
+    }
+}
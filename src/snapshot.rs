@@ -0,0 +1,190 @@
+//! Crash-safety snapshots: while a Game is played the engine writes its state to a temp file every
+//! few turns (see GameConfig::snapshot_every), and if the engine panics it writes one last
+//! Snapshot with the panic message. Either way the file holds the config (and so the seed), the
+//! deal and every turn so far, a ready-made reproduction of whatever went wrong. The file is
+//! removed once the Game finishes.
+
+use std::any::Any;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use serde::{Deserialize, Serialize};
+
+use crate::card::Card;
+use crate::config::GameConfig;
+use crate::play::Play;
+use crate::player::Player;
+use crate::trick::NUM_PLAYERS;
+
+/// Counts the Games snapshotted by this process, so Games played at the same time (e.g. by tests)
+/// never share a file.
+static GAMES: AtomicUsize = AtomicUsize::new(0);
+
+/// The state of a Game, as written to the snapshot file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// Everything the Game was played with, including its seed.
+    pub config: GameConfig,
+
+    /// The cards dealt to each seat, after any redeal or card exchange.
+    pub deal: Vec<Vec<Card>>,
+
+    /// The cards each seat held when the Snapshot was taken.
+    pub cards: Vec<Vec<Card>>,
+
+    /// Every turn of the finished Tricks. After a panic, the turns of the Trick being played are
+    /// missing here, but their cards are already gone from cards.
+    pub plays: Vec<Play>,
+
+    /// What the engine panicked with, None for the regular snapshots.
+    pub panic: Option<String>,
+}
+
+impl Snapshot {
+    /// Writes the Snapshot to a JSON file.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(path, json)
+    }
+
+    /// Reads a Snapshot written by save.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let json = fs::read_to_string(path)?;
+        serde_json::from_str(&json).map_err(io::Error::from)
+    }
+}
+
+/// Takes the Snapshots of one Game.
+pub(crate) struct Snapshotter {
+    /// How many turns apart the regular Snapshots are.
+    every: usize,
+
+    /// The temp file written to.
+    path: PathBuf,
+
+    config: GameConfig,
+    deal: Vec<Vec<Card>>,
+
+    /// How many turns had been played at the last Snapshot.
+    taken_at: usize,
+}
+
+impl Snapshotter {
+    /// Starts snapshotting a Game just dealt, None if the config asks for no snapshots.
+    pub(crate) fn new(config: &GameConfig, players: &[Player; NUM_PLAYERS]) -> Option<Self> {
+        let every = config.snapshot_every?.max(1);
+        let game = GAMES.fetch_add(1, Ordering::Relaxed);
+        let file_name = format!("bigtwo-snapshot-{}-{game}.json", std::process::id());
+        Some(Self {
+            every,
+            path: std::env::temp_dir().join(file_name),
+            config: config.clone(),
+            deal: cards_of(players),
+            taken_at: 0,
+        })
+    }
+
+    /// Writes a Snapshot if another `every` turns have been played since the last one. Failing to
+    /// write one never stops the Game.
+    pub(crate) fn after_trick(&mut self, players: &[Player; NUM_PLAYERS], plays: &[Play]) {
+        if plays.len() >= self.taken_at + self.every {
+            self.taken_at = plays.len();
+            let _ = self.snapshot(players, plays, None).save(&self.path);
+        }
+    }
+
+    /// Writes the final Snapshot once the engine has panicked, and says where it is.
+    pub(crate) fn after_panic(
+        &self,
+        players: &[Player; NUM_PLAYERS],
+        plays: &[Play],
+        payload: &(dyn Any + Send),
+    ) {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| String::from("unknown panic"));
+        let snapshot = self.snapshot(players, plays, Some(message));
+        match snapshot.save(&self.path) {
+            Ok(()) => eprintln!(
+                "The Game (seed {:?}) crashed after {} turns, a snapshot to reproduce it was written to {}",
+                self.config.seed,
+                plays.len(),
+                self.path.display()
+            ),
+            Err(e) => eprintln!("The Game crashed, and its snapshot could not be written: {e}"),
+        }
+    }
+
+    /// The Game is over, its snapshots are no longer needed.
+    pub(crate) fn finish(self) {
+        let _ = fs::remove_file(&self.path);
+    }
+
+    fn snapshot(
+        &self,
+        players: &[Player; NUM_PLAYERS],
+        plays: &[Play],
+        panic: Option<String>,
+    ) -> Snapshot {
+        Snapshot {
+            config: self.config.clone(),
+            deal: self.deal.clone(),
+            cards: cards_of(players),
+            plays: plays.to_vec(),
+            panic,
+        }
+    }
+}
+
+fn cards_of(players: &[Player; NUM_PLAYERS]) -> Vec<Vec<Card>> {
+    players.iter().map(|player| player.cards.clone()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::hand::Hand;
+    use crate::tests::test_util::vec_card_from_str;
+
+    #[test]
+    fn test_snapshots() {
+        let config = GameConfig {
+            seed: Some(4),
+            snapshot_every: Some(3),
+            ..GameConfig::default()
+        };
+        let mut players = <[Player; NUM_PLAYERS]>::default();
+        players[0].cards = vec_card_from_str("3C 5D AS");
+        let mut snapshotter = Snapshotter::new(&config, &players).unwrap();
+        let path = snapshotter.path.clone();
+
+        // nothing is written until 3 turns have been played
+        let mut plays = vec![Play::now(0, "3C".parse().unwrap(), 0)];
+        snapshotter.after_trick(&players, &plays);
+        assert!(!path.exists());
+        plays.push(Play::now(1, Hand::Pass, 0));
+        plays.push(Play::now(2, Hand::Pass, 0));
+        players[0].cards.remove(0);
+        snapshotter.after_trick(&players, &plays);
+        let snapshot = Snapshot::load(&path).unwrap();
+        assert_eq!(snapshot.config, config);
+        assert_eq!(snapshot.deal[0], vec_card_from_str("3C 5D AS"));
+        assert_eq!(snapshot.cards[0], vec_card_from_str("5D AS"));
+        assert_eq!(snapshot.plays.len(), 3);
+        assert_eq!(snapshot.panic, None);
+
+        let payload: Box<dyn Any + Send> = Box::new("the engine broke");
+        snapshotter.after_panic(&players, &plays, payload.as_ref());
+        let snapshot = Snapshot::load(&path).unwrap();
+        assert_eq!(snapshot.panic.as_deref(), Some("the engine broke"));
+
+        snapshotter.finish();
+        assert!(!path.exists());
+        assert!(Snapshotter::new(&GameConfig::default(), &players).is_none());
+    }
+}
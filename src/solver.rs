@@ -0,0 +1,228 @@
+//! An exhaustive endgame solver. Given a Position where every player's cards are known, decides
+//! whether one player (the "hero") can force a win no matter how the opponents respond.
+//!
+//! The search visits every playable Hand for every player, so it is only practical for endgames
+//! where few cards remain, or given a deadline (see can_force_win_before) to give up by.
+
+use std::collections::BTreeSet;
+use std::time::Instant;
+
+use crate::card::Card;
+use crate::hand::{generate::playable_hands, Hand};
+use crate::rules::Rules;
+use crate::trick::Seat;
+
+/// A snapshot of a Game in progress, with every player's cards visible.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Position {
+    /// The cards held by each player, indexed by player id.
+    pub hands: Vec<Vec<Card>>,
+
+    /// The Hand the current player must beat, Pass if they are starting a new Trick.
+    pub hand_to_beat: Hand,
+
+    /// The player whose turn it is.
+    pub current_player: usize,
+
+    /// Every player who has passed so far this Trick.
+    pub passed: BTreeSet<usize>,
+}
+
+impl Position {
+    /// Returns the player who has run out of cards, if any.
+    pub fn winner(&self) -> Option<usize> {
+        self.hands.iter().position(|cards| cards.is_empty())
+    }
+
+    /// Every Hand the current player may play.
+    pub fn playable_hands(&self, rules: &Rules) -> Vec<Hand> {
+        playable_hands(&self.hand_to_beat, &self.hands[self.current_player], rules)
+    }
+
+    /// Returns the Position after the current player plays this Hand, following the same Trick
+    /// rules as the engine: once every other player has passed, the remaining player starts a new
+    /// Trick.
+    ///
+    /// # Panics
+    /// - If the current player does not hold the cards in the Hand.
+    pub fn play(&self, hand: &Hand) -> Position {
+        let mut next = self.clone();
+        let num_players = self.hands.len();
+
+        if let Hand::Pass = hand {
+            next.passed.insert(self.current_player);
+            if next.passed.len() == num_players - 1 {
+                next.current_player = (0..num_players)
+                    .find(|id| !next.passed.contains(id))
+                    .unwrap();
+                next.passed.clear();
+                next.hand_to_beat = Hand::Pass;
+                return next;
+            }
+        } else {
+            let cards = &mut next.hands[self.current_player];
+            for card in hand.cards() {
+                let index = cards
+                    .iter()
+                    .position(|held| held == card)
+                    .expect("the current player must hold the cards they play");
+                cards.remove(index);
+            }
+            next.hand_to_beat = *hand;
+        }
+
+        next.current_player = Seat::new(self.current_player, num_players)
+            .expect("the current player is seated")
+            .next_where(|seat| !next.passed.contains(&seat.index()))
+            .expect("one player must not have passed")
+            .index();
+        next
+    }
+}
+
+/// Returns true if the hero can force a win using at most `plays` more turns of their own,
+/// no matter what the opponents do.
+pub fn can_force_win(position: &Position, hero: usize, plays: usize, rules: &Rules) -> bool {
+    search(position, hero, plays, rules, None).expect("there is no deadline")
+}
+
+/// As can_force_win, but gives up once the deadline has passed, returning None, so a search can
+/// keep to a time budget.
+pub fn can_force_win_before(
+    position: &Position,
+    hero: usize,
+    plays: usize,
+    rules: &Rules,
+    deadline: Instant,
+) -> Option<bool> {
+    search(position, hero, plays, rules, Some(deadline))
+}
+
+/// Searches every reply, None if the deadline passed first.
+fn search(
+    position: &Position,
+    hero: usize,
+    plays: usize,
+    rules: &Rules,
+    deadline: Option<Instant>,
+) -> Option<bool> {
+    if let Some(winner) = position.winner() {
+        return Some(winner == hero);
+    }
+    if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+        return None;
+    }
+    let heros_turn = position.current_player == hero;
+    if heros_turn && plays == 0 {
+        return Some(false);
+    }
+    let plays = if heros_turn { plays - 1 } else { plays };
+    for hand in position.playable_hands(rules) {
+        // the hero needs one winning Hand, the opponents one Hand that stops them
+        if search(&position.play(&hand), hero, plays, rules, deadline)? == heros_turn {
+            return Some(heros_turn);
+        }
+    }
+    Some(!heros_turn)
+}
+
+/// Returns a Hand the hero can play now that forces a win within `plays` turns of their own
+/// (including this one), if there is one.
+///
+/// # Panics
+/// - If it is not the hero's turn.
+pub fn find_forced_win(
+    position: &Position,
+    hero: usize,
+    plays: usize,
+    rules: &Rules,
+) -> Option<Hand> {
+    assert_eq!(position.current_player, hero, "it must be the hero's turn");
+    if plays == 0 {
+        return None;
+    }
+    position
+        .playable_hands(rules)
+        .into_iter()
+        .find(|hand| can_force_win(&position.play(hand), hero, plays - 1, rules))
+}
+
+/// Returns the Hand the current opponent should play to stop the hero forcing a win within
+/// `plays` turns. If the hero wins no matter what, the first playable Hand is returned.
+///
+/// # Panics
+/// - If it is the hero's turn.
+pub fn find_refutation(position: &Position, hero: usize, plays: usize, rules: &Rules) -> Hand {
+    assert_ne!(
+        position.current_player, hero,
+        "it must be an opponent's turn"
+    );
+    let hands = position.playable_hands(rules);
+    hands
+        .iter()
+        .find(|hand| !can_force_win(&position.play(hand), hero, plays, rules))
+        .unwrap_or(&hands[0])
+        .to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::tests::test_util::vec_card_from_str;
+
+    fn position(hands: [&str; 4], hand_to_beat: &str, current_player: usize) -> Position {
+        Position {
+            hands: hands.map(vec_card_from_str).to_vec(),
+            hand_to_beat: hand_to_beat.parse().unwrap(),
+            current_player,
+            passed: BTreeSet::new(),
+        }
+    }
+
+    #[test]
+    fn test_play() {
+        let start = position(["2S 3C", "2H", "4D", "5D"], "", 0);
+        let after = start.play(&"2S".parse().unwrap());
+        assert_eq!(after.current_player, 1);
+        assert_eq!(after.hands[0], vec_card_from_str("3C"));
+
+        // everybody passes, player 0 starts a new Trick
+        let after = after.play(&Hand::Pass).play(&Hand::Pass).play(&Hand::Pass);
+        assert_eq!(after.current_player, 0);
+        assert_eq!(after.hand_to_beat, Hand::Pass);
+        assert!(after.passed.is_empty());
+
+        let after = after.play(&"3C".parse().unwrap());
+        assert_eq!(after.winner(), Some(0));
+    }
+
+    #[test]
+    fn test_forced_win() {
+        let rules = Rules::default();
+        let start = position(["2S 3C", "2H", "4D", "5D"], "", 0);
+
+        // leading the 2S first is the only way to win
+        assert_eq!(
+            find_forced_win(&start, 0, 2, &rules),
+            Some("2S".parse().unwrap())
+        );
+        assert_eq!(find_forced_win(&start, 0, 1, &rules), None);
+
+        // leading the 3C lets player 1 out with the 2H
+        let after = start.play(&"3C".parse().unwrap());
+        assert!(!can_force_win(&after, 0, 5, &rules));
+        assert_eq!(find_refutation(&after, 0, 5, &rules), "2H".parse().unwrap());
+
+        // given time the search finishes, out of time it gives up
+        let later = Instant::now() + std::time::Duration::from_secs(60);
+        assert_eq!(
+            can_force_win_before(&after, 0, 5, &rules, later),
+            Some(false)
+        );
+        assert_eq!(
+            can_force_win_before(&after, 0, 5, &rules, Instant::now()),
+            None
+        );
+    }
+}
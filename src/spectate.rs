@@ -0,0 +1,171 @@
+//! Spectators watch a Game from behind: everything said to the whole Table reaches them only after
+//! a delay (e.g. 30 seconds), so a Game streamed publicly can't be watched live and relayed back to
+//! the people playing it. Spectators never see what is said to a single seat.
+//!
+//! A feed with commentary (see commentary) narrates the turns in place of what the Table says.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use crate::commentary::Commentator;
+use crate::game::GameResult;
+use crate::play::Play;
+use crate::table::{Message, Recipient};
+
+/// Holds back what the Table says until spectators may see it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpectatorFeed {
+    delay: Duration,
+
+    /// What has been said, oldest first, with when it may be shown.
+    pending: VecDeque<(Instant, String)>,
+
+    /// Narrates the Game, if the feed has commentary.
+    commentator: Option<Commentator>,
+}
+
+impl SpectatorFeed {
+    /// A feed running this far behind the Game, Duration::ZERO for a live one.
+    pub fn new(delay: Duration) -> Self {
+        Self {
+            delay,
+            pending: VecDeque::new(),
+            commentator: None,
+        }
+    }
+
+    /// The same feed, narrating the turns (see narrate) rather than passing on what the Table says.
+    pub fn with_commentary(self) -> Self {
+        Self {
+            commentator: Some(Commentator::default()),
+            ..self
+        }
+    }
+
+    /// How far behind the Game the feed runs.
+    pub fn delay(&self) -> Duration {
+        self.delay
+    }
+
+    /// Holds back the Messages said at `now` to everybody, the rest are dropped. A feed with
+    /// commentary drops them all.
+    pub fn record(&mut self, now: Instant, messages: &[Message]) {
+        if self.commentator.is_some() {
+            return;
+        }
+        for message in messages {
+            if message.to == Recipient::Everybody {
+                self.push(now, message.text.clone());
+            }
+        }
+    }
+
+    /// Holds back the narration of every Play of the Game made by `now` and not yet narrated, and
+    /// of its end once there is a result. Does nothing unless the feed has commentary.
+    pub fn narrate(&mut self, now: Instant, plays: &[Play], result: Option<&GameResult>) {
+        let Some(commentator) = &mut self.commentator else {
+            return;
+        };
+        for line in commentator.catch_up(plays, result) {
+            self.pending.push_back((now + self.delay, line));
+        }
+    }
+
+    /// Narrates the next Game from its start, e.g. after a rematch.
+    pub fn next_game(&mut self) {
+        if let Some(commentator) = &mut self.commentator {
+            *commentator = Commentator::default();
+        }
+    }
+
+    /// Holds back a line said at `now`.
+    pub fn push(&mut self, now: Instant, text: String) {
+        self.pending.push_back((now + self.delay, text));
+    }
+
+    /// Takes every line that may be shown at `now`, oldest first.
+    pub fn due(&mut self, now: Instant) -> Vec<String> {
+        let mut due = vec![];
+        while self.next_due().is_some_and(|at| at <= now) {
+            due.extend(self.pending.pop_front().map(|(_, text)| text));
+        }
+        due
+    }
+
+    /// When the next line may be shown, None if nothing is being held back.
+    pub fn next_due(&self) -> Option<Instant> {
+        self.pending.front().map(|(at, _)| *at)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn message(to: Recipient, text: &str) -> Message {
+        Message {
+            to,
+            text: String::from(text),
+            seq: 0,
+        }
+    }
+
+    #[test]
+    fn test_spectators_are_held_back() {
+        let mut feed = SpectatorFeed::new(Duration::from_secs(30));
+        let start = Instant::now();
+        let at = |seconds: u64| start + Duration::from_secs(seconds);
+        feed.record(
+            at(0),
+            &[
+                message(Recipient::Everybody, "Player 0 played 3C"),
+                message(Recipient::Seat(1), "Your cards: 4D 9S"),
+            ],
+        );
+        feed.record(at(10), &[message(Recipient::Everybody, "Player 1 passed")]);
+        assert!(feed.due(at(29)).is_empty(), "nothing is shown live");
+        assert_eq!(feed.next_due(), Some(at(30)));
+        assert_eq!(feed.due(at(35)), ["Player 0 played 3C"]);
+        assert_eq!(
+            feed.due(at(45)),
+            ["Player 1 passed"],
+            "never a seat's cards"
+        );
+        assert_eq!(feed.next_due(), None);
+
+        let mut live = SpectatorFeed::new(Duration::ZERO);
+        live.push(at(0), String::from("Game Over"));
+        assert_eq!(live.due(at(0)), ["Game Over"]);
+    }
+
+    #[test]
+    fn test_commentary() {
+        let mut feed = SpectatorFeed::new(Duration::from_secs(30)).with_commentary();
+        let start = Instant::now();
+        let at = |seconds: u64| start + Duration::from_secs(seconds);
+        let plays = [
+            Play::now(0, "3C".parse().unwrap(), 0),
+            Play::now(1, "".parse().unwrap(), 0),
+        ];
+        feed.record(
+            at(0),
+            &[message(Recipient::Everybody, "Player 0 played 3C")],
+        );
+        feed.narrate(at(0), &plays[..1], None);
+        feed.narrate(at(10), &plays, None);
+        assert_eq!(
+            feed.due(at(45)),
+            ["Player 0 leads with the three of clubs", "Player 1 passes"]
+        );
+
+        feed.next_game();
+        feed.narrate(at(50), &plays[..1], None);
+        assert_eq!(feed.due(at(80)), ["Player 0 leads with the three of clubs"]);
+
+        // without commentary, narrating does nothing
+        let mut plain = SpectatorFeed::new(Duration::ZERO);
+        plain.narrate(at(0), &plays, None);
+        assert_eq!(plain.next_due(), None);
+    }
+}
@@ -0,0 +1,258 @@
+//! Keeps track of how each player is doing across many Games, including notable feats
+//! (Achievements) earned along the way.
+
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::audit::GameId;
+use crate::constants::CARDS_PER_PLAYER;
+use crate::game::GameResult;
+use crate::hand::Hand;
+use crate::play::PassReason;
+
+/// The number of cards dealt to each player, an opponent still holding all of them was shut out.
+const SHUT_OUT_CARD_COUNT: usize = CARDS_PER_PLAYER;
+
+/// A notable feat performed during a Game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Achievement {
+    /// Won the Game by being dealt a Dragon.
+    WonWithDragon,
+
+    /// Played a Straight-Flush.
+    PlayedStraightFlush,
+
+    /// Won the Game while an opponent never got to play a single card.
+    ShutOutOpponent,
+}
+
+impl Display for Achievement {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            Self::WonWithDragon => write!(f, "won with a Dragon"),
+            Self::PlayedStraightFlush => write!(f, "played a Straight-Flush"),
+            Self::ShutOutOpponent => write!(f, "shut out an opponent at 13 cards"),
+        }
+    }
+}
+
+/// An Achievement earned by a specific player.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EarnedAchievement {
+    pub player_id: usize,
+    pub achievement: Achievement,
+}
+
+/// Finds every Achievement earned during a Game, from its result and every Play made.
+pub fn find_achievements(result: &GameResult) -> Vec<EarnedAchievement> {
+    let mut earned = vec![];
+
+    if result.won_by_dragon {
+        earned.push(EarnedAchievement {
+            player_id: result.winner,
+            achievement: Achievement::WonWithDragon,
+        });
+    }
+
+    for play in &result.plays {
+        if let Hand::StraightFlush(..) = play.hand {
+            earned.push(EarnedAchievement {
+                player_id: play.player_id,
+                achievement: Achievement::PlayedStraightFlush,
+            });
+        }
+    }
+
+    if !result.won_by_dragon {
+        let scores = &result.scores;
+        for (cards_left, penalty) in scores.cards_left.iter().zip(&scores.penalties) {
+            // a dead seat in heads-up play never plays, so is not shut out
+            if *cards_left == SHUT_OUT_CARD_COUNT && *penalty > 0 {
+                earned.push(EarnedAchievement {
+                    player_id: result.winner,
+                    achievement: Achievement::ShutOutOpponent,
+                });
+            }
+        }
+    }
+
+    earned
+}
+
+/// How a single player is doing across many Games.
+/// Serializable so it can be stored alongside a player profile.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlayerStats {
+    pub games_played: usize,
+    pub games_won: usize,
+    pub total_points: i64,
+
+    /// How many times each Achievement has been earned.
+    pub achievements: BTreeMap<Achievement, usize>,
+
+    /// Every Game recorded, in order.
+    #[serde(default)]
+    pub games: Vec<GameId>,
+
+    /// The thinking time charged to this player's clocks, after any latency allowance.
+    #[serde(default)]
+    pub thinking_time: Duration,
+
+    /// The wall-clock time this player spent thinking, including any time their moves spent in
+    /// transit.
+    #[serde(default)]
+    pub raw_thinking_time: Duration,
+
+    /// How many times this player passed holding nothing they could play.
+    #[serde(default)]
+    pub forced_passes: usize,
+
+    /// How many times this player passed while they could have played something.
+    #[serde(default)]
+    pub voluntary_passes: usize,
+}
+
+impl PlayerStats {
+    /// Adds the outcome of a Game to this player's stats, player_id is where they were seated.
+    pub fn record(&mut self, player_id: usize, result: &GameResult) {
+        self.games_played += 1;
+        self.games.push(result.meta.id);
+        if result.winner == player_id && !result.is_draw() {
+            self.games_won += 1;
+        }
+        self.total_points += result.scores.points[player_id] as i64;
+        let thinking_time = result.thinking_times.get(player_id).copied();
+        self.thinking_time += thinking_time.unwrap_or_default();
+        // results recorded before raw times were kept had no allowance
+        self.raw_thinking_time += result
+            .raw_thinking_times
+            .get(player_id)
+            .copied()
+            .or(thinking_time)
+            .unwrap_or_default();
+        for play in result
+            .plays
+            .iter()
+            .filter(|play| play.player_id == player_id)
+        {
+            match play.pass_reason {
+                Some(PassReason::Forced) => self.forced_passes += 1,
+                Some(PassReason::Voluntary) => self.voluntary_passes += 1,
+                None => {}
+            }
+        }
+        for earned in &result.achievements {
+            if earned.player_id == player_id {
+                *self.achievements.entry(earned.achievement).or_default() += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::config::GameConfig;
+    use crate::play::Play;
+    use crate::tests::test_util::vec_card_from_str;
+
+    #[test]
+    fn test_find_achievements() {
+        let config = GameConfig::default();
+        let shut_out = "3C 4C 5C 6C 7C 8C 9C TC JC QC KC AC 2C";
+        let remaining = ["", "3D", shut_out, "4D 5D"].map(vec_card_from_str);
+        let mut result = GameResult::new(0, false, &remaining, &config);
+        result.plays = vec![
+            Play::now(0, "7S 6S 5S 4S 3S".parse().unwrap(), 0),
+            Play::now(1, "8S 7D 6D 5H 4H".parse().unwrap(), 0),
+            Play::now(2, Hand::Pass, 0),
+            Play::now(3, "KD QD JD TD 9D".parse().unwrap(), 0),
+            Play::now(0, "2S".parse().unwrap(), 1),
+        ];
+
+        let earned = find_achievements(&result);
+        assert_eq!(
+            earned,
+            vec![
+                EarnedAchievement {
+                    player_id: 0,
+                    achievement: Achievement::PlayedStraightFlush
+                },
+                EarnedAchievement {
+                    player_id: 3,
+                    achievement: Achievement::PlayedStraightFlush
+                },
+                EarnedAchievement {
+                    player_id: 0,
+                    achievement: Achievement::ShutOutOpponent
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dragon_is_not_a_shut_out() {
+        let config = GameConfig::default();
+        let thirteen = "3C 4C 5C 6C 7C 8C 9C TC JC QC KC AC 2C";
+        let remaining = [thirteen, thirteen, "", thirteen].map(vec_card_from_str);
+        let result = GameResult::new(2, true, &remaining, &config);
+        let earned = find_achievements(&result);
+        assert_eq!(
+            earned,
+            vec![EarnedAchievement {
+                player_id: 2,
+                achievement: Achievement::WonWithDragon
+            }]
+        );
+    }
+
+    #[test]
+    fn test_record() {
+        let config = GameConfig::default();
+        let remaining = ["", "3D", "4D 5D", "6D"].map(vec_card_from_str);
+        let mut result = GameResult::new(0, false, &remaining, &config);
+        result.achievements = vec![EarnedAchievement {
+            player_id: 0,
+            achievement: Achievement::PlayedStraightFlush,
+        }];
+
+        let mut stats = PlayerStats::default();
+        stats.record(0, &result);
+        stats.record(0, &result);
+        assert_eq!(stats.games_played, 2);
+        assert_eq!(stats.games_won, 2);
+        assert_eq!(stats.total_points, 8);
+        assert_eq!(stats.achievements[&Achievement::PlayedStraightFlush], 2);
+
+        let mut stats = PlayerStats::default();
+        stats.record(2, &result);
+        assert_eq!(stats.games_won, 0);
+        assert_eq!(stats.total_points, -2);
+        assert!(stats.achievements.is_empty());
+
+        // both thinking times add up, results without raw times count the charged one
+        result.thinking_times[1] = Duration::from_secs(4);
+        result.raw_thinking_times[1] = Duration::from_secs(5);
+        let mut stats = PlayerStats::default();
+        stats.record(1, &result);
+        result.raw_thinking_times.clear();
+        stats.record(1, &result);
+        assert_eq!(stats.thinking_time, Duration::from_secs(8));
+        assert_eq!(stats.raw_thinking_time, Duration::from_secs(9));
+
+        // passes are counted by reason, those recorded without one are not counted
+        result.plays = vec![
+            Play::now(1, Hand::Pass, 0).with_pass_reason(Some(PassReason::Forced)),
+            Play::now(1, Hand::Pass, 1).with_pass_reason(Some(PassReason::Voluntary)),
+            Play::now(1, Hand::Pass, 2),
+            Play::now(2, Hand::Pass, 2).with_pass_reason(Some(PassReason::Forced)),
+        ];
+        let mut stats = PlayerStats::default();
+        stats.record(1, &result);
+        assert_eq!((stats.forced_passes, stats.voluntary_passes), (1, 1));
+    }
+}
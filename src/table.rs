@@ -0,0 +1,1515 @@
+//! A Table runs a Game one message at a time, for front ends that cannot block on stdin, e.g. a
+//! chat bot or a server. Each seat is either a person, who sends lines of text (the same card
+//! strings as the local CLI, "pass", "cards", "hands", "sync", "since <seq>", "client <name>",
+//! "peek <seat>" or "help"), or the standard AI, which plays as soon as it is their turn.
+//! Everything the Table has to say comes back as Messages, addressed either to everybody or to a
+//! single seat, so no terminal is assumed.
+//!
+//! The Game is played by the same engine as a local Game (see trick::Trick), one turn at a time,
+//! and the cards are dealt as they are for a local Game too (see game::deal_cards). Whatever the
+//! engine narrates while dealing is said to everybody once the Table starts. A person dealt a
+//! hopeless hand calls for a redeal, or not, as the AI in their seat would.
+//!
+//! Once the Game is over the people may send "ready", and once all of them have the same Table
+//! deals the next Game of the Match (see Table::rematch), carrying the scores so far.
+//!
+//! In practice (see rules::house::Practice) nothing is played until every person with a mulligan
+//! left has typed "keep", or "mulligan" to have the cards dealt again, as a local Game asks them
+//! before it begins.
+
+use std::collections::BTreeSet;
+use std::fmt::Display;
+use std::time::{Duration, Instant};
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use crate::audit::GameMeta;
+use crate::card::Card;
+use crate::config::GameConfig;
+use crate::exchange::{perform_exchange, Exchange};
+use crate::game::{
+    deal_again, deal_cards, find_opening_player, find_player_with_dragon, opening_announcement,
+    remaining_after_dragon, stalemate_announcement, trick_won_announcement, GameResult,
+};
+use crate::hand::Hand;
+use crate::match_play::MatchSummary;
+use crate::output::{self, Buffer, Output};
+use crate::play::{state_hash, PassReason, Play};
+use crate::player::{Player, TurnView};
+use crate::recap::find_key_moments;
+use crate::rules::{cheat_sheet::CheatSheet, validate::Rejection};
+use crate::sanity::InvariantError;
+use crate::scoring::captured_points;
+use crate::seats::Controller;
+use crate::stats::find_achievements;
+use crate::trick::{
+    ask_for_hand, is_out_but_playing_on, next_leader, Seat, StepStatus, Trick, NUM_PLAYERS,
+};
+use crate::views::{GameState, StateView, Viewer};
+
+/// Explains the commands a person may send to the Table.
+pub const HELP: &str = "Type the cards to play e.g. \"3C 3D 3S\", \"pass\" to pass, \
+                        \"cards\" to see your cards, \"hands\" to see which Hands beat which, \
+                        \"sync\" to catch up on the Game, \"ready\" for a rematch once it is over.";
+
+/// Who a Message is for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Recipient {
+    /// Everybody at the Table.
+    Everybody,
+
+    /// Only the person in this seat, e.g. their cards.
+    Seat(usize),
+}
+
+/// Something the Table has to say.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+    pub to: Recipient,
+    pub text: String,
+
+    /// Counts every Message the Table has said, from 1, so a client that lost track can ask for
+    /// everything after the last one it saw.
+    pub seq: u64,
+}
+
+/// A Game in progress, driven by text from the people sitting at it.
+pub struct Table {
+    config: GameConfig,
+    players: [Player; NUM_PLAYERS],
+
+    /// The seats played by people, the rest are played by the AI.
+    people: BTreeSet<usize>,
+
+    /// Every turn of the Game so far, and the Trick being played.
+    history: Vec<Play>,
+    trick: Trick,
+
+    /// What the engine narrated while dealing, said to everybody once the Table starts.
+    dealt: Vec<String>,
+
+    /// When the current player's turn began, their thinking time is charged when they play.
+    turn_started: Instant,
+
+    /// The current player's last attempt this turn, if it was turned down, see
+    /// TurnView::rejection.
+    rejection: Option<Rejection>,
+
+    /// Identifies the Game, recorded in its result.
+    meta: GameMeta,
+
+    /// Set once the Game is over.
+    result: Option<GameResult>,
+
+    /// Set if the Game broke an invariant (see GameConfig::check_invariants), nothing more is
+    /// played once it is.
+    invariant_error: Option<InvariantError>,
+
+    /// How many times each seat has peeked at an opponent's cards, see rules::house::Practice.
+    peeked: [usize; NUM_PLAYERS],
+
+    /// How many mulligans each seat has left, see rules::house::Practice.
+    mulligans: [usize; NUM_PLAYERS],
+
+    /// The people yet to keep their cards in practice, nothing is played until there are none.
+    undecided: BTreeSet<usize>,
+
+    /// Deals the cards again for a mulligan.
+    rng: StdRng,
+
+    /// The people ready for a rematch, once the Game is over.
+    ready: BTreeSet<usize>,
+
+    /// The results of the Games played at this Table before this one, see rematch.
+    earlier: Vec<GameResult>,
+
+    /// Messages waiting to be returned to the caller.
+    outbox: Vec<Message>,
+
+    /// Every Message said so far, in order, for clients catching up.
+    log: Vec<Message>,
+}
+
+/// Formats cards so that they can be copied straight back into a message, e.g. "3C 4D 4S".
+fn cards_to_text(cards: &[Card]) -> String {
+    cards
+        .iter()
+        .map(|card| card.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The players at a Table, as the AI plays them (as strongly as the config's seats say), before
+/// the cards are dealt.
+fn seat_players(config: &GameConfig) -> [Player; NUM_PLAYERS] {
+    let mut players = <[Player; NUM_PLAYERS]>::default();
+    for (seat, player) in players.iter_mut().enumerate() {
+        let seat_config = config.seat(seat);
+        player.convert_to_ai(seat_config.ai_level, seat_config.aggressiveness);
+        player.time_left = config.time_bank;
+        player.time_budget = config.ai_time_budget;
+    }
+    players
+}
+
+/// Runs part of the engine that narrates (see output::current), and returns what it said.
+fn narrated(run: impl FnOnce()) -> Vec<String> {
+    let narration = Buffer::default();
+    {
+        let _output = output::install(Output::to(narration.clone()));
+        run();
+    }
+    narration.contents().lines().map(String::from).collect()
+}
+
+/// The first Trick of a Game, led by whoever was dealt the opening card.
+fn opening_trick(config: &GameConfig, players: &[Player; NUM_PLAYERS]) -> Trick {
+    let opening_player = Seat::new(find_opening_player(players, &config.rules), NUM_PLAYERS)
+        .expect("the opening player is seated");
+    Trick::new(
+        opening_player,
+        players,
+        &[],
+        &config.rules,
+        &config.house_rules,
+        config.check_invariants,
+    )
+}
+
+/// Understands a line of text as a Hand, cards may be in any order and any case.
+fn parse_attempt(text: &str) -> Result<Hand, String> {
+    if text.eq_ignore_ascii_case("pass") {
+        return Ok(Hand::Pass);
+    }
+    let mut cards = text
+        .split_whitespace()
+        .map(|card_str| {
+            card_str
+                .to_uppercase()
+                .parse::<Card>()
+                .map_err(|e| format!("could not understand {card_str}, {e:?}"))
+        })
+        .collect::<Result<Vec<Card>, String>>()?;
+    cards.sort();
+    cards.reverse();
+    Hand::try_from_cards(&cards).map_err(|e| format!("invalid hand {e:?}"))
+}
+
+impl Table {
+    /// Deals a new Game, the given seats are played by people and the rest by the AI (as strongly
+    /// as the config's seats say). Nothing is played until start is called.
+    ///
+    /// # Panics
+    /// - If a seat is not at the Table.
+    pub fn new(config: &GameConfig, people: &[usize]) -> Self {
+        Self::deal(config, people, None)
+    }
+
+    /// Deals a new Game as new does, then exchanges cards as the last Game of the Match left
+    /// owing, if it did (see exchange::Exchange::after). A person who won the last Game gives back
+    /// the cards the AI in their seat would.
+    fn deal(config: &GameConfig, people: &[usize], exchange: Option<&Exchange>) -> Self {
+        let mut players = seat_players(config);
+        let seed = config.seed.unwrap_or_else(rand::random);
+        let mut rng = StdRng::seed_from_u64(seed);
+        let dealt = narrated(|| {
+            deal_cards(&mut players, &config.rules, &mut rng);
+            if let Some(exchange) = exchange {
+                perform_exchange(&mut players, exchange);
+            }
+        });
+        let mut table = Self::seat(config, people, players, rng);
+        table.meta.seed = Some(seed);
+        table.dealt = dealt;
+        table
+    }
+
+    /// Seats a Game whose cards have already been dealt, e.g. to play the same deal again with
+    /// the players in different seats. Nothing is played until start is called.
+    ///
+    /// # Panics
+    /// - If a seat is not at the Table.
+    pub fn from_deal(
+        config: &GameConfig,
+        people: &[usize],
+        deal: [Vec<Card>; NUM_PLAYERS],
+    ) -> Self {
+        let mut players = seat_players(config);
+        for (player, mut cards) in players.iter_mut().zip(deal) {
+            cards.sort();
+            player.cards = cards;
+        }
+        let rng = StdRng::seed_from_u64(config.seed.unwrap_or_else(rand::random));
+        Self::seat(config, people, players, rng)
+    }
+
+    /// Seats the players, once their cards are dealt.
+    ///
+    /// # Panics
+    /// - If a seat is not at the Table.
+    fn seat(
+        config: &GameConfig,
+        people: &[usize],
+        players: [Player; NUM_PLAYERS],
+        rng: StdRng,
+    ) -> Self {
+        assert!(
+            people.iter().all(|seat| *seat < NUM_PLAYERS),
+            "there are only {NUM_PLAYERS} seats"
+        );
+        let trick = opening_trick(config, &players);
+        let mulligans = std::array::from_fn(|seat| {
+            if people.contains(&seat) && !config.rules.is_dead_seat(seat) {
+                config.house_rules.mulligans()
+            } else {
+                0
+            }
+        });
+
+        Self {
+            config: config.clone(),
+            players,
+            people: people.iter().copied().collect(),
+            history: vec![],
+            trick,
+            dealt: vec![],
+            turn_started: Instant::now(),
+            rejection: None,
+            meta: GameMeta::new(config, config.seed),
+            result: None,
+            invariant_error: None,
+            peeked: [0; NUM_PLAYERS],
+            mulligans,
+            undecided: BTreeSet::new(),
+            rng,
+            ready: BTreeSet::new(),
+            earlier: vec![],
+            outbox: vec![],
+            log: vec![],
+        }
+    }
+
+    /// Shows everybody their cards and plays until it is a person's turn (or the Game is over),
+    /// once every person has kept their cards in practice.
+    pub fn start(&mut self) -> Vec<Message> {
+        self.say(
+            Recipient::Everybody,
+            format!("Playing {} rules. {HELP}", self.config.preset),
+        );
+        for line in std::mem::take(&mut self.dealt) {
+            self.say(Recipient::Everybody, line);
+        }
+        self.show_dealt_cards();
+        std::mem::take(&mut self.outbox)
+    }
+
+    /// Shows every person their cards, then either asks those with a mulligan left whether to keep
+    /// them or begins the Game.
+    fn show_dealt_cards(&mut self) {
+        for seat in self.people.clone() {
+            self.show_cards(seat);
+        }
+        self.undecided = self
+            .people
+            .iter()
+            .copied()
+            .filter(|seat| self.mulligans[*seat] > 0)
+            .collect();
+        if self.undecided.is_empty() {
+            self.begin();
+        }
+        for seat in self.undecided.clone() {
+            self.say(
+                Recipient::Seat(seat),
+                format!(
+                    "Practice: type \"keep\" to play these cards, or \"mulligan\" to have them \
+                     dealt again ({} left).",
+                    self.mulligans[seat]
+                ),
+            );
+        }
+    }
+
+    /// Plays until it is a person's turn (or the Game is over), unless a Dragon was dealt.
+    fn begin(&mut self) {
+        if self.config.rules.dragon_is_natural_win {
+            if let Some(winner) = find_player_with_dragon(&self.players, &self.config.rules) {
+                self.say(
+                    Recipient::Everybody,
+                    format!("Player {winner} was dealt a Dragon and wins immediately!!"),
+                );
+                let remaining = remaining_after_dragon(&self.players, winner);
+                self.finish(GameResult::new(winner, true, &remaining, &self.config));
+                return;
+            }
+        }
+
+        let opening_player = self.trick.current_player().index();
+        self.say(
+            Recipient::Everybody,
+            opening_announcement(opening_player, &self.players[opening_player]),
+        );
+        self.turn_started = Instant::now();
+        self.advance();
+    }
+
+    /// Handles a line from a person while the people decide whether to keep their cards in
+    /// practice.
+    fn decide(&mut self, seat: usize, text: &str) {
+        if !self.undecided.contains(&seat) {
+            self.say(
+                Recipient::Seat(seat),
+                "Waiting for everybody to keep their cards (practice).",
+            );
+        } else if text.eq_ignore_ascii_case("keep") {
+            self.undecided.remove(&seat);
+            self.say(
+                Recipient::Everybody,
+                format!("Player {seat} keeps their cards (practice)"),
+            );
+            if self.undecided.is_empty() {
+                self.begin();
+            }
+        } else if text.eq_ignore_ascii_case("mulligan") {
+            self.mulligans[seat] -= 1;
+            self.say(
+                Recipient::Everybody,
+                format!("Player {seat} takes a mulligan (practice), the cards are dealt again"),
+            );
+            let (players, rng) = (&mut self.players, &mut self.rng);
+            for line in narrated(|| deal_again(players, rng)) {
+                self.say(Recipient::Everybody, line);
+            }
+            self.trick = opening_trick(&self.config, &self.players);
+            self.show_dealt_cards();
+        } else {
+            self.say(
+                Recipient::Seat(seat),
+                "error: type \"keep\" to play these cards, or \"mulligan\" to have them dealt again",
+            );
+        }
+    }
+
+    /// Handles a line of text sent by the person in this seat, and returns everything the Table
+    /// has to say about it.
+    pub fn handle(&mut self, seat: usize, text: &str) -> Vec<Message> {
+        let text = text.trim();
+        if self.result.is_some() {
+            if self.people.contains(&seat) && text.eq_ignore_ascii_case("ready") {
+                self.get_ready(seat);
+            } else {
+                self.say(
+                    Recipient::Seat(seat),
+                    "The Game is over, type \"ready\" for a rematch.",
+                );
+            }
+        } else if !self.people.contains(&seat) {
+            self.say(Recipient::Seat(seat), "You are not sitting at this Table.");
+        } else if let Some(e) = self.invariant_error {
+            self.say(
+                Recipient::Seat(seat),
+                format!("{e}, the Game has been stopped"),
+            );
+        } else if text.eq_ignore_ascii_case("help") {
+            self.say(Recipient::Seat(seat), HELP);
+        } else if let Some(client) = text.strip_prefix("client ") {
+            self.meta.participants[seat].client = Some(client.trim().to_string());
+        } else if text.eq_ignore_ascii_case("hands") {
+            let sheet = CheatSheet::of(&self.config.rules, &self.config.house_rules);
+            for line in sheet.to_string().lines() {
+                self.say(Recipient::Seat(seat), line);
+            }
+        } else if text.eq_ignore_ascii_case("cards") {
+            self.show_cards(seat);
+        } else if let Some(peeked) = text.strip_prefix("peek ") {
+            match peeked.trim().parse() {
+                Ok(peeked) => self.peek(seat, peeked),
+                Err(_) => self.say(
+                    Recipient::Seat(seat),
+                    format!("error: could not understand {peeked}, expected a seat"),
+                ),
+            }
+        } else if text.eq_ignore_ascii_case("sync") {
+            self.show_snapshot(seat);
+        } else if let Some(seq) = text.strip_prefix("since ") {
+            match seq.trim().parse() {
+                Ok(seq) => {
+                    // resent to the seat that asked, whoever they were first said to
+                    let missed = self.events_since(seq, Recipient::Seat(seat));
+                    self.outbox
+                        .extend(missed.into_iter().map(|message| Message {
+                            to: Recipient::Seat(seat),
+                            ..message
+                        }));
+                }
+                Err(_) => self.say(
+                    Recipient::Seat(seat),
+                    format!("error: could not understand {seq}, expected a number"),
+                ),
+            }
+        } else if !self.undecided.is_empty() {
+            self.decide(seat, text);
+        } else if self.trick.current_player() != seat {
+            self.say(
+                Recipient::Seat(seat),
+                format!("It is Player {}'s turn.", self.trick.current_player()),
+            );
+        } else {
+            let current_player = self.current_player();
+            match parse_attempt(text).and_then(|attempt| self.try_play(current_player, attempt)) {
+                Ok(()) => self.advance(),
+                Err(e) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!(seat, text, error = %e, "attempt rejected");
+                    self.say(Recipient::Seat(seat), format!("error: {e}"))
+                }
+            }
+        }
+        std::mem::take(&mut self.outbox)
+    }
+
+    /// Plays a Hand for the person in this seat, as handle does for typed text, for callers that
+    /// already hold a Hand. Returns what is wrong with it if it cannot be played.
+    pub fn play_hand(&mut self, seat: usize, hand: Hand) -> Result<Vec<Message>, String> {
+        if self.result.is_some() {
+            return Err(String::from("The Game is over."));
+        }
+        if !self.people.contains(&seat) {
+            return Err(String::from("You are not sitting at this Table."));
+        }
+        if let Some(e) = self.invariant_error {
+            return Err(format!("{e}, the Game has been stopped"));
+        }
+        if !self.undecided.is_empty() {
+            return Err(String::from(
+                "Waiting for everybody to keep their cards (practice).",
+            ));
+        }
+        if self.trick.current_player() != seat {
+            return Err(format!(
+                "It is Player {}'s turn.",
+                self.trick.current_player()
+            ));
+        }
+        self.try_play(self.current_player(), hand)?;
+        self.advance();
+        Ok(std::mem::take(&mut self.outbox))
+    }
+
+    /// The player whose turn it is.
+    pub fn current_player(&self) -> Seat {
+        self.trick.current_player()
+    }
+
+    /// The Hand the current player must beat, Pass if they are starting a new Trick.
+    pub fn hand_to_beat(&self) -> Hand {
+        self.trick.hand_to_beat(&self.history)
+    }
+
+    /// The cards held by the player in this seat.
+    pub fn cards(&self, seat: usize) -> &[Card] {
+        &self.players[seat].cards
+    }
+
+    /// Everything the current player may see on their turn, with their legal plays worked out.
+    pub fn turn_view(&self) -> TurnView<'_> {
+        self.trick
+            .turn_view(&self.players, &self.history, self.rejection)
+    }
+
+    /// Identifies the public state of the Game, every turn so far, see play::next_state_hash.
+    pub fn state_hash(&self) -> u64 {
+        state_hash(self.history.last())
+    }
+
+    /// The seq of the last Message said, 0 if nothing has been said yet.
+    pub fn last_seq(&self) -> u64 {
+        self.log.len() as u64
+    }
+
+    /// Every Message said after seq that the recipient was sent, i.e. those for everybody, and
+    /// (for a seat) those for that seat, so a reconnecting client can catch up.
+    pub fn events_since(&self, seq: u64, recipient: Recipient) -> Vec<Message> {
+        self.log
+            .iter()
+            .skip(seq as usize)
+            .filter(|message| message.to == Recipient::Everybody || message.to == recipient)
+            .cloned()
+            .collect()
+    }
+
+    /// The full state of the Game, including everybody's cards, see views::GameState::view.
+    pub fn state(&self) -> GameState<'_> {
+        GameState {
+            cards: self
+                .players
+                .iter()
+                .map(|player| player.cards.as_slice())
+                .collect(),
+            plays: &self.history,
+            current_player: self.current_player().index(),
+            hand_to_beat: self.hand_to_beat(),
+            winner: self.result.as_ref().map(|result| result.winner),
+        }
+    }
+
+    /// What the viewer may see of the Game.
+    pub fn view(&self, viewer: Viewer) -> StateView {
+        self.state().view(viewer)
+    }
+
+    /// Every turn of the Game so far.
+    pub fn plays(&self) -> &[Play] {
+        &self.history
+    }
+
+    /// Identifies the Game and what it is played with, see audit.
+    pub fn meta(&self) -> &GameMeta {
+        &self.meta
+    }
+
+    /// The GameResult, once the Game is over.
+    pub fn result(&self) -> Option<&GameResult> {
+        self.result.as_ref()
+    }
+
+    /// Hands a person's seat to the AI (e.g. they were kicked), which plays straight away if it is
+    /// their turn, and returns everything the Table has to say about it.
+    pub fn replace_with_ai(&mut self, seat: Seat) -> Vec<Message> {
+        if self.people.remove(&seat.index()) {
+            self.ready.remove(&seat.index());
+            self.meta.participants[seat.index()].controller = Controller::Ai;
+            self.say(
+                Recipient::Everybody,
+                format!("Player {seat} is played by the AI from now on"),
+            );
+            if self.undecided.remove(&seat.index()) {
+                if self.undecided.is_empty() {
+                    self.begin();
+                }
+            } else if self.result.is_none()
+                && self.undecided.is_empty()
+                && self.current_player() == seat
+            {
+                self.advance();
+            }
+        }
+        std::mem::take(&mut self.outbox)
+    }
+
+    /// Takes this much off each of the seat's turns before it is charged to their clock, to make
+    /// up for a slow connection (see latency::Heartbeat).
+    pub fn set_latency_allowance(&mut self, seat: usize, allowance: Duration) {
+        self.players[seat].latency_allowance = allowance;
+    }
+
+    /// Returns true once the Game is over and every person is ready for the next one.
+    pub fn rematch_agreed(&self) -> bool {
+        self.result.is_some() && self.ready == self.people
+    }
+
+    /// Deals the next Game of the Match, with the same people and rules (but a new seed), once
+    /// everybody has agreed to a rematch, and exchanges cards first if the Rules say so. The scores
+    /// so far and every Message said are carried over, so clients can keep asking for everything
+    /// "since <seq>". Nothing is played until start is called.
+    pub fn rematch(&self) -> Option<Table> {
+        if !self.rematch_agreed() {
+            return None;
+        }
+        let config = GameConfig {
+            seed: None,
+            ..self.config.clone()
+        };
+        let people: Vec<usize> = self.people.iter().copied().collect();
+        let exchange = self
+            .result
+            .as_ref()
+            .and_then(|result| Exchange::after(result, &config.rules));
+        let mut table = Table::deal(&config, &people, exchange.as_ref());
+        table.earlier = self.match_results();
+        table.log = self.log.clone();
+        Some(table)
+    }
+
+    /// The results of every finished Game played at this Table, including this one.
+    pub fn match_results(&self) -> Vec<GameResult> {
+        let mut results = self.earlier.clone();
+        results.extend(self.result.clone());
+        results
+    }
+
+    /// The Match played at this Table so far.
+    pub fn match_summary(&self) -> MatchSummary {
+        MatchSummary::from_results(self.match_results())
+    }
+
+    fn get_ready(&mut self, seat: usize) {
+        self.ready.insert(seat);
+        self.say(
+            Recipient::Everybody,
+            format!(
+                "Player {seat} is ready for a rematch ({} of {})",
+                self.ready.len(),
+                self.people.len()
+            ),
+        );
+        if self.rematch_agreed() {
+            self.say(
+                Recipient::Everybody,
+                "Everybody is ready, dealing the next Game.",
+            );
+        }
+    }
+
+    fn say(&mut self, to: Recipient, text: impl Display) {
+        let message = Message {
+            to,
+            text: text.to_string(),
+            seq: self.log.len() as u64 + 1,
+        };
+        self.log.push(message.clone());
+        self.outbox.push(message);
+    }
+
+    /// Shows the seat an opponent's cards, if practice still allows them a peek.
+    fn peek(&mut self, seat: usize, peeked: usize) {
+        if self.peeked[seat] >= self.config.house_rules.peeks() {
+            self.say(
+                Recipient::Seat(seat),
+                "error: peeking is only allowed in practice, once per Game",
+            );
+        } else if peeked == seat || peeked >= NUM_PLAYERS || self.config.rules.is_dead_seat(peeked)
+        {
+            self.say(
+                Recipient::Seat(seat),
+                format!("error: there is no opponent in seat {peeked}"),
+            );
+        } else {
+            self.peeked[seat] += 1;
+            self.say(
+                Recipient::Everybody,
+                format!("Player {seat} peeks at Player {peeked}'s cards (practice)"),
+            );
+            let cards = cards_to_text(&self.players[peeked].cards);
+            self.say(
+                Recipient::Seat(seat),
+                format!("Player {peeked} holds {cards}"),
+            );
+        }
+    }
+
+    fn show_cards(&mut self, seat: usize) {
+        let cards = cards_to_text(&self.players[seat].cards);
+        self.say(Recipient::Seat(seat), format!("Your cards: {cards}"));
+    }
+
+    /// Sends the seat everything public about the Game so far, one line per Trick, for a client
+    /// whose state hash no longer matches.
+    fn show_snapshot(&mut self, seat: usize) {
+        let mut tricks: Vec<String> = vec![];
+        for play in &self.history {
+            let turn = match play.hand {
+                Hand::Pass => format!("Player {} passed", play.player_id),
+                hand => format!("Player {} played {hand}", play.player_id),
+            };
+            match tricks.get_mut(play.trick) {
+                Some(trick) => *trick += &format!(", {turn}"),
+                None => tricks.push(format!("Trick {}: {turn}", play.trick)),
+            }
+        }
+        for trick in tricks {
+            self.say(Recipient::Seat(seat), trick);
+        }
+        let card_counts: Vec<usize> = self.players.iter().map(|p| p.cards.len()).collect();
+        self.say(
+            Recipient::Seat(seat),
+            format!(
+                "Sync {:016x} after {} turns, cards left {card_counts:?}, Player {} to play.",
+                self.state_hash(),
+                self.history.len(),
+                self.trick.current_player()
+            ),
+        );
+        self.show_cards(seat);
+    }
+
+    /// Plays every AI turn (and the turns of people who have run out of time) until it is a
+    /// person's turn, then asks them to play.
+    fn advance(&mut self) {
+        while self.result.is_none()
+            && self.invariant_error.is_none()
+            && (!self.people.contains(&self.current_player().index())
+                || self.players[self.current_player().index()].is_flagged())
+        {
+            let seat = self.current_player();
+            let attempt = ask_for_hand(&self.players[seat.index()], &self.turn_view());
+            self.try_play(seat, attempt)
+                .expect("the AI only attempts playable hands");
+        }
+
+        if self.result.is_none() && self.invariant_error.is_none() {
+            let seat = self.current_player().index();
+            let hand_to_beat = self.hand_to_beat();
+            let prompt = if !matches!(hand_to_beat, Hand::Pass) {
+                format!("Your turn, beat {hand_to_beat} or pass.")
+            } else if self.trick.must_play_three_of_clubs(&self.history) {
+                format!(
+                    "Your turn, start the game using the {}.",
+                    self.players[seat].cards[0]
+                )
+            } else {
+                String::from("Your turn, start the trick by playing any valid hand.")
+            };
+            self.say(Recipient::Seat(seat), prompt);
+            self.show_cards(seat);
+        }
+    }
+
+    /// Checks that the current seat may play the attempt, then plays it.
+    fn try_play(&mut self, seat: Seat, attempt: Hand) -> Result<(), String> {
+        assert_eq!(
+            self.current_player(),
+            seat,
+            "only the current player may play"
+        );
+        let view = self.turn_view();
+        if let Err(error) = self.config.house_rules.check_play(&view, &attempt) {
+            self.rejection = Some(Rejection { attempt, error });
+            return Err(error.to_string());
+        }
+        let pass_reason = matches!(attempt, Hand::Pass).then(|| view.pass_reason());
+        self.play(seat, attempt, pass_reason);
+        Ok(())
+    }
+
+    /// The invariant the Game broke, if GameConfig::check_invariants is set and it broke one.
+    pub fn invariant_error(&self) -> Option<&InvariantError> {
+        self.invariant_error.as_ref()
+    }
+
+    /// Plays a Hand known to be playable as the engine does (see trick::Trick::play), and says
+    /// what happened. The invariants are checked after the turn if the config asks for it, a
+    /// broken one is announced and kept, and stops the Game.
+    fn play(&mut self, seat: Seat, hand: Hand, pass_reason: Option<PassReason>) {
+        if self.players[seat.index()].record_thinking_time(self.turn_started.elapsed()) {
+            #[cfg(feature = "tracing")]
+            tracing::info!(%seat, "flagged");
+            self.say(
+                Recipient::Everybody,
+                format!("Player {seat} has run out of time and will pass from now on"),
+            );
+        }
+        self.turn_started = Instant::now();
+        self.rejection = None;
+        let trick_status = self
+            .trick
+            .play(&mut self.players, &mut self.history, hand, pass_reason);
+        #[cfg(feature = "tracing")]
+        tracing::debug!(%seat, %hand, trick = self.history[self.history.len() - 1].trick, "turn");
+
+        if let Hand::Pass = hand {
+            self.say(Recipient::Everybody, format!("Player {seat} passed"));
+        } else {
+            self.say(Recipient::Everybody, format!("Player {seat} played {hand}"));
+            if is_out_but_playing_on(seat.index(), &self.players, &self.config.rules) {
+                self.say(
+                    Recipient::Everybody,
+                    format!("Player {seat} is out, their partner plays on"),
+                );
+            }
+        }
+
+        if let Err(e) = self
+            .trick
+            .check_invariants(&self.players, &self.history, trick_status)
+        {
+            #[cfg(feature = "tracing")]
+            tracing::error!(error = %e, "invariant broken");
+            self.say(
+                Recipient::Everybody,
+                format!("{e}, the Game has been stopped"),
+            );
+            self.invariant_error = Some(e);
+            return;
+        }
+        match trick_status {
+            StepStatus::Continue => {}
+            StepStatus::TrickOver(winner) => self.end_trick(winner),
+            StepStatus::GameOver(winner) => {
+                self.trick.close(winner, &mut self.players, &self.history);
+                self.say(
+                    Recipient::Everybody,
+                    format!("Game Over, Player {winner} wins!!"),
+                );
+                let result =
+                    GameResult::new(winner.index(), false, &self.remaining(), &self.config);
+                self.finish(result);
+            }
+        }
+    }
+
+    /// Closes the current Trick and starts the next one, led by its winner (or their partner, if
+    /// they went out winning it), unless the Game has reached the turn cap.
+    fn end_trick(&mut self, winner: Seat) {
+        #[cfg(feature = "tracing")]
+        tracing::info!(index = self.history[self.history.len() - 1].trick, %winner, "trick won");
+        let leader = next_leader(winner, &self.players, &self.config.rules);
+        let announcement = match self
+            .config
+            .house_rules
+            .ends_trick(self.trick.plays(&self.history))
+        {
+            true => {
+                format!("Player {winner} wins the trick (by house rule) and starts the next trick")
+            }
+            false => trick_won_announcement(winner.index(), leader.index()),
+        };
+        self.say(Recipient::Everybody, announcement);
+        self.trick.close(winner, &mut self.players, &self.history);
+        if self.config.reached_turn_cap(self.history.len()) {
+            let result =
+                GameResult::stalemated(self.config.stalemate, &self.remaining(), &self.config);
+            self.say(Recipient::Everybody, stalemate_announcement(&result));
+            self.finish(result);
+            return;
+        }
+        self.trick = Trick::new(
+            leader,
+            &self.players,
+            &self.history,
+            &self.config.rules,
+            &self.config.house_rules,
+            self.config.check_invariants,
+        );
+    }
+
+    /// The cards everybody is still holding.
+    fn remaining(&self) -> Vec<Vec<Card>> {
+        self.players
+            .iter()
+            .map(|player| player.cards.clone())
+            .collect()
+    }
+
+    /// Records every Play, totals up the clocks and captured points, announces the scores and
+    /// achievements, and remembers the GameResult.
+    fn finish(&mut self, mut result: GameResult) {
+        result.meta = self.meta.clone();
+        result.thinking_times = self
+            .players
+            .iter()
+            .map(|player| player.thinking_time)
+            .collect();
+        result.raw_thinking_times = self
+            .players
+            .iter()
+            .map(|player| player.raw_thinking_time)
+            .collect();
+        #[cfg(feature = "tracing")]
+        tracing::info!(winner = result.winner, "game over");
+        result.plays = self.history.clone();
+        result.captured_points = self
+            .players
+            .iter()
+            .map(|player| captured_points(&player.captured, &self.config.rules))
+            .collect();
+        result.key_moments = find_key_moments(&result.plays);
+        result.achievements = find_achievements(&result);
+        for earned in &result.achievements {
+            self.say(
+                Recipient::Everybody,
+                format!("Player {} {}!", earned.player_id, earned.achievement),
+            );
+        }
+        self.say(
+            Recipient::Everybody,
+            format!("Scores: {:?}", result.scores.points),
+        );
+        if let Some(rule) = self.config.rules.point_cards {
+            self.say(
+                Recipient::Everybody,
+                format!("Points captured ({rule}): {:?}", result.captured_points),
+            );
+        }
+        self.result = Some(result);
+        if !self.earlier.is_empty() {
+            let summary = self.match_summary();
+            self.say(
+                Recipient::Everybody,
+                format!(
+                    "Match totals after {} Games: {:?}",
+                    summary.results.len(),
+                    summary.totals
+                ),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::card::rank::Rank;
+    use crate::constants::CARDS_PER_PLAYER;
+    use crate::play::find_desync;
+    use crate::player::AiLevel;
+    use crate::rules::house::{Practice, SevenResetsTheTrick};
+    use crate::rules::validate::PlayError;
+    use crate::rules::{PointCardRule, TeamPlay};
+    use crate::scoring::Stalemate;
+    use crate::tests::test_util::vec_card_from_str;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    fn seeded(seed: u64) -> GameConfig {
+        GameConfig {
+            seed: Some(seed),
+            ..GameConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_ai_only_table() {
+        let mut table = Table::new(&seeded(1), &[]);
+        let messages = table.start();
+        let result = table.result().unwrap();
+        assert!(table.cards(result.winner).is_empty());
+        assert!(messages.iter().all(|m| m.to == Recipient::Everybody));
+        assert!(messages.last().unwrap().text.starts_with("Scores"));
+    }
+
+    #[test]
+    fn test_turn_cap() {
+        let config = GameConfig {
+            max_turns: Some(1),
+            stalemate: Stalemate::FewestCards,
+            ..seeded(1)
+        };
+        let mut table = Table::new(&config, &[]);
+        let messages = table.start();
+        let result = table.result().unwrap();
+        assert_eq!(result.stalemate, Some(Stalemate::FewestCards));
+        assert!(!table.cards(result.winner).is_empty());
+        assert!(messages
+            .iter()
+            .any(|m| m.text.contains("the turn cap was reached")));
+    }
+
+    #[test]
+    fn test_redeal() {
+        // anybody without a Two calls for a redeal, until everybody holds one
+        let mut config = seeded(1);
+        config.rules.redeal_threshold = Some(Rank::Ace);
+        let mut table = Table::new(&config, &[0]);
+        assert!((0..NUM_PLAYERS).all(|seat| table.cards(seat).last().unwrap().rank == Rank::Two));
+        let messages = table.start();
+        assert!(messages[0].text.starts_with("Playing "));
+        assert_eq!(messages[1].text, "Dealing Cards...");
+        let redeal = messages
+            .iter()
+            .find(|m| m.text.ends_with("calls for a redeal"))
+            .unwrap();
+        assert_eq!(redeal.to, Recipient::Everybody);
+    }
+
+    #[test]
+    fn test_check_invariants() {
+        let config = GameConfig {
+            check_invariants: true,
+            ..seeded(4)
+        };
+        let mut table = Table::new(&config, &[]);
+        table.start();
+        assert!(table.result().is_some());
+        assert_eq!(table.invariant_error(), None);
+
+        // a card goes missing, which stops the Game at the first turn
+        let mut table = Table::new(&config, &[0]);
+        table.players[2].cards.pop();
+        let messages = table.start();
+        assert_eq!(
+            table.invariant_error(),
+            Some(&InvariantError::CardCount { count: 51 })
+        );
+        assert_eq!(table.plays().len(), 1);
+        assert!(table.result().is_none());
+        assert!(messages
+            .iter()
+            .any(|m| m.text.ends_with("the Game has been stopped")));
+        assert!(!messages.iter().any(|m| m.text.starts_with("Your turn")));
+        let messages = table.handle(0, "pass");
+        assert!(messages[0].text.ends_with("the Game has been stopped"));
+        assert!(table.play_hand(0, Hand::Pass).is_err());
+    }
+
+    #[test]
+    fn test_peek() {
+        let mut table = Table::new(&seeded(4), &[0]);
+        table.start();
+        let messages = table.handle(0, "peek 1");
+        assert!(messages[0]
+            .text
+            .starts_with("error: peeking is only allowed"));
+
+        let mut config = seeded(4);
+        config.house_rules.add(Arc::new(Practice));
+        let mut table = Table::new(&config, &[0]);
+        table.start();
+        assert_eq!(
+            table.handle(0, "peek 0")[0].text,
+            "error: there is no opponent in seat 0"
+        );
+        let messages = table.handle(0, "peek 2");
+        assert_eq!(messages[0].to, Recipient::Everybody);
+        assert_eq!(
+            messages[0].text,
+            "Player 0 peeks at Player 2's cards (practice)"
+        );
+        assert_eq!(messages[1].to, Recipient::Seat(0));
+        assert_eq!(
+            messages[1].text,
+            format!("Player 2 holds {}", cards_to_text(&table.players[2].cards))
+        );
+        assert!(table.handle(0, "peek 3")[0].text.starts_with("error:"));
+        assert!(table.meta().practice);
+    }
+
+    #[test]
+    fn test_mulligan() {
+        let mut config = seeded(4);
+        config.house_rules.add(Arc::new(Practice));
+        let mut table = Table::new(&config, &[0, 2]);
+        let dealt = table.cards(0).to_vec();
+        let messages = table.start();
+        let asked: Vec<Recipient> = messages
+            .iter()
+            .filter(|message| message.text.starts_with("Practice:"))
+            .map(|message| message.to)
+            .collect();
+        assert_eq!(asked, [Recipient::Seat(0), Recipient::Seat(2)]);
+        assert!(
+            table.history.is_empty(),
+            "nothing is played until the cards are kept"
+        );
+        assert!(table.handle(0, "3C")[0]
+            .text
+            .starts_with("error: type \"keep\""));
+        assert!(table.play_hand(0, Hand::Pass).is_err());
+
+        let messages = table.handle(2, "mulligan");
+        assert_eq!(
+            messages[0].text,
+            "Player 2 takes a mulligan (practice), the cards are dealt again"
+        );
+        assert_ne!(table.cards(0), dealt);
+        // seat 2 has no mulligan left, so only seat 0 is asked again
+        let asked: Vec<Recipient> = messages
+            .iter()
+            .filter(|message| message.text.starts_with("Practice:"))
+            .map(|message| message.to)
+            .collect();
+        assert_eq!(asked, [Recipient::Seat(0)]);
+        assert!(table.handle(2, "keep")[0].text.starts_with("Waiting"));
+
+        let messages = table.handle(0, "keep");
+        assert!(messages
+            .iter()
+            .any(|message| message.text.ends_with("and may begin")));
+        assert!(table.undecided.is_empty());
+    }
+
+    #[test]
+    fn test_point_cards_are_captured() {
+        let mut config = seeded(2);
+        config.rules.point_cards = Some(PointCardRule::FivesTensKings);
+        let mut table = Table::new(&config, &[]);
+        let messages = table.start();
+        let result = table.result().unwrap();
+        // every card played was captured by somebody
+        let played: u32 = table
+            .plays()
+            .iter()
+            .flat_map(|play| play.hand.cards())
+            .map(|card| PointCardRule::FivesTensKings.points(card))
+            .sum();
+        assert_eq!(result.captured_points.iter().sum::<u32>(), played);
+        assert!(messages.last().unwrap().text.starts_with("Points captured"));
+    }
+
+    #[test]
+    fn test_sync() {
+        let mut table = Table::new(&seeded(3), &[0]);
+        table.start();
+        let hash = table.state_hash();
+        assert_eq!(find_desync(table.plays()), None);
+        assert_eq!(hash, state_hash(table.plays().last()));
+
+        let messages = table.handle(0, "sync");
+        assert!(messages.iter().all(|m| m.to == Recipient::Seat(0)));
+        let summary = &messages[messages.len() - 2].text;
+        assert!(
+            summary.starts_with(&format!("Sync {hash:016x}")),
+            "{summary}"
+        );
+        // asking does not change the Game
+        assert_eq!(table.state_hash(), hash);
+
+        let view = table.view(Viewer::Seat(0));
+        assert_eq!(view.state_hash, hash);
+        assert_eq!(view.cards[0].as_deref(), Some(table.cards(0)));
+        assert!(view.cards[1..].iter().all(Option::is_none));
+    }
+
+    #[test]
+    fn test_events_since() {
+        let mut table = Table::new(&seeded(3), &[0, 2]);
+        let said = table.start();
+        assert!(said.windows(2).all(|pair| pair[1].seq == pair[0].seq + 1));
+        assert_eq!(said.last().unwrap().seq, table.last_seq());
+
+        // seat 0 sees what was said to everybody and to them, never to seat 2
+        let missed = table.events_since(0, Recipient::Seat(0));
+        assert!(missed.iter().all(|m| m.to != Recipient::Seat(2)));
+        let expected: Vec<Message> = said
+            .iter()
+            .filter(|m| m.to != Recipient::Seat(2))
+            .cloned()
+            .collect();
+        assert_eq!(missed, expected);
+        assert!(table
+            .events_since(table.last_seq(), Recipient::Everybody)
+            .is_empty());
+
+        // asking again is answered with the same Messages, only to the seat that asked, and
+        // nothing new is said
+        let last_seq = table.last_seq();
+        let resent = table.handle(0, "since 1");
+        let texts = |messages: &[Message]| {
+            messages
+                .iter()
+                .map(|m| (m.seq, m.text.clone()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(texts(&resent), texts(&expected[1..]));
+        assert!(resent.iter().all(|m| m.to == Recipient::Seat(0)));
+        assert_eq!(table.last_seq(), last_seq);
+    }
+
+    #[test]
+    fn test_house_rules() {
+        let mut config = seeded(4);
+        config.house_rules.add(Arc::new(SevenResetsTheTrick));
+        let mut table = Table::new(&config, &[]);
+        table.start();
+        assert!(table.result().is_some());
+        // every Seven ends its Trick, and its player leads the next one
+        let plays = table.plays();
+        let sevens: Vec<usize> = (0..plays.len() - 1)
+            .filter(|turn| {
+                plays[*turn]
+                    .hand
+                    .cards()
+                    .any(|card| card.rank == Rank::Seven)
+            })
+            .collect();
+        assert!(!sevens.is_empty());
+        for turn in sevens {
+            assert_eq!(plays[turn + 1].trick, plays[turn].trick + 1);
+            assert_eq!(plays[turn + 1].player_id, plays[turn].player_id);
+        }
+    }
+
+    #[test]
+    fn test_heads_up() {
+        for seed in 0..8 {
+            let mut config = seeded(seed);
+            config.rules.heads_up = true;
+            let mut table = Table::new(&config, &[]);
+            table.start();
+            let result = table.result().unwrap();
+            // the dead seats never play, and owe nothing
+            assert!(table.plays().iter().all(|play| play.player_id % 2 == 0));
+            assert_eq!(table.cards(1).len(), CARDS_PER_PLAYER);
+            assert_eq!(result.scores.penalties[1], 0);
+            assert_eq!(result.scores.penalties[3], 0);
+            // whoever opens plays the lowest live card
+            let lowest = [0, 2]
+                .map(|seat| *Table::new(&config, &[]).cards(seat).first().unwrap())
+                .into_iter()
+                .min()
+                .unwrap();
+            assert!(table.plays()[0].hand.cards().any(|card| *card == lowest));
+        }
+    }
+
+    #[test]
+    fn test_rematch() {
+        let deal = ["3C", "4C 9D", "5H 5S", "6D 8C"].map(vec_card_from_str);
+        let mut table = Table::from_deal(&seeded(0), &[0, 2], deal);
+        table.start();
+        table.handle(0, "3C");
+        assert!(table.result().is_some());
+        assert!(table.rematch().is_none(), "nobody is ready yet");
+
+        let messages = table.handle(0, "ready");
+        assert_eq!(messages[0].text, "Player 0 is ready for a rematch (1 of 2)");
+        table.handle(1, "ready");
+        table.handle(2, "pass");
+        assert!(!table.rematch_agreed(), "only the people sitting may vote");
+        let messages = table.handle(2, "ready");
+        assert_eq!(
+            messages.last().unwrap().text,
+            "Everybody is ready, dealing the next Game."
+        );
+
+        let mut rematch = table.rematch().unwrap();
+        assert_ne!(rematch.meta().id, table.meta().id);
+        assert_eq!(rematch.last_seq(), table.last_seq(), "the seqs carry on");
+        assert_eq!(rematch.cards(0).len(), CARDS_PER_PLAYER);
+        let messages = rematch.start();
+        assert_eq!(messages[0].seq, table.last_seq() + 1);
+        assert_eq!(rematch.match_results().len(), 1, "still playing");
+
+        // with nobody to wait for, the AI plays on straight away, carrying the scores
+        let mut table = Table::new(&seeded(1), &[]);
+        table.start();
+        let first = table.result().unwrap().scores.points.clone();
+        let mut rematch = table.rematch().unwrap();
+        let messages = rematch.start();
+        let summary = rematch.match_summary();
+        assert_eq!(summary.results.len(), 2);
+        let second = &summary.results[1].scores.points;
+        let totals: Vec<i32> = first.iter().zip(second).map(|(a, b)| a + b).collect();
+        assert_eq!(summary.totals, totals);
+        assert_eq!(
+            messages.last().unwrap().text,
+            format!("Match totals after 2 Games: {totals:?}")
+        );
+    }
+
+    #[test]
+    fn test_rematch_with_exchange() {
+        let mut config = seeded(1);
+        config.rules.exchange_cards = 2;
+        let mut table = Table::new(&config, &[]);
+        table.start();
+        let exchange = Exchange::after(table.result().unwrap(), &config.rules).unwrap();
+        let mut rematch = table.rematch().unwrap();
+        let messages = rematch.start();
+        let texts: Vec<&str> = messages.iter().map(|m| m.text.as_str()).collect();
+        let given = format!("Player {} gives their best ", exchange.loser);
+        let given_back = format!("Player {} gives back ", exchange.winner);
+        assert!(
+            texts.iter().any(|text| text.starts_with(&given)),
+            "{texts:#?}"
+        );
+        assert!(texts.iter().any(|text| text.starts_with(&given_back)));
+        assert_eq!(rematch.match_results().len(), 2);
+    }
+
+    #[test]
+    fn test_replace_with_ai() {
+        let mut table = Table::new(&seeded(5), &[0, 1, 2, 3]);
+        table.start();
+        let seat = table.current_player();
+        let messages = table.replace_with_ai(seat);
+        assert_eq!(
+            messages[0].text,
+            format!("Player {seat} is played by the AI from now on")
+        );
+        assert_ne!(table.current_player(), seat, "the AI played straight away");
+        assert_eq!(seat, table.plays()[0].player_id);
+        assert_eq!(
+            table.meta().participants[seat.index()].controller,
+            Controller::Ai
+        );
+        assert!(table.replace_with_ai(seat).is_empty(), "only once");
+    }
+
+    #[test]
+    fn test_custom_deal_without_three_of_clubs() {
+        let deal = ["4C 9D", "5H 5S", "6D 8C", "4D 7S"].map(vec_card_from_str);
+        let mut table = Table::from_deal(&seeded(0), &[0], deal);
+        assert_eq!(table.current_player(), 0);
+        let messages = table.start();
+        assert!(messages
+            .iter()
+            .any(|message| message.text == "Player 0 has the lowest card, 4C, and may begin"));
+
+        // the lowest card must be included
+        let error = table
+            .try_play(table.current_player(), "9D".parse().unwrap())
+            .unwrap_err();
+        assert_eq!(error, "Must play a hand that includes the 4C.");
+        table
+            .try_play(table.current_player(), "4C".parse().unwrap())
+            .unwrap();
+    }
+
+    #[test]
+    fn test_teams() {
+        for (teams, ai_level) in [
+            (TeamPlay::FirstOut, AiLevel::Easy),
+            (TeamPlay::BothOut, AiLevel::Easy),
+            (TeamPlay::BothOut, AiLevel::Hard),
+        ] {
+            let mut config = GameConfig {
+                ai_level,
+                ..seeded(2)
+            };
+            config.rules.teams = Some(teams);
+            let mut table = Table::new(&config, &[]);
+            table.start();
+            let result = table.result().unwrap();
+            let partner = (result.winner + 2) % NUM_PLAYERS;
+            assert!(table.cards(result.winner).is_empty());
+            assert_eq!(table.cards(partner).is_empty(), teams == TeamPlay::BothOut);
+            // the scores are pooled
+            let points = &result.scores.points;
+            assert_eq!(points[result.winner], points[partner]);
+            assert!(points[result.winner] > 0);
+            assert_eq!(points.iter().sum::<i32>(), 0);
+
+            // nobody beats their partner's Hand, unless it takes them out
+            let plays = table.plays();
+            for (turn, play) in plays.iter().enumerate() {
+                let beaten = plays[..turn]
+                    .iter()
+                    .rev()
+                    .take_while(|earlier| earlier.trick == play.trick)
+                    .find(|earlier| earlier.hand != Hand::Pass);
+                let goes_out = plays[turn + 1..]
+                    .iter()
+                    .all(|later| later.player_id != play.player_id);
+                if play.hand != Hand::Pass && !goes_out {
+                    assert_ne!(
+                        beaten.map(|earlier| (earlier.player_id + 2) % NUM_PLAYERS),
+                        Some(play.player_id)
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_hard_ai_table() {
+        let config = GameConfig {
+            ai_level: AiLevel::Hard,
+            ..seeded(1)
+        };
+        let mut table = Table::new(&config, &[]);
+        table.start();
+        let result = table.result().unwrap();
+        assert!(table.cards(result.winner).is_empty());
+        // every Trick is started with a Hand, never a Pass
+        let plays = table.plays();
+        assert_ne!(plays[0].hand, Hand::Pass);
+        assert!(plays
+            .windows(2)
+            .filter(|pair| pair[0].trick != pair[1].trick)
+            .all(|pair| pair[1].hand != Hand::Pass));
+    }
+
+    #[test]
+    fn test_person_plays_to_the_end() {
+        let mut table = Table::new(&seeded(2), &[0]);
+        let messages = table.start();
+        assert!(table.handle(0, "client test-bot/0.1").is_empty());
+        assert!(messages
+            .iter()
+            .any(|m| m.to == Recipient::Seat(0) && m.text.starts_with("Your cards")));
+
+        while table.result().is_none() {
+            assert_eq!(table.current_player(), 0);
+            let text = if let Hand::Pass = table.hand_to_beat() {
+                // always valid, the cards are sorted so the 3C comes first
+                table.cards(0)[0].to_string().to_lowercase()
+            } else {
+                String::from("PASS")
+            };
+            let messages = table.handle(0, &text);
+            assert!(!messages.iter().any(|m| m.text.starts_with("error")));
+        }
+        let result = table.result().unwrap();
+        assert!(table.cards(result.winner).is_empty());
+        assert_eq!(result.plays, table.plays());
+        let last = result.plays.last().unwrap();
+        assert_eq!(last.player_id, result.winner);
+        assert!(last.trick > 0);
+
+        // the result can be traced back to this Game
+        assert_eq!(result.meta.id, table.meta().id);
+        assert_eq!(result.meta.seed, Some(2));
+        let client = result.meta.participants[0].client.as_deref();
+        assert_eq!(client, Some("test-bot/0.1"));
+    }
+
+    #[test]
+    fn test_time_bank() {
+        let time_bank = Duration::from_millis(50);
+        let config = GameConfig {
+            time_bank: Some(time_bank),
+            ..seeded(2)
+        };
+        let mut table = Table::new(&config, &[0]);
+        table.start();
+        assert_eq!(table.current_player(), 0);
+
+        // the person thinks for too long, then the AI plays out their remaining turns
+        std::thread::sleep(time_bank);
+        let text = if let Hand::Pass = table.hand_to_beat() {
+            table.cards(0)[0].to_string()
+        } else {
+            String::from("pass")
+        };
+        let messages = table.handle(0, &text);
+        assert!(messages
+            .iter()
+            .any(|m| m.text == "Player 0 has run out of time and will pass from now on"));
+        let result = table.result().unwrap();
+        assert!(result.thinking_times[0] >= time_bank);
+        assert!(result.thinking_times[1] < time_bank);
+    }
+
+    #[test]
+    fn test_bad_messages() {
+        let mut table = Table::new(&seeded(3), &[0, 1, 2, 3]);
+        table.start();
+        let seat = table.current_player().index();
+        let other = (seat + 1) % NUM_PLAYERS;
+
+        let messages = table.handle(other, "3C");
+        assert_eq!(messages[0].to, Recipient::Seat(other));
+        assert!(messages[0].text.contains("turn"));
+
+        for text in ["pass", "ZZ", "3C 4D", "2S 2H 2D 2C"] {
+            let messages = table.handle(seat, text);
+            assert_eq!(messages.len(), 1, "{text}");
+            assert!(messages[0].text.starts_with("error"), "{text}");
+        }
+        assert_eq!(table.current_player(), seat);
+        // the last attempt that was a Hand is handed back with why it was turned down
+        let rejection = table.turn_view().rejection.unwrap();
+        assert_eq!(rejection.attempt, Hand::Pass);
+        assert_eq!(rejection.error, PlayError::MustLead);
+
+        let messages = table.handle(seat, "3c");
+        assert_eq!(messages[0].to, Recipient::Everybody);
+        assert_eq!(messages[0].text, format!("Player {seat} played 3C"));
+        assert_eq!(table.current_player(), other);
+        assert_eq!(table.turn_view().rejection, None);
+
+        let messages = table.handle(seat, "cards");
+        assert_eq!(messages[0].to, Recipient::Seat(seat));
+        let messages = table.handle(seat, "hands");
+        assert!(messages
+            .iter()
+            .all(|message| message.to == Recipient::Seat(seat)));
+        assert!(messages
+            .iter()
+            .any(|message| message.text.contains("Full House")));
+        assert_eq!(table.handle(9, "pass")[0].to, Recipient::Seat(9));
+    }
+}
@@ -1,11 +1,21 @@
 //! Utilities that are only useful for making unittest fixtures or assertions.
 
 use crate::card::Card;
+use crate::play::Play;
 
 /// Useful for making unittest fixtures, expects them to be correct (calls unwrap()).
 pub fn vec_card_from_str(input: &str) -> Vec<Card> {
     input
-        .split(' ')
+        .split_whitespace()
         .map(|x| x.parse().unwrap())
         .collect::<Vec<Card>>()
 }
+
+/// Useful for making unittest fixtures, a Play for each (player_id, hand, trick), where a Pass is
+/// "", expects them to be correct (calls unwrap()).
+pub fn plays(plays: &[(usize, &str, usize)]) -> Vec<Play> {
+    plays
+        .iter()
+        .map(|(player_id, hand, trick)| Play::now(*player_id, hand.parse().unwrap(), *trick))
+        .collect()
+}
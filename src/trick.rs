@@ -5,19 +5,23 @@
 //! The Caller is expected to keep track of the Players, provide the Players with Cards,
 //! and keep track of which player is supposed to begin the Trick.
 //!
-//! Use with is fn perform_trick(...) -> TrickResult
+//! Use with is fn perform_trick(...) -> TrickResult, which blocks on each Player's input. A front
+//! end that cannot block (see table::Table) steps through the Trick itself instead, one turn at a
+//! time: Trick::new, then Trick::turn_view and Trick::play for each turn, then Trick::close.
 
-mod check_player_can_play_hand;
-use check_player_can_play_hand::check_player_can_play_hand;
-
-mod next_player_id;
-use next_player_id::next_player_id;
+mod seat;
+pub use seat::{Seat, SeatError};
 
 use std::collections::BTreeSet;
+use std::time::Instant;
 
-use crate::card::THREE_OF_CLUBS;
+use crate::card::Card;
 use crate::hand::Hand;
-use crate::player::Player;
+use crate::output::current;
+use crate::play::{hand_to_beat, PassReason, Play};
+use crate::player::{Player, TurnView};
+use crate::rules::{house::HouseRules, validate::Rejection, Rules, StartRule};
+use crate::sanity::{check_invariants, InvariantError};
 
 /// There are many variations of this game with non-4 numbers of players, but for now we focus on
 /// the base game.
@@ -28,130 +32,311 @@ pub const NUM_PLAYERS: usize = 4;
 pub enum TrickResult {
     /// Informs the caller that this Trick ended without anybody winning the Game, so another Trick
     /// is needed.
-    NewTrick(usize),
+    NewTrick(Seat),
 
     /// Informs the caller that this Trick ended with somebody winning the Game.
-    GameOver(usize),
+    GameOver(Seat),
+
+    /// Informs the caller that the Trick was stopped, having broken an invariant (only checked if
+    /// asked to, see sanity).
+    Invalid(InvariantError),
 }
 
 /// Performs the entire Trick and returns the TrickResult.
 /// It is expected that the caller will keep calling this until it produces a TrickResult::GameOver.
 ///
 /// # Arguments
-/// - starting_player_idx: the caller is responsible for deciding which player must begin.
+/// - starting_player: the caller is responsible for deciding which player must begin.
 /// - players: the caller is responsible for keeping track of the players.
-/// - is_first_trick_of_game: the caller is responsible for knowing if this is the first trick or
-/// not (if this is the first then special 3 of Clubs logic will be used).
+/// - history: every Play of the Game so far, this Trick's Plays are appended (if it is empty then
+///   this is the first trick, and special 3 of Clubs logic will be used).
+/// - rules: the rules of the variant being played.
+/// - house_rules: any house rules in effect on top of them.
+/// - check_invariants: checks the invariants (see sanity) after every turn, stopping the Trick
+///   with TrickResult::Invalid if one is broken.
 ///
 /// # Panics
 /// - Will panic for any internal programming error which causes the Trick to enter an illogical /
-/// incoherent state.
+///   incoherent state.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(skip(players, history, rules, house_rules))
+)]
 pub fn perform_trick(
-    starting_player_idx: usize,
+    starting_player: Seat,
     players: &mut [Player; NUM_PLAYERS],
-    is_first_trick_of_game: bool,
+    history: &mut Vec<Play>,
+    rules: &Rules,
+    house_rules: &HouseRules,
+    check_invariants: bool,
 ) -> TrickResult {
-    let mut trick = Trick::start(starting_player_idx, players, is_first_trick_of_game);
-    trick.do_trick(players)
+    let mut trick = Trick::new(
+        starting_player,
+        players,
+        history,
+        rules,
+        house_rules,
+        check_invariants,
+    );
+    let trick_result = trick.do_trick(players, history);
+    let winner = match trick_result {
+        TrickResult::NewTrick(winner) | TrickResult::GameOver(winner) => winner,
+        TrickResult::Invalid(_) => return trick_result,
+    };
+    trick.close(winner, players, history);
+    #[cfg(feature = "tracing")]
+    match trick_result {
+        TrickResult::NewTrick(winner) => tracing::info!(index = trick.index, %winner, "trick won"),
+        TrickResult::GameOver(winner) => tracing::info!(index = trick.index, %winner, "game won"),
+        TrickResult::Invalid(_) => {}
+    }
+    trick_result
+}
+
+/// Builds everything the player may see on their turn, with their legal plays worked out.
+#[allow(clippy::too_many_arguments)]
+fn turn_view<'a>(
+    player_id: usize,
+    players: &'a [Player; NUM_PLAYERS],
+    hand_to_beat: Hand,
+    must_play_three_of_clubs: bool,
+    trick: &'a [Play],
+    history: &'a [Play],
+    rules: &Rules,
+    house_rules: &HouseRules,
+    rejection: Option<Rejection>,
+) -> TurnView<'a> {
+    let mut view = TurnView::new(&players[player_id].cards, hand_to_beat, NUM_PLAYERS);
+    view.player_id = player_id;
+    view.must_play_three_of_clubs = must_play_three_of_clubs;
+    view.trick = trick;
+    view.history = history;
+    view.card_counts = players.iter().map(|player| player.cards.len()).collect();
+    view.rules = *rules;
+    view.max_attempts = players[player_id].max_attempts;
+    view.rejection = rejection;
+    view.time_budget = players[player_id].decision_budget();
+    view.show_inferences = players[player_id].show_inferences;
+    view.show_odds = players[player_id].show_odds;
+    let legal = house_rules.legal_plays(&view);
+    view.with_legal_plays(legal)
+}
+
+/// Asks the Player for their attempt this turn: the first Hand of the Game, a Hand to start the
+/// Trick with, or a Hand that beats the Hand to beat (or a Pass).
+pub(crate) fn ask_for_hand(player: &Player, view: &TurnView) -> Hand {
+    if !matches!(view.hand_to_beat, Hand::Pass) {
+        (player.submit_hand)(view)
+    } else if view.must_play_three_of_clubs {
+        (player.start_game)(view)
+    } else {
+        (player.start_trick)(view)
+    }
+}
+
+/// Attributes every card played in a Trick to its winner, who captures them. The last Trick of the
+/// Game is captured by whoever went out.
+fn capture_trick(winner: &mut Player, plays: &[Play]) {
+    winner
+        .captured
+        .extend(plays.iter().flat_map(|play| play.hand.cards().copied()));
+}
+
+/// The seats that take no part in a Trick: the dead seats in heads-up play, and in partnerships,
+/// anybody who has already gone out.
+fn sitting_out(players: &[Player; NUM_PLAYERS], rules: &Rules) -> BTreeSet<usize> {
+    (0..NUM_PLAYERS)
+        .filter(|seat| rules.is_dead_seat(*seat) || is_out_but_playing_on(*seat, players, rules))
+        .collect()
+}
+
+/// Returns true if this player has gone out, but in partnerships their partner plays on, so
+/// they sit the rest of the Game out.
+pub(crate) fn is_out_but_playing_on(
+    player_id: usize,
+    players: &[Player; NUM_PLAYERS],
+    rules: &Rules,
+) -> bool {
+    let card_counts: Vec<usize> = players.iter().map(|player| player.cards.len()).collect();
+    card_counts[player_id] == 0 && !rules.ends_game(player_id, &card_counts)
+}
+
+/// Who leads the next Trick: its winner, unless they went out winning it, then their partner.
+pub(crate) fn next_leader(winner: Seat, players: &[Player; NUM_PLAYERS], rules: &Rules) -> Seat {
+    match rules.partner(winner.index()) {
+        Some(partner) if players[winner.index()].cards.is_empty() => {
+            Seat::new(partner, NUM_PLAYERS).expect("partners sit at the same Table")
+        }
+        _ => winner,
+    }
+}
+
+/// Returns true if the Player is in strict mode, and has had this many attempts rejected.
+fn out_of_attempts(player: &Player, rejected: usize) -> bool {
+    player.max_attempts.is_some_and(|max| rejected >= max)
+}
+
+/// Announces that the Player has given up (or run out of attempts), from now on they pass, or
+/// start tricks with their smallest card.
+fn forfeit(player_id: usize, player: &mut Player) {
+    if !player.forfeited {
+        #[cfg(feature = "tracing")]
+        tracing::info!(player_id, "forfeited");
+        writeln!(
+            current(),
+            "Player {player_id} forfeits and will pass from now on"
+        );
+    }
+    player.forfeit();
 }
 
-/// Represents the current state of a Trick, keeps track of which hands have been played and who
-/// has passed, and who is the current player.
+/// Announces that the Player's input has closed, and hands them to the AI for the rest of the Game.
+fn hand_to_ai(player_id: usize, player: &mut Player) {
+    #[cfg(feature = "tracing")]
+    tracing::info!(player_id, "input closed");
+    writeln!(
+        current(),
+        "Player {player_id}'s input has closed, the AI plays for them from now on"
+    );
+    player.hand_to_ai();
+}
+
+/// Charges the time since the turn started to the Player's clock, and announces if they have run
+/// out of time. A Hand submitted as time runs out still stands.
+fn charge_thinking_time(player_id: usize, player: &mut Player, started: Instant) {
+    if player.record_thinking_time(started.elapsed()) {
+        #[cfg(feature = "tracing")]
+        tracing::info!(player_id, "flagged");
+        writeln!(
+            current(),
+            "Player {player_id} has run out of time and will pass from now on"
+        );
+    }
+}
+
+/// Represents the current state of a Trick, keeps track of who has passed, and who is the current
+/// player. The Plays themselves are kept in the history of the Game, from start on.
 #[derive(Debug)]
-struct Trick {
-    /// The hand used to start the Trick, and all following played hands.
-    played_hands: Vec<Hand>,
+pub(crate) struct Trick {
+    /// Which Trick of the Game this is, counting from 0.
+    index: usize,
+
+    /// Where the Plays of this Trick begin in the history of the Game.
+    start: usize,
 
     /// Used to index into a [Player; NUM_PLAYERS] which is passed into functions
     /// TODO: (maybe) use lifetimes and a reference to [Player; NUM_PLAYERS].
-    current_player_id: usize,
+    current_player_id: Seat,
 
     /// Keeps track of all players who have passed so far this Trick
-    passed_player_ids: BTreeSet<usize>,
+    passed_player_ids: BTreeSet<Seat>,
+
+    /// The rules of the variant being played.
+    rules: Rules,
+
+    /// Any house rules in effect on top of them.
+    house_rules: HouseRules,
+
+    /// Checks the invariants after every turn, see sanity.
+    check_invariants: bool,
 }
 
 /// Returned at the end of each Player's turn, informs the caller whether the Trick has ended (and
 /// how), or ig the Trick continues
-#[derive(Debug)]
-enum StepStatus {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum StepStatus {
     /// Informs the caller that this Trick is not over, keep playing.
     Continue,
 
     /// Informs the caller that this Trick ended without anybody winning the Game, so another Trick
     /// is needed.
-    TrickOver(usize),
+    TrickOver(Seat),
 
     /// Informs the caller that this Trick ended with somebody winning the Game.
-    GameOver(usize),
+    GameOver(Seat),
 }
 
 impl Trick {
-    /// Used to construct and initialize a new Trick, starting_player_id will be used to index
-    /// into players, to request their starting hand and take their cards.
-    fn start(
-        starting_player_id: usize,
-        players: &mut [Player; NUM_PLAYERS],
-        is_first: bool,
+    /// Used to construct and initialize the next Trick of the Game, nobody has played in it yet.
+    /// The leader is the first to be asked for a Hand. Nobody plays the dead seats (or for anybody
+    /// who has gone out), they are treated as having passed already.
+    pub(crate) fn new(
+        leader: Seat,
+        players: &[Player; NUM_PLAYERS],
+        history: &[Play],
+        rules: &Rules,
+        house_rules: &HouseRules,
+        check_invariants: bool,
     ) -> Self {
-        let player = &mut players[starting_player_id];
-
-        let starting_hand = if is_first {
-            loop {
-                assert_eq!(player.cards[0], THREE_OF_CLUBS);
-                let attempt = (player.start_game)(&player.cards);
-                if let Err(e) = check_player_can_play_hand(&Hand::Pass, player, &attempt) {
-                    println!("{}", e);
-                    continue;
-                } else if let Hand::Pass = attempt {
-                    println!("Starting Hand cannot be Pass.");
-                    continue;
-                } else if *attempt.cards().last().unwrap() != THREE_OF_CLUBS {
-                    println!("Must play a hand that includes the Three of Clubs.");
-                    continue;
-                } else {
-                    break attempt;
-                }
-            }
-        } else {
-            loop {
-                let attempt = (player.start_trick)(&player.cards);
-                if let Err(e) = check_player_can_play_hand(&Hand::Pass, player, &attempt) {
-                    println!("{}", e);
-                    continue;
-                } else if let Hand::Pass = attempt {
-                    println!("Starting Hand cannot be Pass.");
-                    continue;
-                } else {
-                    break attempt;
-                }
-            }
-        };
+        let sitting_out = sitting_out(players, rules);
+        Self {
+            index: history.last().map_or(0, |play| play.trick + 1),
+            start: history.len(),
+            current_player_id: leader,
+            passed_player_ids: Seat::all(NUM_PLAYERS)
+                .filter(|seat| sitting_out.contains(&seat.index()))
+                .collect(),
+            rules: *rules,
+            house_rules: house_rules.clone(),
+            check_invariants,
+        }
+    }
 
-        println!("Player {starting_player_id} begins with {starting_hand}");
-        player.remove_hand_from_cards(&starting_hand);
+    /// The player whose turn it is.
+    pub(crate) fn current_player(&self) -> Seat {
+        self.current_player_id
+    }
 
-        let next_player_id = next_player_id(starting_player_id, &BTreeSet::new(), NUM_PLAYERS);
+    /// Every Play of this Trick so far, given every Play of the Game.
+    pub(crate) fn plays<'a>(&self, history: &'a [Play]) -> &'a [Play] {
+        &history[self.start..]
+    }
 
-        Self {
-            played_hands: vec![starting_hand],
-            current_player_id: next_player_id,
-            passed_player_ids: BTreeSet::new(),
-        }
+    /// The Hand the current player must beat, Pass if they are starting the Trick.
+    pub(crate) fn hand_to_beat(&self, history: &[Play]) -> Hand {
+        hand_to_beat(self.plays(history))
+    }
+
+    /// Returns true if nobody has played yet this Game, and the first Hand must include the Three
+    /// of Clubs (or the lowest card in play).
+    pub(crate) fn must_play_three_of_clubs(&self, history: &[Play]) -> bool {
+        history.is_empty() && self.rules.start_rule == StartRule::MustPlayLowestCard
+    }
+
+    /// Builds everything the current player may see on their turn, with their legal plays worked
+    /// out (house rules included).
+    pub(crate) fn turn_view<'a>(
+        &self,
+        players: &'a [Player; NUM_PLAYERS],
+        history: &'a [Play],
+        rejection: Option<Rejection>,
+    ) -> TurnView<'a> {
+        turn_view(
+            self.current_player_id.index(),
+            players,
+            self.hand_to_beat(history),
+            self.must_play_three_of_clubs(history),
+            self.plays(history),
+            &history[..self.start],
+            &self.rules,
+            &self.house_rules,
+            rejection,
+        )
     }
 
     /// Used to perform the entirety of the Trick, running all Player's turns,
     /// collecting their Hands, keeping track of their Passes, and ending when
     /// the Game ends, or when all but one Player has passed.
-    fn do_trick(&mut self, players: &mut [Player; NUM_PLAYERS]) -> TrickResult {
-        // its possible the trick is started and the game is over instantly because
-        // the player that started the trick finished their cards
-        if let StepStatus::GameOver(winner) = self.is_trick_over(players) {
-            return TrickResult::GameOver(winner);
-        }
-
+    fn do_trick(
+        &mut self,
+        players: &mut [Player; NUM_PLAYERS],
+        history: &mut Vec<Play>,
+    ) -> TrickResult {
         loop {
-            self.do_player_turn(players);
-            let trick_status = self.is_trick_over(players);
+            let trick_status = self.do_player_turn(players, history);
+            if let Err(e) = self.check_invariants(players, history, trick_status) {
+                break TrickResult::Invalid(e);
+            }
             match trick_status {
                 StepStatus::Continue => continue,
                 StepStatus::TrickOver(last_player) => break TrickResult::NewTrick(last_player),
@@ -160,69 +345,195 @@ impl Trick {
         }
     }
 
-    /// Used to collect a Player's Hand (or Pass) on their turn.
+    /// Used to collect a Player's Hand (or Pass) on their turn, and play it.
+    fn do_player_turn(
+        &mut self,
+        players: &mut [Player; NUM_PLAYERS],
+        history: &mut Vec<Play>,
+    ) -> StepStatus {
+        let player_id = self.current_player_id.index();
+        let begins = self.plays(history).is_empty();
+
+        // this blocks
+        let started = Instant::now();
+        let mut rejected = 0;
+        let mut rejection = None;
+        let (submitted_hand, pass_reason) = loop {
+            let view = self.turn_view(players, history, rejection);
+            let attempt = ask_for_hand(&players[player_id], &view);
+            if view.has_input_closed() {
+                hand_to_ai(player_id, &mut players[player_id]);
+                continue;
+            }
+            if view.has_resigned() {
+                forfeit(player_id, &mut players[player_id]);
+                continue;
+            }
+
+            let is_attempt_allowed = self.house_rules.check_play(&view, &attempt);
+            match is_attempt_allowed {
+                Ok(()) => {
+                    break (
+                        attempt,
+                        matches!(attempt, Hand::Pass).then(|| view.pass_reason()),
+                    )
+                }
+                Err(e) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!(player_id, %attempt, error = %e, "attempt rejected");
+                    if begins {
+                        writeln!(current(), "{}", e);
+                    } else {
+                        writeln!(current(), "{}: {}", attempt, e);
+                    }
+                    rejection = Some(Rejection { attempt, error: e });
+                }
+            }
+            rejected += 1;
+            if out_of_attempts(&players[player_id], rejected) {
+                forfeit(player_id, &mut players[player_id]);
+            }
+        };
+        charge_thinking_time(player_id, &mut players[player_id], started);
+        #[cfg(feature = "tracing")]
+        tracing::debug!(player_id, hand = %submitted_hand, "turn");
+        if let Hand::Pass = submitted_hand {
+            writeln!(current(), "Player {player_id} passed");
+        } else if begins {
+            writeln!(current(), "Player {player_id} begins with {submitted_hand}");
+        } else {
+            writeln!(current(), "Player {player_id} played {submitted_hand}");
+        }
+        let trick_status = self.play(players, history, submitted_hand, pass_reason);
+        if !matches!(submitted_hand, Hand::Pass)
+            && is_out_but_playing_on(player_id, players, &self.rules)
+        {
+            writeln!(
+                current(),
+                "Player {player_id} is out, their partner plays on"
+            );
+        }
+        trick_status
+    }
+
+    /// Plays a Hand (or Pass) for the current player, who was allowed to play it (see
+    /// HouseRules::check_play): records the Play, takes its cards from the player, and moves the
+    /// turn on to whoever has not passed. Returns whether the Trick, or the Game, is now over.
     ///
     /// # Panics
     ///
-    /// - If there are fewer than 2 players remaining in the Trick (i.e. have not passed)
-    /// - If any of the players have 0 cards (this would mean the game is already over)/
-    fn do_player_turn(&mut self, players: &mut [Player; NUM_PLAYERS]) {
+    /// - If the current player has already passed, i.e. the Trick is already over
+    /// - If the current player has 0 cards (they have already gone out)
+    pub(crate) fn play(
+        &mut self,
+        players: &mut [Player; NUM_PLAYERS],
+        history: &mut Vec<Play>,
+        hand: Hand,
+        pass_reason: Option<PassReason>,
+    ) -> StepStatus {
+        let player_id = self.current_player_id.index();
         assert!(
-            self.passed_player_ids.len() < NUM_PLAYERS - 1,
-            "there must be at least 2 players who have not yet passed"
+            !self.passed_player_ids.contains(&self.current_player_id),
+            "the current player must not have passed"
         );
 
         assert!(
-            players.iter().all(|p| !p.cards.is_empty()),
-            "all players must have some cards in order to step (game should have \
-            ended when any player went to 0 cards)"
+            !players[player_id].cards.is_empty(),
+            "the current player must have some cards in order to step (they have already gone \
+            out)"
         );
 
-        let player = &mut players[self.current_player_id];
+        history.push(
+            Play::following(history.last(), player_id, hand, self.index)
+                .with_pass_reason(pass_reason),
+        );
+        if let Hand::Pass = hand {
+            self.passed_player_ids.insert(self.current_player_id);
+        } else {
+            players[player_id].remove_hand_from_cards(&hand);
+            // in partnerships their partner plays on, so they sit the rest of the Trick out
+            if is_out_but_playing_on(player_id, players, &self.rules) {
+                self.passed_player_ids.insert(self.current_player_id);
+            }
+        }
 
-        // this blocks
-        let hand_to_beat = self.played_hands.last().unwrap();
-        let submitted_hand = loop {
-            let attempt = (player.submit_hand)(hand_to_beat, &player.cards);
+        // the Trick (or the Game) may be over, then whoever is next never plays
+        self.current_player_id = if self.everybody_else_passed(history) {
+            self.last_player_id(history)
+        } else {
+            self.current_player_id
+                .next(&self.passed_player_ids)
+                .unwrap_or(self.current_player_id)
+        };
+        self.status(players, history)
+    }
 
-            let is_attempt_allowed = check_player_can_play_hand(hand_to_beat, player, &attempt);
+    /// Ends the Trick, won by winner: they capture every card played in it, and the house rules
+    /// are told it is over.
+    pub(crate) fn close(
+        &self,
+        winner: Seat,
+        players: &mut [Player; NUM_PLAYERS],
+        history: &[Play],
+    ) {
+        let plays = self.plays(history);
+        capture_trick(&mut players[winner.index()], plays);
+        self.house_rules.on_trick_end(plays, winner.index());
+    }
 
-            match is_attempt_allowed {
-                Ok(()) => break attempt,
-                Err(e) => println!("{}: {}", attempt, e),
-            }
-        };
-        if let Hand::Pass = submitted_hand {
-            println!("Player {} passed", self.current_player_id);
-            self.passed_player_ids.insert(self.current_player_id);
-        } else {
-            println!(
-                "Player {} played {}",
-                self.current_player_id, submitted_hand
-            );
-            player.remove_hand_from_cards(&submitted_hand);
-            self.played_hands.push(submitted_hand);
+    /// Checks the invariants (see sanity) after a turn, if asked to.
+    pub(crate) fn check_invariants(
+        &self,
+        players: &[Player; NUM_PLAYERS],
+        history: &[Play],
+        trick_status: StepStatus,
+    ) -> Result<(), InvariantError> {
+        if !self.check_invariants {
+            return Ok(());
         }
-        self.current_player_id =
-            next_player_id(self.current_player_id, &self.passed_player_ids, NUM_PLAYERS);
+        let cards: Vec<Vec<Card>> = players.iter().map(|player| player.cards.clone()).collect();
+        let passed: BTreeSet<usize> = self
+            .passed_player_ids
+            .iter()
+            .map(|seat| seat.index())
+            .collect();
+        let current_player =
+            matches!(trick_status, StepStatus::Continue).then(|| self.current_player_id.index());
+        check_invariants(&cards, history, self.index, &passed, current_player)
     }
 
-    /// Returns StepStatus::GameOver if a player has 0 cards (that player has won).
-    /// Returns StepStatus::TrickOver if only one player remains in the trick (3 have passed).
-    fn is_trick_over(&self, players: &[Player; NUM_PLAYERS]) -> StepStatus {
-        for (player_id, player) in players.iter().enumerate() {
-            if player.cards.is_empty() {
-                return StepStatus::GameOver(player_id);
-            }
+    /// Whoever played the Hand to beat, i.e. the last player who did not pass.
+    fn last_player_id(&self, history: &[Play]) -> Seat {
+        let play = self
+            .plays(history)
+            .iter()
+            .rfind(|play| !matches!(play.hand, Hand::Pass))
+            .expect("a trick starts with a play");
+        Seat::new(play.player_id, NUM_PLAYERS).expect("every play is made from a seat")
+    }
+
+    /// Returns true if everybody but the last player to play has passed (or is sitting out).
+    fn everybody_else_passed(&self, history: &[Play]) -> bool {
+        let last_player_id = self.last_player_id(history);
+        Seat::all(NUM_PLAYERS)
+            .all(|seat| seat == last_player_id || self.passed_player_ids.contains(&seat))
+    }
+
+    /// Returns StepStatus::GameOver if the last player to play has gone out and that ends the
+    /// Game (that player has won).
+    /// Returns StepStatus::TrickOver if everybody else has passed, or a house rule ends the trick
+    /// (won by whoever played last).
+    fn status(&self, players: &[Player; NUM_PLAYERS], history: &[Play]) -> StepStatus {
+        let last_player_id = self.last_player_id(history);
+        let card_counts: Vec<usize> = players.iter().map(|player| player.cards.len()).collect();
+        if card_counts[last_player_id.index()] == 0
+            && self.rules.ends_game(last_player_id.index(), &card_counts)
+        {
+            return StepStatus::GameOver(last_player_id);
         }
 
-        if self.passed_player_ids.len() == (NUM_PLAYERS - 1) {
-            for (player_id, _) in players.iter().enumerate() {
-                if !self.passed_player_ids.contains(&player_id) {
-                    return StepStatus::TrickOver(player_id);
-                }
-            }
-            unreachable!();
+        if self.house_rules.ends_trick(self.plays(history)) || self.everybody_else_passed(history) {
+            StepStatus::TrickOver(last_player_id)
         } else {
             StepStatus::Continue
         }
@@ -233,8 +544,36 @@ impl Trick {
 mod tests {
 
     use super::*;
+    use crate::rules::validate::PlayError;
     use crate::tests::test_util::vec_card_from_str;
 
+    fn seat(index: usize) -> Seat {
+        Seat::new(index, NUM_PLAYERS).unwrap()
+    }
+
+    /// Starts a Trick led by leader, and plays the lead as perform_trick does.
+    fn start(
+        leader: usize,
+        players: &mut [Player; NUM_PLAYERS],
+        history: &mut Vec<Play>,
+    ) -> (Trick, StepStatus) {
+        let mut trick = Trick::new(
+            seat(leader),
+            players,
+            history,
+            &Rules::default(),
+            &HouseRules::default(),
+            false,
+        );
+        let trick_status = trick.do_player_turn(players, history);
+        (trick, trick_status)
+    }
+
+    /// A history with an earlier Trick in it, so the next Trick need not start with the 3C.
+    fn after_first_trick() -> Vec<Play> {
+        vec![Play::now(0, "3C".parse().unwrap(), 0)]
+    }
+
     #[test]
     fn test_trick_start() {
         // setup a trick where NUM_PLAYERS players are dealt cards, P1 initializes the Trick with 3C, P2 is
@@ -245,13 +584,12 @@ mod tests {
         players[2].cards = vec_card_from_str("3H");
         players[3].cards = vec_card_from_str("7D");
         let starting_player_id: usize = 1;
-        let trick: Trick = Trick::start(starting_player_id, &mut players, true);
-        assert!(matches!(
-            trick.is_trick_over(&players),
-            StepStatus::Continue
-        ));
+        let mut history = vec![];
+        let (trick, trick_status) = start(starting_player_id, &mut players, &mut history);
+        assert_eq!(trick_status, StepStatus::Continue);
+        assert_eq!(trick.index, 0);
 
-        match trick.played_hands.last().unwrap() {
+        match &trick.hand_to_beat(&history) {
             Hand::Lone(a) => assert_eq!(a, &"3C".parse().unwrap()),
             a => panic!("{}", a),
         }
@@ -267,13 +605,13 @@ mod tests {
         players[2].cards = vec_card_from_str("3H");
         players[3].cards = vec_card_from_str("7D");
         let starting_player_id: usize = 1;
-        let trick: Trick = Trick::start(starting_player_id, &mut players, false);
-        assert!(matches!(
-            trick.is_trick_over(&players),
-            StepStatus::Continue
-        ));
+        let mut history = after_first_trick();
+        let (trick, trick_status) = start(starting_player_id, &mut players, &mut history);
+        assert_eq!(trick_status, StepStatus::Continue);
+        assert_eq!(trick.index, 1);
+        assert_eq!(trick.plays(&history).len(), 1);
 
-        match trick.played_hands.last().unwrap() {
+        match &trick.hand_to_beat(&history) {
             Hand::Lone(a) => assert_eq!(a, &"4D".parse().unwrap()),
             a => panic!("{}", a),
         }
@@ -288,10 +626,9 @@ mod tests {
         players[2].cards = vec_card_from_str("3H");
         players[3].cards = vec_card_from_str("7D");
         let starting_player_id: usize = 2;
-        let trick = Trick::start(starting_player_id, &mut players, false);
-        assert!(
-            matches!(trick.is_trick_over(&players), StepStatus::GameOver(p) if p == starting_player_id)
-        );
+        let mut history = after_first_trick();
+        let (trick, trick_status) = start(starting_player_id, &mut players, &mut history);
+        assert!(matches!(trick_status, StepStatus::GameOver(p) if p == starting_player_id));
         assert_eq!(trick.passed_player_ids.len(), 0);
         assert_eq!(trick.current_player_id, 3);
         assert_eq!(players[starting_player_id].cards.len(), 0);
@@ -307,86 +644,73 @@ mod tests {
         players[2].cards = vec_card_from_str("3H 4D");
         players[3].cards = vec_card_from_str("7D 4S");
         let starting_player_id: usize = 0;
-        let mut trick: Trick = Trick::start(starting_player_id, &mut players, false);
-        assert!(matches!(
-            trick.is_trick_over(&players),
-            StepStatus::Continue
-        ));
+        let mut history = after_first_trick();
+        let (mut trick, trick_status) = start(starting_player_id, &mut players, &mut history);
+        assert_eq!(trick_status, StepStatus::Continue);
 
         // P1 plays 7D, then P2
-        trick.do_player_turn(&mut players);
-        assert!(matches!(
-            trick.is_trick_over(&players),
-            StepStatus::Continue
-        ));
-        match trick.played_hands.last().unwrap() {
+        let trick_status = trick.do_player_turn(&mut players, &mut history);
+        assert_eq!(trick_status, StepStatus::Continue);
+        match &trick.hand_to_beat(&history) {
             Hand::Lone(a) => assert_eq!(a, &"6D".parse().unwrap()),
             a => panic!("{}", a),
         }
         assert_eq!(trick.passed_player_ids.len(), 1);
-        assert!(trick.passed_player_ids.contains(&1));
+        assert!(trick.passed_player_ids.contains(&seat(1)));
         assert_eq!(trick.current_player_id, 2);
 
         // P2 must pass, then P3
-        trick.do_player_turn(&mut players);
-        assert!(matches!(
-            trick.is_trick_over(&players),
-            StepStatus::Continue
-        ));
-        match trick.played_hands.last().unwrap() {
+        let trick_status = trick.do_player_turn(&mut players, &mut history);
+        assert_eq!(trick_status, StepStatus::Continue);
+        match &trick.hand_to_beat(&history) {
             Hand::Lone(a) => assert_eq!(a, &"6D".parse().unwrap()),
             a => panic!("{}", a),
         }
         assert_eq!(trick.passed_player_ids.len(), 2);
-        assert!(trick.passed_player_ids.contains(&1));
-        assert!(trick.passed_player_ids.contains(&2));
+        assert!(trick.passed_player_ids.contains(&seat(1)));
+        assert!(trick.passed_player_ids.contains(&seat(2)));
         assert_eq!(trick.current_player_id, 3);
 
         // P3 plays 7D, then to P0
-        trick.do_player_turn(&mut players);
-        assert!(matches!(
-            trick.is_trick_over(&players),
-            StepStatus::Continue
-        ));
-        match trick.played_hands.last().unwrap() {
+        let trick_status = trick.do_player_turn(&mut players, &mut history);
+        assert_eq!(trick_status, StepStatus::Continue);
+        match &trick.hand_to_beat(&history) {
             Hand::Lone(a) => assert_eq!(a, &"7D".parse().unwrap()),
             a => panic!("{}", a),
         }
         assert_eq!(trick.passed_player_ids.len(), 2);
-        assert!(trick.passed_player_ids.contains(&1));
-        assert!(trick.passed_player_ids.contains(&2));
+        assert!(trick.passed_player_ids.contains(&seat(1)));
+        assert!(trick.passed_player_ids.contains(&seat(2)));
         assert_eq!(trick.current_player_id, 0);
 
         // P0 plays Ace of Spades, then to P3 (skipping P1 and P2 who passed)
-        trick.do_player_turn(&mut players);
-        assert!(matches!(
-            trick.is_trick_over(&players),
-            StepStatus::Continue
-        ));
-        match trick.played_hands.last().unwrap() {
+        let trick_status = trick.do_player_turn(&mut players, &mut history);
+        assert_eq!(trick_status, StepStatus::Continue);
+        match &trick.hand_to_beat(&history) {
             Hand::Lone(a) => assert_eq!(a, &"AS".parse().unwrap()),
             a => panic!("{}", a),
         }
         assert_eq!(trick.passed_player_ids.len(), 2);
-        assert!(trick.passed_player_ids.contains(&1));
-        assert!(trick.passed_player_ids.contains(&2));
+        assert!(trick.passed_player_ids.contains(&seat(1)));
+        assert!(trick.passed_player_ids.contains(&seat(2)));
         assert_eq!(trick.current_player_id, 3);
 
         // P3 passes, Trick is Over and P0 won the Trick
-        trick.do_player_turn(&mut players);
-        match trick.is_trick_over(&players) {
+        let trick_status = trick.do_player_turn(&mut players, &mut history);
+        match trick_status {
             StepStatus::TrickOver(winner) => assert_eq!(winner, 0),
             a => panic!("{:?}", a),
         }
-        match trick.played_hands.last().unwrap() {
+        match &trick.hand_to_beat(&history) {
             Hand::Lone(a) => assert_eq!(a, &"AS".parse().unwrap()),
             a => panic!("{}", a),
         }
         assert_eq!(trick.passed_player_ids.len(), 3);
-        assert!(trick.passed_player_ids.contains(&1));
-        assert!(trick.passed_player_ids.contains(&2));
-        assert!(trick.passed_player_ids.contains(&3));
+        assert!(trick.passed_player_ids.contains(&seat(1)));
+        assert!(trick.passed_player_ids.contains(&seat(2)));
+        assert!(trick.passed_player_ids.contains(&seat(3)));
         assert_eq!(trick.current_player_id, 0);
+        assert_eq!(trick.plays(&history).len(), 6);
     }
 
     #[test]
@@ -399,68 +723,282 @@ mod tests {
         players[2].cards = vec_card_from_str("3H 4D");
         players[3].cards = vec_card_from_str("7D 4S");
         let starting_player_id: usize = 0;
-        let mut trick: Trick = Trick::start(starting_player_id, &mut players, false);
-        assert!(matches!(
-            trick.is_trick_over(&players),
-            StepStatus::Continue
-        ));
+        let mut history = after_first_trick();
+        let (mut trick, trick_status) = start(starting_player_id, &mut players, &mut history);
+        assert_eq!(trick_status, StepStatus::Continue);
 
         // P1 plays 7D, then P2
-        trick.do_player_turn(&mut players);
-        assert!(matches!(
-            trick.is_trick_over(&players),
-            StepStatus::Continue
-        ));
-        match trick.played_hands.last().unwrap() {
+        let trick_status = trick.do_player_turn(&mut players, &mut history);
+        assert_eq!(trick_status, StepStatus::Continue);
+        match &trick.hand_to_beat(&history) {
             Hand::Lone(a) => assert_eq!(a, &"6D".parse().unwrap()),
             a => panic!("{}", a),
         }
         assert_eq!(trick.passed_player_ids.len(), 1);
-        assert!(trick.passed_player_ids.contains(&1));
+        assert!(trick.passed_player_ids.contains(&seat(1)));
         assert_eq!(trick.current_player_id, 2);
 
         // P2 must pass, then P3
-        trick.do_player_turn(&mut players);
-        assert!(matches!(
-            trick.is_trick_over(&players),
-            StepStatus::Continue
-        ));
-        match trick.played_hands.last().unwrap() {
+        let trick_status = trick.do_player_turn(&mut players, &mut history);
+        assert_eq!(trick_status, StepStatus::Continue);
+        match &trick.hand_to_beat(&history) {
             Hand::Lone(a) => assert_eq!(a, &"6D".parse().unwrap()),
             a => panic!("{}", a),
         }
         assert_eq!(trick.passed_player_ids.len(), 2);
-        assert!(trick.passed_player_ids.contains(&1));
-        assert!(trick.passed_player_ids.contains(&2));
+        assert!(trick.passed_player_ids.contains(&seat(1)));
+        assert!(trick.passed_player_ids.contains(&seat(2)));
         assert_eq!(trick.current_player_id, 3);
 
         // P3 plays 7D, then to P0
-        trick.do_player_turn(&mut players);
-        assert!(matches!(
-            trick.is_trick_over(&players),
-            StepStatus::Continue
-        ));
-        match trick.played_hands.last().unwrap() {
+        let trick_status = trick.do_player_turn(&mut players, &mut history);
+        assert_eq!(trick_status, StepStatus::Continue);
+        match &trick.hand_to_beat(&history) {
             Hand::Lone(a) => assert_eq!(a, &"7D".parse().unwrap()),
             a => panic!("{}", a),
         }
         assert_eq!(trick.passed_player_ids.len(), 2);
-        assert!(trick.passed_player_ids.contains(&1));
-        assert!(trick.passed_player_ids.contains(&2));
+        assert!(trick.passed_player_ids.contains(&seat(1)));
+        assert!(trick.passed_player_ids.contains(&seat(2)));
         assert_eq!(trick.current_player_id, 0);
 
         // P0 plays Ace of Spades, Game is now over!
-        trick.do_player_turn(&mut players);
-        assert!(
-            matches!(trick.is_trick_over(&players), StepStatus::GameOver(p) if p == starting_player_id)
-        );
-        match trick.played_hands.last().unwrap() {
+        let trick_status = trick.do_player_turn(&mut players, &mut history);
+        assert!(matches!(trick_status, StepStatus::GameOver(p) if p == starting_player_id));
+        match &trick.hand_to_beat(&history) {
             Hand::Lone(a) => assert_eq!(a, &"AS".parse().unwrap()),
             a => panic!("{}", a),
         }
         assert_eq!(trick.passed_player_ids.len(), 2);
-        assert!(trick.passed_player_ids.contains(&1));
-        assert!(trick.passed_player_ids.contains(&2));
+        assert!(trick.passed_player_ids.contains(&seat(1)));
+        assert!(trick.passed_player_ids.contains(&seat(2)));
         assert_eq!(trick.current_player_id, 3);
     }
+
+    #[test]
+    fn test_step_by_play() {
+        // a front end that cannot block asks for the turn view, then plays what it was sent
+        let mut players = <[Player; NUM_PLAYERS]>::default();
+        players[0].cards = vec_card_from_str("3C 5D AS");
+        players[1].cards = vec_card_from_str("3D 4H");
+        players[2].cards = vec_card_from_str("3H 4D");
+        players[3].cards = vec_card_from_str("7D 4S");
+        let mut history = vec![];
+        let rules = Rules::default();
+        let mut trick = Trick::new(
+            seat(0),
+            &players,
+            &history,
+            &rules,
+            &HouseRules::default(),
+            false,
+        );
+        let view = trick.turn_view(&players, &history, None);
+        assert!(view.must_play_three_of_clubs);
+        assert!(!view.is_legal(&"5D".parse().unwrap()));
+        let three_of_clubs = "3C".parse().unwrap();
+        assert!(view.is_legal(&three_of_clubs));
+
+        let trick_status = trick.play(&mut players, &mut history, three_of_clubs, None);
+        assert_eq!(trick_status, StepStatus::Continue);
+        assert_eq!(trick.current_player(), 1);
+        for _ in 1..NUM_PLAYERS {
+            let view = trick.turn_view(&players, &history, None);
+            assert!(!view.must_play_three_of_clubs);
+            let pass_reason = view.pass_reason();
+            trick.play(&mut players, &mut history, Hand::Pass, Some(pass_reason));
+        }
+        assert_eq!(trick.current_player(), 0);
+        assert_eq!(trick.hand_to_beat(&history), three_of_clubs);
+
+        // whoever won captures the Trick
+        let trick_status = trick.status(&players, &history);
+        assert_eq!(trick_status, StepStatus::TrickOver(seat(0)));
+        trick.close(seat(0), &mut players, &history);
+        assert_eq!(players[0].captured, vec_card_from_str("3C"));
+        let next = Trick::new(
+            next_leader(seat(0), &players, &rules),
+            &players,
+            &history,
+            &rules,
+            &HouseRules::default(),
+            false,
+        );
+        assert_eq!(next.index, 1);
+        assert!(next.plays(&history).is_empty());
+        assert_eq!(next.hand_to_beat(&history), Hand::Pass);
+    }
+
+    #[test]
+    fn test_perform_trick_records_plays() {
+        let mut players = <[Player; NUM_PLAYERS]>::default();
+        players[0].cards = vec_card_from_str("3C 5D AS");
+        players[1].cards = vec_card_from_str("3D 4H");
+        players[2].cards = vec_card_from_str("3H 4D");
+        players[3].cards = vec_card_from_str("7D 4S");
+        let mut history = vec![];
+
+        // P0 leads 3C, P1 3D, P2 3H, P3 7D, P0 AS, then everybody passes
+        let trick_result = perform_trick(
+            seat(0),
+            &mut players,
+            &mut history,
+            &Rules::default(),
+            &HouseRules::default(),
+            false,
+        );
+        assert!(matches!(trick_result, TrickResult::NewTrick(winner) if winner == 0));
+        let played: Vec<(usize, Hand)> = history
+            .iter()
+            .map(|play| (play.player_id, play.hand))
+            .collect();
+        assert_eq!(
+            played,
+            vec![
+                (0, "3C".parse().unwrap()),
+                (1, "3D".parse().unwrap()),
+                (2, "3H".parse().unwrap()),
+                (3, "7D".parse().unwrap()),
+                (0, "AS".parse().unwrap()),
+                (1, Hand::Pass),
+                (2, Hand::Pass),
+                (3, Hand::Pass),
+            ]
+        );
+        assert!(history.iter().all(|play| play.trick == 0));
+
+        // the next Trick is numbered 1, and P0 goes out
+        let trick_result = perform_trick(
+            seat(0),
+            &mut players,
+            &mut history,
+            &Rules::default(),
+            &HouseRules::default(),
+            false,
+        );
+        assert!(matches!(trick_result, TrickResult::GameOver(winner) if winner == 0));
+        assert_eq!(history.last().unwrap().trick, 1);
+    }
+
+    #[test]
+    fn test_house_rule_ends_trick() {
+        use crate::rules::house::SevenResetsTheTrick;
+        use std::sync::Arc;
+
+        let mut players = <[Player; NUM_PLAYERS]>::default();
+        players[0].cards = vec_card_from_str("3C 5D AS");
+        players[1].cards = vec_card_from_str("3D 4H");
+        players[2].cards = vec_card_from_str("3H 4D");
+        players[3].cards = vec_card_from_str("7D 4S");
+        let mut house_rules = HouseRules::default();
+        house_rules.add(Arc::new(SevenResetsTheTrick));
+        let mut history = vec![];
+
+        // P3's 7D ends the Trick at once, P3 leads the next one
+        let trick_result = perform_trick(
+            seat(0),
+            &mut players,
+            &mut history,
+            &Rules::default(),
+            &house_rules,
+            false,
+        );
+        assert!(matches!(trick_result, TrickResult::NewTrick(winner) if winner == 3));
+        assert_eq!(history.len(), 4);
+        assert_eq!(players[3].captured.len(), 4);
+    }
+
+    #[test]
+    fn test_forfeit() {
+        let mut players = <[Player; NUM_PLAYERS]>::default();
+        players[0].cards = vec_card_from_str("3C 5D AS");
+        players[1].cards = vec_card_from_str("3D 4H");
+        players[2].cards = vec_card_from_str("3H 4D");
+        players[3].cards = vec_card_from_str("7D 4S");
+        // P0 keeps trying a card they do not hold, P1 gives up straight away
+        players[0].max_attempts = Some(2);
+        players[0].start_game = |_| "2S".parse().unwrap();
+        players[1].submit_hand = |view| {
+            view.resign();
+            Hand::Pass
+        };
+        let mut history = vec![];
+
+        perform_trick(
+            seat(0),
+            &mut players,
+            &mut history,
+            &Rules::default(),
+            &HouseRules::default(),
+            false,
+        );
+        assert!(players[0].forfeited);
+        assert!(players[1].forfeited);
+        assert!(!players[2].forfeited);
+        // both are played for, P0 leads the 3C, P1 passes
+        assert_eq!(history[0].hand, "3C".parse().unwrap());
+        assert_eq!(history[1].hand, Hand::Pass);
+    }
+
+    #[test]
+    fn test_input_closed() {
+        let mut players = <[Player; NUM_PLAYERS]>::default();
+        players[0].cards = vec_card_from_str("3C 5D AS");
+        players[1].cards = vec_card_from_str("3D 4H");
+        players[2].cards = vec_card_from_str("3H 4D");
+        players[3].cards = vec_card_from_str("7D 4S");
+        // P1's input closes, and the AI plays for them rather than passing
+        players[1].submit_hand = |view| {
+            view.close_input();
+            Hand::Pass
+        };
+        let mut history = vec![];
+
+        perform_trick(
+            seat(0),
+            &mut players,
+            &mut history,
+            &Rules::default(),
+            &HouseRules::default(),
+            false,
+        );
+        assert!(!players[1].forfeited);
+        assert_eq!(history[1].player_id, 1);
+        assert_eq!(history[1].hand, "3D".parse().unwrap());
+    }
+
+    #[test]
+    fn test_rejection_is_handed_back() {
+        let mut players = <[Player; NUM_PLAYERS]>::default();
+        players[0].cards = vec_card_from_str("3C 5D AS");
+        players[1].cards = vec_card_from_str("3D 4H");
+        players[2].cards = vec_card_from_str("3H 4D");
+        players[3].cards = vec_card_from_str("7D 4S");
+        // P0 first tries a card they do not hold, then plays the first legal play instead
+        players[0].max_attempts = Some(2);
+        players[0].start_game = |view| match view.rejection {
+            None => "2S".parse().unwrap(),
+            Some(rejection) => {
+                assert_eq!(rejection.attempt, "2S".parse().unwrap());
+                assert_eq!(rejection.error, PlayError::StolenCards);
+                view.legal_plays()[0]
+            }
+        };
+        players[1].submit_hand = |view| {
+            assert_eq!(view.rejection, None);
+            Hand::Pass
+        };
+        let mut history = vec![];
+
+        perform_trick(
+            seat(0),
+            &mut players,
+            &mut history,
+            &Rules::default(),
+            &HouseRules::default(),
+            false,
+        );
+        assert!(!players[0].forfeited);
+        assert_eq!(history[0].hand, "3C".parse().unwrap());
+    }
 }
@@ -0,0 +1,129 @@
+//! Identifies a seat at the Table, an index into [Player; NUM_PLAYERS] that is known to be in range.
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter};
+
+/// A seat at a Table of num_players, checked when it is made so it can always index the players.
+/// Seats going round the Table stay at the same Table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Seat {
+    index: usize,
+    num_players: usize,
+}
+
+/// Represents the possible errors from attempting to make a Seat.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeatError {
+    /// There is no seat with this index at a Table of num_players.
+    OutOfRange { index: usize, num_players: usize },
+}
+
+impl Display for SeatError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            SeatError::OutOfRange { index, num_players } => write!(
+                f,
+                "there is no seat {index} at a table of {num_players} players"
+            ),
+        }
+    }
+}
+
+impl Display for Seat {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.index)
+    }
+}
+
+/// Lets a Seat be compared with a bare index, e.g. a player_id recorded in a Play.
+impl PartialEq<usize> for Seat {
+    fn eq(&self, index: &usize) -> bool {
+        self.index == *index
+    }
+}
+
+impl Seat {
+    /// The seat with this index at a Table of num_players.
+    pub fn new(index: usize, num_players: usize) -> Result<Self, SeatError> {
+        if index >= num_players {
+            return Err(SeatError::OutOfRange { index, num_players });
+        }
+        Ok(Self { index, num_players })
+    }
+
+    /// Every seat at a Table of num_players, in order.
+    pub fn all(num_players: usize) -> impl Iterator<Item = Seat> {
+        (0..num_players).map(move |index| Self { index, num_players })
+    }
+
+    /// The index into the players.
+    pub fn index(self) -> usize {
+        self.index
+    }
+
+    /// How many players sit at this seat's Table.
+    pub fn num_players(self) -> usize {
+        self.num_players
+    }
+
+    /// The next seat round the Table that has not passed, None if every other seat has.
+    pub fn next(self, passed: &BTreeSet<Seat>) -> Option<Seat> {
+        self.next_where(|seat| !passed.contains(&seat))
+    }
+
+    /// The next seat round the Table for which `up` is true, None if it is false for every other
+    /// seat.
+    pub fn next_where(self, up: impl Fn(Seat) -> bool) -> Option<Seat> {
+        (1..self.num_players)
+            .map(|offset| Self {
+                index: (self.index + offset) % self.num_players,
+                ..self
+            })
+            .find(|seat| up(*seat))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::trick::NUM_PLAYERS;
+
+    fn seat(index: usize) -> Seat {
+        Seat::new(index, NUM_PLAYERS).unwrap()
+    }
+
+    #[test]
+    fn test_next() {
+        let has_passed: BTreeSet<Seat> = BTreeSet::new();
+        assert_eq!(seat(0).next(&has_passed), Some(seat(1)));
+        assert_eq!(seat(3).next(&has_passed), Some(seat(0)));
+
+        let has_passed = BTreeSet::from([seat(1), seat(2)]);
+        assert_eq!(seat(0).next(&has_passed), Some(seat(3)));
+
+        let has_passed = BTreeSet::from([seat(0), seat(3)]);
+        assert_eq!(seat(2).next(&has_passed), Some(seat(1)));
+
+        // once everybody else has passed, nobody is next
+        let has_passed = BTreeSet::from([seat(0), seat(1), seat(3)]);
+        assert_eq!(seat(2).next(&has_passed), None);
+    }
+
+    #[test]
+    fn test_out_of_range() {
+        assert_eq!(
+            Seat::new(4, NUM_PLAYERS),
+            Err(SeatError::OutOfRange {
+                index: 4,
+                num_players: NUM_PLAYERS
+            })
+        );
+        // front ends add their own "error: " prefix
+        assert_eq!(
+            Seat::new(4, NUM_PLAYERS).unwrap_err().to_string(),
+            "there is no seat 4 at a table of 4 players"
+        );
+        assert_eq!(Seat::new(1, 2).unwrap().next_where(|_| true).unwrap(), 0);
+        assert_eq!(Seat::all(3).map(Seat::index).collect::<Vec<_>>(), [0, 1, 2]);
+    }
+}
@@ -0,0 +1,153 @@
+//! Builds what each recipient may see of a Game: a seat sees its own cards, a spectator sees
+//! nobody's, and a replay sees everybody's. Front ends send these views, never the full state,
+//! so hidden cards cannot leak.
+
+use serde::{Deserialize, Serialize};
+
+use crate::card::Card;
+use crate::hand::Hand;
+use crate::play::{state_hash, Play};
+
+/// Who a view of the Game is for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Viewer {
+    /// The player in this seat, who sees only their own cards.
+    Seat(usize),
+
+    /// Somebody watching, who sees nobody's cards.
+    Spectator,
+
+    /// Somebody going over the Game afterwards, who sees everybody's cards.
+    Replay,
+}
+
+impl Viewer {
+    /// Returns true if this viewer may see the cards held in the seat. Everybody's cards are
+    /// shown once the Game is over.
+    pub fn can_see(&self, seat: usize, game_over: bool) -> bool {
+        match self {
+            Viewer::Seat(own) => *own == seat || game_over,
+            Viewer::Spectator => game_over,
+            Viewer::Replay => true,
+        }
+    }
+}
+
+/// Everything about a Game at one moment, including every player's cards. Not serializable, only
+/// the views built from it are sent anywhere.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameState<'a> {
+    /// The cards held in each seat.
+    pub cards: Vec<&'a [Card]>,
+
+    /// Every turn of the Game so far.
+    pub plays: &'a [Play],
+
+    /// The player whose turn it is.
+    pub current_player: usize,
+
+    /// The Hand the current player must beat, Pass if they are starting a new Trick.
+    pub hand_to_beat: Hand,
+
+    /// The winner, once the Game is over.
+    pub winner: Option<usize>,
+}
+
+/// What one viewer may see of a Game, ready to be serialized and sent.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateView {
+    /// Who the view is for.
+    pub viewer: Viewer,
+
+    /// The cards held in each seat, None where the viewer may not see them.
+    pub cards: Vec<Option<Vec<Card>>>,
+
+    /// How many cards are held in each seat, always visible.
+    pub card_counts: Vec<usize>,
+
+    /// Every turn of the Game so far, always visible.
+    pub plays: Vec<Play>,
+
+    /// The player whose turn it is.
+    pub current_player: usize,
+
+    /// The Hand the current player must beat, Pass if they are starting a new Trick.
+    pub hand_to_beat: Hand,
+
+    /// The winner, once the Game is over.
+    pub winner: Option<usize>,
+
+    /// Identifies the public state, see play::next_state_hash.
+    pub state_hash: u64,
+}
+
+impl GameState<'_> {
+    /// Redacts the state for the viewer.
+    pub fn view(&self, viewer: Viewer) -> StateView {
+        let game_over = self.winner.is_some();
+        StateView {
+            viewer,
+            cards: self
+                .cards
+                .iter()
+                .enumerate()
+                .map(|(seat, cards)| viewer.can_see(seat, game_over).then(|| cards.to_vec()))
+                .collect(),
+            card_counts: self.cards.iter().map(|cards| cards.len()).collect(),
+            plays: self.plays.to_vec(),
+            current_player: self.current_player,
+            hand_to_beat: self.hand_to_beat,
+            winner: self.winner,
+            state_hash: state_hash(self.plays.last()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::tests::test_util::vec_card_from_str;
+
+    #[test]
+    fn test_views() {
+        let hands = [
+            vec_card_from_str("3C 4D"),
+            vec_card_from_str("5S"),
+            vec_card_from_str("6H 7C 8D"),
+            vec_card_from_str("9S"),
+        ];
+        let plays = [Play::following(None, 0, "3D".parse().unwrap(), 0)];
+        let mut state = GameState {
+            cards: hands.iter().map(|cards| cards.as_slice()).collect(),
+            plays: &plays,
+            current_player: 1,
+            hand_to_beat: "3D".parse().unwrap(),
+            winner: None,
+        };
+
+        let seat = state.view(Viewer::Seat(2));
+        assert_eq!(seat.cards, vec![None, None, Some(hands[2].clone()), None]);
+        assert_eq!(seat.card_counts, vec![2, 1, 3, 1]);
+        assert_eq!(seat.state_hash, plays[0].state_hash);
+
+        let spectator = state.view(Viewer::Spectator);
+        assert!(spectator.cards.iter().all(Option::is_none));
+        assert_eq!(spectator.plays, plays.to_vec());
+
+        let replay = state.view(Viewer::Replay);
+        assert!(replay.cards.iter().all(Option::is_some));
+
+        // nothing hidden is serialized
+        let json = serde_json::to_string(&spectator).unwrap();
+        assert_eq!(serde_json::from_str::<StateView>(&json).unwrap(), spectator);
+
+        // everybody's cards are shown at the end
+        state.winner = Some(1);
+        assert!(state
+            .view(Viewer::Spectator)
+            .cards
+            .iter()
+            .all(Option::is_some));
+    }
+}
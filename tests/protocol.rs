@@ -0,0 +1,378 @@
+//! The wire protocol of the server (see bigtwo::server), as scripted clients see it: each test
+//! runs a server in-process on a free port, connects to it over TCP, and checks what it says back,
+//! for well-behaved clients and for clients that play out of turn, ask for stale seqs or send
+//! lines the server will not take.
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use bigtwo::config::GameConfig;
+use bigtwo::constants::NUM_PLAYERS;
+use bigtwo::inbound::Limits;
+use bigtwo::output::{self, Buffer, Output};
+use bigtwo::seats::{Controller, SeatConfig};
+use bigtwo::server::{serve, ServerOptions};
+use bigtwo::table::HELP;
+
+/// How long a client waits for the server to say something before the test fails.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Starts a server for a Game with the first `people` seats played over the network and the rest
+/// by the AI, dealt the same cards every time. Returns where to connect, the thread serving,
+/// which ends once a player leaves, and what the server logs.
+fn start(people: usize, options: ServerOptions) -> (SocketAddr, JoinHandle<()>, Buffer) {
+    let mut config = GameConfig {
+        seed: Some(7),
+        ..GameConfig::default()
+    };
+    config.seats = (0..NUM_PLAYERS)
+        .map(|seat| SeatConfig {
+            controller: match seat < people {
+                true => Controller::Network,
+                false => Controller::Ai,
+            },
+            ..config.seat(seat)
+        })
+        .collect();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let log = Buffer::default();
+    let output = Output::to(log.clone());
+    let server = thread::spawn(move || {
+        let _output = output::install(output);
+        serve(listener, config, options)
+    });
+    (address, server, log)
+}
+
+/// Spectators see everything straight away, so a server is done as soon as a player leaves.
+fn options() -> ServerOptions {
+    ServerOptions {
+        spectator_delay: Duration::ZERO,
+        ..ServerOptions::default()
+    }
+}
+
+/// A scripted protocol client.
+struct Client {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+
+    /// Every line received so far, for the failure messages.
+    received: Vec<String>,
+}
+
+impl Client {
+    fn connect(address: SocketAddr) -> Self {
+        let stream = TcpStream::connect(address).unwrap();
+        stream.set_read_timeout(Some(TIMEOUT)).unwrap();
+        Self {
+            reader: BufReader::new(stream.try_clone().unwrap()),
+            writer: stream,
+            received: vec![],
+        }
+    }
+
+    /// Connects and waits to be seated, so the clients are seated in the order they connect.
+    fn sit(address: SocketAddr, seat: usize) -> Self {
+        let mut client = Self::connect(address);
+        let welcome = client.expect("Welcome to Big Two!");
+        assert!(
+            welcome.contains(&format!("You are Player {seat} ")),
+            "{welcome}"
+        );
+        client
+    }
+
+    fn send(&mut self, line: &str) {
+        self.send_bytes(format!("{line}\n").as_bytes());
+    }
+
+    /// The server may already have hung up, which the next read shows.
+    fn send_bytes(&mut self, bytes: &[u8]) {
+        let _ = self.writer.write_all(bytes);
+    }
+
+    /// The next line the server sends, None once it hangs up.
+    fn next_line(&mut self) -> Option<String> {
+        let mut line = String::new();
+        match self.reader.read_line(&mut line) {
+            Ok(0) => None,
+            Ok(_) => {
+                let line = line.trim_end().to_string();
+                self.received.push(line.clone());
+                Some(line)
+            }
+            Err(e) => panic!(
+                "nothing received for {TIMEOUT:?} ({e}), got {:#?}",
+                self.received
+            ),
+        }
+    }
+
+    /// Reads until a line starting with prefix, and returns it.
+    fn expect(&mut self, prefix: &str) -> String {
+        loop {
+            match self.next_line() {
+                Some(line) if line.starts_with(prefix) => return line,
+                Some(_) => {}
+                None => panic!("hung up before {prefix:?}, got {:#?}", self.received),
+            }
+        }
+    }
+
+    /// Sends "help" and returns every line received before the help comes back, i.e. everything
+    /// the server said in answer to the lines sent before it.
+    fn until_help(&mut self) -> Vec<String> {
+        self.send("help");
+        let mut lines = vec![];
+        loop {
+            match self.next_line() {
+                Some(line) if line == HELP => return lines,
+                Some(line) => lines.push(line),
+                None => panic!("hung up before the help, got {:#?}", self.received),
+            }
+        }
+    }
+
+    /// Asks whose turn it is, skipping the "Sync <hash> at <seq>" lines broadcast meanwhile.
+    fn whose_turn(&mut self) -> usize {
+        self.send("sync");
+        loop {
+            let sync = self.expect("Sync ");
+            if let Some((_, player)) = sync.split_once("Player ") {
+                return player.trim_end_matches(" to play.").parse().unwrap();
+            }
+        }
+    }
+
+    /// Plays until the Game is over, as simply as possible: the lowest card to start a Trick, and
+    /// a pass otherwise. Returns the line announcing the winner.
+    fn play_out(&mut self) -> String {
+        loop {
+            let line = self.next_line().expect("the Game is not over");
+            if line.starts_with("Game Over") {
+                return line;
+            } else if line.starts_with("Your turn, beat") {
+                self.pace();
+                self.send("pass");
+            } else if line.starts_with("Your turn") {
+                let cards = self.expect("Your cards: ");
+                let lowest = cards.split_whitespace().nth(2).unwrap().to_string();
+                self.pace();
+                self.send(&lowest);
+            }
+        }
+    }
+
+    /// Waits long enough to never send lines too quickly, see Limits::refill.
+    fn pace(&self) {
+        thread::sleep(Limits::default().refill);
+    }
+}
+
+#[test]
+fn test_joining() {
+    let (address, server, _) = start(2, options());
+    let mut host = Client::sit(address, 0);
+    host.expect("You are the host.");
+    host.expect("Waiting for 1 more players...");
+
+    // nothing reaches a Table until every seat is taken
+    host.send("3C");
+    host.expect("The Game has not started, waiting for 1 more players...");
+
+    // the host chooses the rules, and everybody joining is told which
+    host.send("preset taiwan");
+    host.expect("The host changed the rules to taiwan.");
+    let mut guest = Client::sit(address, 1);
+    assert!(guest.received[0].ends_with("playing taiwan rules."));
+    host.expect("Player 1 (");
+    for client in [&mut host, &mut guest] {
+        client.expect("Playing ");
+        client.expect("Your cards: ");
+        client.expect("Sync ");
+    }
+
+    // only the host sends host commands, and the rules are fixed once the Game has started
+    guest.send("kick 0");
+    guest.expect("error: only the host may do that");
+    host.send("preset hk");
+    host.expect("error: the Game has already started");
+
+    // everybody after the players watches
+    let mut spectator = Client::connect(address);
+    spectator.expect("Welcome to Big Two! You are watching, 0 seconds behind the players.");
+
+    drop(guest);
+    host.expect("Player 1 left, the Game is abandoned.");
+    server.join().unwrap();
+}
+
+#[test]
+fn test_turns() {
+    let (address, server, _) = start(2, options());
+    let mut clients = [Client::sit(address, 0), Client::sit(address, 1)];
+    for client in clients.iter_mut() {
+        client.expect("Sync ");
+    }
+    let current = clients[0].whose_turn();
+    assert!(current < 2, "the AI plays until it is a person's turn");
+    let waiting = 1 - current;
+
+    // playing out of turn
+    clients[waiting].send("pass");
+    clients[waiting].expect(&format!("It is Player {current}'s turn."));
+
+    // lines that are not Hands, or Hands that may not be played
+    clients[current].send("ZZ");
+    clients[current].expect("error: could not understand ZZ");
+    clients[current].send("3C 4D");
+    clients[current].expect("error: invalid hand");
+
+    // a play is broadcast, followed by the new state
+    let prompt = clients[current]
+        .received
+        .iter()
+        .rfind(|line| line.starts_with("Your turn"))
+        .cloned()
+        .unwrap();
+    let hand = match prompt.starts_with("Your turn, beat") {
+        true => String::from("pass"),
+        false => {
+            clients[current].send("cards");
+            let cards = clients[current].expect("Your cards: ");
+            cards.split_whitespace().nth(2).unwrap().to_string()
+        }
+    };
+    clients[current].send(&hand);
+    // "Player <n> played <hand>" or "Player <n> passed"
+    clients[waiting].expect(&format!("Player {current} p"));
+    clients[waiting].expect("Sync ");
+
+    drop(clients);
+    server.join().unwrap();
+}
+
+#[test]
+fn test_since() {
+    let (address, server, _) = start(2, options());
+    let mut clients = [Client::sit(address, 0), Client::sit(address, 1)];
+    for client in clients.iter_mut() {
+        client.expect("Sync ");
+    }
+    let seat_cards: Vec<String> = clients
+        .iter()
+        .map(|client| {
+            let cards = client
+                .received
+                .iter()
+                .find(|line| line.starts_with("Your cards: "));
+            cards.unwrap().clone()
+        })
+        .collect();
+
+    // "since 0" replays everything said to everybody, and to this seat only
+    clients[0].send("since 0");
+    let replayed = clients[0].until_help();
+    assert!(replayed[0].starts_with("Playing "), "{replayed:#?}");
+    assert!(replayed.contains(&seat_cards[0]));
+    assert!(!replayed.contains(&seat_cards[1]));
+
+    // the seq of the last Sync line is up to date, there is nothing to replay (what the other
+    // seat was told since is not for this one), and neither is there after a seq yet to come
+    let sync = clients[1]
+        .received
+        .iter()
+        .rfind(|line| line.starts_with("Sync "));
+    let (_, seq) = sync.unwrap().split_once(" at ").unwrap();
+    clients[1].send(&format!("since {seq}"));
+    assert_eq!(clients[1].until_help(), Vec::<String>::new());
+    clients[1].send(&format!("since {}", u64::MAX));
+    assert_eq!(clients[1].until_help(), Vec::<String>::new());
+
+    // and a seq that is not a number
+    clients[1].send("since yesterday");
+    clients[1].expect("error: could not understand yesterday, expected a number");
+
+    drop(clients);
+    server.join().unwrap();
+}
+
+#[test]
+fn test_bad_lines() {
+    let (address, server, _) = start(2, options());
+    let mut clients = [Client::sit(address, 0), Client::sit(address, 1)];
+    for client in clients.iter_mut() {
+        client.expect("Sync ");
+    }
+
+    let oversized = "3C ".repeat(Limits::default().max_line_bytes);
+    clients[0].send(&oversized);
+    clients[0].expect("error: the line is too long");
+    clients[0].send_bytes(b"3C \xff\xfe\n");
+    clients[0].expect("error: the line is not text");
+    clients[0].send("3C\x07");
+    clients[0].expect("error: the line holds a control character");
+
+    // a burst of more lines than allowed, in one write so they arrive together however busy the
+    // machine running the test is
+    let burst = "cards\n".repeat(Limits::default().burst as usize + 1);
+    clients[1].send_bytes(burst.as_bytes());
+    clients[1].expect("error: too many lines, slow down");
+
+    // too many bad lines and the client is disconnected, which abandons the Game
+    for _ in 0..=Limits::default().max_strikes {
+        clients[0].send(&oversized);
+    }
+    clients[0].expect("Disconnected for sending too many bad lines.");
+    while clients[0].next_line().is_some() {}
+    clients[1].expect("Player 0 left, the Game is abandoned.");
+    server.join().unwrap();
+}
+
+#[test]
+fn test_heartbeat() {
+    let (address, server, _) = start(
+        1,
+        ServerOptions {
+            heartbeat: Some(Duration::from_millis(100)),
+            ..options()
+        },
+    );
+    let mut client = Client::sit(address, 0);
+
+    // the heartbeat asks to be answered, and the answer never reaches the Table
+    let ping = client.expect("ping ");
+    client.send(&ping.replace("ping", "pong"));
+    let answered = client.until_help();
+    assert!(answered.iter().all(|line| !line.starts_with("error")));
+
+    drop(client);
+    server.join().unwrap();
+}
+
+#[test]
+fn test_rematch() {
+    let (address, server, log) = start(1, options());
+    let mut client = Client::sit(address, 0);
+    client.play_out();
+    client.expect("Scores: ");
+    client.send("3C");
+    client.expect("The Game is over, type \"ready\" for a rematch.");
+    client.send("ready");
+    client.expect("Player 0 is ready for a rematch (1 of 1)");
+    client.expect("Everybody is ready, dealing the next Game.");
+    client.expect("Playing ");
+
+    drop(client);
+    server.join().unwrap();
+
+    // the server logs every Game, and rates it, on the thread it was started from
+    let log = log.contents();
+    assert!(log.contains("(seat 0) joined from "), "{log}");
+    assert!(log.contains("Ratings after Game "), "{log}");
+    assert!(log.contains("Player 0 left, Game "), "{log}");
+}